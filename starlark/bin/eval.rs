@@ -26,6 +26,7 @@ use gazebo::prelude::*;
 use itertools::Either;
 use lsp_types::Diagnostic;
 use lsp_types::Url;
+use starlark::codemap::ColumnUnit;
 use starlark::environment::FrozenModule;
 use starlark::environment::Globals;
 use starlark::environment::Module;
@@ -57,6 +58,8 @@ pub(crate) struct Context {
     pub(crate) module: Option<Module>,
     pub(crate) builtin_docs: HashMap<LspUrl, String>,
     pub(crate) builtin_symbols: HashMap<String, LspUrl>,
+    /// How columns of messages are counted, UTF-16 code units for the LSP.
+    pub(crate) column_unit: ColumnUnit,
 }
 
 /// The outcome of evaluating (checking, parsing or running) given starlark code.
@@ -121,6 +124,7 @@ impl Context {
             module,
             builtin_docs,
             builtin_symbols,
+            column_unit: ColumnUnit::Char,
         })
     }
 
@@ -164,12 +168,17 @@ impl Context {
 
     // Convert an anyhow over iterator of EvalMessage, into an iterator of EvalMessage
     fn err(
+        &self,
         file: &str,
         result: anyhow::Result<EvalResult<impl Iterator<Item = EvalMessage>>>,
     ) -> EvalResult<impl Iterator<Item = EvalMessage>> {
         match result {
             Err(e) => EvalResult {
-                messages: Either::Left(iter::once(EvalMessage::from_anyhow(Path::new(file), &e))),
+                messages: Either::Left(iter::once(EvalMessage::from_anyhow_in(
+                    Path::new(file),
+                    &e,
+                    self.column_unit,
+                ))),
                 ast: None,
             },
             Ok(res) => EvalResult {
@@ -184,7 +193,7 @@ impl Context {
         content: String,
     ) -> EvalResult<impl Iterator<Item = EvalMessage>> {
         let file = "expression";
-        Self::err(
+        self.err(
             file,
             AstModule::parse(file, content, &dialect()).map(|module| self.go(file, module)),
         )
@@ -192,7 +201,7 @@ impl Context {
 
     pub(crate) fn file(&self, file: &Path) -> EvalResult<impl Iterator<Item = EvalMessage>> {
         let filename = &file.to_string_lossy();
-        Self::err(
+        self.err(
             filename,
             fs::read_to_string(file)
                 .map(|content| self.file_with_contents(filename, content))
@@ -205,7 +214,7 @@ impl Context {
         filename: &str,
         content: String,
     ) -> EvalResult<impl Iterator<Item = EvalMessage>> {
-        Self::err(
+        self.err(
             filename,
             AstModule::parse(filename, content, &dialect()).map(|module| self.go(filename, module)),
         )
@@ -223,9 +232,15 @@ impl Context {
         let mut eval = Evaluator::new(module);
         eval.enable_terminal_breakpoint_console();
         let globals = globals();
-        Self::err(
+        // A module kept between runs is a REPL session, which continues where it left off.
+        let res = if self.module.is_some() {
+            eval.eval_statements_incrementally(ast, &globals)
+        } else {
+            eval.eval_module(ast, &globals)
+        };
+        self.err(
             file,
-            eval.eval_module(ast, &globals).map(|v| {
+            res.map(|v| {
                 if self.print_non_none && !v.is_none() {
                     println!("{}", v);
                 }
@@ -248,7 +263,11 @@ impl Context {
             Some(globals.as_slice())
         };
 
-        module.lint(globals).into_iter().map(EvalMessage::from)
+        let column_unit = self.column_unit;
+        module
+            .lint(globals)
+            .into_iter()
+            .map(move |x| EvalMessage::from_lint_in(x, column_unit))
     }
 }
 
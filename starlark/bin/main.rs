@@ -35,6 +35,7 @@ use std::sync::Arc;
 use eval::Context;
 use gazebo::prelude::*;
 use itertools::Either;
+use starlark::codemap::ColumnUnit;
 use starlark::errors::EvalMessage;
 use starlark::errors::EvalSeverity;
 use starlark::lsp;
@@ -241,6 +242,7 @@ fn main() -> anyhow::Result<()> {
 
         if args.lsp {
             ctx.mode = ContextMode::Check;
+            ctx.column_unit = ColumnUnit::Utf16;
             lsp::server::stdio_server(ctx)?;
         } else if is_interactive {
             interactive(&ctx)?;
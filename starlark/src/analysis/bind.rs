@@ -291,6 +291,11 @@ fn stmt(x: &AstStmt, res: &mut Vec<Bind>) {
             expr(rhs, res);
             expr_lvalue(lhs, res);
         }
+        Stmt::Del(x) => {
+            // `del x` requires `x` to be bound, so counts as a use.
+            x.visit_expr(|x| expr(x, res));
+            x.visit_lvalue(|x| res.push(Bind::Get(x.clone().into_map(|AssignIdentP(s, ())| s))));
+        }
         Stmt::For(dest, box (inner, body)) => {
             expr(inner, res);
             expr_lvalue(dest, res);
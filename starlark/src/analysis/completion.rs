@@ -0,0 +1,439 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Completions of the names and attributes which could be written at a position.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use gazebo::prelude::*;
+use itertools::Itertools;
+
+use crate::codemap::ColumnUnit;
+use crate::codemap::LineCol;
+use crate::codemap::Pos;
+use crate::environment::Globals;
+use crate::syntax::ast::AssignP;
+use crate::syntax::ast::AstAssign;
+use crate::syntax::ast::AstExpr;
+use crate::syntax::ast::AstLiteral;
+use crate::syntax::ast::AstNoPayload;
+use crate::syntax::ast::AstParameter;
+use crate::syntax::ast::AstStmt;
+use crate::syntax::ast::Clause;
+use crate::syntax::ast::Expr;
+use crate::syntax::ast::Parameter;
+use crate::syntax::ast::Stmt;
+use crate::syntax::uniplate::Visit;
+use crate::syntax::AstModule;
+use crate::values::dict::Dict;
+use crate::values::docs::DocItem;
+use crate::values::docs::Member;
+use crate::values::function::FUNCTION_TYPE;
+use crate::values::Heap;
+use crate::values::Value;
+
+/// What a [`Completion`] refers to.
+#[derive(Debug, Clone, Copy, Dupe, PartialEq, Eq)]
+pub enum CompletionKind {
+    /// A function or method.
+    Function,
+    /// A variable, parameter or global constant.
+    Variable,
+    /// An attribute of a value which is not a method.
+    Field,
+}
+
+/// A name which could be written at a position. See [`AstModule::complete`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    /// The full name, which starts with whatever has already been typed.
+    pub name: String,
+    /// What the name refers to.
+    pub kind: CompletionKind,
+    /// The signature of a function, e.g. `f(x, y = 1) -> int`, if known.
+    pub signature: Option<String>,
+}
+
+/// How a name is bound in a scope.
+enum Binding<'a> {
+    /// A `def`, with its parameters and return type.
+    Def(&'a [AstParameter], Option<&'a AstExpr>),
+    /// The right hand side of each assignment, or `None` if it wasn't `name = expr`.
+    Variable(Vec<Option<&'a AstExpr>>),
+}
+
+type Scope<'a> = HashMap<&'a str, Binding<'a>>;
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn bind_variable<'a>(scope: &mut Scope<'a>, name: &'a str, rhs: Option<&'a AstExpr>) {
+    match scope.get_mut(name) {
+        Some(Binding::Variable(xs)) => xs.push(rhs),
+        _ => {
+            scope.insert(name, Binding::Variable(vec![rhs]));
+        }
+    }
+}
+
+fn bind_assign<'a>(scope: &mut Scope<'a>, lhs: &'a AstAssign, rhs: Option<&'a AstExpr>) {
+    match &**lhs {
+        AssignP::Identifier(x) => bind_variable(scope, &x.0, rhs),
+        _ => lhs.visit_lvalue(|x| bind_variable(scope, &x.0, None)),
+    }
+}
+
+fn bind_params<'a>(scope: &mut Scope<'a>, params: &'a [AstParameter]) {
+    for p in params {
+        match &**p {
+            Parameter::Normal(x, _)
+            | Parameter::WithDefaultValue(x, _, _)
+            | Parameter::Args(x, _)
+            | Parameter::KwArgs(x, _) => bind_variable(scope, &x.0, None),
+            Parameter::NoArgs => {}
+        }
+    }
+}
+
+/// Names bound by a statement in the current scope, not looking inside nested `def`s.
+fn bind_stmt<'a>(scope: &mut Scope<'a>, x: &'a AstStmt) {
+    match &**x {
+        Stmt::Assign(lhs, rhs) => bind_assign(scope, lhs, Some(rhs)),
+        // Updating in place keeps the type, so only matters for the first binding.
+        Stmt::AssignModify(lhs, _, _) => lhs.visit_lvalue(|x| {
+            scope
+                .entry(x.0.as_str())
+                .or_insert_with(|| Binding::Variable(vec![None]));
+        }),
+        Stmt::For(var, box (_, body)) => {
+            bind_assign(scope, var, None);
+            bind_stmt(scope, body);
+        }
+        Stmt::Def(name, params, ret, _, _) => {
+            scope.insert(&name.0, Binding::Def(params, ret.as_deref()));
+        }
+        Stmt::Load(load) => load
+            .args
+            .iter()
+            .for_each(|(x, _)| bind_variable(scope, &x.0, None)),
+        _ => x.visit_stmt(|x| bind_stmt(scope, x)),
+    }
+}
+
+/// Push the scopes of the `def`s, `lambda`s and comprehensions around `pos`, outermost first.
+fn enclosing_scopes<'a>(x: Visit<'a, AstNoPayload>, pos: Pos, scopes: &mut Vec<Scope<'a>>) {
+    x.visit_children(|x| {
+        let span = match &x {
+            Visit::Stmt(x) => x.span,
+            Visit::Expr(x) => x.span,
+        };
+        if !span.contains(pos) {
+            return;
+        }
+        let mut scope = Scope::new();
+        match &x {
+            Visit::Stmt(x) => {
+                if let Stmt::Def(_, params, _, body, _) = &***x {
+                    bind_params(&mut scope, params);
+                    bind_stmt(&mut scope, body);
+                }
+            }
+            Visit::Expr(x) => match &***x {
+                Expr::Lambda(params, _, _) => bind_params(&mut scope, params),
+                Expr::ListComprehension(_, for_, clauses)
+                | Expr::DictComprehension(_, for_, clauses) => {
+                    bind_assign(&mut scope, &for_.var, None);
+                    for clause in clauses {
+                        if let Clause::For(for_) = clause {
+                            bind_assign(&mut scope, &for_.var, None);
+                        }
+                    }
+                }
+                _ => {}
+            },
+        }
+        if !scope.is_empty() {
+            scopes.push(scope);
+        }
+        enclosing_scopes(x, pos, scopes);
+    })
+}
+
+/// A value of the same type as the literal `x`, to ask for its attributes.
+fn literal_value<'v>(x: &AstExpr, heap: &'v Heap) -> Option<Value<'v>> {
+    match &**x {
+        Expr::Literal(AstLiteral::String(_)) => Some(heap.alloc_str("").to_value()),
+        Expr::List(_) | Expr::ListComprehension(..) => Some(heap.alloc_list(&[])),
+        Expr::Dict(_) | Expr::DictComprehension(..) => Some(heap.alloc(Dict::default())),
+        Expr::Tuple(_) => Some(heap.alloc_tuple(&[])),
+        _ => None,
+    }
+}
+
+/// A value of the same type as the expression which ends `before`, if that is a string
+/// literal, a global, or a local only ever assigned literals of a single type.
+fn receiver_value<'v>(
+    before: &str,
+    scopes: &[Scope],
+    globals: &'v Globals,
+    heap: &'v Heap,
+) -> Option<Value<'v>> {
+    if before.ends_with(&['"', '\''][..]) {
+        return Some(heap.alloc_str("").to_value());
+    }
+    let name = &before[before.trim_end_matches(is_ident_char).len()..];
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    match scopes.iter().rev().find_map(|scope| scope.get(name)) {
+        Some(Binding::Variable(rhs)) => {
+            let values = rhs
+                .iter()
+                .map(|x| literal_value((*x)?, heap))
+                .collect::<Option<Vec<_>>>()?;
+            let (first, rest) = values.split_first()?;
+            if rest.iter().all(|x| x.get_type() == first.get_type()) {
+                Some(*first)
+            } else {
+                None
+            }
+        }
+        Some(Binding::Def(..)) => None,
+        None => globals.get(name),
+    }
+}
+
+/// Complete `name`, which refers to `x`, as a function if `x` is one, otherwise as `kind`.
+fn value_completion(name: String, x: Value, kind: CompletionKind) -> Completion {
+    match x.documentation() {
+        Some(DocItem::Function(f)) => Completion {
+            signature: Some(f.render_signature(&name)),
+            name,
+            kind: CompletionKind::Function,
+        },
+        _ => Completion {
+            name,
+            kind: if x.get_type() == FUNCTION_TYPE {
+                CompletionKind::Function
+            } else {
+                kind
+            },
+            signature: None,
+        },
+    }
+}
+
+fn attribute_completions<'v>(x: Value<'v>, heap: &'v Heap) -> Vec<Completion> {
+    let members: HashMap<String, Member> = match x.documentation() {
+        Some(DocItem::Object(x)) => x.members.into_iter().collect(),
+        _ => HashMap::new(),
+    };
+    x.dir_attr().into_map(|name| match members.get(&name) {
+        Some(Member::Function(f)) => Completion {
+            signature: Some(f.render_signature(&name)),
+            name,
+            kind: CompletionKind::Function,
+        },
+        Some(Member::Property(_)) => Completion {
+            name,
+            kind: CompletionKind::Field,
+            signature: None,
+        },
+        None => match x.get_attr(&name, heap) {
+            Ok(Some(v)) => value_completion(name, v, CompletionKind::Field),
+            _ => Completion {
+                name,
+                kind: CompletionKind::Field,
+                signature: None,
+            },
+        },
+    })
+}
+
+fn name_completions(scopes: &[Scope], globals: &Globals) -> Vec<Completion> {
+    let mut seen = HashSet::new();
+    let mut res = Vec::new();
+    // Inner scopes shadow outer ones.
+    for scope in scopes.iter().rev() {
+        for (name, binding) in scope {
+            if !seen.insert(*name) {
+                continue;
+            }
+            res.push(match binding {
+                Binding::Def(params, ret) => Completion {
+                    name: (*name).to_owned(),
+                    kind: CompletionKind::Function,
+                    signature: Some(format!(
+                        "{}({}){}",
+                        name,
+                        params.iter().map(|x| &x.node).join(", "),
+                        ret.map(|x| format!(" -> {}", x.node)).unwrap_or_default()
+                    )),
+                },
+                Binding::Variable(_) => Completion {
+                    name: (*name).to_owned(),
+                    kind: CompletionKind::Variable,
+                    signature: None,
+                },
+            });
+        }
+    }
+    res.sort_by(|x, y| x.name.cmp(&y.name));
+    let mut builtins: Vec<_> = globals
+        .names()
+        .map(|x| x.as_str())
+        .filter(|x| !seen.contains(x))
+        .filter_map(|x| {
+            Some(value_completion(
+                x.to_owned(),
+                globals.get(x)?,
+                CompletionKind::Variable,
+            ))
+        })
+        .collect();
+    builtins.sort_by(|x, y| x.name.cmp(&y.name));
+    res.extend(builtins);
+    res
+}
+
+impl AstModule {
+    /// The names which could be written at `pos`, starting with the partial identifier
+    /// before it. Lines and columns are zero-based, with columns counted in characters.
+    ///
+    /// After `obj.` these are the attributes of `obj`, provided its type is obvious: a string
+    /// literal, a global, or a local only ever assigned literals of one type. Otherwise they
+    /// are the local names in scope, sorted, followed by the names in `globals`, sorted.
+    /// Since the module must have parsed, editors will usually ask about an earlier
+    /// version of the file when the cursor directly follows a `.`.
+    ///
+    /// ```
+    /// use starlark::codemap::LineCol;
+    /// use starlark::environment::Globals;
+    /// use starlark::syntax::AstModule;
+    /// use starlark::syntax::CompletionKind;
+    /// use starlark::syntax::Dialect;
+    ///
+    /// let ast = AstModule::parse(
+    ///     "x.star",
+    ///     "xs = []\ndef f(x, y = 1):\n    xs.ap".to_owned(),
+    ///     &Dialect::Standard,
+    /// )
+    /// .unwrap();
+    /// let globals = Globals::standard();
+    /// let completions = ast.complete(LineCol { line: 2, column: 9 }, &globals);
+    /// assert_eq!(completions.len(), 1);
+    /// assert_eq!(completions[0].name, "append");
+    /// assert_eq!(completions[0].kind, CompletionKind::Function);
+    ///
+    /// let completions = ast.complete(LineCol { line: 2, column: 4 }, &globals);
+    /// let names: Vec<_> = completions.iter().map(|x| x.name.as_str()).collect();
+    /// assert_eq!(&names[..4], ["f", "x", "xs", "y"]);
+    /// assert_eq!(completions[0].signature.as_deref(), Some("f(x, y = 1)"));
+    /// assert!(names.contains(&"len"));
+    /// ```
+    pub fn complete(&self, pos: LineCol, globals: &Globals) -> Vec<Completion> {
+        let offset = self.codemap.line_col_to_offset(pos, ColumnUnit::Char);
+        let before = &self.codemap.source()[..offset];
+        let start = before.trim_end_matches(is_ident_char).len();
+        let prefix = &before[start..];
+
+        let mut scopes = vec![Scope::new()];
+        bind_stmt(&mut scopes[0], &self.statement);
+        enclosing_scopes(
+            Visit::Stmt(&self.statement),
+            Pos::new(offset as u32),
+            &mut scopes,
+        );
+
+        let heap = Heap::new();
+        let res = match before[..start].strip_suffix('.') {
+            Some(receiver) => match receiver_value(receiver, &scopes, globals, &heap) {
+                Some(x) => attribute_completions(x, &heap),
+                None => Vec::new(),
+            },
+            None => name_completions(&scopes, globals),
+        };
+        res.into_iter()
+            .filter(|x| x.name.starts_with(prefix))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::Dialect;
+
+    fn complete(program: &str, line: usize, column: usize) -> Vec<Completion> {
+        let module = AstModule::parse("X", program.to_owned(), &Dialect::Extended).unwrap();
+        module.complete(LineCol { line, column }, &Globals::extended())
+    }
+
+    fn names(xs: &[Completion]) -> Vec<&str> {
+        xs.map(|x| x.name.as_str())
+    }
+
+    #[test]
+    fn test_complete_names() {
+        let program = r#"
+load("foo", "loaded")
+value = 1
+def outer(param):
+    inner = 2
+    return [lambda arg: va for va in [value]]
+"#;
+        assert_eq!(names(&complete(program, 5, 26))[..2], ["va", "value"]);
+        let res = complete(program, 5, 24);
+        assert_eq!(
+            &names(&res)[..7],
+            ["arg", "inner", "loaded", "outer", "param", "va", "value"]
+        );
+        assert_eq!(res[3].kind, CompletionKind::Function);
+        assert_eq!(res[3].signature.as_deref(), Some("outer(param)"));
+        assert_eq!(res[6].kind, CompletionKind::Variable);
+        let len = res.iter().find(|x| x.name == "len").unwrap();
+        assert_eq!(len.kind, CompletionKind::Function);
+        // Outside the `def`, its locals are not in scope.
+        assert_eq!(
+            names(&complete(program, 2, 0))[..3],
+            ["loaded", "outer", "value"]
+        );
+    }
+
+    #[test]
+    fn test_complete_attributes() {
+        let program = r#"
+s = "x"
+xs = []
+xs = [1]
+mixed = []
+mixed = {}
+def f():
+    return "".st + s.up + xs.ex + mixed.ex + json.en
+"#;
+        assert_eq!(names(&complete(program, 7, 16)), ["startswith", "strip"]);
+        assert_eq!(names(&complete(program, 7, 23)), ["upper"]);
+        let res = complete(program, 7, 31);
+        assert_eq!(names(&res), ["extend"]);
+        assert_eq!(res[0].kind, CompletionKind::Function);
+        assert!(res[0].signature.is_some());
+        assert!(complete(program, 7, 42).is_empty());
+        assert_eq!(names(&complete(program, 7, 52)), ["encode"]);
+    }
+}
@@ -20,6 +20,8 @@ use crate::analysis::bind::Assigner;
 use crate::analysis::bind::Bind;
 use crate::analysis::bind::Scope;
 use crate::codemap::CodeMap;
+use crate::codemap::ColumnUnit;
+use crate::codemap::LineCol;
 use crate::codemap::Pos;
 use crate::codemap::ResolvedSpan;
 use crate::codemap::Span;
@@ -41,7 +43,9 @@ use crate::syntax::AstModule;
 pub enum IdentifierDefinition {
     /// The definition was found at this location in the current file.
     Location {
+        /// The identifier that was looked up.
         source: ResolvedSpan,
+        /// Where it is defined.
         destination: ResolvedSpan,
     },
     /// The symbol was loaded from another file. "destination" is the position within the
@@ -49,23 +53,39 @@ pub enum IdentifierDefinition {
     /// name of the symbol within that file are provided so that additional lookups can
     /// happen to find the original definition.
     LoadedLocation {
+        /// The identifier that was looked up.
         source: ResolvedSpan,
+        /// The symbol in the `load()` statement.
         destination: ResolvedSpan,
+        /// The path of the loaded module, as written in the `load()` statement.
         path: String,
+        /// The name of the symbol in the loaded module.
         name: String,
     },
     /// The symbol is the path component of a `load` statement. This is the raw string
     /// that is in the AST, and needs to be properly resolved to a path to be useful.
-    LoadPath { source: ResolvedSpan, path: String },
+    LoadPath {
+        /// The path string.
+        source: ResolvedSpan,
+        /// The path, as written in the `load()` statement.
+        path: String,
+    },
     /// A literal string in the source code. This can be a standalone string, or part of e.g. a
     /// list, a dictionary, a return value, etc.
     StringLiteral {
+        /// The string literal.
         source: ResolvedSpan,
+        /// The value of the string.
         literal: String,
     },
     /// A named symbol was found, but it was not found in any of the current scopes. It
     /// should be considered a global symbol, and attempted to be resolved externally.
-    Unresolved { source: ResolvedSpan, name: String },
+    Unresolved {
+        /// The identifier that was looked up.
+        source: ResolvedSpan,
+        /// The name of the identifier.
+        name: String,
+    },
     /// Either the provided location was not an access of a variable, or no definition
     /// could be found.
     NotFound,
@@ -159,7 +179,9 @@ impl<'a> From<TempDottedDefinition<'a>> for TempDefinition<'a> {
 /// for ones that access members (via '.' syntax)
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Definition {
+    /// A standalone identifier, e.g. `x`.
     Identifier(IdentifierDefinition),
+    /// An identifier in a dot expression, e.g. `y` in `x.y`.
     Dotted(DottedDefinition),
 }
 
@@ -203,6 +225,9 @@ impl Definition {
 
 /// Container that holds an AST module and returns things like definition locations,
 /// lists of symbols, etc.
+///
+/// Columns, both given and returned, are counted in UTF-16 code units, as in the
+/// Language Server Protocol.
 pub(crate) struct LspModule {
     pub(crate) ast: AstModule,
 }
@@ -212,33 +237,190 @@ impl LspModule {
         Self { ast }
     }
 
+    fn resolve_span(&self, span: Span) -> ResolvedSpan {
+        self.ast.codemap.resolve_span_in(span, ColumnUnit::Utf16)
+    }
+
     /// Attempts to find the location where a symbol is defined in the module.
     ///
-    /// `line` and `col` are zero based indexes of a location of the symbol to attempt to lookup.
+    /// `line` and `col` are zero based indexes of a location of the symbol to attempt to lookup,
+    /// with `col` in UTF-16 code units.
     ///
     /// This method also handles scoping properly (i.e. an access of "foo" in a function
     /// will return location of the parameter "foo", even if there is a global called "foo").
     pub(crate) fn find_definition(&self, line: u32, col: u32) -> Definition {
+        let line_col = LineCol {
+            line: line as usize,
+            column: col as usize,
+        };
+        let offset = self
+            .ast
+            .codemap
+            .line_col_to_offset(line_col, ColumnUnit::Utf16);
+        self.ast
+            .find_definition_at(Pos::new(offset as u32), ColumnUnit::Utf16)
+    }
+
+    /// Attempt to find the location in this module where an exported symbol is defined.
+    pub(crate) fn find_exported_symbol(&self, name: &str) -> Option<ResolvedSpan> {
+        self.ast
+            .exported_symbols()
+            .iter()
+            .find_map(|(span, symbol)| {
+                if *symbol == name {
+                    Some(span.resolve_span_in(ColumnUnit::Utf16))
+                } else {
+                    None
+                }
+            })
+    }
+
+    /// Attempt to find the location in this module where a member of a struct (named `name`)
+    /// is defined.
+    ///
+    /// This helps with the common idiom of
+    /// ```python
+    /// ...
+    /// Foo = struct(
+    ///     member1 = _member1,
+    /// )
+    /// ```
+    ///
+    /// which is imported by other files and used as `Foo.member1`. Rather than jumping to `Foo`,
+    /// this would jump to `_member1` if it exists.
+    pub(crate) fn find_exported_symbol_and_member(
+        &self,
+        name: &str,
+        member: &str,
+    ) -> Option<ResolvedSpan> {
+        // If we can't fully resolve the symbol, try to get as "close" as possible.
+        // Whether this is the left hand side of an assign statement, or maybe the
+        // left side of a named argument in the struct that matches the member name.
+        let mut arg_span = None;
+        let mut identifier_span = None;
+        let mut symbol_to_lookup = None;
+
+        self.ast.statement.visit_stmt(|v| {
+            if symbol_to_lookup.is_some() {
+                return;
+            }
+
+            if let StmtP::Assign(l, r) = &v.node {
+                let main_assign_span = match &l.node {
+                    AssignP::Identifier(main_assign_id) if main_assign_id.0 == name => {
+                        main_assign_id.span
+                    }
+                    _ => {
+                        return;
+                    }
+                };
+                // If nothing else, go to the left hand of the assignment expression.
+                if identifier_span.is_none() {
+                    identifier_span = Some(main_assign_span);
+                }
+
+                // Look for a function call to `struct`.
+                if let ExprP::Call(function_name, args) = &r.node {
+                    match &function_name.node {
+                        ExprP::Identifier(function_name, _) if function_name.node == "struct" => {}
+                        _ => {
+                            return;
+                        }
+                    }
+
+                    for arg in args {
+                        if let ArgumentP::Named(arg_name, arg_expr) = &arg.node {
+                            if arg_name.node != member {
+                                continue;
+                            }
+                            if arg_span.is_none() {
+                                arg_span = Some(arg_name.span);
+                            }
+                            if let ExprP::Identifier(arg_value_id, _) = &arg_expr.node {
+                                symbol_to_lookup = Some(arg_value_id.span);
+                                return;
+                            }
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+
+        // Try to find the symbol that is assigned, but if not, try to get to that "closest" span.
+        symbol_to_lookup
+            .and_then(|span| {
+                let resolved = self.resolve_span(span);
+                self.find_definition(resolved.begin_line as u32, resolved.begin_column as u32)
+                    .local_destination()
+            })
+            .or_else(|| match (arg_span, identifier_span) {
+                (Some(span), _) => Some(self.resolve_span(span)),
+                (None, Some(span)) => Some(self.resolve_span(span)),
+                (None, None) => None,
+            })
+    }
+}
+
+impl AstModule {
+    /// Find where the symbol at `pos` is defined: a local variable, a function parameter,
+    /// a top-level definition, or a symbol from a `load()`, in which case the
+    /// [`LoadedLocation`](IdentifierDefinition::LoadedLocation) also has the path of
+    /// the loaded module and the name of the symbol in it.
+    ///
+    /// Lines and columns, both of `pos` and in the result, are 0-based with columns counted in
+    /// Unicode scalar values, as in [`offset_to_line_col`](AstModule::offset_to_line_col).
+    /// Returns `None` if there is no symbol at `pos`.
+    ///
+    /// ```
+    /// use starlark::codemap::LineCol;
+    /// use starlark::syntax::{AstModule, Definition, Dialect, IdentifierDefinition};
+    ///
+    /// let program = "load('foo.star', bar = 'baz')\ndef f(x):\n    return bar(x)\n";
+    /// let ast = AstModule::parse("x.star", program.to_owned(), &Dialect::Standard).unwrap();
+    /// match ast.find_definition(LineCol { line: 2, column: 11 }) {
+    ///     Some(Definition::Identifier(IdentifierDefinition::LoadedLocation { path, name, .. })) => {
+    ///         assert_eq!(("foo.star", "baz"), (path.as_str(), name.as_str()));
+    ///     }
+    ///     x => panic!("unexpected definition: {:?}", x),
+    /// }
+    /// match ast.find_definition(LineCol { line: 2, column: 15 }) {
+    ///     Some(Definition::Identifier(IdentifierDefinition::Location { destination, .. })) => {
+    ///         assert_eq!((1, 6), (destination.begin_line, destination.begin_column));
+    ///     }
+    ///     x => panic!("unexpected definition: {:?}", x),
+    /// }
+    /// assert_eq!(None, ast.find_definition(LineCol { line: 1, column: 0 }));
+    /// ```
+    pub fn find_definition(&self, pos: LineCol) -> Option<Definition> {
+        let offset = self.codemap.line_col_to_offset(pos, ColumnUnit::Char);
+        match self.find_definition_at(Pos::new(offset as u32), ColumnUnit::Char) {
+            Definition::Identifier(IdentifierDefinition::NotFound) => None,
+            x => Some(x),
+        }
+    }
+
+    /// Find where the symbol at `current_pos` is defined, resolving spans with columns in `unit`s.
+    fn find_definition_at(&self, current_pos: Pos, unit: ColumnUnit) -> Definition {
         // TODO(nmj): This should probably just store references to all of the AST nodes
         //            when the LSPModule object is created, and then we can do a much faster
         //            lookup, especially in cases where a file has not been changed, so the
         //            LSPModule doesn't need to reparse anything.
 
-        let scope = scope(&self.ast);
-        let line_span = self.ast.codemap.line_span(line as usize);
-        let current_pos = std::cmp::min(line_span.begin() + col, line_span.end());
+        let scope = scope(self);
 
         // Finalize the results after recursing down from and back up to the the top level scope.
         match Self::find_definition_in_scope(&scope, current_pos) {
             TempDefinition::Identifier(def) => self
-                .get_definition_location(def, &scope, current_pos)
+                .get_definition_location(def, &scope, current_pos, unit)
                 .into(),
             TempDefinition::Dotted(def) => DottedDefinition {
-                source: self.ast.codemap.resolve_span(def.source),
+                source: self.codemap.resolve_span_in(def.source, unit),
                 root_definition_location: self.get_definition_location(
                     def.root_definition_location,
                     &scope,
                     current_pos,
+                    unit,
                 ),
                 segments: def.segments.iter().map(|s| s.node.to_owned()).collect(),
             }
@@ -350,153 +532,56 @@ impl LspModule {
         definition: TempIdentifierDefinition,
         scope: &Scope,
         current_pos: Pos,
+        unit: ColumnUnit,
     ) -> IdentifierDefinition {
+        let resolve = |span: Span| self.codemap.resolve_span_in(span, unit);
         match definition {
             TempIdentifierDefinition::Location {
                 source,
                 destination,
             } => IdentifierDefinition::Location {
-                source: self.ast.codemap.resolve_span(source),
-                destination: self.ast.codemap.resolve_span(destination),
+                source: resolve(source),
+                destination: resolve(destination),
             },
             TempIdentifierDefinition::Name { source, name } => match scope.bound.get(name) {
                 None => IdentifierDefinition::Unresolved {
-                    source: self.ast.codemap.resolve_span(source),
+                    source: resolve(source),
                     name: name.to_owned(),
                 },
                 Some((Assigner::Load { path, name }, span)) => {
                     IdentifierDefinition::LoadedLocation {
-                        source: self.ast.codemap.resolve_span(source),
-                        destination: self.ast.codemap.resolve_span(*span),
+                        source: resolve(source),
+                        destination: resolve(*span),
                         path: path.node.clone(),
                         name: name.node.clone(),
                     }
                 }
                 Some((_, span)) => IdentifierDefinition::Location {
-                    source: self.ast.codemap.resolve_span(source),
-                    destination: self.ast.codemap.resolve_span(*span),
+                    source: resolve(source),
+                    destination: resolve(*span),
                 },
             },
             // If we could not find the symbol, see if the current position is within
             // a load statement (these are not exposed as Get/Set in bind).
-            TempIdentifierDefinition::NotFound => self.find_definition_from_ast(current_pos),
+            TempIdentifierDefinition::NotFound => self.find_definition_from_ast(current_pos, unit),
             TempIdentifierDefinition::LoadedLocation {
                 source,
                 destination,
                 path,
                 name,
             } => IdentifierDefinition::LoadedLocation {
-                source: self.ast.codemap.resolve_span(source),
-                destination: self.ast.codemap.resolve_span(destination),
+                source: resolve(source),
+                destination: resolve(destination),
                 path: path.to_owned(),
                 name: name.to_owned(),
             },
         }
     }
 
-    /// Attempt to find the location in this module where an exported symbol is defined.
-    pub(crate) fn find_exported_symbol(&self, name: &str) -> Option<ResolvedSpan> {
-        self.ast
-            .exported_symbols()
-            .iter()
-            .find_map(|(span, symbol)| {
-                if *symbol == name {
-                    Some(span.resolve_span())
-                } else {
-                    None
-                }
-            })
-    }
-
-    /// Attempt to find the location in this module where a member of a struct (named `name`)
-    /// is defined.
-    ///
-    /// This helps with the common idiom of
-    /// ```python
-    /// ...
-    /// Foo = struct(
-    ///     member1 = _member1,
-    /// )
-    /// ```
-    ///
-    /// which is imported by other files and used as `Foo.member1`. Rather than jumping to `Foo`,
-    /// this would jump to `_member1` if it exists.
-    pub(crate) fn find_exported_symbol_and_member(
-        &self,
-        name: &str,
-        member: &str,
-    ) -> Option<ResolvedSpan> {
-        // If we can't fully resolve the symbol, try to get as "close" as possible.
-        // Whether this is the left hand side of an assign statement, or maybe the
-        // left side of a named argument in the struct that matches the member name.
-        let mut arg_span = None;
-        let mut identifier_span = None;
-        let mut symbol_to_lookup = None;
-
-        self.ast.statement.visit_stmt(|v| {
-            if symbol_to_lookup.is_some() {
-                return;
-            }
-
-            if let StmtP::Assign(l, r) = &v.node {
-                let main_assign_span = match &l.node {
-                    AssignP::Identifier(main_assign_id) if main_assign_id.0 == name => {
-                        main_assign_id.span
-                    }
-                    _ => {
-                        return;
-                    }
-                };
-                // If nothing else, go to the left hand of the assignment expression.
-                if identifier_span.is_none() {
-                    identifier_span = Some(main_assign_span);
-                }
-
-                // Look for a function call to `struct`.
-                if let ExprP::Call(function_name, args) = &r.node {
-                    match &function_name.node {
-                        ExprP::Identifier(function_name, _) if function_name.node == "struct" => {}
-                        _ => {
-                            return;
-                        }
-                    }
-
-                    for arg in args {
-                        if let ArgumentP::Named(arg_name, arg_expr) = &arg.node {
-                            if arg_name.node != member {
-                                continue;
-                            }
-                            if arg_span.is_none() {
-                                arg_span = Some(arg_name.span);
-                            }
-                            if let ExprP::Identifier(arg_value_id, _) = &arg_expr.node {
-                                symbol_to_lookup = Some(arg_value_id.span);
-                                return;
-                            }
-                            break;
-                        }
-                    }
-                }
-            }
-        });
-
-        // Try to find the symbol that is assigned, but if not, try to get to that "closest" span.
-        symbol_to_lookup
-            .and_then(|span| {
-                let resolved = self.ast.codemap.resolve_span(span);
-                self.find_definition(resolved.begin_line as u32, resolved.begin_column as u32)
-                    .local_destination()
-            })
-            .or_else(|| match (arg_span, identifier_span) {
-                (Some(span), _) => Some(self.ast.codemap.resolve_span(span)),
-                (None, Some(span)) => Some(self.ast.codemap.resolve_span(span)),
-                (None, None) => None,
-            })
-    }
-
-    fn find_definition_from_ast(&self, pos: Pos) -> IdentifierDefinition {
+    fn find_definition_from_ast(&self, pos: Pos, unit: ColumnUnit) -> IdentifierDefinition {
         fn visit_node(
             codemap: &CodeMap,
+            unit: ColumnUnit,
             pos: Pos,
             ret: &mut Option<IdentifierDefinition>,
             node: Visit<AstNoPayload>,
@@ -504,13 +589,14 @@ impl LspModule {
             if ret.is_some() {
                 return;
             }
+            let resolve = |span: Span| codemap.resolve_span_in(span, unit);
             match node {
                 Visit::Expr(Spanned {
                     node: Expr::Literal(AstLiteral::String(s)),
                     ..
                 }) if s.span.contains(pos) => {
                     *ret = Some(IdentifierDefinition::StringLiteral {
-                        source: codemap.resolve_span(s.span),
+                        source: resolve(s.span),
                         literal: s.node.to_owned(),
                     });
                 }
@@ -520,15 +606,15 @@ impl LspModule {
                 }) => {
                     *ret = if load.module.span.contains(pos) {
                         Some(IdentifierDefinition::LoadPath {
-                            source: codemap.resolve_span(load.module.span),
+                            source: resolve(load.module.span),
                             path: load.module.node.to_owned(),
                         })
                     } else {
                         load.args.iter().find_map(|(assign, name)| {
                             if assign.span.contains(pos) || name.span.contains(pos) {
                                 Some(IdentifierDefinition::LoadedLocation {
-                                    source: codemap.resolve_span(name.span),
-                                    destination: codemap.resolve_span(name.span),
+                                    source: resolve(name.span),
+                                    destination: resolve(name.span),
                                     path: load.module.node.to_owned(),
                                     name: name.node.to_owned(),
                                 })
@@ -538,28 +624,31 @@ impl LspModule {
                         })
                     }
                 }
-                v => v.visit_children(|node| visit_node(codemap, pos, ret, node)),
+                v => v.visit_children(|node| visit_node(codemap, unit, pos, ret, node)),
             }
         }
 
         let mut ret = None;
         visit_node(
-            &self.ast.codemap,
+            &self.codemap,
+            unit,
             pos,
             &mut ret,
-            Visit::Stmt(&self.ast.statement),
+            Visit::Stmt(&self.statement),
         );
         ret.unwrap_or(IdentifierDefinition::NotFound)
     }
-}
 
-impl AstModule {
     /// Find the location of a top level function call that has a kwarg "name", and a string value
-    /// matching `name`.
+    /// matching `name`, resolving the span with columns in `unit`s.
     ///
     /// NOTE: If the AST is exposed in the future, this function may be removed and implemented
     ///       by specific programs instead.
-    pub fn find_function_call_with_name(&self, name: &str) -> Option<ResolvedSpan> {
+    pub fn find_function_call_with_name(
+        &self,
+        name: &str,
+        unit: ColumnUnit,
+    ) -> Option<ResolvedSpan> {
         let mut ret = None;
 
         fn visit_node(ret: &mut Option<Span>, name: &str, node: Visit<AstNoPayload>) {
@@ -594,7 +683,7 @@ impl AstModule {
         }
 
         visit_node(&mut ret, name, Visit::Stmt(&self.statement));
-        ret.map(|span| self.codemap.resolve_span(span))
+        ret.map(|span| self.codemap.resolve_span_in(span, unit))
     }
 }
 
@@ -607,6 +696,7 @@ pub(crate) mod helpers {
 
     use crate::analysis::LspModule;
     use crate::codemap::CodeMap;
+    use crate::codemap::ColumnUnit;
     use crate::codemap::Pos;
     use crate::codemap::ResolvedSpan;
     use crate::codemap::Span;
@@ -692,7 +782,7 @@ pub(crate) mod helpers {
                         Pos::new(start.unwrap() as u32),
                         Pos::new(end.unwrap() as u32),
                     );
-                    (id, code_map.resolve_span(span))
+                    (id, code_map.resolve_span_in(span, ColumnUnit::Utf16))
                 })
                 .collect();
 
@@ -803,6 +893,7 @@ mod test {
     use crate::analysis::Definition;
     use crate::analysis::IdentifierDefinition;
     use crate::analysis::LspModule;
+    use crate::codemap::ColumnUnit;
 
     #[test]
     fn find_definition_loaded_symbol() -> anyhow::Result<()> {
@@ -1005,6 +1096,31 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn find_definition_utf16_columns() -> anyhow::Result<()> {
+        let contents = dedent(
+            r#"
+        <x>x</x> = 1
+        y = ("🔬é", <x_var>x</x_var>)
+        "#,
+        )
+        .trim()
+        .to_owned();
+        let parsed = FixtureWithRanges::from_fixture("foo.star", &contents)?;
+        let module = parsed.module()?;
+
+        // The emoji is two UTF-16 code units.
+        assert_eq!(12, parsed.begin_column("x_var"));
+        assert_eq!(
+            Definition::from(IdentifierDefinition::Location {
+                source: parsed.span("x_var"),
+                destination: parsed.span("x")
+            }),
+            module.find_definition(parsed.begin_line("x_var"), parsed.begin_column("x_var"))
+        );
+        Ok(())
+    }
+
     #[test]
     fn find_definition_unknown_clicks() -> anyhow::Result<()> {
         let contents = dedent(
@@ -1127,6 +1243,7 @@ mod test {
             <foo>foo</foo>(name = "foo_name")
             bar("bar_name")
             baz(name = "baz_name")
+            x = "🔬é"; <qux>qux</qux>(name = "qux_name")
 
             def x(name = "foo_name"):
                 pass
@@ -1140,9 +1257,24 @@ mod test {
         let module = parsed.module()?;
         assert_eq!(
             Some(parsed.span("foo")),
-            module.ast.find_function_call_with_name("foo_name")
+            module
+                .ast
+                .find_function_call_with_name("foo_name", ColumnUnit::Utf16)
+        );
+        assert_eq!(
+            None,
+            module
+                .ast
+                .find_function_call_with_name("bar_name", ColumnUnit::Utf16)
+        );
+        // The emoji is two UTF-16 code units.
+        assert_eq!(11, parsed.begin_column("qux"));
+        assert_eq!(
+            Some(parsed.span("qux")),
+            module
+                .ast
+                .find_function_call_with_name("qux_name", ColumnUnit::Utf16)
         );
-        assert_eq!(None, module.ast.find_function_call_with_name("bar_name"));
         Ok(())
     }
 
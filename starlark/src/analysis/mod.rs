@@ -15,20 +15,25 @@
  * limitations under the License.
  */
 
+pub use completion::Completion;
+pub use completion::CompletionKind;
 #[cfg(all(test, not(windows)))]
 pub(crate) use definition::helpers::FixtureWithRanges;
-pub(crate) use definition::Definition;
-pub(crate) use definition::DottedDefinition;
-pub(crate) use definition::IdentifierDefinition;
+pub use definition::Definition;
+pub use definition::DottedDefinition;
+pub use definition::IdentifierDefinition;
 pub(crate) use definition::LspModule;
 pub use types::EvalMessage;
 pub use types::EvalSeverity;
 pub use types::Lint;
 
 use crate::analysis::types::LintT;
+use crate::environment::Globals;
+use crate::errors::Diagnostic;
 use crate::syntax::AstModule;
 
 mod bind;
+mod completion;
 mod definition;
 mod dubious;
 mod exported;
@@ -36,6 +41,7 @@ mod flow;
 mod incompatible;
 mod names;
 mod performance;
+mod typecheck;
 mod types;
 
 impl AstModule {
@@ -59,4 +65,13 @@ impl AstModule {
         res.extend(performance::performance(self).into_iter().map(LintT::erase));
         res
     }
+
+    /// Run a best-effort static type checker over the module, using the type annotations
+    /// of parameters and return types, along with the types of literals and of the `globals`.
+    /// Only reports errors that are certain, such as passing a string to a parameter annotated
+    /// as `int.type`, or calling an int. Checking is not sound, so passing does not mean
+    /// evaluation will not fail with a type error.
+    pub fn typecheck(&self, globals: &Globals) -> Vec<Diagnostic> {
+        typecheck::typecheck(self, globals)
+    }
 }
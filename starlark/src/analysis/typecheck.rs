@@ -0,0 +1,695 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Best-effort static type checking, using type annotations and literals.
+//!
+//! The checker is deliberately unsound: whenever it is not sure about the type
+//! of an expression it assumes the expression could be anything, so it only
+//! reports errors which would definitely happen if the code was run.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Display;
+
+use thiserror::Error;
+
+use crate::codemap::CodeMap;
+use crate::codemap::Span;
+use crate::environment::Globals;
+use crate::errors::Diagnostic;
+use crate::eval::CallStack;
+use crate::syntax::ast::Argument;
+use crate::syntax::ast::AssignP;
+use crate::syntax::ast::AstArgument;
+use crate::syntax::ast::AstAssign;
+use crate::syntax::ast::AstExpr;
+use crate::syntax::ast::AstLiteral;
+use crate::syntax::ast::AstParameter;
+use crate::syntax::ast::AstStmt;
+use crate::syntax::ast::BinOp;
+use crate::syntax::ast::Clause;
+use crate::syntax::ast::Expr;
+use crate::syntax::ast::ForClause;
+use crate::syntax::ast::Parameter;
+use crate::syntax::ast::Stmt;
+use crate::syntax::uniplate::Visit;
+use crate::syntax::AstModule;
+use crate::values::Heap;
+
+#[derive(Error, Debug)]
+pub(crate) enum TypecheckError {
+    #[error("Expression `{0}` of type `{1}` does not match the type annotation `{2}` for {3}")]
+    TypeAnnotationMismatch(String, Ty, String, String),
+    #[error("Expression `{0}` of type `{1}` is not callable")]
+    NotCallable(String, Ty),
+}
+
+/// Types whose values can never be called.
+const NOT_CALLABLE: &[&str] = &[
+    "NoneType", "bool", "int", "float", "string", "list", "dict", "tuple", "range",
+];
+
+/// The statically known type of an expression.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Ty {
+    /// Nothing is known about the value.
+    Any,
+    /// The value has one of these types, as returned by `type()`.
+    OneOf(Vec<String>),
+}
+
+impl Ty {
+    fn name(x: &str) -> Ty {
+        Ty::OneOf(vec![x.to_owned()])
+    }
+
+    /// Parse a string type annotation, mirroring the runtime interpretation.
+    fn from_str(x: &str) -> Ty {
+        if x.is_empty() || x.starts_with('_') {
+            Ty::Any
+        } else {
+            Ty::name(x)
+        }
+    }
+
+    fn union(self, other: Ty) -> Ty {
+        match (self, other) {
+            (Ty::OneOf(mut xs), Ty::OneOf(ys)) => {
+                for y in ys {
+                    if !xs.contains(&y) {
+                        xs.push(y);
+                    }
+                }
+                Ty::OneOf(xs)
+            }
+            _ => Ty::Any,
+        }
+    }
+
+    /// Is it certain that no value of this type is also of the other type.
+    fn disjoint(&self, other: &Ty) -> bool {
+        match (self, other) {
+            (Ty::OneOf(xs), Ty::OneOf(ys)) => !xs.iter().any(|x| ys.contains(x)),
+            _ => false,
+        }
+    }
+
+    fn is_callable(&self) -> bool {
+        match self {
+            Ty::Any => true,
+            Ty::OneOf(xs) => xs.iter().any(|x| !NOT_CALLABLE.contains(&x.as_str())),
+        }
+    }
+}
+
+impl Display for Ty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ty::Any => write!(f, "_"),
+            Ty::OneOf(xs) => write!(f, "{}", xs.join(" or ")),
+        }
+    }
+}
+
+/// A parameter of a `def`, as far as call checking is concerned.
+struct Param<'a> {
+    name: &'a str,
+    ty: Ty,
+    annotation: Option<&'a AstExpr>,
+    /// Can this parameter be passed positionally.
+    positional: bool,
+}
+
+struct Signature<'a> {
+    params: Vec<Param<'a>>,
+    ret: Ty,
+}
+
+enum Binding<'a> {
+    Value(Ty),
+    Def(Signature<'a>),
+}
+
+/// A local scope, either a `def`, a `lambda` or a comprehension.
+struct Scope<'a> {
+    names: HashMap<&'a str, Ty>,
+    /// The annotated return type, only for `def`.
+    ret: Option<(Ty, &'a AstExpr)>,
+}
+
+struct Typecheck<'a> {
+    codemap: &'a CodeMap,
+    globals: &'a Globals,
+    heap: Heap,
+    /// How many times each name is bound at the module level.
+    bound: HashMap<&'a str, usize>,
+    /// Module-level names bound exactly once, in a way we understand.
+    module: HashMap<&'a str, Binding<'a>>,
+    scopes: Vec<Scope<'a>>,
+    res: Vec<Diagnostic>,
+}
+
+/// Names bound by an assignment target.
+fn assign_names<'a>(x: &'a AstAssign, res: &mut Vec<&'a str>) {
+    match &**x {
+        AssignP::Identifier(x) => res.push(&x.0),
+        AssignP::Tuple(xs) => xs.iter().for_each(|x| assign_names(x, res)),
+        AssignP::Starred(x) => assign_names(x, res),
+        AssignP::ArrayIndirection(_) | AssignP::Slice(..) | AssignP::Dot(..) => {}
+    }
+}
+
+/// Names bound by a statement in the current scope, not looking inside nested `def`s.
+fn stmt_names<'a>(x: &'a AstStmt, res: &mut Vec<&'a str>) {
+    match &**x {
+        Stmt::Assign(lhs, _) | Stmt::AssignModify(lhs, _, _) => assign_names(lhs, res),
+        Stmt::For(var, box (_, body)) => {
+            assign_names(var, res);
+            stmt_names(body, res);
+        }
+        Stmt::Def(name, ..) => res.push(&name.0),
+        Stmt::Load(load) => res.extend(load.args.iter().map(|(x, _)| x.0.as_str())),
+        _ => x.visit_stmt(|x| stmt_names(x, res)),
+    }
+}
+
+impl<'a> Typecheck<'a> {
+    fn error(&mut self, span: Span, err: TypecheckError) {
+        self.res.push(Diagnostic {
+            message: err.into(),
+            span: Some(self.codemap.file_span(span)),
+            call_stack: CallStack::default(),
+        });
+    }
+
+    fn is_local(&self, name: &str) -> bool {
+        self.scopes.iter().any(|s| s.names.contains_key(name))
+    }
+
+    fn lookup(&self, name: &str) -> Ty {
+        if let Some(ty) = self.scopes.iter().rev().find_map(|s| s.names.get(name)) {
+            return ty.clone();
+        }
+        match self.module.get(name) {
+            Some(Binding::Value(ty)) => ty.clone(),
+            Some(Binding::Def(_)) => Ty::name("function"),
+            None if self.bound.contains_key(name) => Ty::Any,
+            None => match self.globals.get(name) {
+                Some(v) => Ty::name(v.get_type()),
+                None => Ty::Any,
+            },
+        }
+    }
+
+    /// The signature of the function being called, if it is a known module-level `def`.
+    fn signature(&self, f: &AstExpr) -> Option<&Signature<'a>> {
+        match &**f {
+            Expr::Identifier(name, ()) if !self.is_local(&name.node) => {
+                match self.module.get(name.node.as_str()) {
+                    Some(Binding::Def(sig)) => Some(sig),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Interpret a type annotation, following the rules of runtime type checking.
+    fn annotation(&self, x: &AstExpr) -> Ty {
+        match &**x {
+            Expr::Literal(AstLiteral::String(x)) => Ty::from_str(&x.node),
+            Expr::List(xs) => match xs.as_slice() {
+                [] => Ty::Any,
+                [_] => Ty::name("list"),
+                [x, xs @ ..] => xs
+                    .iter()
+                    .fold(self.annotation(x), |acc, x| acc.union(self.annotation(x))),
+            },
+            Expr::Dict(_) => Ty::name("dict"),
+            Expr::Tuple(_) => Ty::name("tuple"),
+            Expr::Identifier(name, ())
+                if !self.bound.contains_key(name.node.as_str()) && !self.is_local(&name.node) =>
+            {
+                match self.globals.get(&name.node) {
+                    Some(v) if v.is_none() => Ty::name("NoneType"),
+                    Some(v) => v.unpack_str().map_or(Ty::Any, Ty::from_str),
+                    None => Ty::Any,
+                }
+            }
+            // Things like `int.type`
+            Expr::Dot(box x, attr) if attr.node == "type" => match &**x {
+                Expr::Identifier(name, ())
+                    if !self.bound.contains_key(name.node.as_str())
+                        && !self.is_local(&name.node) =>
+                {
+                    match self.globals.get(&name.node) {
+                        Some(v) => match v.get_attr("type", &self.heap) {
+                            Ok(Some(t)) => t.unpack_str().map_or(Ty::Any, Ty::from_str),
+                            _ => Ty::Any,
+                        },
+                        None => Ty::Any,
+                    }
+                }
+                _ => Ty::Any,
+            },
+            _ => Ty::Any,
+        }
+    }
+
+    fn annotation_opt(&self, x: Option<&AstExpr>) -> Ty {
+        x.map_or(Ty::Any, |x| self.annotation(x))
+    }
+
+    /// Infer the type of an expression, using literals and annotations.
+    fn expr_ty(&self, x: &AstExpr) -> Ty {
+        match &**x {
+            Expr::Literal(AstLiteral::Int(_)) => Ty::name("int"),
+            Expr::Literal(AstLiteral::Float(_)) => Ty::name("float"),
+            Expr::Literal(AstLiteral::String(_)) => Ty::name("string"),
+            Expr::Tuple(_) => Ty::name("tuple"),
+            Expr::List(_) | Expr::ListComprehension(..) => Ty::name("list"),
+            Expr::Dict(_) | Expr::DictComprehension(..) => Ty::name("dict"),
+            Expr::Lambda(..) => Ty::name("function"),
+            Expr::Not(_) => Ty::name("bool"),
+            Expr::Op(_, op, _) => match op {
+                BinOp::Equal
+                | BinOp::NotEqual
+                | BinOp::Less
+                | BinOp::Greater
+                | BinOp::LessOrEqual
+                | BinOp::GreaterOrEqual
+                | BinOp::In
+                | BinOp::NotIn => Ty::name("bool"),
+                _ => Ty::Any,
+            },
+            Expr::If(box (_, a, b)) => self.expr_ty(a).union(self.expr_ty(b)),
+            Expr::Identifier(name, ()) => self.lookup(&name.node),
+            Expr::Call(f, _) => self.signature(f).map_or(Ty::Any, |sig| sig.ret.clone()),
+            _ => Ty::Any,
+        }
+    }
+
+    fn check_value(&mut self, x: &AstExpr, expected: &Ty, annotation: &AstExpr, what: String) {
+        let ty = self.expr_ty(x);
+        if ty.disjoint(expected) {
+            self.error(
+                x.span,
+                TypecheckError::TypeAnnotationMismatch(
+                    x.to_string(),
+                    ty,
+                    annotation.to_string(),
+                    what,
+                ),
+            );
+        }
+    }
+
+    fn signature_of(&self, params: &'a [AstParameter], ret: Option<&'a AstExpr>) -> Signature<'a> {
+        let mut positional = true;
+        let mut res = Vec::new();
+        for p in params {
+            match &**p {
+                Parameter::Normal(name, ty) | Parameter::WithDefaultValue(name, ty, _) => {
+                    let annotation = ty.as_deref();
+                    res.push(Param {
+                        name: &name.0,
+                        ty: self.annotation_opt(annotation),
+                        annotation,
+                        positional,
+                    })
+                }
+                Parameter::NoArgs | Parameter::Args(..) => positional = false,
+                Parameter::KwArgs(..) => {}
+            }
+        }
+        Signature {
+            params: res,
+            ret: self.annotation_opt(ret),
+        }
+    }
+
+    /// Record the module-level bindings, in statement order.
+    fn module_bindings(&mut self, x: &'a AstStmt) {
+        match &**x {
+            Stmt::Assign(lhs, box rhs) => {
+                if let AssignP::Identifier(name) = &**lhs {
+                    if self.bound.get(name.0.as_str()) == Some(&1) {
+                        let ty = self.expr_ty(rhs);
+                        self.module.insert(&name.0, Binding::Value(ty));
+                    }
+                }
+            }
+            Stmt::Def(name, params, ret, _, ()) => {
+                if self.bound.get(name.0.as_str()) == Some(&1) {
+                    let sig = self.signature_of(params, ret.as_deref());
+                    self.module.insert(&name.0, Binding::Def(sig));
+                }
+            }
+            _ => x.visit_stmt(|x| self.module_bindings(x)),
+        }
+    }
+
+    fn call(&mut self, x: &AstExpr, f: &AstExpr, args: &[AstArgument]) {
+        let ty = self.expr_ty(f);
+        if !ty.is_callable() {
+            self.error(x.span, TypecheckError::NotCallable(f.to_string(), ty));
+            return;
+        }
+        let sig = match self.signature(f) {
+            Some(sig) => sig,
+            None => return,
+        };
+        let mut errors = Vec::new();
+        let mut position = Some(0);
+        for arg in args {
+            let (param, arg) = match &**arg {
+                Argument::Positional(arg) => match position {
+                    Some(i) => {
+                        position = Some(i + 1);
+                        (sig.params.get(i).filter(|p| p.positional), arg)
+                    }
+                    None => continue,
+                },
+                Argument::Named(name, arg) => {
+                    (sig.params.iter().find(|p| p.name == name.node), arg)
+                }
+                Argument::Args(_) => {
+                    // We no longer know which parameter each positional argument binds to
+                    position = None;
+                    continue;
+                }
+                Argument::KwArgs(_) => continue,
+            };
+            if let Some(Param {
+                name,
+                ty,
+                annotation: Some(annotation),
+                ..
+            }) = param
+            {
+                let arg_ty = self.expr_ty(arg);
+                if arg_ty.disjoint(ty) {
+                    errors.push((
+                        arg.span,
+                        TypecheckError::TypeAnnotationMismatch(
+                            arg.to_string(),
+                            arg_ty,
+                            annotation.to_string(),
+                            format!("argument `{}`", name),
+                        ),
+                    ));
+                }
+            }
+        }
+        for (span, err) in errors {
+            self.error(span, err);
+        }
+    }
+
+    fn with_scope(&mut self, scope: Scope<'a>, f: impl FnOnce(&mut Self)) {
+        self.scopes.push(scope);
+        f(self);
+        self.scopes.pop();
+    }
+
+    fn comprehension(
+        &mut self,
+        for_: &'a ForClause,
+        clauses: &'a [Clause],
+        end: impl FnOnce(&mut Self),
+    ) {
+        let mut names = Vec::new();
+        assign_names(&for_.var, &mut names);
+        for clause in clauses {
+            if let Clause::For(ForClause { var, .. }) = clause {
+                assign_names(var, &mut names);
+            }
+        }
+        self.expr(&for_.over);
+        let scope = Scope {
+            names: names.into_iter().map(|x| (x, Ty::Any)).collect(),
+            ret: None,
+        };
+        self.with_scope(scope, |this| {
+            for clause in clauses {
+                match clause {
+                    Clause::For(ForClause { over, .. }) => this.expr(over),
+                    Clause::If(x) => this.expr(x),
+                }
+            }
+            end(this);
+        })
+    }
+
+    fn expr(&mut self, x: &'a AstExpr) {
+        match &**x {
+            Expr::Call(f, args) => {
+                self.call(x, f, args);
+                x.visit_expr(|x| self.expr(x));
+            }
+            Expr::Lambda(params, body, ()) => {
+                let mut names = HashMap::new();
+                for p in params {
+                    let (name, _, default) = p.split();
+                    if let Some(default) = default {
+                        self.expr(default);
+                    }
+                    if let Some(name) = name {
+                        names.insert(name.0.as_str(), Ty::Any);
+                    }
+                }
+                self.with_scope(Scope { names, ret: None }, |this| this.expr(body));
+            }
+            Expr::ListComprehension(e, for_, clauses) => {
+                self.comprehension(for_, clauses, |this| this.expr(e))
+            }
+            Expr::DictComprehension(box (k, v), for_, clauses) => {
+                self.comprehension(for_, clauses, |this| {
+                    this.expr(k);
+                    this.expr(v);
+                })
+            }
+            _ => x.visit_expr(|x| self.expr(x)),
+        }
+    }
+
+    fn def(&mut self, params: &'a [AstParameter], ret: Option<&'a AstExpr>, body: &'a AstStmt) {
+        let mut names = Vec::new();
+        stmt_names(body, &mut names);
+        let mut names: HashMap<&str, Ty> = names.into_iter().map(|x| (x, Ty::Any)).collect();
+        for p in params {
+            match &**p {
+                Parameter::Normal(name, ty) => {
+                    names.insert(&name.0, self.annotation_opt(ty.as_deref()));
+                }
+                Parameter::WithDefaultValue(name, ty, default) => {
+                    self.expr(default);
+                    let expected = self.annotation_opt(ty.as_deref());
+                    if let Some(ty) = ty {
+                        self.check_value(
+                            default,
+                            &expected,
+                            ty,
+                            format!("default of parameter `{}`", name.0),
+                        );
+                    }
+                    names.insert(&name.0, expected);
+                }
+                Parameter::Args(name, _) | Parameter::KwArgs(name, _) => {
+                    names.insert(&name.0, Ty::Any);
+                }
+                Parameter::NoArgs => {}
+            }
+        }
+        let ret = ret.map(|x| (self.annotation(x), x));
+        self.with_scope(Scope { names, ret }, |this| this.stmt(body));
+    }
+
+    fn stmt(&mut self, x: &'a AstStmt) {
+        match &**x {
+            Stmt::Def(_, params, ret, body, ()) => self.def(params, ret.as_deref(), body),
+            Stmt::Return(e) => {
+                if let Some(e) = e {
+                    self.expr(e);
+                }
+                if let Some((expected, annotation)) = self.scopes.last().and_then(|s| s.ret.clone())
+                {
+                    match e {
+                        Some(e) => {
+                            self.check_value(e, &expected, annotation, "return type".to_owned())
+                        }
+                        None if expected.disjoint(&Ty::name("NoneType")) => self.error(
+                            x.span,
+                            TypecheckError::TypeAnnotationMismatch(
+                                "None".to_owned(),
+                                Ty::name("NoneType"),
+                                annotation.to_string(),
+                                "return type".to_owned(),
+                            ),
+                        ),
+                        None => {}
+                    }
+                }
+            }
+            _ => x.visit_children(|x| match x {
+                Visit::Stmt(x) => self.stmt(x),
+                Visit::Expr(x) => self.expr(x),
+            }),
+        }
+    }
+}
+
+pub(crate) fn typecheck(module: &AstModule, globals: &Globals) -> Vec<Diagnostic> {
+    let mut names = Vec::new();
+    stmt_names(&module.statement, &mut names);
+    let mut bound = HashMap::new();
+    for name in names {
+        *bound.entry(name).or_insert(0) += 1;
+    }
+    let mut checker = Typecheck {
+        codemap: &module.codemap,
+        globals,
+        heap: Heap::new(),
+        bound,
+        module: HashMap::new(),
+        scopes: Vec::new(),
+        res: Vec::new(),
+    };
+    checker.module_bindings(&module.statement);
+    checker.stmt(&module.statement);
+    checker.res
+}
+
+#[cfg(test)]
+mod tests {
+    use gazebo::prelude::*;
+
+    use super::*;
+    use crate::syntax::Dialect;
+
+    fn typecheck_messages(x: &str) -> Vec<String> {
+        let m = AstModule::parse("X", x.to_owned(), &Dialect::Extended).unwrap();
+        m.typecheck(&Globals::standard())
+            .map(|d| format!("{}: {}", d.span.as_ref().unwrap().resolve_span(), d.message))
+    }
+
+    #[test]
+    fn test_typecheck_arguments() {
+        let res = typecheck_messages(
+            r#"
+def f(x: int.type, y: "string" = "", *args, z: [str.type, None] = None):
+    pass
+f("test")
+f(1, 2)
+f(1, y = [])
+f(1, z = 3)
+f(1, "a", 2, 3, z = "z")
+f(1, *["ok"])
+f(True if x else 1, y = 1 if x else "")
+"#,
+        );
+        assert_eq!(res.len(), 4, "{:?}", res);
+        assert!(res[0].contains("`\"test\"` of type `string`"), "{}", res[0]);
+        assert!(res[0].contains("`int.type` for argument `x`"), "{}", res[0]);
+        assert!(res[1].contains("`2` of type `int`"), "{}", res[1]);
+        assert!(res[2].contains("`[]` of type `list`"), "{}", res[2]);
+        assert!(res[3].contains("`3` of type `int`"), "{}", res[3]);
+        assert!(res[3].contains("argument `z`"), "{}", res[3]);
+    }
+
+    #[test]
+    fn test_typecheck_defaults_and_returns() {
+        let res = typecheck_messages(
+            r#"
+def f(x: int.type = "") -> str.type:
+    if x:
+        return 1
+    elif x == 2:
+        return
+    return "ok"
+def g(x) -> ["string", None]:
+    if x:
+        return
+    return x
+"#,
+        );
+        assert_eq!(res.len(), 3, "{:?}", res);
+        assert!(res[0].contains("default of parameter `x`"), "{}", res[0]);
+        assert!(res[1].contains("`1` of type `int`"), "{}", res[1]);
+        assert!(res[2].contains("`None` of type `NoneType`"), "{}", res[2]);
+    }
+
+    #[test]
+    fn test_typecheck_not_callable() {
+        let res = typecheck_messages(
+            r#"
+x = 1
+y = 1
+y = len
+z = "a" if y else None
+def f(x, y: str.type):
+    x()
+    y()
+[v() for v in [len]]
+x()
+y()
+z()
+"foo"()
+None()
+"#,
+        );
+        assert_eq!(res.len(), 5, "{:?}", res);
+        assert!(
+            res[0].contains("`y` of type `string` is not callable"),
+            "{}",
+            res[0]
+        );
+        assert!(
+            res[1].contains("`x` of type `int` is not callable"),
+            "{}",
+            res[1]
+        );
+        assert!(
+            res[2].contains("`z` of type `string or NoneType`"),
+            "{}",
+            res[2]
+        );
+        assert!(res[3].contains("`\"foo\"` of type `string`"), "{}", res[3]);
+        assert!(res[4].contains("`None` of type `NoneType`"), "{}", res[4]);
+    }
+
+    #[test]
+    fn test_typecheck_shadowing() {
+        // Names rebound locally or at the module level are not trusted.
+        let res = typecheck_messages(
+            r#"
+def f(x: int.type):
+    pass
+def g(f):
+    f("test")
+def h(x):
+    len = 1
+    len(x)
+int = str
+def k(x: int.type):
+    pass
+k("test")
+"#,
+        );
+        assert!(res.is_empty(), "{:?}", res);
+    }
+}
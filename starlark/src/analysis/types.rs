@@ -28,6 +28,7 @@ use lsp_types::Range;
 use serde::Serialize;
 
 use crate::codemap::CodeMap;
+use crate::codemap::ColumnUnit;
 use crate::codemap::FileSpan;
 use crate::codemap::ResolvedSpan;
 use crate::codemap::Span;
@@ -61,6 +62,63 @@ pub struct Lint {
     pub original: String,
 }
 
+/// Span of a [`Lint`] in JSON output.
+#[derive(Serialize)]
+struct LintJsonSpan {
+    begin_line: usize,
+    begin_column: usize,
+    end_line: usize,
+    end_column: usize,
+    begin_offset: u32,
+    end_offset: u32,
+}
+
+/// JSON representation of a [`Lint`].
+#[derive(Serialize)]
+struct LintJson<'a> {
+    path: &'a str,
+    severity: EvalSeverity,
+    rule_id: &'a str,
+    message: &'a str,
+    span: LintJsonSpan,
+}
+
+impl Lint {
+    fn severity(&self) -> EvalSeverity {
+        if self.serious {
+            EvalSeverity::Warning
+        } else {
+            // Start with all non-serious errors disabled, and ramp up from there
+            EvalSeverity::Disabled
+        }
+    }
+
+    /// Serialize this lint as a JSON object with fields
+    /// `path`, `severity`, `rule_id` (same as [`short_name`](Lint::short_name)), `message`
+    /// and `span`.
+    ///
+    /// `span` contains 0-based `begin_line`, `begin_column`, `end_line`, `end_column`
+    /// and byte offsets `begin_offset` and `end_offset` within the file.
+    pub fn to_json(&self) -> String {
+        let resolved = self.location.resolve_span();
+        let json = LintJson {
+            path: self.location.filename(),
+            severity: self.severity(),
+            rule_id: &self.short_name,
+            message: &self.problem,
+            span: LintJsonSpan {
+                begin_line: resolved.begin_line,
+                begin_column: resolved.begin_column,
+                end_line: resolved.end_line,
+                end_column: resolved.end_column,
+                begin_offset: self.location.span.begin().get(),
+                end_offset: self.location.span.end().get(),
+            },
+        };
+        serde_json::to_string(&json).unwrap()
+    }
+}
+
 impl Display for Lint {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}: {}", self.location, self.problem)
@@ -162,6 +220,12 @@ impl Display for EvalMessage {
 impl EvalMessage {
     /// Convert from an `anyhow::Error`, including some type checking, to an `EvalMessage`
     pub fn from_anyhow(file: &Path, x: &anyhow::Error) -> Self {
+        Self::from_anyhow_in(file, x, ColumnUnit::Char)
+    }
+
+    /// Like [`from_anyhow`](EvalMessage::from_anyhow), but with the columns of the span
+    /// counted in `unit`s. Language servers should use [`ColumnUnit::Utf16`].
+    pub fn from_anyhow_in(file: &Path, x: &anyhow::Error, unit: ColumnUnit) -> Self {
         match x.downcast_ref::<StarlarkDiagnostic>() {
             Some(
                 d @ StarlarkDiagnostic {
@@ -171,7 +235,7 @@ impl EvalMessage {
                 },
             ) => {
                 let original = span.source_span().to_owned();
-                let resolved_span = span.resolve_span();
+                let resolved_span = span.resolve_span_in(unit);
                 Self {
                     path: span.filename().to_owned(),
                     span: Some(resolved_span),
@@ -193,19 +257,14 @@ impl EvalMessage {
             },
         }
     }
-}
 
-impl From<Lint> for EvalMessage {
-    fn from(x: Lint) -> Self {
+    /// Convert a [`Lint`] to an `EvalMessage`, with the columns of the span counted in `unit`s.
+    /// The [`From`] conversion counts [`ColumnUnit::Char`]s.
+    pub fn from_lint_in(x: Lint, unit: ColumnUnit) -> Self {
         Self {
             path: x.location.filename().to_owned(),
-            span: Some(x.location.resolve_span()),
-            severity: if x.serious {
-                EvalSeverity::Warning
-            } else {
-                // Start with all non-serious errors disabled, and ramp up from there
-                EvalSeverity::Disabled
-            },
+            span: Some(x.location.resolve_span_in(unit)),
+            severity: x.severity(),
             name: x.short_name,
             description: x.problem,
             full_error_with_span: None,
@@ -214,6 +273,12 @@ impl From<Lint> for EvalMessage {
     }
 }
 
+impl From<Lint> for EvalMessage {
+    fn from(x: Lint) -> Self {
+        Self::from_lint_in(x, ColumnUnit::Char)
+    }
+}
+
 impl From<EvalMessage> for Diagnostic {
     fn from(x: EvalMessage) -> Self {
         let range = match x.span {
@@ -252,6 +317,8 @@ fn kebab(xs: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::syntax::AstModule;
+    use crate::syntax::Dialect;
 
     #[test]
     fn test_lint_kebab() {
@@ -266,4 +333,23 @@ mod tests {
             "duplicate-top-level-assign"
         );
     }
+
+    #[test]
+    fn test_lint_to_json() {
+        let m = AstModule::parse(
+            "foo.star",
+            "def f():\n    return 1\n    x = 2\n".to_owned(),
+            &Dialect::Extended,
+        )
+        .unwrap();
+        let lints = m.lint(None);
+        let lint = lints
+            .iter()
+            .find(|l| l.short_name == "unreachable")
+            .unwrap();
+        assert_eq!(
+            r#"{"path":"foo.star","severity":"warning","rule_id":"unreachable","message":"Unreachable statement `x = 2`","span":{"begin_line":2,"begin_column":4,"end_line":2,"end_column":9,"begin_offset":26,"end_offset":31}}"#,
+            lint.to_json()
+        );
+    }
 }
@@ -165,39 +165,10 @@ pub(crate) fn test_functions(builder: &mut GlobalsBuilder) {
         Ok(Struct::new(SmallMap::new()))
     }
 
-    // Approximate version of a method used by the Go test suite
-    fn set<'v>(xs: Value<'v>) -> anyhow::Result<Value<'v>> {
-        Ok(xs)
-    }
-
-    fn assert_eq<'v>(a: Value<'v>, b: Value<'v>) -> anyhow::Result<NoneType> {
-        assert_equals(a, b)
-    }
-
-    fn assert_ne<'v>(a: Value<'v>, b: Value<'v>) -> anyhow::Result<NoneType> {
-        assert_different(a, b)
-    }
-
     fn assert_lt<'v>(a: Value<'v>, b: Value<'v>) -> anyhow::Result<NoneType> {
         assert_less_than(a, b)
     }
 
-    fn assert_true(a: Value) -> anyhow::Result<NoneType> {
-        if !a.to_bool() {
-            Err(anyhow::anyhow!("assertion failed"))
-        } else {
-            Ok(NoneType)
-        }
-    }
-
-    fn assert_false(a: Value) -> anyhow::Result<NoneType> {
-        if a.to_bool() {
-            Err(anyhow::anyhow!("assertion failed"))
-        } else {
-            Ok(NoneType)
-        }
-    }
-
     // This is only safe to call at the top-level of a Starlark module
     fn garbage_collect(eval: &mut Evaluator) -> anyhow::Result<NoneType> {
         eval.trigger_gc();
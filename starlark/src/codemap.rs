@@ -45,6 +45,11 @@ impl Pos {
     pub const fn new(x: u32) -> Self {
         Self(x)
     }
+
+    /// Byte offset of this position.
+    pub const fn get(self) -> u32 {
+        self.0
+    }
 }
 
 impl Add<u32> for Pos {
@@ -81,11 +86,6 @@ impl Span {
         self.end
     }
 
-    /// The length in bytes of the text of the span
-    pub fn len(self) -> u32 {
-        self.end.0 - self.begin.0
-    }
-
     /// Create a span that encloses both `self` and `other`.
     pub fn merge(self, other: Span) -> Span {
         Span {
@@ -301,6 +301,10 @@ impl CodeMap {
     /// if `pos` points to a byte in the middle of a UTF-8 character.
     fn find_line_col(&self, pos: Pos) -> LineCol {
         assert!(pos <= self.full_span().end());
+        self.find_line_col_in(pos, ColumnUnit::Char)
+    }
+
+    fn find_line_col_in(&self, pos: Pos, unit: ColumnUnit) -> LineCol {
         match &self.0 {
             CodeMapImpl::Real(_) => {
                 let line = self.find_line(pos);
@@ -308,7 +312,8 @@ impl CodeMap {
                 let byte_col = pos.0 - line_span.begin.0;
                 let column = self.source_span(line_span)[..byte_col as usize]
                     .chars()
-                    .count();
+                    .map(|c| unit.width(c))
+                    .sum();
 
                 LineCol { line, column }
             }
@@ -319,6 +324,48 @@ impl CodeMap {
         }
     }
 
+    /// Gets the line and column of a byte offset, with the column counted in `unit`s.
+    ///
+    /// Offsets past the end of the file give the end of the file,
+    /// and offsets in the middle of a UTF-8 character give the start of that character.
+    pub(crate) fn offset_to_line_col(&self, offset: usize, unit: ColumnUnit) -> LineCol {
+        let source = self.source();
+        let mut offset = cmp::min(offset, source.len());
+        while !source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        self.find_line_col_in(Pos(offset as u32), unit)
+    }
+
+    /// Gets the byte offset of a line and column, with the column counted in `unit`s.
+    ///
+    /// As in the Language Server Protocol, columns past the end of a line give the end of
+    /// the line (before any `\r` or `\n`), and lines past the end of the file give the end
+    /// of the file. Columns in the middle of a character give the start of that character.
+    pub(crate) fn line_col_to_offset(&self, line_col: LineCol, unit: ColumnUnit) -> usize {
+        match &self.0 {
+            CodeMapImpl::Real(data) => {
+                let begin = match data.lines.get(line_col.line) {
+                    Some(begin) => begin.0 as usize,
+                    None => return data.source.len(),
+                };
+                let line = self.source_line(line_col.line);
+                let mut column = 0;
+                for (i, c) in line.char_indices() {
+                    column += unit.width(c);
+                    if column > line_col.column {
+                        return begin + i;
+                    }
+                }
+                begin + line.len()
+            }
+            CodeMapImpl::Native(data) => cmp::min(
+                line_col.column.saturating_sub(data.start.column),
+                NativeCodeMap::SOURCE.len(),
+            ),
+        }
+    }
+
     /// Gets the full source text of the file
     pub fn source(&self) -> &str {
         match &self.0 {
@@ -365,6 +412,14 @@ impl CodeMap {
         ResolvedSpan::from_span(begin, end)
     }
 
+    /// Like [`resolve_span`](CodeMap::resolve_span), but with columns counted in `unit`s.
+    pub(crate) fn resolve_span_in(&self, span: Span, unit: ColumnUnit) -> ResolvedSpan {
+        assert!(span.end <= self.full_span().end());
+        let begin = self.find_line_col_in(span.begin, unit);
+        let end = self.find_line_col_in(span.end, unit);
+        ResolvedSpan::from_span(begin, end)
+    }
+
     /// Gets the source text of a line.
     ///
     /// The string returned does not include the terminating \r or \n characters.
@@ -381,8 +436,10 @@ impl CodeMap {
 }
 
 /// A line and column.
+///
+/// Both are 0-based, but print out 1-based as `line:column`.
 #[derive(Copy, Clone, Dupe, Hash, Eq, PartialEq, Debug)]
-struct LineCol {
+pub struct LineCol {
     /// The line number within the file (0-indexed).
     pub line: usize,
 
@@ -390,6 +447,33 @@ struct LineCol {
     pub column: usize,
 }
 
+impl Display for LineCol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line + 1, self.column + 1)
+    }
+}
+
+/// How columns are counted within a line.
+///
+/// Columns are in [`Char`](ColumnUnit::Char)s unless a `unit` is requested,
+/// e.g. with [`FileSpan::resolve_span_in`].
+#[derive(Copy, Clone, Dupe, Eq, PartialEq, Debug)]
+pub enum ColumnUnit {
+    /// Unicode scalar values, i.e. `char`s.
+    Char,
+    /// UTF-16 code units, as used by the Language Server Protocol.
+    Utf16,
+}
+
+impl ColumnUnit {
+    fn width(self, c: char) -> usize {
+        match self {
+            ColumnUnit::Char => 1,
+            ColumnUnit::Utf16 => c.len_utf16(),
+        }
+    }
+}
+
 /// A file, and a line and column range within it.
 #[derive(Clone, Copy, Dupe, Eq, PartialEq, Debug)]
 pub struct FileSpanRef<'a> {
@@ -438,6 +522,11 @@ impl<'a> FileSpanRef<'a> {
     pub fn resolve_span(&self) -> ResolvedSpan {
         self.file.resolve_span(self.span)
     }
+
+    /// Resolve span offsets to lines and columns, with columns counted in `unit`s.
+    pub fn resolve_span_in(&self, unit: ColumnUnit) -> ResolvedSpan {
+        self.file.resolve_span_in(self.span, unit)
+    }
 }
 
 impl FileSpan {
@@ -464,6 +553,11 @@ impl FileSpan {
         self.as_ref().resolve_span()
     }
 
+    /// Resolve the span to lines and columns, with columns counted in `unit`s.
+    pub fn resolve_span_in(&self, unit: ColumnUnit) -> ResolvedSpan {
+        self.as_ref().resolve_span_in(unit)
+    }
+
     /// Resolve the span to lines and columns.
     pub fn resolve(&self) -> ResolvedFileSpan {
         ResolvedFileSpan {
@@ -588,11 +682,9 @@ mod tests {
         for line in 0..3 {
             let line_str = codemap.source_line(line);
             let line_span = codemap.line_span(line);
+            let line_len = (line_span.end.0 - line_span.begin.0) as usize;
             // The line_str omits trailing newlines
-            assert_eq!(
-                line_str.len() + if line < 2 { 1 } else { 0 },
-                line_span.len() as usize
-            );
+            assert_eq!(line_str.len() + if line < 2 { 1 } else { 0 }, line_len);
             assert_eq!(line_str, source.lines().nth(line).unwrap());
             assert_eq!(codemap.find_line(line_span.begin), line);
             // The final character might be a newline, which is counted as the next line.
@@ -607,7 +699,7 @@ mod tests {
                 codemap.find_line_col(end),
                 LineCol {
                     line,
-                    column: line_span.len() as usize - 1
+                    column: line_len - 1
                 }
             );
         }
@@ -636,6 +728,77 @@ mod tests {
             codemap.find_line_col(codemap.full_span().begin + 33),
             LineCol { line: 1, column: 1 }
         );
+        assert_eq!(
+            ResolvedSpan {
+                begin_line: 0,
+                begin_column: 15,
+                end_line: 1,
+                end_column: 2,
+            },
+            codemap.resolve_span_in(Span::new(Pos(21), Pos(33)), ColumnUnit::Utf16)
+        );
+    }
+
+    #[test]
+    fn test_offset_line_col() {
+        let content = "ab°\r\n🔬x\n";
+        let codemap = CodeMap::new("<test>".to_owned(), content.to_owned());
+        let cases = [
+            (0, (0, 0), (0, 0)),
+            (2, (0, 2), (0, 2)),
+            (4, (0, 3), (0, 3)),
+            (6, (1, 0), (1, 0)),
+            (10, (1, 1), (1, 2)),
+            (11, (1, 2), (1, 3)),
+            (12, (2, 0), (2, 0)),
+        ];
+        for (offset, (line, column), (line16, column16)) in cases {
+            let line_col = LineCol { line, column };
+            let line_col16 = LineCol {
+                line: line16,
+                column: column16,
+            };
+            assert_eq!(
+                line_col,
+                codemap.offset_to_line_col(offset, ColumnUnit::Char)
+            );
+            assert_eq!(
+                line_col16,
+                codemap.offset_to_line_col(offset, ColumnUnit::Utf16)
+            );
+            assert_eq!(
+                offset,
+                codemap.line_col_to_offset(line_col, ColumnUnit::Char)
+            );
+            assert_eq!(
+                offset,
+                codemap.line_col_to_offset(line_col16, ColumnUnit::Utf16)
+            );
+        }
+
+        // Out of range or inside a character.
+        assert_eq!(
+            LineCol { line: 1, column: 0 },
+            codemap.offset_to_line_col(8, ColumnUnit::Char)
+        );
+        assert_eq!(
+            LineCol { line: 2, column: 0 },
+            codemap.offset_to_line_col(100, ColumnUnit::Char)
+        );
+        let line_col = |line, column| LineCol { line, column };
+        assert_eq!(
+            6,
+            codemap.line_col_to_offset(line_col(1, 1), ColumnUnit::Utf16)
+        );
+        assert_eq!(
+            4,
+            codemap.line_col_to_offset(line_col(0, 10), ColumnUnit::Char)
+        );
+        assert_eq!(
+            content.len(),
+            codemap.line_col_to_offset(line_col(5, 0), ColumnUnit::Char)
+        );
+        assert_eq!("2:3", line_col(1, 2).to_string());
     }
 
     #[test]
@@ -275,7 +275,7 @@ impl GlobalsBuilder {
     }
 
     /// A fluent API for modifying [`GlobalsBuilder`] using [`struct_`](GlobalsBuilder::struct_).
-    pub fn with_struct(mut self, name: &str, f: impl Fn(&mut GlobalsBuilder)) -> Self {
+    pub fn with_struct(mut self, name: &str, f: impl FnOnce(&mut GlobalsBuilder)) -> Self {
         self.struct_(name, f);
         self
     }
@@ -631,4 +631,54 @@ assert_eq(magic.my_type, "magic")
 assert_eq(magic.my_value, 42)"#,
         );
     }
+
+    #[test]
+    fn test_struct_namespace() {
+        #[starlark_module]
+        fn fs(builder: &mut GlobalsBuilder) {
+            fn read(path: &str) -> anyhow::Result<String> {
+                Ok(format!("contents of {}", path))
+            }
+        }
+
+        let mut a = Assert::new();
+        a.globals_add(|b| {
+            b.struct_("fs", |b| {
+                fs(b);
+                b.struct_("path", |b| b.set("sep", "/"));
+            })
+        });
+        a.pass(
+            r#"
+assert_eq(fs.read("x"), "contents of x")
+assert_eq(fs.path.sep, "/")"#,
+        );
+    }
+
+    #[test]
+    fn test_with_struct() {
+        #[starlark_module]
+        fn fs(builder: &mut GlobalsBuilder) {
+            fn read(path: &str) -> anyhow::Result<String> {
+                Ok(format!("contents of {}", path))
+            }
+        }
+
+        // The callbacks are `FnOnce`, so they can move values in.
+        let sep = "/".to_owned();
+        let globals = GlobalsBuilder::new()
+            .with_struct("fs", move |b| {
+                fs(b);
+                b.struct_("path", move |b| b.set("sep", sep));
+            })
+            .build();
+        let mut a = Assert::new();
+        a.globals(globals);
+        a.all_true(
+            r#"
+fs.read("x") == "contents of x"
+fs.path.sep == "/"
+"#,
+        );
+    }
 }
@@ -48,6 +48,7 @@ use crate::values::docs::DocString;
 use crate::values::docs::DocStringKind;
 use crate::values::layout::heap::heap_type::HeapKind;
 use crate::values::layout::heap::profile::aggregated::AggregateHeapProfileInfo;
+use crate::values::AllocFrozenValue;
 use crate::values::Freezer;
 use crate::values::FrozenHeap;
 use crate::values::FrozenHeapRef;
@@ -195,6 +196,12 @@ impl FrozenModule {
         self.module.0.names()
     }
 
+    /// Iterate through the exported names which have a value bound in this module,
+    /// unlike [`names`](FrozenModule::names), which also includes names which are never assigned.
+    pub fn bound_names(&self) -> impl Iterator<Item = FrozenStringValue> + '_ {
+        self.module.0.bound_names()
+    }
+
     /// Obtain the [`FrozenHeapRef`] which owns the storage of all values defined in this module.
     pub fn frozen_heap(&self) -> &FrozenHeapRef {
         &self.heap
@@ -249,6 +256,10 @@ impl FrozenModuleData {
         self.names.symbols().map(|x| x.0)
     }
 
+    pub fn bound_names(&self) -> impl Iterator<Item = FrozenStringValue> + '_ {
+        self.items().map(|x| x.0)
+    }
+
     pub fn describe(&self) -> String {
         self.items()
             .map(|(name, val)| val.to_value().describe(&name))
@@ -314,14 +325,32 @@ impl Module {
     }
 
     /// Get the frozen heap on which frozen values are allocated by this module.
+    ///
+    /// When the module is [frozen](Module::freeze) this heap becomes the heap of the
+    /// [`FrozenModule`], so values allocated here are kept as they are rather than copied.
     pub fn frozen_heap(&self) -> &FrozenHeap {
         &self.frozen_heap
     }
 
-    pub(crate) fn names(&self) -> &MutableNames {
+    pub(crate) fn mutable_names(&self) -> &MutableNames {
         &self.names
     }
 
+    /// Iterate through the exported names defined in this module,
+    /// including names which have not been assigned a value.
+    pub fn names(&self) -> impl Iterator<Item = FrozenStringValue> + '_ {
+        self.names
+            .all_names_and_visibilities()
+            .into_iter()
+            .filter(|(_name, vis)| *vis == Visibility::Public)
+            .map(|(name, _vis)| name)
+    }
+
+    /// Iterate through the exported names which have a value bound in this module.
+    pub fn bound_names(&self) -> impl Iterator<Item = FrozenStringValue> + '_ {
+        self.names().filter(|x| self.get(x.as_str()).is_some())
+    }
+
     pub(crate) fn slots<'v>(&'v self) -> &'v MutableSlots<'v> {
         // Not true because of variance, but mostly true. Don't export further.
         unsafe { transmute!(&'v MutableSlots<'static>, &'v MutableSlots<'v>, &self.slots) }
@@ -406,6 +435,17 @@ impl Module {
         slots.set_slot(slot, value);
     }
 
+    /// Allocate a value on the [`frozen_heap`](Module::frozen_heap) of this module
+    /// and set a variable to it, returning the allocated value.
+    ///
+    /// The value is immediately usable by code evaluated in this module,
+    /// and survives [`freeze`](Module::freeze) without being copied.
+    pub fn set_frozen(&self, name: &str, value: impl AllocFrozenValue) -> FrozenValue {
+        let value = self.frozen_heap.alloc(value);
+        self.set(name, value.to_value());
+        value
+    }
+
     /// Symbols starting with underscore are considered private.
     pub(crate) fn default_visibility(symbol: &str) -> Visibility {
         match symbol.starts_with('_') {
@@ -498,6 +538,26 @@ mod tests {
     use crate::eval::ProfileMode;
     use crate::syntax::AstModule;
     use crate::syntax::Dialect;
+    use crate::values::FrozenStringValue;
+
+    #[test]
+    fn test_set_frozen() -> anyhow::Result<()> {
+        let module = Module::new();
+        let value = module.set_frozen("constant", vec!["a", "b"]);
+        let mut eval = Evaluator::new(&module);
+        let ast = AstModule::parse(
+            "a.star",
+            "result = len(constant)".to_owned(),
+            &Dialect::Standard,
+        )?;
+        eval.eval_module(ast, &Globals::standard())?;
+        drop(eval);
+        let frozen = module.freeze()?;
+        assert_eq!(Some(2), frozen.get("result")?.unpack_int());
+        // Not copied by freezing.
+        assert!(frozen.get("constant")?.value().ptr_eq(value.to_value()));
+        Ok(())
+    }
 
     #[test]
     fn test_gen_heap_summary_profile() {
@@ -529,4 +589,44 @@ x = f(1)
         // Smoke test.
         assert!(heap_summary.contains("\"x.star.f\""), "{:?}", heap_summary);
     }
+
+    #[test]
+    fn test_names() {
+        let module = Module::new();
+        {
+            let mut eval = Evaluator::new(&module);
+            eval.eval_module(
+                AstModule::parse(
+                    "x.star",
+                    r"
+def f():
+    pass
+x = 1
+_private = 2
+if False:
+    unbound = 3
+"
+                    .to_owned(),
+                    &Dialect::Extended,
+                )
+                .unwrap(),
+                &Globals::standard(),
+            )
+            .unwrap();
+        }
+        let sorted = |names: Vec<FrozenStringValue>| {
+            let mut names: Vec<_> = names.iter().map(|x| x.as_str().to_owned()).collect();
+            names.sort();
+            names
+        };
+        assert_eq!(vec!["f", "unbound", "x"], sorted(module.names().collect()));
+        assert_eq!(vec!["f", "x"], sorted(module.bound_names().collect()));
+        assert_eq!(Some(1), module.get("x").and_then(|x| x.unpack_int()));
+        assert!(module.get("_private").is_none());
+        assert!(module.get("unbound").is_none());
+
+        let module = module.freeze().unwrap();
+        assert_eq!(vec!["f", "unbound", "x"], sorted(module.names().collect()));
+        assert_eq!(vec!["f", "x"], sorted(module.bound_names().collect()));
+    }
 }
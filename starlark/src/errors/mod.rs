@@ -46,6 +46,8 @@ pub(crate) mod did_you_mean;
 ///
 /// The underlying [`message`](Diagnostic::message) is an [`anyhow::Error`].
 /// The [`Diagnostic`] structure itself usually stored within an [`anyhow::Error`].
+/// Use [`downcast_ref`](anyhow::Error::downcast_ref) to get at the structured
+/// [`call_stack`](Diagnostic::call_stack) of an evaluation error, e.g. for logging.
 #[derive(Debug)]
 pub struct Diagnostic {
     /// Underlying error for the [`Diagnostic`].
@@ -216,10 +218,17 @@ fn get_display_list_for_diagnostic<'a>(
     color: bool,
 ) -> DisplayList<'a> {
     fn convert_span_to_range_relative_to_first_line(
-        diagnostic_span: Span,
+        diagnostic_span: &FileSpan,
         start_column: usize,
     ) -> (usize, usize) {
-        let span_length = diagnostic_span.len() as usize;
+        // `annotate_snippets` measures ranges in chars, counting the line breaks
+        // of a multi-line span, so a byte length would overshoot on non-ASCII
+        // source and lose the end of the underline.
+        let span_length = diagnostic_span
+            .file
+            .source_span(diagnostic_span.span)
+            .chars()
+            .count();
         (start_column, start_column + span_length)
     }
 
@@ -241,7 +250,7 @@ fn get_display_list_for_diagnostic<'a>(
             annotations: vec![SourceAnnotation {
                 label: "",
                 annotation_type: AnnotationType::Error,
-                range: convert_span_to_range_relative_to_first_line(span.span, region.begin_column),
+                range: convert_span_to_range_relative_to_first_line(span, region.begin_column),
             }],
         }
     }
@@ -283,6 +292,7 @@ fn diagnostic_stderr(diagnostic: &Diagnostic) {
 
 #[cfg(test)]
 mod tests {
+    use crate::assert;
     use crate::errors::truncate_snippet;
 
     #[test]
@@ -300,4 +310,16 @@ mod tests {
         assert_eq!(("Київ", ""), truncate_snippet("Київ", 5));
         assert_eq!(("па", "..."), truncate_snippet("паляниця", 5));
     }
+
+    #[test]
+    fn test_multiline_span() {
+        let err = assert::fail("x = (\"ї\" +\n  1)", "not supported");
+        let display = err.to_string();
+        // Every line of the span is shown, with the underline starting
+        // on the first line and finishing on the last. The lines are indented
+        // to make room for the bar joining the two ends of the underline.
+        assert!(display.contains("1 |   x = (\"ї\" +"), "{}", display);
+        assert!(display.contains("  1)"), "{}", display);
+        assert_eq!(2, display.matches('^').count(), "{}", display);
+    }
 }
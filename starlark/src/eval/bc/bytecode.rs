@@ -49,6 +49,14 @@ impl Bc {
     #[cold]
     #[inline(never)]
     pub(crate) fn slow_arg_at_ptr(addr_ptr: BcPtrAddr) -> &BcInstrSlowArg {
+        match Self::find_slow_arg_at_ptr(addr_ptr) {
+            Some(slow_arg) => slow_arg,
+            None => panic!("span not found for addr: {:?}", addr_ptr),
+        }
+    }
+
+    /// Find span for instruction, if the instruction has one.
+    fn find_slow_arg_at_ptr(addr_ptr: BcPtrAddr) -> Option<&BcInstrSlowArg> {
         let mut ptr = addr_ptr;
         loop {
             let opcode = ptr.get_opcode();
@@ -61,12 +69,10 @@ impl Bc {
                 } = &end_of_bc.arg;
                 let code_start_ptr = ptr.sub(*end_addr);
                 let addr = addr_ptr.offset_from(code_start_ptr);
-                for (next_addr, next_span) in slow_args {
-                    if *next_addr == addr {
-                        return next_span;
-                    }
-                }
-                panic!("span not found for addr: {}", addr);
+                return slow_args
+                    .iter()
+                    .find(|(next_addr, _)| *next_addr == addr)
+                    .map(|(_, next_span)| next_span);
             }
             ptr = ptr.add(opcode.size_of_repr());
         }
@@ -83,6 +89,21 @@ impl Bc {
         add_span_to_expr_error(e, span, eval)
     }
 
+    /// Like [`wrap_error_for_instr_ptr`](Bc::wrap_error_for_instr_ptr), for an error
+    /// raised before running the instruction, which might not have a span.
+    #[cold]
+    #[inline(never)]
+    fn wrap_error_before_instr_ptr(
+        ptr: BcPtrAddr,
+        e: anyhow::Error,
+        eval: &Evaluator,
+    ) -> EvalException {
+        match Self::find_slow_arg_at_ptr(ptr) {
+            Some(slow_arg) => add_span_to_expr_error(e, slow_arg.span, eval),
+            None => EvalException(e),
+        }
+    }
+
     /// Run the bytecode in the current frame allocated in the evaluator.
     ///
     /// Frame must be allocated properly, otherwise it will likely result in memory corruption.
@@ -158,11 +179,30 @@ pub(crate) enum RunBlockResult<'v> {
 
 /// Execute the code block, either a module, a function body or a loop body.
 // Do not inline this function because it is called from two places: function and loop.
-pub(crate) fn run_block<'v>(eval: &mut Evaluator<'v, '_>, mut ip: BcPtrAddr) -> RunBlockResult<'v> {
+pub(crate) fn run_block<'v>(eval: &mut Evaluator<'v, '_>, ip: BcPtrAddr) -> RunBlockResult<'v> {
+    // Decide once per block rather than once per instruction,
+    // so evaluation without an instruction budget or interrupt flag pays nothing for them.
+    if eval.checks_each_instruction() {
+        run_block_impl::<true>(eval, ip)
+    } else {
+        run_block_impl::<false>(eval, ip)
+    }
+}
+
+#[cfg_attr(not(debug_assertions), inline(always))]
+fn run_block_impl<'v, const CHECK_EACH_INSTR: bool>(
+    eval: &mut Evaluator<'v, '_>,
+    mut ip: BcPtrAddr,
+) -> RunBlockResult<'v> {
     // Copy frame pointer to local variable to generate more efficient code.
     let frame = eval.current_frame;
 
     loop {
+        if CHECK_EACH_INSTR {
+            if let Err(e) = eval.consume_instruction_budget() {
+                return RunBlockResult::Err(Bc::wrap_error_before_instr_ptr(ip, e, eval));
+            }
+        }
         // Note most functions called from here must be carefully annotated
         // as `#[inline(always)]` otherwise LLVM considers them too large to inline.
         //
@@ -20,16 +20,24 @@
 use gazebo::prelude::*;
 
 use crate::collections::symbol_map::Symbol;
+use crate::eval::bc::compiler::expr::write_expr_opt;
 use crate::eval::bc::compiler::expr::write_n_exprs;
+use crate::eval::bc::instr_impl::InstrDelArrayIndex;
+use crate::eval::bc::instr_impl::InstrDelLocal;
+use crate::eval::bc::instr_impl::InstrDelLocalCaptured;
+use crate::eval::bc::instr_impl::InstrDelSlice;
 use crate::eval::bc::instr_impl::InstrSetArrayIndex;
 use crate::eval::bc::instr_impl::InstrSetObjectField;
+use crate::eval::bc::instr_impl::InstrSetSlice;
 use crate::eval::bc::instr_impl::InstrStoreModuleAndExport;
 use crate::eval::bc::instr_impl::InstrUnpack;
+use crate::eval::bc::instr_impl::InstrUnpackStarred;
 use crate::eval::bc::stack_ptr::BcSlotIn;
 use crate::eval::bc::stack_ptr::BcSlotOut;
 use crate::eval::bc::writer::BcWriter;
 use crate::eval::compiler::span::IrSpanned;
 use crate::eval::compiler::stmt::AssignCompiledValue;
+use crate::eval::runtime::call_stack::FrozenFileSpan;
 
 impl AssignCompiledValue {
     /// After evaluation of `(x, y[z]) = ...`, variables `x`, `y` and `z` as definitely assigned.
@@ -44,6 +52,18 @@ impl AssignCompiledValue {
                 array.mark_definitely_assigned_after(bc);
                 index.mark_definitely_assigned_after(bc);
             }
+            AssignCompiledValue::Slice(box (array, start, stop, step)) => {
+                array.mark_definitely_assigned_after(bc);
+                if let Some(start) = start {
+                    start.mark_definitely_assigned_after(bc);
+                }
+                if let Some(stop) = stop {
+                    stop.mark_definitely_assigned_after(bc);
+                }
+                if let Some(step) = step {
+                    step.mark_definitely_assigned_after(bc);
+                }
+            }
             AssignCompiledValue::LocalCaptured(_slot) => {}
             AssignCompiledValue::Local(slot) => {
                 bc.mark_definitely_assigned(*slot);
@@ -53,6 +73,25 @@ impl AssignCompiledValue {
                     x.mark_definitely_assigned_after(bc);
                 }
             }
+            AssignCompiledValue::Starred(x) => x.mark_definitely_assigned_after(bc),
+        }
+    }
+}
+
+/// Unpack `value` into `targets`, where the target at index `starred`
+/// (if any) receives a list of the remaining values.
+fn write_unpack(
+    span: FrozenFileSpan,
+    value: BcSlotIn,
+    starred: Option<usize>,
+    targets: &[BcSlotOut],
+    bc: &mut BcWriter,
+) {
+    let targets = bc.heap.alloc_any_slice_display_from_debug(targets);
+    match starred {
+        None => bc.write_instr::<InstrUnpack>(span, (value, targets)),
+        Some(starred) => {
+            bc.write_instr::<InstrUnpackStarred>(span, (value, starred as u32, targets))
         }
     }
 }
@@ -72,34 +111,53 @@ impl IrSpanned<AssignCompiledValue> {
                     bc.write_instr::<InstrSetArrayIndex>(span, (value, array, index));
                 });
             }
+            AssignCompiledValue::Slice(box (ref array, ref start, ref stop, ref step)) => {
+                array.write_bc_cb(bc, |array, bc| {
+                    write_expr_opt(start, bc, |start, bc| {
+                        write_expr_opt(stop, bc, |stop, bc| {
+                            write_expr_opt(step, bc, |step, bc| {
+                                bc.write_instr::<InstrSetSlice>(
+                                    span,
+                                    (value, array, start, stop, step),
+                                )
+                            })
+                        })
+                    })
+                });
+            }
             AssignCompiledValue::Tuple(ref xs) => {
                 // All assignments are to local variables, e. g.
                 // ```
                 // (x, y, z) = ...
                 // ```
                 // so we can avoid using intermediate register.
+                let starred = xs
+                    .iter()
+                    .position(|x| matches!(x.node, AssignCompiledValue::Starred(..)));
                 let all_local = xs
                     .try_map(|x| {
-                        x.as_local_non_captured()
+                        x.unstarred()
+                            .as_local_non_captured()
                             .map(|l| l.to_bc_slot().to_out())
                             .ok_or(())
                     })
                     .ok();
                 if let Some(all_local) = all_local {
-                    let args = bc.heap.alloc_any_slice_display_from_debug(&all_local);
-                    bc.write_instr::<InstrUnpack>(span, (value, args));
+                    write_unpack(span, value, starred, &all_local, bc);
                 } else {
                     bc.alloc_slots(xs.len() as u32, |slots, bc| {
                         let args: Vec<BcSlotOut> = slots.iter().map(|s| s.to_out()).collect();
-                        let args = bc.heap.alloc_any_slice_display_from_debug(&args);
-                        bc.write_instr::<InstrUnpack>(span, (value, args));
+                        write_unpack(span, value, starred, &args, bc);
 
                         for (x, slot) in xs.iter().zip(slots.iter()) {
-                            x.write_bc(slot.to_in(), bc);
+                            x.unstarred().write_bc(slot.to_in(), bc);
                         }
                     });
                 }
             }
+            AssignCompiledValue::Starred(..) => {
+                unreachable!("starred assignment target outside of tuple")
+            }
             AssignCompiledValue::Local(slot) => {
                 bc.write_mov(span, value, slot.to_bc_slot().to_out());
             }
@@ -111,4 +169,39 @@ impl IrSpanned<AssignCompiledValue> {
             }
         }
     }
+
+    /// Write `del` of this target.
+    pub(crate) fn write_bc_del(&self, bc: &mut BcWriter) {
+        let span = self.span;
+        match self.node {
+            AssignCompiledValue::ArrayIndirection(ref array, ref index) => {
+                write_n_exprs([array, index], bc, |[array, index], bc| {
+                    bc.write_instr::<InstrDelArrayIndex>(span, (array, index));
+                });
+            }
+            AssignCompiledValue::Slice(box (ref array, ref start, ref stop, ref step)) => {
+                array.write_bc_cb(bc, |array, bc| {
+                    write_expr_opt(start, bc, |start, bc| {
+                        write_expr_opt(stop, bc, |stop, bc| {
+                            write_expr_opt(step, bc, |step, bc| {
+                                bc.write_instr::<InstrDelSlice>(span, (array, start, stop, step))
+                            })
+                        })
+                    })
+                });
+            }
+            AssignCompiledValue::Local(slot) => {
+                bc.write_instr::<InstrDelLocal>(span, slot);
+            }
+            AssignCompiledValue::LocalCaptured(slot) => {
+                bc.write_instr::<InstrDelLocalCaptured>(span, slot);
+            }
+            AssignCompiledValue::Dot(..)
+            | AssignCompiledValue::Tuple(..)
+            | AssignCompiledValue::Starred(..)
+            | AssignCompiledValue::Module(..) => {
+                unreachable!("`del` target is validated to be an index, a slice or a local")
+            }
+        }
+    }
 }
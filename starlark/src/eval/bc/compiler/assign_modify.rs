@@ -17,10 +17,14 @@
 
 //! Write operators like `+=`.
 
+use itertools::Itertools;
+
 use crate::collections::symbol_map::Symbol;
+use crate::eval::bc::compiler::expr::write_exprs;
 use crate::eval::bc::compiler::expr::write_n_exprs;
 use crate::eval::bc::instr_impl::InstrAddAssign;
 use crate::eval::bc::instr_impl::InstrArrayIndex;
+use crate::eval::bc::instr_impl::InstrArrayIndex2;
 use crate::eval::bc::instr_impl::InstrArrayIndexSet;
 use crate::eval::bc::instr_impl::InstrBitAnd;
 use crate::eval::bc::instr_impl::InstrBitOrAssign;
@@ -36,6 +40,7 @@ use crate::eval::bc::instr_impl::InstrRightShift;
 use crate::eval::bc::instr_impl::InstrSetObjectField;
 use crate::eval::bc::instr_impl::InstrStoreModule;
 use crate::eval::bc::instr_impl::InstrSub;
+use crate::eval::bc::instr_impl::InstrTupleNPop;
 use crate::eval::bc::stack_ptr::BcSlotIn;
 use crate::eval::bc::stack_ptr::BcSlotOut;
 use crate::eval::bc::stack_ptr::BcSlotsN;
@@ -81,6 +86,11 @@ impl AssignModifyLhs {
                 array.mark_definitely_assigned_after(bc);
                 index.mark_definitely_assigned_after(bc);
             }
+            AssignModifyLhs::Array2(array, index0, index1) => {
+                array.mark_definitely_assigned_after(bc);
+                index0.mark_definitely_assigned_after(bc);
+                index1.mark_definitely_assigned_after(bc);
+            }
             AssignModifyLhs::LocalCaptured(_) => {}
             AssignModifyLhs::Local(local) => bc.mark_definitely_assigned(local.node),
             AssignModifyLhs::Module(_) => {}
@@ -98,10 +108,11 @@ impl AssignModifyLhs {
             AssignModifyLhs::Dot(ref object, ref field) => {
                 object.write_bc_cb(bc, |object, bc| {
                     bc.alloc_slots_c(|lhs_rhs: BcSlotsN<2>, bc| {
+                        let interned = bc.heap.alloc_str_intern(field.as_str());
                         let field = Symbol::new(field.as_str());
                         bc.write_instr::<InstrObjectField>(
                             span,
-                            (object, field.clone(), lhs_rhs.get::<0>().to_out()),
+                            (object, field.clone(), interned, lhs_rhs.get::<0>().to_out()),
                         );
                         rhs.write_bc(lhs_rhs.get::<1>().to_out(), bc);
                         op.write_bc(
@@ -140,6 +151,37 @@ impl AssignModifyLhs {
                     })
                 });
             }
+            AssignModifyLhs::Array2(ref array, ref index0, ref index1) => {
+                array.write_bc_cb(bc, |array, bc| {
+                    write_exprs([index0, index1], bc, |indices, bc| {
+                        let (index0, index1) = indices.iter().collect_tuple().unwrap();
+                        bc.alloc_slots_c(|slots: BcSlotsN<2>, bc| {
+                            let temp_slot = slots.get::<0>();
+                            let rhs_slot = slots.get::<1>();
+
+                            bc.write_instr::<InstrArrayIndex2>(
+                                span,
+                                (array, index0, index1, temp_slot.to_out()),
+                            );
+                            rhs.write_bc(rhs_slot.to_out(), bc);
+                            op.write_bc(
+                                temp_slot.to_in(),
+                                rhs_slot.to_in(),
+                                temp_slot.to_out(),
+                                span,
+                                bc,
+                            );
+                            // `set_at` has no two-index form, so store with a tuple key,
+                            // reusing the rhs slot which is no longer needed.
+                            bc.write_instr::<InstrTupleNPop>(span, (indices, rhs_slot.to_out()));
+                            bc.write_instr::<InstrArrayIndexSet>(
+                                span,
+                                (array, rhs_slot.to_in(), temp_slot.to_in()),
+                            );
+                        })
+                    })
+                });
+            }
             AssignModifyLhs::Local(s) => bc.alloc_slots_c(|lhs_rhs: BcSlotsN<2>, bc| {
                 let slot = s.node;
                 bc.write_load_local(span, slot, lhs_rhs.get::<0>().to_out());
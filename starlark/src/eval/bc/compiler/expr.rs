@@ -156,6 +156,11 @@ impl ExprCompiled {
                     c.mark_definitely_assigned_after(bc);
                 }
             }
+            ExprCompiled::Index2(box (a, i0, i1)) => {
+                a.mark_definitely_assigned_after(bc);
+                i0.mark_definitely_assigned_after(bc);
+                i1.mark_definitely_assigned_after(bc);
+            }
             ExprCompiled::Builtin1(_op, expr) => {
                 expr.mark_definitely_assigned_after(bc);
             }
@@ -336,6 +341,15 @@ impl IrSpanned<ExprCompiled> {
                     })
                 });
             }
+            ExprCompiled::Index2(box (ref array, ref index0, ref index1)) => {
+                write_n_exprs(
+                    [array, index0, index1],
+                    bc,
+                    |[array, index0, index1], bc| {
+                        bc.write_instr::<InstrArrayIndex2>(span, (array, index0, index1, target))
+                    },
+                );
+            }
             ExprCompiled::Builtin1(Builtin1::Not, box ref expr) => {
                 Self::write_not(expr, target, bc)
             }
@@ -356,7 +370,11 @@ impl IrSpanned<ExprCompiled> {
                             bc.write_instr::<InstrFormatOne>(span, (*before, expr, *after, target))
                         }
                         Builtin1::Dot(field) => {
-                            bc.write_instr::<InstrObjectField>(span, (expr, field.clone(), target))
+                            let interned = bc.heap.alloc_str_intern(field.as_str());
+                            bc.write_instr::<InstrObjectField>(
+                                span,
+                                (expr, field.clone(), interned, target),
+                            )
                         }
                     }
                 });
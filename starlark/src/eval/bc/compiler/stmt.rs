@@ -34,6 +34,7 @@ use crate::eval::compiler::stmt::StmtCompileContext;
 use crate::eval::compiler::stmt::StmtCompiled;
 use crate::eval::compiler::stmt::StmtsCompiled;
 use crate::eval::runtime::call_stack::FrozenFileSpan;
+use crate::eval::runtime::slots::LocalSlotId;
 use crate::values::FrozenHeap;
 use crate::values::FrozenRef;
 use crate::values::FrozenStringValue;
@@ -100,6 +101,11 @@ impl StmtCompiled {
                 rhs.mark_definitely_assigned_after(bc);
                 lhs.mark_definitely_assigned_after(bc);
             }
+            StmtCompiled::Del(x) => match x.node {
+                // The variable is unbound after `del`.
+                AssignCompiledValue::Local(_) => {}
+                _ => x.mark_definitely_assigned_after(bc),
+            },
             StmtCompiled::If(box (cond, t, f)) => {
                 cond.mark_definitely_assigned_after(bc);
                 // We could merge `t` and `f` definitely assigned, e. g.
@@ -227,6 +233,7 @@ impl IrSpanned<StmtCompiled> {
             StmtCompiled::AssignModify(ref lhs, op, ref rhs) => {
                 lhs.write_bc(span, op, rhs, bc);
             }
+            StmtCompiled::Del(ref x) => x.write_bc_del(bc),
             StmtCompiled::If(box (ref c, ref t, ref f)) => {
                 Self::write_if_else(c, t, f, compiler, bc);
             }
@@ -244,6 +251,25 @@ impl IrSpanned<StmtCompiled> {
 }
 
 impl StmtsCompiled {
+    /// Local variables unbound by `del` anywhere in this block.
+    fn collect_deleted_locals(&self, res: &mut Vec<LocalSlotId>) {
+        for stmt in self.stmts() {
+            match &stmt.node {
+                StmtCompiled::Del(x) => {
+                    if let Some(local) = x.as_local_non_captured() {
+                        res.push(local);
+                    }
+                }
+                StmtCompiled::If(box (_, t, f)) => {
+                    t.collect_deleted_locals(res);
+                    f.collect_deleted_locals(res);
+                }
+                StmtCompiled::For(box (_, _, body)) => body.collect_deleted_locals(res),
+                _ => {}
+            }
+        }
+    }
+
     pub(crate) fn as_bc(
         &self,
         compiler: &StmtCompileContext,
@@ -251,11 +277,14 @@ impl StmtsCompiled {
         param_count: u32,
         heap: &FrozenHeap,
     ) -> Bc {
+        let mut deleted_locals = Vec::new();
+        self.collect_deleted_locals(&mut deleted_locals);
         let mut bc = BcWriter::new(
             compiler.bc_profile,
             compiler.record_call_enter_exit,
             local_names,
             param_count,
+            deleted_locals,
             heap,
         );
         self.write_bc(compiler, &mut bc);
@@ -113,6 +113,11 @@ impl<'v> BcFramePtr<'v> {
         self.frame_mut().set_slot(slot, value)
     }
 
+    /// Unbind a local variable, e.g. after `del x`.
+    pub(crate) fn clear_slot(mut self, slot: LocalSlotIdCapturedOrNot) {
+        self.frame_mut().clear_slot(slot)
+    }
+
     #[inline(always)]
     pub(crate) fn get_bc_slot(self, slot: BcSlotIn) -> Value<'v> {
         self.frame().get_bc_slot(slot)
@@ -237,6 +242,11 @@ impl<'v> BcFrame<'v> {
         }
     }
 
+    pub(crate) fn clear_slot(&mut self, slot: LocalSlotIdCapturedOrNot) {
+        debug_assert!(slot.0 < self.local_count);
+        unsafe { self.slots.as_mut_ptr().add(slot.0 as usize).write(None) }
+    }
+
     #[inline(always)]
     pub(crate) fn get_bc_slot_range(&self, slots: BcSlotInRange) -> &[Value<'v>] {
         debug_assert!(slots.end.get().0 <= self.local_count + self.max_stack_size);
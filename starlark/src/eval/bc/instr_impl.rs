@@ -79,6 +79,7 @@ use crate::values::layout::value_not_special::FrozenValueNotSpecial;
 use crate::values::list::List;
 use crate::values::string::interpolation::format_one;
 use crate::values::string::interpolation::percent_s_one;
+use crate::values::structs::Struct;
 use crate::values::types::known_methods::KnownMethod;
 use crate::values::typing::TypeCompiled;
 use crate::values::FrozenRef;
@@ -149,13 +150,20 @@ pub(crate) struct InstrStoreLocalCapturedImpl;
 pub(crate) struct InstrStoreModuleImpl;
 pub(crate) struct InstrStoreModuleAndExportImpl;
 pub(crate) struct InstrUnpackImpl;
+pub(crate) struct InstrUnpackStarredImpl;
 pub(crate) struct InstrArrayIndexImpl;
+pub(crate) struct InstrArrayIndex2Impl;
 pub(crate) struct InstrSetArrayIndexImpl;
 pub(crate) struct InstrArrayIndexSetImpl;
 pub(crate) struct InstrObjectFieldImpl;
 pub(crate) struct InstrObjectFieldRawImpl;
 pub(crate) struct InstrSetObjectFieldImpl;
 pub(crate) struct InstrSliceImpl;
+pub(crate) struct InstrSetSliceImpl;
+pub(crate) struct InstrDelLocalImpl;
+pub(crate) struct InstrDelLocalCapturedImpl;
+pub(crate) struct InstrDelArrayIndexImpl;
+pub(crate) struct InstrDelSliceImpl;
 
 pub(crate) type InstrLoadLocal = InstrNoFlow<InstrLoadLocalImpl>;
 pub(crate) type InstrLoadLocalCaptured = InstrNoFlow<InstrLoadLocalCapturedImpl>;
@@ -165,13 +173,20 @@ pub(crate) type InstrStoreLocalCaptured = InstrNoFlow<InstrStoreLocalCapturedImp
 pub(crate) type InstrStoreModule = InstrNoFlow<InstrStoreModuleImpl>;
 pub(crate) type InstrStoreModuleAndExport = InstrNoFlow<InstrStoreModuleAndExportImpl>;
 pub(crate) type InstrUnpack = InstrNoFlow<InstrUnpackImpl>;
+pub(crate) type InstrUnpackStarred = InstrNoFlow<InstrUnpackStarredImpl>;
 pub(crate) type InstrArrayIndex = InstrNoFlow<InstrArrayIndexImpl>;
+pub(crate) type InstrArrayIndex2 = InstrNoFlow<InstrArrayIndex2Impl>;
 pub(crate) type InstrSetArrayIndex = InstrNoFlow<InstrSetArrayIndexImpl>;
 pub(crate) type InstrArrayIndexSet = InstrNoFlow<InstrArrayIndexSetImpl>;
 pub(crate) type InstrObjectField = InstrNoFlow<InstrObjectFieldImpl>;
 pub(crate) type InstrObjectFieldRaw = InstrNoFlow<InstrObjectFieldRawImpl>;
 pub(crate) type InstrSetObjectField = InstrNoFlow<InstrSetObjectFieldImpl>;
 pub(crate) type InstrSlice = InstrNoFlow<InstrSliceImpl>;
+pub(crate) type InstrSetSlice = InstrNoFlow<InstrSetSliceImpl>;
+pub(crate) type InstrDelLocal = InstrNoFlow<InstrDelLocalImpl>;
+pub(crate) type InstrDelLocalCaptured = InstrNoFlow<InstrDelLocalCapturedImpl>;
+pub(crate) type InstrDelArrayIndex = InstrNoFlow<InstrDelArrayIndexImpl>;
+pub(crate) type InstrDelSlice = InstrNoFlow<InstrDelSliceImpl>;
 
 impl InstrNoFlowImpl for InstrLoadLocalImpl {
     type Arg = (LocalSlotId, BcSlotOut);
@@ -315,6 +330,41 @@ impl InstrNoFlowImpl for InstrUnpackImpl {
     }
 }
 
+impl InstrNoFlowImpl for InstrUnpackStarredImpl {
+    /// Source, index of the starred target, targets.
+    type Arg = (BcSlotIn, u32, FrozenRef<'static, [BcSlotOut]>);
+
+    #[inline(always)]
+    fn run_with_args<'v>(
+        eval: &mut Evaluator<'v, '_>,
+        frame: BcFramePtr<'v>,
+        _ip: BcPtrAddr,
+        (source, starred, target): &(BcSlotIn, u32, FrozenRef<'static, [BcSlotOut]>),
+    ) -> anyhow::Result<()> {
+        let v = frame.get_bc_slot(*source);
+        let items = v.iterate_collect(eval.heap())?;
+        let fixed = target.len() - 1;
+        if items.len() < fixed {
+            return Err(
+                AssignError::NotEnoughValuesToUnpack(fixed as i32, items.len() as i32).into(),
+            );
+        }
+        let starred = *starred as usize;
+        let rest_end = items.len() - (fixed - starred);
+        for (t, item) in target[..starred].iter().zip(&items[..starred]) {
+            frame.set_bc_slot(*t, *item);
+        }
+        frame.set_bc_slot(
+            target[starred],
+            eval.heap().alloc_list(&items[starred..rest_end]),
+        );
+        for (t, item) in target[starred + 1..].iter().zip(&items[rest_end..]) {
+            frame.set_bc_slot(*t, *item);
+        }
+        Ok(())
+    }
+}
+
 impl InstrNoFlowImpl for InstrArrayIndexImpl {
     type Arg = (BcSlotIn, BcSlotIn, BcSlotOut);
 
@@ -333,6 +383,25 @@ impl InstrNoFlowImpl for InstrArrayIndexImpl {
     }
 }
 
+impl InstrNoFlowImpl for InstrArrayIndex2Impl {
+    type Arg = (BcSlotIn, BcSlotIn, BcSlotIn, BcSlotOut);
+
+    #[inline(always)]
+    fn run_with_args<'v>(
+        eval: &mut Evaluator<'v, '_>,
+        frame: BcFramePtr<'v>,
+        _ip: BcPtrAddr,
+        (array, index0, index1, target): &(BcSlotIn, BcSlotIn, BcSlotIn, BcSlotOut),
+    ) -> anyhow::Result<()> {
+        let array = frame.get_bc_slot(*array);
+        let index0 = frame.get_bc_slot(*index0);
+        let index1 = frame.get_bc_slot(*index1);
+        let value = array.at2(index0, index1, eval.heap())?;
+        frame.set_bc_slot(*target, value);
+        Ok(())
+    }
+}
+
 impl InstrNoFlowImpl for InstrSetArrayIndexImpl {
     type Arg = (BcSlotIn, BcSlotIn, BcSlotIn);
 
@@ -367,17 +436,24 @@ impl InstrNoFlowImpl for InstrArrayIndexSetImpl {
     }
 }
 
+/// Get a field, the field name is given both as a symbol and as an interned string.
 impl InstrNoFlowImpl for InstrObjectFieldImpl {
-    type Arg = (BcSlotIn, Symbol, BcSlotOut);
+    type Arg = (BcSlotIn, Symbol, FrozenStringValue, BcSlotOut);
 
     #[inline(always)]
     fn run_with_args<'v>(
         eval: &mut Evaluator<'v, '_>,
         frame: BcFramePtr<'v>,
         _ip: BcPtrAddr,
-        (object, field, target): &(BcSlotIn, Symbol, BcSlotOut),
+        (object, field, interned, target): &(BcSlotIn, Symbol, FrozenStringValue, BcSlotOut),
     ) -> anyhow::Result<()> {
         let object = frame.get_bc_slot(*object);
+        if let Some(s) = Struct::from_value(object) {
+            if let Some(value) = s.get_attr_interned(*interned) {
+                frame.set_bc_slot(*target, value);
+                return Ok(());
+            }
+        }
         let value = get_attr_hashed_bind(object, field, eval.heap())?;
         frame.set_bc_slot(*target, value);
         Ok(())
@@ -452,6 +528,107 @@ impl InstrNoFlowImpl for InstrSliceImpl {
     }
 }
 
+impl InstrNoFlowImpl for InstrSetSliceImpl {
+    type Arg = (
+        BcSlotIn,
+        BcSlotIn,
+        Option<BcSlotIn>,
+        Option<BcSlotIn>,
+        Option<BcSlotIn>,
+    );
+
+    #[inline(always)]
+    fn run_with_args<'v>(
+        eval: &mut Evaluator<'v, '_>,
+        frame: BcFramePtr<'v>,
+        _ip: BcPtrAddr,
+        (source, list, start, stop, step): &(
+            BcSlotIn,
+            BcSlotIn,
+            Option<BcSlotIn>,
+            Option<BcSlotIn>,
+            Option<BcSlotIn>,
+        ),
+    ) -> anyhow::Result<()> {
+        let value = frame.get_bc_slot(*source);
+        let list = frame.get_bc_slot(*list);
+        let start = start.map(|s| frame.get_bc_slot(s));
+        let stop = stop.map(|s| frame.get_bc_slot(s));
+        let step = step.map(|s| frame.get_bc_slot(s));
+        list.set_slice(start, stop, step, value, eval.heap())
+    }
+}
+
+impl InstrNoFlowImpl for InstrDelLocalImpl {
+    type Arg = LocalSlotId;
+
+    fn run_with_args<'v>(
+        eval: &mut Evaluator<'v, '_>,
+        frame: BcFramePtr<'v>,
+        _ip: BcPtrAddr,
+        slot: &LocalSlotId,
+    ) -> anyhow::Result<()> {
+        eval.del_slot_local(frame, *slot)
+    }
+}
+
+impl InstrNoFlowImpl for InstrDelLocalCapturedImpl {
+    type Arg = LocalCapturedSlotId;
+
+    fn run_with_args<'v>(
+        eval: &mut Evaluator<'v, '_>,
+        _frame: BcFramePtr<'v>,
+        _ip: BcPtrAddr,
+        slot: &LocalCapturedSlotId,
+    ) -> anyhow::Result<()> {
+        eval.del_slot_local_captured(*slot)
+    }
+}
+
+impl InstrNoFlowImpl for InstrDelArrayIndexImpl {
+    type Arg = (BcSlotIn, BcSlotIn);
+
+    #[inline(always)]
+    fn run_with_args<'v>(
+        _eval: &mut Evaluator<'v, '_>,
+        frame: BcFramePtr<'v>,
+        _ip: BcPtrAddr,
+        (array, index): &(BcSlotIn, BcSlotIn),
+    ) -> anyhow::Result<()> {
+        let array = frame.get_bc_slot(*array);
+        let index = frame.get_bc_slot(*index);
+        array.del_at(index)
+    }
+}
+
+impl InstrNoFlowImpl for InstrDelSliceImpl {
+    type Arg = (
+        BcSlotIn,
+        Option<BcSlotIn>,
+        Option<BcSlotIn>,
+        Option<BcSlotIn>,
+    );
+
+    #[inline(always)]
+    fn run_with_args<'v>(
+        eval: &mut Evaluator<'v, '_>,
+        frame: BcFramePtr<'v>,
+        _ip: BcPtrAddr,
+        (list, start, stop, step): &(
+            BcSlotIn,
+            Option<BcSlotIn>,
+            Option<BcSlotIn>,
+            Option<BcSlotIn>,
+        ),
+    ) -> anyhow::Result<()> {
+        let list = frame.get_bc_slot(*list);
+        let start = start.map(|s| frame.get_bc_slot(s));
+        let stop = stop.map(|s| frame.get_bc_slot(s));
+        let step = step.map(|s| frame.get_bc_slot(s));
+        list.del_slice(start, stop, step, eval.heap())
+    }
+}
+
 pub(crate) struct InstrEqImpl;
 pub(crate) struct InstrEqConstImpl;
 pub(crate) struct InstrEqPtrImpl;
@@ -1553,6 +1730,13 @@ fn call_method_common<'v>(
     span: FrozenRef<'static, FrozenFileSpan>,
     target: BcSlotOut,
 ) -> anyhow::Result<()> {
+    if let Some(r) = this
+        .get_ref()
+        .invoke_method(this, symbol.as_str(), arguments, eval)
+    {
+        frame.set_bc_slot(target, r?);
+        return Ok(());
+    }
     // TODO: wrong span: should be span of `object.method`, not of the whole expression
     let method = get_attr_hashed_raw(this, symbol, eval.heap())?;
     let r = match method {
@@ -40,10 +40,17 @@ pub(crate) enum BcOpcode {
     StoreModule,
     StoreModuleAndExport,
     Unpack,
+    UnpackStarred,
     ArrayIndex,
+    ArrayIndex2,
     SetArrayIndex,
     ArrayIndexSet,
     Slice,
+    SetSlice,
+    DelLocal,
+    DelLocalCaptured,
+    DelArrayIndex,
+    DelSlice,
     ObjectField,
     ObjectFieldRaw,
     SetObjectField,
@@ -71,6 +71,9 @@ pub(crate) struct BcWriter<'f> {
     local_names: FrozenRef<'f, [FrozenStringValue]>,
     /// Local variables which are known to be definitely assigned at current program point.
     definitely_assigned: BcDefinitelyAssigned,
+    /// Local variables unbound by `del` somewhere in the function,
+    /// so never considered definitely assigned.
+    deleted_locals: Vec<LocalSlotId>,
     /// Max observed stack size.
     max_stack_size: u32,
 
@@ -85,13 +88,16 @@ impl<'f> BcWriter<'f> {
         call_enter_exit: bool,
         local_names: FrozenRef<'f, [FrozenStringValue]>,
         param_count: u32,
+        deleted_locals: Vec<LocalSlotId>,
         heap: &'f FrozenHeap,
     ) -> BcWriter<'f> {
         assert!(param_count as usize <= local_names.len());
         let mut definitely_assigned =
             BcDefinitelyAssigned::new(local_names.len().try_into().unwrap());
         for i in 0..param_count {
-            definitely_assigned.mark_definitely_assigned(LocalSlotId(i));
+            if !deleted_locals.contains(&LocalSlotId(i)) {
+                definitely_assigned.mark_definitely_assigned(LocalSlotId(i));
+            }
         }
         BcWriter {
             profile,
@@ -101,6 +107,7 @@ impl<'f> BcWriter<'f> {
             stack_size: 0,
             local_names,
             definitely_assigned,
+            deleted_locals,
             max_stack_size: 0,
             heap,
         }
@@ -117,6 +124,7 @@ impl<'f> BcWriter<'f> {
             stack_size,
             local_names,
             definitely_assigned,
+            deleted_locals,
             max_stack_size,
             heap,
         } = self;
@@ -124,6 +132,7 @@ impl<'f> BcWriter<'f> {
         let _ = call_enter_exit;
         let _ = heap;
         let _ = definitely_assigned;
+        let _ = deleted_locals;
         assert_eq!(stack_size, 0);
         // Drop lifetime.
         let local_names = unsafe {
@@ -386,7 +395,9 @@ impl<'f> BcWriter<'f> {
     }
 
     pub(crate) fn mark_definitely_assigned(&mut self, local: LocalSlotId) {
-        self.definitely_assigned.mark_definitely_assigned(local);
+        if !self.deleted_locals.contains(&local) {
+            self.definitely_assigned.mark_definitely_assigned(local);
+        }
     }
 
     pub(crate) fn save_definitely_assigned(&self) -> BcDefinitelyAssigned {
@@ -384,6 +384,8 @@ impl Compiler<'_, '_, '_> {
         let scope_names = self.scope_data.get_scope(scope_id);
 
         let has_types = return_type.is_some() || params.has_types();
+        // Return type is only checked if types are checked.
+        let check_return_type = return_type.is_some() && self.check_types;
 
         let inline_def_body = if has_types {
             // It is harder to inline if a function declares parameter types or return type.
@@ -408,14 +410,14 @@ impl Compiler<'_, '_, '_> {
                 .frozen_heap()
                 .alloc_any_slice_display_from_debug(&scope_names.parent),
             stmt_compiled: body.as_bc(
-                &self.compile_context(return_type.is_some()),
+                &self.compile_context(check_return_type),
                 used,
                 param_count,
                 self.eval.module_env.frozen_heap(),
             ),
             body_stmts: body,
             inline_def_body,
-            stmt_compile_context: self.compile_context(return_type.is_some()),
+            stmt_compile_context: self.compile_context(check_return_type),
             globals: self.globals,
         });
 
@@ -115,6 +115,11 @@ impl IsSafeToInlineExpr {
                     && self.is_safe_to_inline_opt_expr(c)
                     && self.is_safe_to_inline_opt_expr(d)
             }
+            ExprCompiled::Index2(box (a, i0, i1)) => {
+                self.is_safe_to_inline_expr(a)
+                    && self.is_safe_to_inline_expr(i0)
+                    && self.is_safe_to_inline_expr(i1)
+            }
             ExprCompiled::Builtin2(bin_op, box (a, b)) => {
                 let _: &Builtin2 = bin_op;
                 self.is_safe_to_inline_expr(a) && self.is_safe_to_inline_expr(b)
@@ -313,6 +318,15 @@ impl<'s, 'v, 'a, 'e> InlineDefCallSite<'s, 'v, 'a, 'e> {
                     node: ExprCompiled::Slice(box (l, a, b, c)),
                 }
             }
+            ExprCompiled::Index2(box (a, i0, i1)) => {
+                let a = self.inline(a)?;
+                let i0 = self.inline(i0)?;
+                let i1 = self.inline(i1)?;
+                IrSpanned {
+                    span,
+                    node: ExprCompiled::index2(a, i0, i1, self.ctx),
+                }
+            }
             ExprCompiled::Seq(box (a, b)) => {
                 let a = self.inline(a)?;
                 let b = self.inline(b)?;
@@ -20,6 +20,7 @@
 use std::cmp::Ordering;
 
 use gazebo::prelude::*;
+use itertools::Itertools;
 use thiserror::Error;
 
 use crate::codemap::Spanned;
@@ -252,6 +253,14 @@ pub(crate) enum ExprCompiled {
             Option<IrSpanned<ExprCompiled>>,
         )>,
     ),
+    /// `a[i, j]`.
+    Index2(
+        Box<(
+            IrSpanned<ExprCompiled>,
+            IrSpanned<ExprCompiled>,
+            IrSpanned<ExprCompiled>,
+        )>,
+    ),
     Builtin1(Builtin1, Box<IrSpanned<ExprCompiled>>),
     LogicalBinOp(
         ExprLogicalBinOp,
@@ -473,6 +482,12 @@ impl IrSpanned<ExprCompiled> {
                 let step = step.as_ref().map(|x| x.optimize(ctx));
                 ExprCompiled::slice(span, v, start, stop, step, ctx)
             }
+            ExprCompiled::Index2(box (ref array, ref index0, ref index1)) => {
+                let array = array.optimize(ctx);
+                let index0 = index0.optimize(ctx);
+                let index1 = index1.optimize(ctx);
+                ExprCompiled::index2(array, index0, index1, ctx)
+            }
             ExprCompiled::Builtin1(ref op, ref e) => {
                 let e = e.optimize(ctx);
                 ExprCompiled::un_op(span, op, e, ctx)
@@ -825,7 +840,11 @@ impl ExprCompiled {
         match v {
             MemberOrValue::Member(m) => match MaybeUnboundValue::new(m) {
                 MaybeUnboundValue::Method(m) => {
-                    Some(ctx.frozen_heap().alloc_simple(BoundMethodGen::new(left, m)))
+                    let this = left.to_value();
+                    match this.get_ref().bind_method(this, attr.as_str(), ctx.heap()) {
+                        Some(v) => v.unpack_frozen(),
+                        None => Some(ctx.frozen_heap().alloc_simple(BoundMethodGen::new(left, m))),
+                    }
                 }
                 MaybeUnboundValue::Attr(..) => None,
             },
@@ -891,6 +910,30 @@ impl ExprCompiled {
         ExprCompiled::Builtin2(Builtin2::ArrayIndex, box (array, index))
     }
 
+    pub(crate) fn index2(
+        array: IrSpanned<ExprCompiled>,
+        index0: IrSpanned<ExprCompiled>,
+        index1: IrSpanned<ExprCompiled>,
+        ctx: &mut OptCtx,
+    ) -> ExprCompiled {
+        let span = array.span.merge(&index1.span);
+        if let (Some(array), Some(index0), Some(index1)) = (
+            array.as_builtin_value(),
+            index0.as_value(),
+            index1.as_value(),
+        ) {
+            if let Ok(v) = array
+                .to_value()
+                .at2(index0.to_value(), index1.to_value(), ctx.heap())
+            {
+                if let Some(expr) = ExprCompiled::try_value(span, v, ctx.frozen_heap()) {
+                    return expr;
+                }
+            }
+        }
+        ExprCompiled::Index2(box (array, index0, index1))
+    }
+
     pub(crate) fn typ(span: FrozenFileSpan, v: IrSpanned<ExprCompiled>) -> ExprCompiled {
         match &v.node {
             ExprCompiled::Value(v) => {
@@ -1087,7 +1130,7 @@ pub(crate) fn get_attr_hashed_bind<'v>(
     let aref = x.get_ref();
     if let Some(methods) = aref.get_methods() {
         if let Some(v) = methods.get_frozen_symbol(attribute) {
-            return MaybeUnboundValue::new(v).bind(x, heap);
+            return MaybeUnboundValue::new(v).bind(x, attribute.as_str(), heap);
         }
     }
     match aref.get_attr_hashed(attribute.as_str_hashed(), heap) {
@@ -1201,8 +1244,21 @@ impl<'v, 'a, 'e> Compiler<'v, 'a, 'e> {
             }
             ExprP::ArrayIndirection(box (array, index)) => {
                 let array = self.expr(array);
-                let index = self.expr(index);
-                ExprCompiled::array_indirection(array, index, &mut self.opt_ctx())
+                match index.node {
+                    ExprP::Tuple(xs) if xs.len() == 2 => {
+                        let (index0, index1) = xs.into_iter().collect_tuple().unwrap();
+                        let index0 = self.expr(index0);
+                        let index1 = self.expr(index1);
+                        ExprCompiled::index2(array, index0, index1, &mut self.opt_ctx())
+                    }
+                    node => {
+                        let index = self.expr(Spanned {
+                            node,
+                            span: index.span,
+                        });
+                        ExprCompiled::array_indirection(array, index, &mut self.opt_ctx())
+                    }
+                }
             }
             ExprP::Slice(collection, start, stop, stride) => {
                 let collection = self.expr(*collection);
@@ -33,6 +33,8 @@ pub(crate) mod stmt;
 
 use std::fmt::Debug;
 
+use once_cell::sync::Lazy;
+
 use crate::codemap::CodeMap;
 use crate::environment::Globals;
 use crate::errors::Diagnostic;
@@ -40,6 +42,7 @@ use crate::eval::compiler::scope::ScopeData;
 use crate::eval::compiler::scope::ScopeId;
 use crate::eval::compiler::scope::ScopeNames;
 use crate::eval::runtime::call_stack::FrozenFileSpan;
+use crate::eval::CallStack;
 use crate::eval::Evaluator;
 use crate::values::FrozenRef;
 
@@ -47,6 +50,19 @@ use crate::values::FrozenRef;
 #[derive(Debug)]
 pub(crate) struct EvalException(pub(crate) anyhow::Error);
 
+impl EvalException {
+    /// Call stack captured when the error was raised, most recent frames at the end.
+    /// Empty if the error has not been through a Starlark call.
+    #[allow(dead_code)] // Dead, but morally a sensible API to provide, and useful for testing
+    pub(crate) fn call_stack(&self) -> &CallStack {
+        static EMPTY: Lazy<CallStack> = Lazy::new(CallStack::default);
+        match self.0.downcast_ref::<Diagnostic>() {
+            Some(d) => &d.call_stack,
+            None => &EMPTY,
+        }
+    }
+}
+
 #[cold]
 #[inline(never)]
 fn add_span_to_error(e: anyhow::Error, span: FrozenFileSpan, eval: &Evaluator) -> anyhow::Error {
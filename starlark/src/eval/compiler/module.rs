@@ -47,7 +47,18 @@ impl<'v> Compiler<'v, '_, '_> {
                     self.eval,
                 ));
             }
-            Some(loader) => expr_throw(loader.load(&name), span, self.eval)?,
+            Some(loader) => {
+                let symbols: Vec<(&str, &str)> = load
+                    .node
+                    .args
+                    .iter()
+                    .map(|(our_name, their_name)| {
+                        (their_name.node.as_str(), our_name.node.0.as_str())
+                    })
+                    .collect();
+                expr_throw(loader.validate_load(&name, &symbols), span, self.eval)?;
+                expr_throw(loader.load(&name), span, self.eval)?
+            }
         };
 
         for (our_name, their_name) in load.node.args {
@@ -622,7 +622,7 @@ impl Stmt {
         dialect: &Dialect,
     ) {
         match &mut stmt.node {
-            StmtP::Assign(dest, _) | StmtP::AssignModify(dest, _, _) => {
+            StmtP::Assign(dest, _) | StmtP::AssignModify(dest, _, _) | StmtP::Del(dest) => {
                 Assign::collect_defines_lvalue(dest, in_loop, scope_data, frozen_heap, result);
             }
             StmtP::For(dest, box (_, body)) => {
@@ -833,6 +833,16 @@ impl ScopeData {
         (binding_id, self.bindings.last_mut().unwrap())
     }
 
+    /// Treat all module variables as assigned more than once, so their values are not inlined.
+    /// Needed when later code evaluated in the same module may assign them again.
+    pub(crate) fn mark_module_bindings_reassignable(&mut self) {
+        for binding in &mut self.bindings {
+            if let Some(Slot::Module(_)) = binding.slot {
+                binding.assign_count = AssignCount::Any;
+            }
+        }
+    }
+
     pub(crate) fn get_scope(&self, ScopeId(id): ScopeId) -> &ScopeNames {
         &self.scopes[id]
     }
@@ -26,6 +26,7 @@
 use std::mem;
 
 use gazebo::prelude::*;
+use itertools::Itertools;
 use thiserror::Error;
 
 use crate::codemap::FileSpanRef;
@@ -54,6 +55,7 @@ use crate::eval::runtime::slots::LocalCapturedSlotId;
 use crate::eval::runtime::slots::LocalSlotId;
 use crate::syntax::ast::AssignOp;
 use crate::syntax::ast::AssignP;
+use crate::syntax::ast::ExprP;
 use crate::syntax::ast::StmtP;
 use crate::values::dict::Dict;
 use crate::values::list::List;
@@ -67,6 +69,12 @@ use crate::values::ValueError;
 pub(crate) enum AssignModifyLhs {
     Dot(IrSpanned<ExprCompiled>, String),
     Array(IrSpanned<ExprCompiled>, IrSpanned<ExprCompiled>),
+    /// `a[i, j] += ...`, read with `at2`.
+    Array2(
+        IrSpanned<ExprCompiled>,
+        IrSpanned<ExprCompiled>,
+        IrSpanned<ExprCompiled>,
+    ),
     Local(IrSpanned<LocalSlotId>),
     LocalCaptured(IrSpanned<LocalCapturedSlotId>),
     Module(IrSpanned<ModuleSlotId>),
@@ -79,6 +87,8 @@ pub(crate) enum StmtCompiled {
     Expr(IrSpanned<ExprCompiled>),
     Assign(IrSpanned<AssignCompiledValue>, IrSpanned<ExprCompiled>),
     AssignModify(AssignModifyLhs, AssignOp, IrSpanned<ExprCompiled>),
+    /// `del` of a single target: a local variable, an index or a slice.
+    Del(IrSpanned<AssignCompiledValue>),
     If(Box<(IrSpanned<ExprCompiled>, StmtsCompiled, StmtsCompiled)>),
     For(
         Box<(
@@ -121,6 +131,11 @@ impl AssignModifyLhs {
             AssignModifyLhs::Array(expr, index) => {
                 AssignModifyLhs::Array(expr.optimize(ctx), index.optimize(ctx))
             }
+            AssignModifyLhs::Array2(expr, index0, index1) => AssignModifyLhs::Array2(
+                expr.optimize(ctx),
+                index0.optimize(ctx),
+                index1.optimize(ctx),
+            ),
             l @ (AssignModifyLhs::Local(..)
             | AssignModifyLhs::LocalCaptured(..)
             | AssignModifyLhs::Module(..)) => l.clone(),
@@ -170,6 +185,10 @@ impl IrSpanned<StmtCompiled> {
                 span,
                 node: StmtCompiled::AssignModify(lhs.optimize(ctx), op, rhs.optimize(ctx)),
             }),
+            StmtCompiled::Del(ref x) => StmtsCompiled::one(IrSpanned {
+                span,
+                node: StmtCompiled::Del(x.optimize(ctx)),
+            }),
         }
     }
 }
@@ -341,13 +360,27 @@ pub(crate) enum AssignError {
     // Incorrect number of value to unpack (expected, got)
     #[error("Unpacked {1} values but expected {0}")]
     IncorrectNumberOfValueToUnpack(i32, i32),
+    // Too few values to unpack with a starred target (expected at least, got)
+    #[error("Unpacked {1} values but expected at least {0}")]
+    NotEnoughValuesToUnpack(i32, i32),
 }
 
 #[derive(Clone, Debug, VisitSpanMut)]
 pub(crate) enum AssignCompiledValue {
     Dot(IrSpanned<ExprCompiled>, String),
     ArrayIndirection(IrSpanned<ExprCompiled>, IrSpanned<ExprCompiled>),
+    /// `a[start:stop:step] = ...`.
+    Slice(
+        Box<(
+            IrSpanned<ExprCompiled>,
+            Option<IrSpanned<ExprCompiled>>,
+            Option<IrSpanned<ExprCompiled>>,
+            Option<IrSpanned<ExprCompiled>>,
+        )>,
+    ),
     Tuple(Vec<IrSpanned<AssignCompiledValue>>),
+    /// Starred element of `Tuple`, assigned a list of the remaining values.
+    Starred(Box<IrSpanned<AssignCompiledValue>>),
     Local(LocalSlotId),
     LocalCaptured(LocalCapturedSlotId),
     Module(ModuleSlotId, String),
@@ -364,6 +397,14 @@ impl AssignCompiledValue {
 }
 
 impl IrSpanned<AssignCompiledValue> {
+    /// The target of a starred element, or self.
+    pub(crate) fn unstarred(&self) -> &IrSpanned<AssignCompiledValue> {
+        match &self.node {
+            AssignCompiledValue::Starred(x) => x,
+            _ => self,
+        }
+    }
+
     pub(crate) fn optimize(&self, ctx: &mut OptCtx) -> IrSpanned<AssignCompiledValue> {
         let span = self.span;
         let assign = match self.node {
@@ -377,10 +418,18 @@ impl IrSpanned<AssignCompiledValue> {
                 let index = index.optimize(ctx);
                 AssignCompiledValue::ArrayIndirection(array, index)
             }
+            AssignCompiledValue::Slice(box (ref array, ref start, ref stop, ref step)) => {
+                let array = array.optimize(ctx);
+                let start = start.as_ref().map(|x| x.optimize(ctx));
+                let stop = stop.as_ref().map(|x| x.optimize(ctx));
+                let step = step.as_ref().map(|x| x.optimize(ctx));
+                AssignCompiledValue::Slice(box (array, start, stop, step))
+            }
             AssignCompiledValue::Tuple(ref xs) => {
                 let xs = xs.map(|x| x.optimize(ctx));
                 AssignCompiledValue::Tuple(xs)
             }
+            AssignCompiledValue::Starred(ref x) => AssignCompiledValue::Starred(box x.optimize(ctx)),
             ref e @ (AssignCompiledValue::Local(..)
             | AssignCompiledValue::LocalCaptured(..)
             | AssignCompiledValue::Module(..)) => e.clone(),
@@ -403,10 +452,18 @@ impl Compiler<'_, '_, '_> {
                 let idx = self.expr(idx);
                 AssignCompiledValue::ArrayIndirection(e, idx)
             }
+            AssignP::Slice(e, start, stop, step) => {
+                let e = self.expr(*e);
+                let start = start.map(|x| self.expr(*x));
+                let stop = stop.map(|x| self.expr(*x));
+                let step = step.map(|x| self.expr(*x));
+                AssignCompiledValue::Slice(box (e, start, stop, step))
+            }
             AssignP::Tuple(v) => {
                 let v = v.into_map(|x| self.assign(x));
                 AssignCompiledValue::Tuple(v)
             }
+            AssignP::Starred(box x) => AssignCompiledValue::Starred(box self.assign(x)),
             AssignP::Identifier(ident) => {
                 let name = ident.node.0;
                 let binding_id = ident
@@ -431,6 +488,26 @@ impl Compiler<'_, '_, '_> {
         IrSpanned { node: assign, span }
     }
 
+    /// Compile `del`, one statement per target, deleting left to right.
+    fn stmt_del(&mut self, x: CstAssign) -> StmtsCompiled {
+        match x.node {
+            AssignP::Tuple(xs) => {
+                let mut r = StmtsCompiled::empty();
+                for x in xs {
+                    r.extend(self.stmt_del(x));
+                }
+                r
+            }
+            _ => {
+                let x = self.assign(x);
+                StmtsCompiled::one(IrSpanned {
+                    span: x.span,
+                    node: StmtCompiled::Del(x),
+                })
+            }
+        }
+    }
+
     fn assign_modify(
         &mut self,
         span_stmt: Span,
@@ -450,10 +527,22 @@ impl Compiler<'_, '_, '_> {
             }
             AssignP::ArrayIndirection(box (e, idx)) => {
                 let e = self.expr(e);
-                let idx = self.expr(idx);
+                let lhs = match idx.node {
+                    ExprP::Tuple(xs) if xs.len() == 2 => {
+                        let (index0, index1) = xs.into_iter().collect_tuple().unwrap();
+                        AssignModifyLhs::Array2(e, self.expr(index0), self.expr(index1))
+                    }
+                    node => {
+                        let idx = self.expr(Spanned {
+                            node,
+                            span: idx.span,
+                        });
+                        AssignModifyLhs::Array(e, idx)
+                    }
+                };
                 StmtsCompiled::one(IrSpanned {
                     span: span_stmt,
-                    node: StmtCompiled::AssignModify(AssignModifyLhs::Array(e, idx), op, rhs),
+                    node: StmtCompiled::AssignModify(lhs, op, rhs),
                 })
             }
             AssignP::Identifier(ident) => {
@@ -495,9 +584,12 @@ impl Compiler<'_, '_, '_> {
                     }
                 }
             }
-            AssignP::Tuple(_) => {
+            AssignP::Tuple(_) | AssignP::Starred(_) => {
                 unreachable!("Assign modify validates that the LHS is never a tuple")
             }
+            AssignP::Slice(..) => {
+                unreachable!("Assign modify validates that the LHS is never a slice")
+            }
         }
     }
 }
@@ -596,7 +688,7 @@ pub(crate) fn bit_or_assign<'v>(
         }
         Ok(lhs)
     } else {
-        lhs_aref.bit_or(rhs, heap)
+        lhs.bit_or(rhs, heap)
     }
 }
 
@@ -783,6 +875,7 @@ impl Compiler<'_, '_, '_> {
                 let rhs = self.expr(*rhs);
                 self.assign_modify(span.span(), lhs, rhs, op)
             }
+            StmtP::Del(x) => self.stmt_del(x),
             StmtP::Load(..) => unreachable!(),
             StmtP::Pass => StmtsCompiled::empty(),
             StmtP::Break => StmtsCompiled::one(IrSpanned {
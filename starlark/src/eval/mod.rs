@@ -57,6 +57,30 @@ impl<'v, 'a> Evaluator<'v, 'a> {
     /// Evaluate an [`AstModule`] with this [`Evaluator`], modifying the in-scope
     /// [`Module`](crate::environment::Module) as appropriate.
     pub fn eval_module(&mut self, ast: AstModule, globals: &Globals) -> anyhow::Result<Value<'v>> {
+        self.eval_module_impl(ast, globals, false)
+    }
+
+    /// Evaluate an [`AstModule`] as a continuation of the code already evaluated in the
+    /// in-scope [`Module`](crate::environment::Module), as done by a REPL.
+    ///
+    /// Bindings made by earlier calls are visible, and may be reassigned following the usual
+    /// module-scope rules. Returns the value of the last statement if it is an expression,
+    /// otherwise `None`. Unlike [`eval_module`](Evaluator::eval_module), a leading string
+    /// literal is not treated as the module docstring.
+    pub fn eval_statements_incrementally(
+        &mut self,
+        ast: AstModule,
+        globals: &Globals,
+    ) -> anyhow::Result<Value<'v>> {
+        self.eval_module_impl(ast, globals, true)
+    }
+
+    fn eval_module_impl(
+        &mut self,
+        ast: AstModule,
+        globals: &Globals,
+        incremental: bool,
+    ) -> anyhow::Result<Value<'v>> {
         let start = Instant::now();
 
         let AstModule {
@@ -78,12 +102,14 @@ impl<'v, 'a> Evaluator<'v, 'a> {
 
         let mut statement = statement.into_map_payload(&mut CompilerAstMap(&mut scope_data));
 
-        if let Some(docstring) = DocString::extract_raw_starlark_docstring(&statement) {
-            self.module_env.set_docstring(docstring)
+        if !incremental {
+            if let Some(docstring) = DocString::extract_raw_starlark_docstring(&statement) {
+                self.module_env.set_docstring(docstring)
+            }
         }
 
         let mut scope = Scope::enter_module(
-            self.module_env.names(),
+            self.module_env.mutable_names(),
             self.module_env.frozen_heap(),
             root_scope_id,
             scope_data,
@@ -100,7 +126,12 @@ impl<'v, 'a> Evaluator<'v, 'a> {
             return Err(e);
         }
 
-        let (module_slots, scope_data) = scope.exit_module();
+        let (module_slots, mut scope_data) = scope.exit_module();
+        if incremental {
+            // Later calls may reassign any module variable, which must then be
+            // seen by functions defined now.
+            scope_data.mark_module_bindings_reassignable();
+        }
         let scope_names = scope_data.get_scope(ScopeId::module());
         let local_names = self
             .frozen_heap()
@@ -129,6 +160,8 @@ impl<'v, 'a> Evaluator<'v, 'a> {
         }
 
         // Evaluation
+        let check_types =
+            dialect.enable_types == DialectTypes::Enable || self.runtime_type_checking;
         let mut compiler = Compiler {
             scope_data,
             locals: Vec::new(),
@@ -137,7 +170,7 @@ impl<'v, 'a> Evaluator<'v, 'a> {
             has_before_stmt: self.before_stmt.enabled(),
             bc_profile: self.bc_profile.enabled(),
             eval: self,
-            check_types: dialect.enable_types == DialectTypes::Enable,
+            check_types,
         };
 
         let res = compiler.eval_module(statement, local_names);
@@ -157,6 +190,32 @@ impl<'v, 'a> Evaluator<'v, 'a> {
     }
 
     /// Evaluate a function stored in a [`Value`], passing in `positional` and `named` arguments.
+    ///
+    /// This is how Rust code calls back into Starlark, e.g. to invoke a handler a script
+    /// registered earlier. A [`FrozenValue`](crate::values::FrozenValue) taken from a frozen
+    /// module can be called after converting it with `to_value()`.
+    ///
+    /// ```
+    /// # fn main() -> anyhow::Result<()> {
+    /// use starlark::environment::Globals;
+    /// use starlark::environment::Module;
+    /// use starlark::eval::Evaluator;
+    /// use starlark::syntax::AstModule;
+    /// use starlark::syntax::Dialect;
+    /// use starlark::values::Value;
+    ///
+    /// let module = Module::new();
+    /// let mut eval = Evaluator::new(&module);
+    /// let program = "def on_event(x, scale = 1):\n    return x * scale";
+    /// let ast = AstModule::parse("handlers.star", program.to_owned(), &Dialect::Standard)?;
+    /// eval.eval_module(ast, &Globals::standard())?;
+    ///
+    /// let handler = module.get("on_event").unwrap();
+    /// let res = eval.eval_function(handler, &[Value::new_int(3)], &[("scale", Value::new_int(2))])?;
+    /// assert_eq!(Some(6), res.unpack_int());
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn eval_function(
         &mut self,
         function: Value<'v>,
@@ -156,25 +156,25 @@ impl Debug for CheapFrame<'_> {
 enum CallStackError {
     #[error("Requested {0}-th top frame, but stack size is {1} (internal error)")]
     StackIsTooShallowForNthTopFrame(usize, usize),
-    #[error("Starlark call stack overflow")]
-    Overflow,
+    #[error("Starlark call stack overflow: call stack exceeded {0} frames")]
+    Overflow(usize),
+    #[error("Cannot set max call stack size to {0}, current call stack size is {1}")]
+    MaxSizeLessThanCurrent(usize, usize),
 }
 
 /// Starlark call stack.
 #[derive(Debug)]
 pub(crate) struct CheapCallStack<'v> {
     count: usize,
-    stack: [CheapFrame<'v>; MAX_CALLSTACK_RECURSION],
+    /// Length of this slice is the max call stack size.
+    stack: Box<[CheapFrame<'v>]>,
 }
 
 impl<'v> Default for CheapCallStack<'v> {
     fn default() -> Self {
         Self {
             count: 0,
-            stack: [CheapFrame {
-                function: Value::new_none(),
-                span: None,
-            }; MAX_CALLSTACK_RECURSION],
+            stack: Self::empty_frames(MAX_CALLSTACK_RECURSION),
         }
     }
 }
@@ -190,7 +190,6 @@ impl<'v> Default for CheapCallStack<'v> {
 // * [tokio default stack size is 2MB][1]
 // [1] https://docs.rs/tokio/0.2.1/tokio/runtime/struct.Builder.html#method.thread_stack_size
 // TODO(nga): count loops in call stack size.
-// TODO(nga): make it configurable.
 const MAX_CALLSTACK_RECURSION: usize = 50;
 
 unsafe impl<'v> Trace<'v> for CheapCallStack<'v> {
@@ -209,6 +208,28 @@ unsafe impl<'v> Trace<'v> for CheapCallStack<'v> {
 }
 
 impl<'v> CheapCallStack<'v> {
+    fn empty_frames(size: usize) -> Box<[CheapFrame<'v>]> {
+        vec![
+            CheapFrame {
+                function: Value::new_none(),
+                span: None,
+            };
+            size
+        ]
+        .into_boxed_slice()
+    }
+
+    /// Change the maximum number of frames this stack can hold.
+    pub(crate) fn set_max_size(&mut self, max_size: usize) -> anyhow::Result<()> {
+        if max_size < self.count {
+            return Err(CallStackError::MaxSizeLessThanCurrent(max_size, self.count).into());
+        }
+        let mut stack = Self::empty_frames(max_size);
+        stack[..self.count].copy_from_slice(&self.stack[..self.count]);
+        self.stack = stack;
+        Ok(())
+    }
+
     /// Push an element to the stack. It is important the each `push` is paired
     /// with a `pop`.
     pub(crate) fn push(
@@ -216,8 +237,8 @@ impl<'v> CheapCallStack<'v> {
         function: Value<'v>,
         span: Option<FrozenRef<'static, FrozenFileSpan>>,
     ) -> anyhow::Result<()> {
-        if unlikely(self.count >= MAX_CALLSTACK_RECURSION) {
-            return Err(CallStackError::Overflow.into());
+        if unlikely(self.count >= self.stack.len()) {
+            return Err(CallStackError::Overflow(self.stack.len()).into());
         }
         self.stack[self.count] = CheapFrame { function, span };
         self.count += 1;
@@ -242,6 +263,16 @@ impl<'v> CheapCallStack<'v> {
         }
     }
 
+    /// The span at the top of the stack, used as the location of calls made
+    /// by native functions, which have no location of their own.
+    pub(crate) fn top_span(&self) -> Option<FrozenRef<'static, FrozenFileSpan>> {
+        if self.count == 0 {
+            None
+        } else {
+            self.stack[self.count - 1].span
+        }
+    }
+
     /// `n`-th element from the top of the stack.
     pub(crate) fn top_nth_function(&self, n: usize) -> anyhow::Result<Value<'v>> {
         let index = self
@@ -282,6 +313,11 @@ impl CallStack {
         self.frames.is_empty()
     }
 
+    /// The frames of the call stack, outermost first.
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
     /// Take the contained frames.
     pub fn into_frames(self) -> Vec<Frame> {
         self.frames
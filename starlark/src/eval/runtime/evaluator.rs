@@ -20,6 +20,9 @@ use std::collections::HashSet;
 use std::mem;
 use std::mem::MaybeUninit;
 use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
 
 use gazebo::any::AnyLifetime;
 use gazebo::cast;
@@ -98,6 +101,10 @@ pub(crate) enum EvaluatorError {
     CoverageNotImplemented,
     #[error("Coverage not enabled")]
     CoverageNotEnabled,
+    #[error("Instruction budget exhausted")]
+    InstructionBudgetExhausted,
+    #[error("Evaluation interrupted")]
+    Interrupted,
 }
 
 /// Number of bytes to allocate between GC's.
@@ -135,6 +142,12 @@ pub struct Evaluator<'v, 'a> {
     pub(crate) profile_or_instrumentation_mode: ProfileOrInstrumentationMode,
     // Extra functions to run on each statement, usually empty
     pub(crate) before_stmt: BeforeStmt<'a>,
+    // Remaining number of instructions to execute, if limited.
+    pub(crate) instruction_budget: Option<u64>,
+    // Flag set from outside to stop the evaluation, if any.
+    pub(crate) interrupt_flag: Option<Arc<AtomicBool>>,
+    // Check type annotations at runtime even if the dialect only parses them.
+    pub(crate) runtime_type_checking: bool,
     // Used for line profiling
     stmt_profile: StmtProfile,
     // Bytecode profile.
@@ -149,12 +162,13 @@ pub struct Evaluator<'v, 'a> {
     /// Field that can be used for any purpose you want (can store types you define).
     /// Typically accessed via native functions you also define.
     pub extra: Option<&'a dyn AnyLifetime<'a>>,
+    /// Like [`extra`](Evaluator::extra), but for data that native functions need to modify.
+    pub extra_mut: Option<&'a mut dyn AnyLifetime<'a>>,
     /// Called to perform console IO each time `breakpoint` function is called.
     pub(crate) breakpoint_handler: Option<Box<dyn Fn() -> Box<dyn BreakpointConsole>>>,
     /// Use in implementation of `print` function.
     pub(crate) print_handler: &'a (dyn PrintHandler + 'a),
     // The Starlark-level call-stack of functions.
-    // Must go last because it's quite a big structure
     pub(crate) call_stack: CheapCallStack<'v>,
 }
 
@@ -180,6 +194,7 @@ impl<'v, 'a> Evaluator<'v, 'a> {
             current_frame: BcFramePtr::null(),
             loader: None,
             extra: None,
+            extra_mut: None,
             next_gc_level: GC_THRESHOLD,
             disable_gc: false,
             alloca: Alloca::new(),
@@ -191,6 +206,9 @@ impl<'v, 'a> Evaluator<'v, 'a> {
             flame_profile: FlameProfile::new(),
             heap_or_flame_profile: false,
             before_stmt: BeforeStmt::default(),
+            instruction_budget: None,
+            interrupt_flag: None,
+            runtime_type_checking: false,
             module_def_info: DefInfo::empty(), // Will be replaced before it is used
             string_pool: StringPool::default(),
             breakpoint_handler: None,
@@ -211,6 +229,68 @@ impl<'v, 'a> Evaluator<'v, 'a> {
         self.verbose_gc = true;
     }
 
+    /// Limit the number of bytecode instructions executed by this evaluator.
+    /// Once the budget is exhausted, evaluation fails with an error.
+    ///
+    /// Unlike a wall-clock timeout, the limit is deterministic.
+    /// Every instruction counts, including those of functions defined in frozen modules,
+    /// but not the work done inside native functions.
+    pub fn set_instruction_budget(&mut self, budget: u64) {
+        self.instruction_budget = Some(budget);
+    }
+
+    /// Set a flag which, once set to `true` (e.g. from another thread),
+    /// makes the evaluation fail with an "Evaluation interrupted" error.
+    ///
+    /// The flag is checked before every bytecode instruction, like the budget of
+    /// [`set_instruction_budget`](Evaluator::set_instruction_budget). The flag is not checked
+    /// while a native function runs, e.g. during `sorted` of a large list, only after it returns.
+    /// The flag is never reset by the evaluator.
+    pub fn set_interrupt_flag(&mut self, flag: Arc<AtomicBool>) {
+        self.interrupt_flag = Some(flag);
+    }
+
+    /// Check the type annotations of `def` parameters and return values at runtime,
+    /// e.g. `def f(x: int.type) -> str.type`, failing on a mismatch when
+    /// the function is called or returns.
+    ///
+    /// Types are always checked for [`DialectTypes::Enable`](crate::syntax::DialectTypes::Enable).
+    /// This enables checking for [`DialectTypes::ParseOnly`](crate::syntax::DialectTypes::ParseOnly),
+    /// where annotations are otherwise ignored.
+    /// Only affects functions defined in code compiled after this call.
+    pub fn enable_runtime_type_checking(&mut self, enable: bool) {
+        self.runtime_type_checking = enable;
+    }
+
+    /// Remaining instruction budget, if set
+    /// by [`set_instruction_budget`](Evaluator::set_instruction_budget).
+    pub fn instruction_budget(&self) -> Option<u64> {
+        self.instruction_budget
+    }
+
+    /// Whether the bytecode interpreter must call
+    /// [`consume_instruction_budget`](Evaluator::consume_instruction_budget) before each instruction.
+    pub(crate) fn checks_each_instruction(&self) -> bool {
+        self.instruction_budget.is_some() || self.interrupt_flag.is_some()
+    }
+
+    /// Called by the bytecode interpreter before each instruction,
+    /// also checks the interrupt flag.
+    pub(crate) fn consume_instruction_budget(&mut self) -> anyhow::Result<()> {
+        if let Some(budget) = &mut self.instruction_budget {
+            match budget.checked_sub(1) {
+                Some(remaining) => *budget = remaining,
+                None => return Err(EvaluatorError::InstructionBudgetExhausted.into()),
+            }
+        }
+        if let Some(flag) = &self.interrupt_flag {
+            if flag.load(Ordering::Relaxed) {
+                return Err(EvaluatorError::Interrupted.into());
+            }
+        }
+        Ok(())
+    }
+
     /// Set the [`FileLoader`] used to resolve `load()` statements.
     /// A list of all load statements can be obtained through
     /// [`AstModule::loads`](crate::syntax::AstModule::loads).
@@ -361,6 +441,16 @@ impl<'v, 'a> Evaluator<'v, 'a> {
         self.breakpoint_handler = Some(RealBreakpointConsole::factory());
     }
 
+    /// Set the maximum number of nested calls, including the module frame.
+    /// Exceeding it results in a "call stack overflow" error.
+    ///
+    /// Default is 50. Each Starlark call also consumes native stack,
+    /// so large values may overflow the native stack instead.
+    /// Fails if the current call stack is already deeper than `max_size`.
+    pub fn set_max_callstack_size(&mut self, max_size: usize) -> anyhow::Result<()> {
+        self.call_stack.set_max_size(max_size)
+    }
+
     /// Obtain the current call-stack, suitable for use with [`Diagnostic`].
     pub fn call_stack(&self) -> CallStack {
         self.call_stack
@@ -368,7 +458,8 @@ impl<'v, 'a> Evaluator<'v, 'a> {
     }
 
     /// Obtain the top location on the call-stack. May be [`None`] if the
-    /// call happened via native functions.
+    /// call happened directly from Rust. Functions called by native functions
+    /// have the location where the native function was called.
     pub fn call_stack_top_location(&self) -> Option<FileSpan> {
         self.call_stack.top_location()
     }
@@ -390,7 +481,86 @@ impl<'v, 'a> Evaluator<'v, 'a> {
         self.before_stmt(f);
     }
 
+    /// Store `extra` in the [`extra`](Evaluator::extra) field, so native functions
+    /// can get it back with [`get_extra`](Evaluator::get_extra).
+    ///
+    /// This is the usual way to give native functions access to the host state:
+    ///
+    /// ```
+    /// use gazebo::any::ProvidesStaticType;
+    /// use starlark::environment::GlobalsBuilder;
+    /// use starlark::environment::Module;
+    /// use starlark::eval::Evaluator;
+    /// use starlark::starlark_module;
+    /// use starlark::syntax::AstModule;
+    /// use starlark::syntax::Dialect;
+    ///
+    /// #[derive(ProvidesStaticType)]
+    /// struct Context {
+    ///     user: String,
+    /// }
+    ///
+    /// #[starlark_module]
+    /// fn globals(builder: &mut GlobalsBuilder) {
+    ///     fn user<'v>(eval: &mut Evaluator<'v, '_>) -> anyhow::Result<String> {
+    ///         Ok(eval.get_extra::<Context>().unwrap().user.clone())
+    ///     }
+    /// }
+    ///
+    /// let context = Context { user: "alice".to_owned() };
+    /// let module = Module::new();
+    /// let mut eval = Evaluator::new(&module);
+    /// eval.set_extra(&context);
+    /// let ast = AstModule::parse("x.star", "user()".to_owned(), &Dialect::Standard).unwrap();
+    /// let globals = GlobalsBuilder::new().with(globals).build();
+    /// let res = eval.eval_module(ast, &globals).unwrap();
+    /// assert_eq!("alice", res.unpack_str().unwrap());
+    /// ```
+    pub fn set_extra<T: AnyLifetime<'a>>(&mut self, extra: &'a T) {
+        self.extra = Some(extra);
+    }
+
+    /// The [`extra`](Evaluator::extra) field, if it was set to a value of type `T`.
+    pub fn get_extra<T: AnyLifetime<'a>>(&self) -> Option<&'a T> {
+        self.extra?.downcast_ref::<T>()
+    }
+
+    /// Store `extra` in the [`extra_mut`](Evaluator::extra_mut) field, so native functions
+    /// can modify it through [`get_extra_mut`](Evaluator::get_extra_mut).
+    pub fn set_extra_mut<T: AnyLifetime<'a>>(&mut self, extra: &'a mut T) {
+        self.extra_mut = Some(extra);
+    }
+
+    /// The [`extra_mut`](Evaluator::extra_mut) field, if it was set to a value of type `T`.
+    pub fn get_extra_mut<T: AnyLifetime<'a>>(&mut self) -> Option<&mut T> {
+        self.extra_mut.as_mut()?.downcast_mut::<T>()
+    }
+
     /// Set the handler invoked when `print` function is used.
+    /// By default the text is written to stderr.
+    ///
+    /// Any `Fn(&str)` closure can be used, e.g. to capture the output in tests:
+    ///
+    /// ```
+    /// use std::cell::RefCell;
+    ///
+    /// use starlark::environment::Globals;
+    /// use starlark::environment::Module;
+    /// use starlark::eval::Evaluator;
+    /// use starlark::syntax::AstModule;
+    /// use starlark::syntax::Dialect;
+    ///
+    /// let output = RefCell::new(Vec::new());
+    /// let handler = |text: &str| output.borrow_mut().push(text.to_owned());
+    /// let module = Module::new();
+    /// let mut eval = Evaluator::new(&module);
+    /// eval.set_print_handler(&handler);
+    /// let ast = AstModule::parse("x.star", "print('hello', 1)".to_owned(), &Dialect::Standard)
+    ///     .unwrap();
+    /// eval.eval_module(ast, &Globals::extended()).unwrap();
+    /// drop(eval);
+    /// assert_eq!(*output.borrow(), ["hello 1"]);
+    /// ```
     pub fn set_print_handler(&mut self, handler: &'a (dyn PrintHandler + 'a)) {
         self.print_handler = handler;
     }
@@ -466,7 +636,7 @@ impl<'v, 'a> Evaluator<'v, 'a> {
             let name = match &eval.module_variables {
                 None => eval
                     .module_env
-                    .names()
+                    .mutable_names()
                     .get_slot(slot)
                     .map(|s| s.as_str().to_owned()),
                 Some(e) => e.0.get_slot_name(slot).map(|s| s.as_str().to_owned()),
@@ -584,6 +754,28 @@ impl<'v, 'a> Evaluator<'v, 'a> {
         };
     }
 
+    /// Unbind a local variable for `del x`, failing if it is not assigned.
+    pub(crate) fn del_slot_local(
+        &self,
+        frame: BcFramePtr<'v>,
+        slot: LocalSlotId,
+    ) -> anyhow::Result<()> {
+        self.get_slot_local(frame, slot)?;
+        frame.clear_slot(slot.to_captured_or_not());
+        Ok(())
+    }
+
+    /// Unbind a captured local variable for `del x`, failing if it is not assigned.
+    pub(crate) fn del_slot_local_captured(&self, slot: LocalCapturedSlotId) -> anyhow::Result<()> {
+        self.get_slot_local_captured(slot)?;
+        let value_captured = self.get_slot_local(self.current_frame, LocalSlotId(slot.0))?;
+        value_captured
+            .downcast_ref::<ValueCaptured>()
+            .expect("not a ValueCaptured")
+            .clear();
+        Ok(())
+    }
+
     /// Take a value from the local slot and store it back wrapped in [`ValueCaptured`].
     pub(crate) fn wrap_local_slot_captured(&mut self, slot: LocalSlotId) {
         let value = self
@@ -28,6 +28,15 @@ use crate::environment::FrozenModule;
 pub trait FileLoader {
     /// Open the file given by the load statement `path`.
     fn load(&self, path: &str) -> anyhow::Result<FrozenModule>;
+
+    /// Check the symbols requested by a `load()` statement before the module is loaded.
+    ///
+    /// `symbols` are pairs of the name in the loaded module and the local name it is bound to,
+    /// e.g. `load("a.star", x = "y")` produces `[("y", "x")]`.
+    /// Called before [`load`](FileLoader::load); an error aborts the `load()` statement.
+    fn validate_load(&self, _path: &str, _symbols: &[(&str, &str)]) -> anyhow::Result<()> {
+        Ok(())
+    }
 }
 
 /// [`FileLoader`] that looks up modules by name from a [`HashMap`].
@@ -193,3 +193,40 @@ impl<'v> FlameProfile<'v> {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use std::time::Instant;
+
+    use crate::eval::runtime::profile::flame::Frame;
+    use crate::eval::runtime::profile::flame::Stacks;
+    use crate::eval::runtime::profile::flame::ValueIndex;
+
+    #[test]
+    fn test_stacks_folded() {
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let names = vec!["f".to_owned(), "g".to_owned()];
+        let frames = vec![
+            (Frame::Push(ValueIndex(0)), at(0)),
+            (Frame::Push(ValueIndex(1)), at(10)),
+            (Frame::Pop, at(30)),
+            (Frame::Pop, at(35)),
+            (Frame::Push(ValueIndex(1)), at(40)),
+            (Frame::Pop, at(45)),
+        ];
+        // Time is attributed to the innermost frame on the stack, and the same
+        // function reached by different call stacks gets separate lines.
+        let mut lines: Vec<_> = Stacks::new(&names, &frames)
+            .render()
+            .lines()
+            .map(str::to_owned)
+            .collect();
+        lines.sort();
+        assert_eq!(
+            vec!["root 5", "root;f 15", "root;f;g 20", "root;g 5"],
+            lines
+        );
+    }
+}
@@ -55,6 +55,8 @@ pub enum ProfileMode {
     BytecodePairs,
     /// Provide output compatible with
     /// [flamegraph.pl](https://github.com/brendangregg/FlameGraph/blob/master/flamegraph.pl).
+    /// Each line is a folded call stack followed by the milliseconds spent with
+    /// that stack on top, e.g. `root;f;g 20`.
     TimeFlame,
     /// Profile runtime typechecking.
     Typecheck,
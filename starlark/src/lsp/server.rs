@@ -226,6 +226,9 @@ pub struct StringLiteralResult {
 #[derive(Default)]
 pub struct LspEvalResult {
     /// The list of diagnostic issues that were encountered while evaluating a starlark program.
+    /// As LSP requires, columns are in UTF-16 code units, so convert them using
+    /// [`ColumnUnit::Utf16`](crate::codemap::ColumnUnit::Utf16), e.g. with
+    /// [`EvalMessage::from_anyhow_in`](crate::errors::EvalMessage::from_anyhow_in).
     pub diagnostics: Vec<Diagnostic>,
     /// If the program could be parsed, the parsed module.
     pub ast: Option<AstModule>,
@@ -56,6 +56,7 @@ use lsp_types::Url;
 use lsp_types::VersionedTextDocumentIdentifier;
 use serde::de::DeserializeOwned;
 
+use crate::codemap::ColumnUnit;
 use crate::errors::EvalMessage;
 use crate::lsp::server::new_notification;
 use crate::lsp::server::server_with_connection;
@@ -122,14 +123,17 @@ impl LspContext for TestServerContext {
             LspUrl::File(path) | LspUrl::Starlark(path) => {
                 match AstModule::parse(&path.to_string_lossy(), content, &Dialect::Extended) {
                     Ok(ast) => {
-                        let diagnostics = ast.lint(None).into_map(|l| EvalMessage::from(l).into());
+                        let diagnostics = ast
+                            .lint(None)
+                            .into_map(|l| EvalMessage::from_lint_in(l, ColumnUnit::Utf16).into());
                         LspEvalResult {
                             diagnostics,
                             ast: Some(ast),
                         }
                     }
                     Err(e) => {
-                        let diagnostics = vec![EvalMessage::from_anyhow(path, &e).into()];
+                        let diagnostics =
+                            vec![EvalMessage::from_anyhow_in(path, &e, ColumnUnit::Utf16).into()];
                         LspEvalResult {
                             diagnostics,
                             ast: None,
@@ -0,0 +1,158 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Assertion functions for tests written in Starlark.
+
+use crate as starlark;
+use crate::environment::GlobalsBuilder;
+use crate::values::none::NoneType;
+use crate::values::Value;
+
+#[derive(Debug, thiserror::Error)]
+enum AssertError {
+    #[error("assert_eq failed{0}\n  left: {1}\n right: {2}")]
+    NotEqual(String, String, String),
+    #[error("assert_ne failed{0}, both values are {1}")]
+    Equal(String, String),
+    #[error("assert_true failed{0}, got {1}")]
+    NotTrue(String, String),
+    #[error("assert_false failed{0}, got {1}")]
+    NotFalse(String, String),
+}
+
+/// The user supplied `msg`, formatted to follow the name of the assertion.
+fn context(msg: Option<&str>) -> String {
+    msg.map(|msg| format!(": {}", msg)).unwrap_or_default()
+}
+
+#[starlark_module]
+pub(crate) fn assert(builder: &mut GlobalsBuilder) {
+    /// Fail unless `a == b`, showing the `repr` of both values and the optional `msg`.
+    ///
+    /// ```
+    /// # starlark::assert::pass(r#"
+    /// assert_eq([1, 2], [1] + [2])
+    /// # "#);
+    /// # starlark::assert::fail(r#"
+    /// assert_eq([1, 2], [1, 3], msg = "lists")  # fail: assert_eq failed: lists
+    /// # "#, "right: [1, 3]");
+    /// ```
+    fn assert_eq<'v>(
+        #[starlark(require = pos)] a: Value<'v>,
+        #[starlark(require = pos)] b: Value<'v>,
+        #[starlark(require = named)] msg: Option<&str>,
+    ) -> anyhow::Result<NoneType> {
+        if a.equals(b)? {
+            Ok(NoneType)
+        } else {
+            Err(AssertError::NotEqual(context(msg), a.to_repr(), b.to_repr()).into())
+        }
+    }
+
+    /// Fail if `a == b`, showing the `repr` of the value and the optional `msg`.
+    ///
+    /// ```
+    /// # starlark::assert::pass(r#"
+    /// assert_ne("a", "b")
+    /// # "#);
+    /// # starlark::assert::fail(r#"
+    /// assert_ne(1, 1)  # fail: assert_ne failed, both values are 1
+    /// # "#, "both values are 1");
+    /// ```
+    fn assert_ne<'v>(
+        #[starlark(require = pos)] a: Value<'v>,
+        #[starlark(require = pos)] b: Value<'v>,
+        #[starlark(require = named)] msg: Option<&str>,
+    ) -> anyhow::Result<NoneType> {
+        if a.equals(b)? {
+            Err(AssertError::Equal(context(msg), a.to_repr()).into())
+        } else {
+            Ok(NoneType)
+        }
+    }
+
+    /// Fail unless `x` is truthy, showing the `repr` of `x` and the optional `msg`.
+    ///
+    /// ```
+    /// # starlark::assert::pass(r#"
+    /// assert_true([1])
+    /// # "#);
+    /// # starlark::assert::fail(r#"
+    /// assert_true({}, msg = "empty")  # fail: assert_true failed: empty, got {}
+    /// # "#, "empty, got {}");
+    /// ```
+    fn assert_true(
+        #[starlark(require = pos)] x: Value,
+        #[starlark(require = named)] msg: Option<&str>,
+    ) -> anyhow::Result<NoneType> {
+        if x.to_bool() {
+            Ok(NoneType)
+        } else {
+            Err(AssertError::NotTrue(context(msg), x.to_repr()).into())
+        }
+    }
+
+    /// Fail unless `x` is falsy, showing the `repr` of `x` and the optional `msg`.
+    ///
+    /// ```
+    /// # starlark::assert::pass(r#"
+    /// assert_false("")
+    /// # "#);
+    /// # starlark::assert::fail(r#"
+    /// assert_false("x")  # fail: assert_false failed, got "x"
+    /// # "#, "got \"x\"");
+    /// ```
+    fn assert_false(
+        #[starlark(require = pos)] x: Value,
+        #[starlark(require = named)] msg: Option<&str>,
+    ) -> anyhow::Result<NoneType> {
+        if x.to_bool() {
+            Err(AssertError::NotFalse(context(msg), x.to_repr()).into())
+        } else {
+            Ok(NoneType)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert;
+
+    #[test]
+    fn test_assert() {
+        assert::pass(
+            r#"
+assert_eq({"a": 1}, dict(a = 1))
+assert_ne(1, 1.5)
+assert_true(True, msg = "unused")
+assert_false(None)
+"#,
+        );
+        assert::fail("assert_eq(1, 2)", "assert_eq failed\n  left: 1\n right: 2");
+        assert::fail(
+            "assert_eq('a', 'b', msg = 'names')",
+            "assert_eq failed: names\n  left: \"a\"\n right: \"b\"",
+        );
+        assert::fail("assert_ne([], [])", "both values are []");
+        assert::fail("assert_true(0)", "assert_true failed, got 0");
+        assert::fail(
+            "assert_false(1, msg = 'x')",
+            "assert_false failed: x, got 1",
+        );
+        assert::fail("assert_true(1, 'msg')", "positional");
+    }
+}
@@ -0,0 +1,61 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Implementation of `bytes` function.
+use crate as starlark;
+use crate::environment::GlobalsBuilder;
+use crate::values::bytes::BytesError;
+use crate::values::bytes::StarlarkBytes;
+use crate::values::Heap;
+use crate::values::Value;
+
+#[starlark_module]
+pub fn global(builder: &mut GlobalsBuilder) {
+    /// [bytes](
+    /// https://github.com/bazelbuild/starlark/blob/master/spec.md#bytes
+    /// ): construct a bytes value.
+    ///
+    /// `bytes(x)` accepts a string, which is encoded as UTF-8, an iterable of
+    /// ints in the range 0-255, or another bytes value.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// bytes("abc") == bytes([97, 98, 99])
+    /// len(bytes("é")) == 2
+    /// # "#);
+    /// ```
+    #[starlark(type = StarlarkBytes::TYPE)]
+    fn bytes<'v>(
+        #[starlark(require = pos)] x: Value<'v>,
+        heap: &'v Heap,
+    ) -> anyhow::Result<StarlarkBytes> {
+        if let Some(s) = x.unpack_str() {
+            return Ok(StarlarkBytes::new(s.as_bytes()));
+        }
+        if let Some(b) = StarlarkBytes::from_value(x) {
+            return Ok(b.clone());
+        }
+        let mut res = Vec::new();
+        for v in x.iterate(heap)? {
+            match v.unpack_int().and_then(|i| u8::try_from(i).ok()) {
+                Some(b) => res.push(b),
+                None => return Err(BytesError::InvalidByte(v.to_repr()).into()),
+            }
+        }
+        Ok(StarlarkBytes::from(res))
+    }
+}
@@ -267,6 +267,7 @@ pub(crate) fn dict_methods(registry: &mut MethodsBuilder) {
         #[starlark(require = pos)] default: Option<Value<'v>>,
     ) -> anyhow::Result<Value<'v>> {
         let mut this = Dict::from_value_mut(this)?;
+        // Hash once, and use the hashed key for both the lookup and the insert.
         let key = key.get_hashed()?;
         if let Some(r) = this.get_hashed(key) {
             return Ok(r);
@@ -294,6 +295,10 @@ pub(crate) fn dict_methods(registry: &mut MethodsBuilder) {
     /// string and used as the key for an insertion into D, with its
     /// corresponding value being `value`.
     ///
+    /// Keys which are already present keep their position in D, and get the new value.
+    /// When a key is given several times, the last value wins,
+    /// with the `name=value` arguments inserted after `pairs`.
+    ///
     /// `update` fails if the dictionary is frozen.
     ///
     /// Examples:
@@ -304,7 +309,8 @@ pub(crate) fn dict_methods(registry: &mut MethodsBuilder) {
     /// x.update([("a", 1), ("b", 2)], c=3)
     /// x.update({"d": 4})
     /// x.update(e=5)
-    /// x == {"a": 1, "b": 2, "c": 3, "d": 4, "e": 5}
+    /// x.update({"a": 6})
+    /// x == {"a": 6, "b": 2, "c": 3, "d": 4, "e": 5}
     /// # "#);
     /// ```
     fn update<'v>(
@@ -374,6 +380,7 @@ pub(crate) fn dict_methods(registry: &mut MethodsBuilder) {
 #[cfg(test)]
 mod tests {
     use crate::assert;
+    use crate::assert::Assert;
 
     #[test]
     fn test_error_codes() {
@@ -381,6 +388,65 @@ mod tests {
         assert::fail("x = {}; x.popitem()", "empty");
     }
 
+    #[test]
+    fn test_get_setdefault() {
+        assert::pass(
+            r#"
+x = {"a": 1}
+assert_eq(x.get("a", 2), 1)
+assert_eq(x.get("b", 2), 2)
+assert_eq(x.get("b"), None)
+assert_eq(x.setdefault("b", []), [])
+x.setdefault("b", [9]).append(3)
+assert_eq(x, {"a": 1, "b": [3]})
+assert_eq(x.setdefault("c"), None)
+assert_eq(x, {"a": 1, "b": [3], "c": None})
+"#,
+        );
+        assert::fail("{}.get([])", "not hashable");
+        assert::fail("{}.setdefault([])", "not hashable");
+
+        let mut a = Assert::new();
+        a.module("x", "frozen_dict = {'a': 1}");
+        a.eq("1", "load('x','frozen_dict')\nfrozen_dict.setdefault('a')");
+        a.fail(
+            "load('x','frozen_dict')\nfrozen_dict.setdefault('b', 2)",
+            "Immutable",
+        );
+    }
+
+    #[test]
+    fn test_update() {
+        assert::pass(
+            r#"
+x = {"a": 1, "b": 2, "c": 3}
+x.update({"b": 20, "d": 4})
+assert_eq(list(x.items()), [("a", 1), ("b", 20), ("c", 3), ("d", 4)])
+x.update([("a", 10), ["e", 5]], c = 30, f = 6)
+assert_eq(list(x.items()), [("a", 10), ("b", 20), ("c", 30), ("d", 4), ("e", 5), ("f", 6)])
+x.update([("g", 1)], g = 2)
+assert_eq(x["g"], 2)
+x.update(x)
+x.update()
+assert_eq(len(x), 7)
+y = {}
+y.update([(i, i * i) for i in range(3)])
+assert_eq(y, {0: 0, 1: 1, 2: 4})
+"#,
+        );
+        assert::fail("{}.update([[1]])", "list of pairs");
+        assert::fail("{}.update([(1, 2, 3)])", "list of pairs");
+        assert::fail("{}.update([([], 1)])", "not hashable");
+        assert::fail("{}.update(None)", "not supported");
+
+        let mut a = Assert::new();
+        a.module("x", "frozen_dict = {'a': 1}");
+        a.fail(
+            "load('x','frozen_dict')\nfrozen_dict.update(b = 2)",
+            "Immutable",
+        );
+    }
+
     #[test]
     fn test_dict_add() {
         assert::fail("{1: 2} + {3: 4}", "not supported");
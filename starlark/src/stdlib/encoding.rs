@@ -0,0 +1,235 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! The `base64` and `hex` modules, to encode binary data as text and back.
+
+use std::fmt::Write;
+
+use crate as starlark;
+use crate::environment::GlobalsBuilder;
+use crate::values::bytes::StarlarkBytes;
+use crate::values::Value;
+
+#[derive(Debug, thiserror::Error)]
+enum EncodingError {
+    #[error("`{0}.encode` expects `str` or `bytes`, got value of type `{1}`")]
+    NotBytes(&'static str, &'static str),
+    #[error("`{0}.decode`: invalid character {1:?} at position {2}")]
+    InvalidChar(&'static str, char, usize),
+    #[error("`{0}.decode`: input of length {1} ends in the middle of a {2}")]
+    Truncated(&'static str, usize, &'static str),
+}
+
+const BASE64_CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// The bytes to encode: strings are encoded as UTF-8.
+fn unpack_bytes<'v>(module: &'static str, x: Value<'v>) -> anyhow::Result<&'v [u8]> {
+    if let Some(s) = x.unpack_str() {
+        Ok(s.as_bytes())
+    } else if let Some(b) = StarlarkBytes::from_value(x) {
+        Ok(b.as_bytes())
+    } else {
+        Err(EncodingError::NotBytes(module, x.get_type()).into())
+    }
+}
+
+fn base64_encode(x: &[u8]) -> String {
+    let mut res = String::with_capacity((x.len() + 2) / 3 * 4);
+    for chunk in x.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0, |n, (i, b)| n | (*b as usize) << (16 - 8 * i));
+        // A chunk of `k` bytes is written as `k + 1` characters, then padded to 4.
+        for i in 0..4 {
+            if i <= chunk.len() {
+                res.push(BASE64_CHARS[(n >> (18 - 6 * i)) & 0x3f] as char);
+            } else {
+                res.push('=');
+            }
+        }
+    }
+    res
+}
+
+fn base64_decode(x: &str) -> anyhow::Result<Vec<u8>> {
+    let data = x
+        .strip_suffix("==")
+        .or_else(|| x.strip_suffix('='))
+        .unwrap_or(x);
+    let mut res = Vec::with_capacity(data.len() / 4 * 3);
+    let mut acc = 0;
+    let mut bits = 0;
+    for (i, c) in data.char_indices() {
+        let v = match c {
+            'A'..='Z' => c as u32 - 'A' as u32,
+            'a'..='z' => c as u32 - 'a' as u32 + 26,
+            '0'..='9' => c as u32 - '0' as u32 + 52,
+            '+' => 62,
+            '/' => 63,
+            _ => return Err(EncodingError::InvalidChar("base64", c, i).into()),
+        };
+        acc = (acc << 6 | v) & 0xffff;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            res.push((acc >> bits) as u8);
+        }
+    }
+    if x.len() % 4 != 0 {
+        return Err(EncodingError::Truncated("base64", x.len(), "group of 4 characters").into());
+    }
+    Ok(res)
+}
+
+fn hex_encode(x: &[u8]) -> String {
+    let mut res = String::with_capacity(x.len() * 2);
+    for b in x {
+        write!(res, "{:02x}", b).unwrap();
+    }
+    res
+}
+
+fn hex_decode(x: &str) -> anyhow::Result<Vec<u8>> {
+    let mut res = Vec::with_capacity(x.len() / 2);
+    let mut high = None;
+    for (i, c) in x.char_indices() {
+        let v = match c.to_digit(16) {
+            Some(v) => v as u8,
+            None => return Err(EncodingError::InvalidChar("hex", c, i).into()),
+        };
+        match high.take() {
+            None => high = Some(v),
+            Some(h) => res.push(h << 4 | v),
+        }
+    }
+    if high.is_some() {
+        return Err(EncodingError::Truncated("hex", x.len(), "byte").into());
+    }
+    Ok(res)
+}
+
+pub(crate) fn encoding(builder: &mut GlobalsBuilder) {
+    #[starlark_module]
+    fn base64_members(builder: &mut GlobalsBuilder) {
+        /// Encode `bytes`, or a string as UTF-8, with the standard base64 alphabet and padding.
+        ///
+        /// ```
+        /// # starlark::assert::all_true(r#"
+        /// base64.encode("hi!?") == "aGkhPw=="
+        /// base64.encode(bytes([0, 255])) == "AP8="
+        /// # "#);
+        /// ```
+        fn encode(#[starlark(require = pos)] x: Value) -> anyhow::Result<String> {
+            Ok(base64_encode(unpack_bytes("base64", x)?))
+        }
+
+        /// Decode a base64 string, with the standard alphabet and padding, to `bytes`.
+        ///
+        /// Fails with the position of the first invalid character, or if the padding is missing.
+        ///
+        /// ```
+        /// # starlark::assert::all_true(r#"
+        /// base64.decode("aGkhPw==") == bytes("hi!?")
+        /// # "#);
+        /// ```
+        fn decode(#[starlark(require = pos)] x: &str) -> anyhow::Result<StarlarkBytes> {
+            Ok(StarlarkBytes::new(base64_decode(x)?))
+        }
+    }
+
+    #[starlark_module]
+    fn hex_members(builder: &mut GlobalsBuilder) {
+        /// Encode `bytes`, or a string as UTF-8, as lowercase hexadecimal, two digits per byte.
+        ///
+        /// ```
+        /// # starlark::assert::all_true(r#"
+        /// hex.encode(bytes([0, 171])) == "00ab"
+        /// # "#);
+        /// ```
+        fn encode(#[starlark(require = pos)] x: Value) -> anyhow::Result<String> {
+            Ok(hex_encode(unpack_bytes("hex", x)?))
+        }
+
+        /// Decode a hexadecimal string, with two digits of either case per byte, to `bytes`.
+        ///
+        /// Fails with the position of the first invalid character, or if the length is odd.
+        ///
+        /// ```
+        /// # starlark::assert::all_true(r#"
+        /// hex.decode("00aB") == bytes([0, 171])
+        /// # "#);
+        /// ```
+        fn decode(#[starlark(require = pos)] x: &str) -> anyhow::Result<StarlarkBytes> {
+            Ok(StarlarkBytes::new(hex_decode(x)?))
+        }
+    }
+
+    builder.struct_("base64", base64_members);
+    builder.struct_("hex", hex_members);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert;
+
+    #[test]
+    fn test_base64() {
+        assert::all_true(
+            r#"
+base64.encode("") == ""
+base64.encode("f") == "Zg=="
+base64.encode("fo") == "Zm8="
+base64.encode("foo") == "Zm9v"
+base64.encode("foob") == "Zm9vYg=="
+base64.encode(bytes([251, 255, 191])) == "+/+/"
+base64.decode("") == bytes("")
+base64.decode("Zg==") == bytes("f")
+base64.decode("Zm8=") == bytes("fo")
+base64.decode("Zm9vYg==") == bytes("foob")
+base64.decode("+/+/") == bytes([251, 255, 191])
+base64.decode(base64.encode("é")) == bytes("é")
+"#,
+        );
+        assert::fail(
+            "base64.decode('Zm9v!g==')",
+            "invalid character '!' at position 4",
+        );
+        assert::fail(
+            "base64.decode('Zg=A')",
+            "invalid character '=' at position 2",
+        );
+        assert::fail("base64.decode('Zg')", "length 2 ends in the middle");
+        assert::fail("base64.encode(1)", "expects `str` or `bytes`");
+    }
+
+    #[test]
+    fn test_hex() {
+        assert::all_true(
+            r#"
+hex.encode("") == ""
+hex.encode(bytes([0, 15, 16, 255])) == "000f10ff"
+hex.encode("é") == "c3a9"
+hex.decode("000F10ff") == bytes([0, 15, 16, 255])
+hex.decode(hex.encode("é")) == bytes("é")
+"#,
+        );
+        assert::fail("hex.decode('0g')", "invalid character 'g' at position 1");
+        assert::fail("hex.decode('abc')", "length 3 ends in the middle of a byte");
+        assert::fail("hex.encode([1])", "expects `str` or `bytes`");
+    }
+}
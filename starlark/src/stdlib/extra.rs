@@ -24,6 +24,7 @@ use gazebo::coerce::coerce;
 use gazebo::coerce::Coerce;
 use gazebo::prelude::*;
 use itertools::Itertools;
+use num_bigint::Sign;
 
 use crate as starlark;
 use crate::collections::symbol_map::Symbol;
@@ -37,6 +38,7 @@ use crate::values::dict::DictRef;
 use crate::values::function::FUNCTION_TYPE;
 use crate::values::layout::typed::string::StringValueLike;
 use crate::values::none::NoneType;
+use crate::values::num::Num;
 use crate::values::regex::StarlarkRegex;
 use crate::values::tuple::Tuple;
 use crate::values::Freeze;
@@ -48,6 +50,7 @@ use crate::values::StarlarkValue;
 use crate::values::StringValue;
 use crate::values::Trace;
 use crate::values::Value;
+use crate::values::ValueError;
 use crate::values::ValueLike;
 
 #[starlark_module]
@@ -74,15 +77,44 @@ pub fn filter(builder: &mut GlobalsBuilder) {
 
 #[starlark_module]
 pub fn map(builder: &mut GlobalsBuilder) {
+    /// Apply a function to each element of an iterable, returning a list of the results.
+    ///
+    /// With several iterables, `map(f, xs, ys)` calls `f` with one element from each,
+    /// stopping at the end of the shortest.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// map(str, [1, 2])                           == ["1", "2"]
+    /// map(lambda x, y: x * y, [1, 2, 3], [4, 5]) == [4, 10]
+    /// # "#);
+    /// ```
     fn map<'v>(
         #[starlark(require = pos)] func: Value<'v>,
         #[starlark(require = pos)] seq: Value<'v>,
+        #[starlark(args)] seqs: Vec<Value<'v>>,
         eval: &mut Evaluator<'v, '_>,
     ) -> anyhow::Result<Value<'v>> {
         let it = seq.iterate(eval.heap())?;
         let mut res = Vec::with_capacity(it.size_hint().0);
-        for v in it {
-            res.push(func.invoke_pos(&[v], eval)?);
+        if seqs.is_empty() {
+            for v in it {
+                res.push(func.invoke_pos(&[v], eval)?);
+            }
+        } else {
+            let mut its = vec![it];
+            for seq in seqs {
+                its.push(seq.iterate(eval.heap())?);
+            }
+            'outer: loop {
+                let mut args = Vec::with_capacity(its.len());
+                for it in &mut its {
+                    match it.next() {
+                        Some(v) => args.push(v),
+                        None => break 'outer,
+                    }
+                }
+                res.push(func.invoke_pos(&args, eval)?);
+            }
         }
         Ok(eval.heap().alloc_list(&res))
     }
@@ -90,6 +122,18 @@ pub fn map(builder: &mut GlobalsBuilder) {
 
 #[starlark_module]
 pub fn partial(builder: &mut GlobalsBuilder) {
+    /// Partially apply a function. `partial(f, *args, **kwargs)` returns a function which,
+    /// when called with `*more_args, **more_kwargs`, calls
+    /// `f(*args, *more_args, **kwargs, **more_kwargs)`.
+    ///
+    /// ```
+    /// # starlark::assert::is_true(r#"
+    /// def f(a, b, c = 0):
+    ///     return (a, b, c)
+    /// g = partial(f, 1, c = 3)
+    /// g(2) == (1, 2, 3) and g(b = 2) == (1, 2, 3)
+    /// # "#);
+    /// ```
     fn partial<'v>(
         #[starlark(require = pos)] func: Value<'v>,
         #[starlark(args)] args: Value<'v>,
@@ -176,6 +220,14 @@ pub trait PrintHandler {
     fn println(&self, text: &str) -> anyhow::Result<()>;
 }
 
+/// Any closure taking the text can be used as a handler, e.g. to log it or collect it in a buffer.
+impl<F: Fn(&str)> PrintHandler for F {
+    fn println(&self, text: &str) -> anyhow::Result<()> {
+        self(text);
+        Ok(())
+    }
+}
+
 pub(crate) struct StderrPrintHandler;
 
 impl PrintHandler for StderrPrintHandler {
@@ -211,8 +263,32 @@ pub fn pprint(builder: &mut GlobalsBuilder) {
 
 #[starlark_module]
 pub fn abs(builder: &mut GlobalsBuilder) {
-    fn abs(#[starlark(require = pos)] x: i32) -> anyhow::Result<i32> {
-        Ok(x.abs())
+    /// Take the absolute value of an int or float.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// abs(-3) == 3
+    /// abs(3) == 3
+    /// abs(-2.5) == 2.5
+    /// abs(-123456789123456789123456789) == 123456789123456789123456789
+    /// # "#);
+    /// ```
+    fn abs<'v>(
+        #[starlark(require = pos)] x: Value<'v>,
+        heap: &'v Heap,
+    ) -> anyhow::Result<Value<'v>> {
+        let negative = match x.unpack_num() {
+            Some(Num::Int(i)) => i < 0,
+            Some(Num::BigInt(b)) => b.get().sign() == Sign::Minus,
+            Some(Num::Float(f)) => f.is_sign_negative(),
+            None => return ValueError::unsupported_owned(x.get_type(), "abs()", None),
+        };
+        // `minus` promotes `abs(-2147483648)` to a bigint.
+        if negative {
+            x.minus(heap)
+        } else {
+            Ok(x)
+        }
     }
 }
 
@@ -234,22 +310,14 @@ impl<'v, V: ValueLike<'v>, S> PartialGen<V, S> {
 
 impl<'v, V: ValueLike<'v>, S> Display for PartialGen<V, S> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "partial({}, *[", self.func)?;
-        for (i, v) in self.pos_content().iter().enumerate() {
-            if i != 0 {
-                write!(f, ",")?;
-            }
-            v.fmt(f)?;
+        write!(f, "partial({}", self.func)?;
+        for v in self.pos_content() {
+            write!(f, ", {}", v)?;
         }
-        write!(f, "], **{{")?;
-        for (i, (k, v)) in self.names.iter().zip(self.named.iter()).enumerate() {
-            if i != 0 {
-                write!(f, ",")?;
-            }
-            write!(f, "{}:", k.0.as_str())?;
-            v.to_value().fmt(f)?;
+        for ((k, _), v) in self.names.iter().zip(self.named.iter()) {
+            write!(f, ", {}={}", k.as_str(), v.to_value())?;
         }
-        write!(f, "}})")
+        write!(f, ")")
     }
 }
 
@@ -323,6 +391,19 @@ mod tests {
     use crate::assert::Assert;
     use crate::stdlib::PrintHandler;
 
+    #[test]
+    fn test_abs() {
+        assert::eq("2147483648", "abs(-2147483647 - 1)");
+        assert::eq("2147483647", "abs(-2147483647)");
+        assert::eq("0", "abs(0)");
+        assert::eq("9223372036854775808", "abs(-9223372036854775808)");
+        assert::eq("9223372036854775808", "abs(9223372036854775808)");
+        assert::eq("'int'", "type(abs(-9223372036854775808))");
+        assert::eq("0.0", "abs(-0.0)");
+        assert::eq("'float'", "type(abs(-1.0))");
+        assert::fail("abs('1')", "not supported");
+    }
+
     #[test]
     fn test_filter() {
         assert::pass(
@@ -359,6 +440,23 @@ assert_eq(["11",8], map(double, ["1",4]))
         );
     }
 
+    #[test]
+    fn test_map_multiple() {
+        assert::pass(
+            r#"
+def add3(x, y, z):
+    return x + y + z
+
+assert_eq([5, 7, 9], map(lambda x, y: x + y, [1, 2, 3], [4, 5, 6]))
+assert_eq([5, 7], map(lambda x, y: x + y, [1, 2, 3], [4, 5]))
+assert_eq([5, 7], map(lambda x, y: x + y, [1, 2], [4, 5, 6]))
+assert_eq(["aA0"], map(add3, ["a", "b"], ["A"], ["0", "1", "2"]))
+assert_eq([], map(add3, [1, 2], [], [3]))
+"#,
+        );
+        assert::fail("map(lambda x: x, [1], [2])", "extra positional");
+    }
+
     #[test]
     fn test_partial() {
         assert::pass(
@@ -389,6 +487,19 @@ assert_eq(
     (partial(sum))(1, 2, 3, third=None, **{"other": True}))
 "#,
         );
+        assert::eq(
+            r#""partial(len, [1], \"x\", key=True)""#,
+            r#"repr(partial(len, [1], "x", key = True))"#,
+        );
+        assert::fail(
+            r#"
+def f(a, b):
+    pass
+partial(f, 1)()
+"#,
+            "Missing parameter `b`",
+        );
+        assert::fail("partial(1, 2)()", "not supported");
     }
 
     #[test]
@@ -433,4 +544,16 @@ assert_eq(dedupe([a,b,a]), [a,b])
         a.pass("print('hw')");
         assert_eq!("hw", s_copy.borrow().as_str());
     }
+
+    #[test]
+    fn test_print_closure() {
+        let lines = RefCell::new(Vec::new());
+        let print_handler = |s: &str| lines.borrow_mut().push(s.to_owned());
+        let mut a = Assert::new();
+        a.set_print_handler(&print_handler);
+        a.pass("print('x', 1, [True])");
+        // The program is run once for each GC strategy.
+        assert!(!lines.borrow().is_empty());
+        assert!(lines.borrow().iter().all(|x| x == "x 1 [True]"));
+    }
 }
@@ -20,13 +20,17 @@
 
 use std::cmp::Ordering;
 use std::fmt::Display;
+use std::num::IntErrorKind;
 use std::num::NonZeroI32;
 
+use num_bigint::BigInt;
+
 use crate as starlark;
 use crate::collections::SmallMap;
 use crate::environment::GlobalsBuilder;
 use crate::eval::Arguments;
 use crate::eval::Evaluator;
+use crate::values::bigint::StarlarkBigInt;
 use crate::values::bool::BOOL_TYPE;
 use crate::values::dict::Dict;
 use crate::values::float::StarlarkFloat;
@@ -67,6 +71,10 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
 
     /// fail: fail the execution
     ///
+    /// The arguments are joined with spaces as by `print`, so strings are shown
+    /// as they are and other values by their `repr`. The error is reported at
+    /// the call to `fail`.
+    ///
     /// Examples:
     /// ```
     /// # starlark::assert::fail(r#"
@@ -75,6 +83,9 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
     /// # starlark::assert::fail(r#"
     /// fail("oops", 1, False)  # fail: oops 1 False
     /// # "#, "oops 1 False");
+    /// # starlark::assert::fail(r#"
+    /// fail("bad value:", ["x", None])  # fail: bad value: ["x", None]
+    /// # "#, r#"bad value: ["x", None]"#);
     /// ```
     fn fail(#[starlark(args)] args: Vec<Value>) -> anyhow::Result<NoneType> {
         let mut s = String::new();
@@ -310,7 +321,7 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
     /// value within the sequence.
     ///
     /// The optional second parameter, `start`, specifies an integer value to
-    /// add to each index.
+    /// add to each index. It may also be passed by name, as in Python.
     ///
     /// Examples:
     ///
@@ -318,6 +329,7 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
     /// # starlark::assert::all_true(r#"
     /// enumerate(["zero", "one", "two"]) == [(0, "zero"), (1, "one"), (2, "two")]
     /// enumerate(["one", "two"], 1) == [(1, "one"), (2, "two")]
+    /// enumerate(["a", "b"], start=5) == [(5, "a"), (6, "b")]
     /// # "#);
     /// ```
     #[starlark(speculative_exec_safe)]
@@ -340,6 +352,8 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
     /// If x is a `float`, the result is x.
     /// if x is an `int`, the result is the nearest floating point value to x.
     /// If x is a string, the string is interpreted as a floating-point literal.
+    /// The strings `"inf"`, `"+inf"`, `"-inf"` and `"nan"` (in any case) give the
+    /// special values, so `float(str(x))` accepts the output of `str` for any float `x`.
     /// With no arguments, `float()` returns `0.0`.
     ///
     /// ```
@@ -352,6 +366,9 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
     /// float('1e2') == 100.0
     /// float(False) == 0.0
     /// float(True) == 1.0
+    /// float('-inf') == -float('+inf')
+    /// str(float(str(float('inf')))) == '+inf'
+    /// str(float(str(float('nan')))) == 'nan'
     /// # "#);
     /// # starlark::assert::fail(r#"
     /// float("hello")   # error: not a valid number
@@ -451,21 +468,34 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
     /// implies `hash(x) == hash(y)``.
     ///
     /// `hash` fails if x, or any value upon which its hash depends, is
-    /// unhashable.
+    /// unhashable, e.g. a list or a dict.
+    ///
+    /// For strings the hash is the one given by the specification (see below).
+    /// For all other values it is the hash used for dictionary keys, so values which
+    /// are equal as dictionary keys (such as `1` and `1.0`) have the same hash.
+    /// Such hashes are stable within a single process, but may change between
+    /// versions of this library, so should not be persisted.
     ///
     /// ```
     /// # starlark::assert::all_true(r#"
     /// hash("hello") != hash("world")
+    /// hash(1) == hash(1.0)
+    /// hash((1, "x")) == hash((1, "x"))
     /// # "#);
     /// ```
     #[starlark(speculative_exec_safe)]
-    fn hash(#[starlark(require = pos)] a: &str) -> anyhow::Result<i32> {
+    fn hash(#[starlark(require = pos)] a: Value) -> anyhow::Result<i32> {
+        let a = match a.unpack_str() {
+            Some(a) => a,
+            None => return Ok(a.get_hashed()?.hash().get() as i32),
+        };
+
         // From the starlark spec:
         // > the hash function for strings is the same as that implemented by java.lang.String.hashCode,
         // > a simple polynomial accumulator over the UTF-16 transcoding of the string:
         // > `s[0]*31^(n-1) + s[1]*31^(n-2) + ... + s[n-1]`
-        // As per spec the function should only support string and bytes types.
-        // We don't have support for bytes, so parameter is forced to be a string.
+        // The spec only defines the function for string and bytes types,
+        // other values use the hash of dictionary keys.
 
         // Most strings are ASCII strings, try them first.
         #[allow(clippy::never_loop)]
@@ -498,12 +528,12 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
     /// If x is a `bool`, the result is 0 for `False` or 1 for `True`.
     ///
     /// If x is a string, it is interpreted like a string literal;
-    /// an optional base prefix (`0`, `0b`, `0B`, `0x`, `0X`) determines which
+    /// an optional base prefix (`0b`, `0B`, `0o`, `0O`, `0x`, `0X`) determines which
     /// base to use. The string may specify an arbitrarily large integer,
     /// whereas true integer literals are restricted to 64 bits.
-    /// If a non-zero `base` argument is provided, the string is interpreted
-    /// in that base and no base prefix is permitted; the base argument may
-    /// specified by name.
+    /// If a non-zero `base` argument (between 2 and 36) is provided, the string
+    /// is interpreted in that base, and a prefix is only permitted if it matches
+    /// the base; the base argument may specified by name.
     ///
     /// `int()` with no arguments returns 0.
     ///
@@ -518,6 +548,11 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
     /// int('16', 10) == 16
     /// int('16', 8) == 14
     /// int('16', 16) == 22
+    /// int('ff', 16) == 255
+    /// int('0xff', 16) == 255
+    /// int('-101', 2) == -5
+    /// int('0o17', 0) == 15
+    /// int('zz', base=36) == 1295
     /// int(0.0) == 0
     /// int(3.14) == 3
     /// int(-12345.6789) == -12345
@@ -541,6 +576,7 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
     fn int<'v>(
         #[starlark(require = pos)] a: Option<Value<'v>>,
         base: Option<Value<'v>>,
+        heap: &'v Heap,
     ) -> anyhow::Result<Value<'v>> {
         if a.is_none() {
             return Ok(Value::new_int(0));
@@ -606,18 +642,19 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
                     error,
                 )
             }
-            match (u32::from_str_radix(s, base), negate) {
-                (Ok(i), false) => i32::try_from(i)
-                    .map(Value::new_int)
-                    .map_err(|_| err(a, base, "overflow")),
-                (Ok(i), true) => {
-                    if i > 0x80000000 {
-                        Err(err(a, base, "overflow"))
-                    } else {
-                        Ok(Value::new_int(0u32.wrapping_sub(i) as i32))
+            match u32::from_str_radix(s, base) {
+                Ok(i) => Ok(heap.alloc(if negate { -(i as i64) } else { i as i64 })),
+                // `from_str_radix` for `BigInt` also accepts `_` separators, which we don't.
+                Err(e) if *e.kind() == IntErrorKind::PosOverflow && !s.contains('_') => {
+                    match BigInt::parse_bytes(s.as_bytes(), base) {
+                        Some(i) => Ok(StarlarkBigInt::alloc_bigint(
+                            if negate { -i } else { i },
+                            heap,
+                        )),
+                        None => Err(err(a, base, "invalid digit found in string")),
                     }
                 }
-                (Err(x), _) => Err(err(a, base, x)),
+                Err(e) => Err(err(a, base, e)),
             }
         } else if let Some(base) = base {
             Err(anyhow::anyhow!(
@@ -708,33 +745,43 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
     /// `max(x)` returns the greatest element in the iterable sequence x.
     ///
     /// It is an error if any element does not support ordered comparison,
-    /// or if the sequence is empty.
+    /// or if the sequence is empty and no `default` is given.
     ///
     /// The optional named parameter `key` specifies a function to be applied
     /// to each element prior to comparison.
     ///
+    /// The optional named parameter `default` is returned if the sequence is empty.
+    /// It can only be given with a single iterable argument.
+    ///
     /// ```
     /// # starlark::assert::all_true(r#"
     /// max([3, 1, 4, 1, 5, 9])               == 9
     /// max("two", "three", "four")           == "two"    # the lexicographically greatest
     /// max("two", "three", "four", key=len)  == "three"  # the longest
+    /// max([], default=0)                    == 0
     /// # "#);
     /// ```
     #[starlark(speculative_exec_safe)]
     fn max<'v>(
         #[starlark(args)] mut args: Vec<Value<'v>>,
         key: Option<Value<'v>>,
+        default: Option<Value<'v>>,
         eval: &mut Evaluator<'v, '_>,
     ) -> anyhow::Result<Value<'v>> {
         let args = if args.len() == 1 {
             args.swap_remove(0)
+        } else if default.is_some() {
+            return Err(anyhow::anyhow!(
+                "Cannot specify a default for max() with multiple positional arguments"
+            ));
         } else {
             eval.heap().alloc(args)
         };
         let mut it = args.iterate(eval.heap())?;
-        let mut max = match it.next() {
-            Some(x) => x,
-            None => {
+        let mut max = match (it.next(), default) {
+            (Some(x), _) => x,
+            (None, Some(default)) => return Ok(default),
+            (None, None) => {
                 return Err(anyhow::anyhow!(
                     "Argument is an empty iterable, max() expect a non empty iterable"
                 ));
@@ -769,30 +816,43 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
     /// `min(x)` returns the least element in the iterable sequence x.
     ///
     /// It is an error if any element does not support ordered comparison,
-    /// or if the sequence is empty.
+    /// or if the sequence is empty and no `default` is given.
+    ///
+    /// The optional named parameter `key` specifies a function to be applied
+    /// to each element prior to comparison.
+    ///
+    /// The optional named parameter `default` is returned if the sequence is empty.
+    /// It can only be given with a single iterable argument.
     ///
     /// ```
     /// # starlark::assert::all_true(r#"
     /// min([3, 1, 4, 1, 5, 9])                 == 1
     /// min("two", "three", "four")             == "four"  # the lexicographically least
     /// min("two", "three", "four", key=len)    == "two"   # the shortest
+    /// min([], default=None)                   == None
     /// # "#);
     /// ```
     #[starlark(speculative_exec_safe)]
     fn min<'v>(
         #[starlark(args)] mut args: Vec<Value<'v>>,
         key: Option<Value<'v>>,
+        default: Option<Value<'v>>,
         eval: &mut Evaluator<'v, '_>,
     ) -> anyhow::Result<Value<'v>> {
         let args = if args.len() == 1 {
             args.swap_remove(0)
+        } else if default.is_some() {
+            return Err(anyhow::anyhow!(
+                "Cannot specify a default for min() with multiple positional arguments"
+            ));
         } else {
             eval.heap().alloc(args)
         };
         let mut it = args.iterate(eval.heap())?;
-        let mut min = match it.next() {
-            Some(x) => x,
-            None => {
+        let mut min = match (it.next(), default) {
+            (Some(x), _) => x,
+            (None, Some(default)) => return Ok(default),
+            (None, None) => {
                 return Err(anyhow::anyhow!(
                     "Argument is an empty iterable, min() expect a non empty iterable"
                 ));
@@ -916,10 +976,14 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
     /// ): formats its argument as a string.
     ///
     /// All strings in the result are double-quoted.
+    /// For `None`, bools, ints, finite floats, strings, and lists, tuples and dicts of these,
+    /// the result is a Starlark expression which evaluates to an equal value.
     ///
     /// ```
     /// # starlark::assert::all_true(r#"
     /// repr(1)                 == '1'
+    /// repr(("x",))            == '("x",)'
+    /// repr(1234567.8)         == '1.2345678e+06'
     /// repr("x")               == "\"x\""
     /// repr([1, "x"])          == "[1, \"x\"]"
     /// repr("test \"'")        == "\"test \\\"'\""
@@ -943,11 +1007,13 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
     /// ): reverse a sequence
     ///
     /// `reversed(x)` returns a new list containing the elements of the iterable
-    /// sequence x in reverse order.
+    /// sequence x in reverse order. For a `range`, the elements are generated
+    /// from last to first, without building the forward list first.
     ///
     /// ```
     /// # starlark::assert::all_true(r#"
     /// reversed(['a', 'b', 'c'])              == ['c', 'b', 'a']
+    /// reversed(('a', 'b', 'c'))              == ['c', 'b', 'a']
     /// reversed(range(5))                     == [4, 3, 2, 1, 0]
     /// reversed("stressed".elems())           == ["d", "e", "s", "s", "e", "r", "t", "s"]
     /// reversed({"one": 1, "two": 2}.keys())  == ["two", "one"]
@@ -958,6 +1024,9 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
         #[starlark(require = pos)] a: Value<'v>,
         heap: &'v Heap,
     ) -> anyhow::Result<Value<'v>> {
+        if let Some(range) = Range::from_value(a) {
+            return Ok(heap.alloc_list_iter(range.iter_rev()?.map(Value::new_int)));
+        }
         let mut v: Vec<Value> = a.iterate(heap)?.collect();
         v.reverse();
         Ok(heap.alloc_list(&v))
@@ -968,14 +1037,17 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
     /// ): sort a sequence
     ///
     /// `sorted(x)` returns a new list containing the elements of the iterable
-    /// sequence x, in sorted order.  The sort algorithm is stable.
+    /// sequence x, in sorted order.  The sort algorithm is stable, so elements
+    /// which compare equal (or have equal keys) keep their relative order
+    /// from the input, even when `reverse` is true.
     ///
     /// The optional named parameter `reverse`, if true, causes `sorted` to
     /// return results in reverse sorted order.
     ///
     /// The optional named parameter `key` specifies a function of one
     /// argument to apply to obtain the value's sort key.
-    /// The default behavior is the identity function.
+    /// The default behavior is the identity function. All keys are computed
+    /// before sorting starts, so if `key` fails, `sorted` fails with that error.
     ///
     /// ```
     /// # starlark::assert::all_true(r#"
@@ -983,6 +1055,7 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
     /// sorted([3, 1, 4, 1, 5, 9], reverse=True)                 == [9, 5, 4, 3, 1, 1]
     /// sorted(["two", "three", "four"], key=len)                == ["two", "four", "three"] # shortest to longest
     /// sorted(["two", "three", "four"], key=len, reverse=True)  == ["three", "four", "two"] # longest to shortest
+    /// sorted(["b", "a", "d", "c"], key=lambda x: x in "ab")     == ["d", "c", "b", "a"] # stable
     /// # "#);
     /// ```
     // This function is not spec-safe, because it may call `key` function
@@ -1117,33 +1190,53 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
     /// of the sequences, and so on.  The result list is only as long as the
     /// shortest of the input sequences.
     ///
+    /// If the named argument `strict` is `True`, `zip` fails if the sequences
+    /// do not all have the same length.
+    ///
     /// ```
     /// # starlark::assert::all_true(r#"
     /// zip()                           == []
     /// zip(range(5))                   == [(0,), (1,), (2,), (3,), (4,)]
     /// zip(range(5), "abc".elems())    == [(0, "a"), (1, "b"), (2, "c")]
+    /// zip([1, 2], "ab".elems(), strict = True) == [(1, "a"), (2, "b")]
     /// # "#);
     /// ```
     #[starlark(speculative_exec_safe)]
     fn zip<'v>(
         #[starlark(args)] args: Vec<Value<'v>>,
+        #[starlark(require = named, default = false)] strict: bool,
         heap: &'v Heap,
     ) -> anyhow::Result<Value<'v>> {
         let mut v = Vec::new();
-        let mut first = true;
-        for arg in args {
+        for (i, arg) in args.into_iter().enumerate() {
             let mut idx = 0;
+            let mut longer = false;
             for e in arg.iterate(heap)? {
-                if first {
+                if i == 0 {
                     v.push(heap.alloc((e,)));
                     idx += 1;
                 } else if idx < v.len() {
                     v[idx] = v[idx].add(heap.alloc((e,)), heap)?;
                     idx += 1;
+                } else if strict {
+                    longer = true;
+                    break;
                 }
             }
+            if strict && i != 0 && (longer || idx < v.len()) {
+                let previous = if i == 1 {
+                    "argument 1".to_owned()
+                } else {
+                    format!("arguments 1-{}", i)
+                };
+                return Err(anyhow::anyhow!(
+                    "zip() argument {} is {} than {}",
+                    i + 1,
+                    if longer { "longer" } else { "shorter" },
+                    previous,
+                ));
+            }
             v.truncate(idx);
-            first = false;
         }
         Ok(heap.alloc_list(&v))
     }
@@ -1153,6 +1246,25 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
 mod tests {
     use crate::assert;
 
+    #[test]
+    fn test_zip_strict() {
+        assert::eq("[(1, 3)]", "zip([1, 2], [3])");
+        assert::eq("[(1, 3, 5)]", "zip([1], [3], [5], strict = True)");
+        assert::eq("[]", "zip(strict = True)");
+        assert::fail(
+            "zip([1, 2], [3], strict = True)",
+            "zip() argument 2 is shorter than argument 1",
+        );
+        assert::fail(
+            "zip([1], [2], [3, 4], strict = True)",
+            "zip() argument 3 is longer than arguments 1-2",
+        );
+        assert::fail(
+            "zip([1], [], [3], strict = True)",
+            "zip() argument 2 is shorter than argument 1",
+        );
+    }
+
     #[test]
     fn test_constants() {
         assert::is_true("not None");
@@ -1165,6 +1277,64 @@ mod tests {
         assert::fail("chr(0x110000)", "not a valid UTF-8");
     }
 
+    #[test]
+    fn test_reversed() {
+        assert::eq("[3, 2, 1]", "reversed((1, 2, 3))");
+        assert::eq("[7, 4, 1]", "reversed(range(1, 10, 3))");
+        assert::eq("[1, 4, 7, 10]", "reversed(range(10, 0, -3))");
+        assert::eq("[]", "reversed(range(5, 0))");
+        assert::eq("[2147483646]", "reversed(range(2147483646, 2147483647))");
+        assert::eq("reversed(range(-5, 17, 4))", "list(range(-5, 17, 4))[::-1]");
+        assert::eq(
+            "[2147483646, -1, -2147483647 - 1]",
+            "reversed(range(-2147483647 - 1, 2147483647, 2147483647))",
+        );
+        assert::fail("reversed(1)", "not supported");
+    }
+
+    #[test]
+    fn test_sorted() {
+        assert::eq(
+            "[(0, 'b'), (0, 'd'), (1, 'a'), (1, 'c')]",
+            "sorted([(1, 'a'), (0, 'b'), (1, 'c'), (0, 'd')], key=lambda x: x[0])",
+        );
+        assert::eq(
+            "[(1, 'a'), (1, 'c'), (0, 'b'), (0, 'd')]",
+            "sorted([(1, 'a'), (0, 'b'), (1, 'c'), (0, 'd')], key=lambda x: x[0], reverse=True)",
+        );
+        assert::fail(
+            r#"
+def key(x):
+    if x == 3:
+        fail("bad key")
+    return x
+sorted([1, 2, 3, 4], key=key)
+"#,
+            "bad key",
+        );
+        assert::fail("sorted([1, 'a'])", "not supported");
+    }
+
+    #[test]
+    fn test_min_max_default() {
+        assert::eq("0", "max([], default=0)");
+        assert::eq("None", "min([], default=None)");
+        assert::eq("3", "max([1, 3, 2], default=0)");
+        assert::eq("'a'", "min(['bb', 'a'], key=len, default='')");
+        assert::eq("'bb'", "max([], key=len, default='bb')");
+        assert::fail("max([])", "empty iterable");
+        assert::fail("min(1, 2, default=0)", "multiple positional arguments");
+    }
+
+    #[test]
+    fn test_enumerate_start() {
+        assert::eq("[(5, 'a'), (6, 'b')]", "enumerate(['a', 'b'], start=5)");
+        assert::eq("[(-1, 'a'), (0, 'b')]", "enumerate(['a', 'b'], -1)");
+        assert::eq("[]", "enumerate([], start=1)");
+        assert::fail("enumerate(['a'], start='1')", "doesn't match");
+        assert::fail("enumerate(['a'], start=1.0)", "doesn't match");
+    }
+
     #[test]
     fn test_hash() {
         assert::eq("0", "hash('')");
@@ -1181,20 +1351,26 @@ hash("te") != hash("st")
 x = "test"; y = "te" + "st"; hash(y) == hash(y)
 "#,
         );
-        assert::fail("hash(None)", "doesn't match");
-        assert::fail("hash(True)", "doesn't match");
-        assert::fail("hash(1)", "doesn't match");
-        assert::fail("hash([])", "doesn't match");
-        assert::fail("hash({})", "doesn't match");
-        assert::fail("hash(range(1))", "doesn't match");
-        assert::fail("hash((1, 2))", "doesn't match");
-        assert::fail(
+        assert::fail("hash([])", "not hashable");
+        assert::fail("hash({})", "not hashable");
+        assert::fail("hash((1, []))", "not hashable");
+        assert::fail("hash(range(1))", "not hashable");
+        assert::all_true(
+            r#"
+hash(None) == hash(None)
+hash(True) != hash(False)
+hash(1) == hash(1.0)
+hash(10000000000) == hash(10000000000.0)
+hash((1, 2)) == hash((1, 2))
+hash((1, 2)) != hash((2, 1))
+"#,
+        );
+        assert::is_true(
             r#"
 def foo():
     pass
-hash(foo)
+hash(foo) == hash(foo)
 "#,
-            "doesn't match",
         );
     }
 
@@ -1204,7 +1380,31 @@ hash(foo)
         assert::eq("-2147483647 - 1", "int('-2147483648')");
         assert::eq("0", "int('0')");
         assert::eq("0", "int('-0')");
-        assert::fail("int('2147483648')", "overflow");
-        assert::fail("int('-2147483649')", "overflow");
+        assert::eq("2147483648", "int('2147483648')");
+        assert::eq("-2147483649", "int('-2147483649')");
+    }
+
+    #[test]
+    fn test_int_base() {
+        assert::eq("255", "int('ff', 16)");
+        assert::eq("255", "int('0XFF', 16)");
+        assert::eq("5", "int('101', 2)");
+        assert::eq("-5", "int('-0b101', 2)");
+        assert::eq(
+            "[26, 10, 8, 31]",
+            "[int(x, 0) for x in ['0x1a', '10', '0o10', '0b11111']]",
+        );
+        assert::eq("-2147483648", "int('-80000000', 16)");
+        assert::eq("2147483648", "int('80000000', 16)");
+        assert::eq(
+            "340282366920938463463374607431768211455",
+            "int('ffffffffffffffffffffffffffffffff', 16)",
+        );
+        assert::eq("-18446744073709551616", "int('-0x10000000000000000', 0)");
+        assert::fail("int('0x10', 10)", "not a valid number");
+        assert::fail("int('fffffffffffffffffg', 16)", "not a valid number");
+        assert::fail("int('1_000_000_000_000', 10)", "not a valid number");
+        assert::fail("int('10', 37)", "not a valid base");
+        assert::fail("int(10, 16)", "non-string with explicit base");
     }
 }
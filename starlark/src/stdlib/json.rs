@@ -15,11 +15,18 @@
  * limitations under the License.
  */
 
+use std::fmt;
 use std::str::FromStr;
 
-use gazebo::prelude::*;
 use num_bigint::BigInt;
-use thiserror::Error;
+use serde::de::DeserializeSeed;
+use serde::de::Error as _;
+use serde::de::MapAccess;
+use serde::de::SeqAccess;
+use serde::de::Visitor;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde_json::value::RawValue;
 
 use crate as starlark;
 use crate::collections::SmallMap;
@@ -27,57 +34,131 @@ use crate::environment::GlobalsBuilder;
 use crate::values::dict::Dict;
 use crate::values::types::bigint::StarlarkBigInt;
 use crate::values::Heap;
+use crate::values::JsonOptions;
 use crate::values::Value;
 
-#[derive(Debug, Error)]
-enum JsonError {
-    #[error("Number can't be represented, perhaps a float value that is too precise, got `{0}")]
-    UnrepresentableNumber(String),
+/// Same as `serde_json`, which can't check it for us because
+/// every nested array or object is parsed separately.
+const MAX_DEPTH: usize = 128;
+
+/// Decodes JSON text straight into Starlark values.
+///
+/// Each value is first captured as raw text, so numbers are read from their text:
+/// integers of any size stay exact, and floats out of range become infinite, as in Python.
+#[derive(Clone, Copy)]
+struct JsonDecoder<'v> {
+    heap: &'v Heap,
+    depth: usize,
 }
 
-fn serde_to_starlark<'v>(x: serde_json::Value, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
-    match x {
-        serde_json::Value::Null => Ok(Value::new_none()),
-        serde_json::Value::Bool(x) => Ok(Value::new_bool(x)),
-        serde_json::Value::Number(x) => {
-            if let Some(x) = x.as_u64() {
-                Ok(heap.alloc(x))
-            } else if let Some(x) = x.as_f64() {
-                Ok(heap.alloc(x))
-            } else if let Ok(x) = BigInt::from_str(&x.to_string()) {
-                Ok(StarlarkBigInt::alloc_bigint(x, heap))
-            } else {
-                Err(JsonError::UnrepresentableNumber(x.to_string()).into())
-            }
+impl<'v> JsonDecoder<'v> {
+    fn number(self, text: &str) -> Value<'v> {
+        if text.contains(|c: char| matches!(c, '.' | 'e' | 'E')) {
+            self.heap
+                .alloc(f64::from_str(text).expect("JSON number is a valid float"))
+        } else {
+            let x = BigInt::from_str(text).expect("JSON integer is a valid BigInt");
+            StarlarkBigInt::alloc_bigint(x, self.heap)
+        }
+    }
+
+    fn nested(self) -> Self {
+        JsonDecoder {
+            heap: self.heap,
+            depth: self.depth + 1,
         }
-        serde_json::Value::String(x) => Ok(heap.alloc(x)),
-        serde_json::Value::Array(x) => {
-            Ok(heap.alloc_list_iter(x.into_try_map(|v| serde_to_starlark(v, heap))?))
+    }
+}
+
+impl<'de, 'v> DeserializeSeed<'de> for JsonDecoder<'v> {
+    type Value = Value<'v>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value<'v>, D::Error> {
+        if self.depth > MAX_DEPTH {
+            return Err(D::Error::custom("recursion limit exceeded"));
+        }
+        let raw = <&RawValue>::deserialize(deserializer)?.get();
+        if raw.starts_with(|c: char| c == '-' || c.is_ascii_digit()) {
+            Ok(self.number(raw))
+        } else {
+            serde_json::Deserializer::from_str(raw)
+                .deserialize_any(self)
+                .map_err(D::Error::custom)
+        }
+    }
+}
+
+impl<'de, 'v> Visitor<'de> for JsonDecoder<'v> {
+    type Value = Value<'v>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a JSON value")
+    }
+
+    fn visit_unit<E>(self) -> Result<Value<'v>, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Value::new_none())
+    }
+
+    fn visit_bool<E>(self, x: bool) -> Result<Value<'v>, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Value::new_bool(x))
+    }
+
+    fn visit_str<E>(self, x: &str) -> Result<Value<'v>, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(self.heap.alloc_str(x).to_value())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut xs: A) -> Result<Value<'v>, A::Error> {
+        let mut res = Vec::with_capacity(xs.size_hint().unwrap_or(0));
+        while let Some(x) = xs.next_element_seed(self.nested())? {
+            res.push(x);
         }
-        serde_json::Value::Object(x) => {
-            let mut mp = SmallMap::with_capacity(x.len());
-            for (k, v) in x {
-                let k = heap.alloc_str(&k).get_hashed_value();
-                let v = serde_to_starlark(v, heap)?;
-                mp.insert_hashed(k, v);
-            }
-            Ok(heap.alloc(Dict::new(mp)))
+        Ok(self.heap.alloc_list(&res))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut xs: A) -> Result<Value<'v>, A::Error> {
+        let mut mp = SmallMap::with_capacity(xs.size_hint().unwrap_or(0));
+        while let Some(k) = xs.next_key::<String>()? {
+            let v = xs.next_value_seed(self.nested())?;
+            mp.insert_hashed(self.heap.alloc_str(&k).get_hashed_value(), v);
         }
+        Ok(self.heap.alloc(Dict::new(mp)))
     }
 }
 
 pub(crate) fn json(globals: &mut GlobalsBuilder) {
     #[starlark_module]
     fn json_members(globals: &mut GlobalsBuilder) {
-        fn encode(#[starlark(require = pos)] x: Value) -> anyhow::Result<String> {
-            x.to_json()
+        fn encode(
+            #[starlark(require = pos)] x: Value,
+            #[starlark(require = named, default = false)] sort_keys: bool,
+            #[starlark(require = named)] indent: Option<i32>,
+        ) -> anyhow::Result<String> {
+            let indent = match indent {
+                None => None,
+                Some(indent) => Some(usize::try_from(indent).map_err(|_| {
+                    anyhow::anyhow!("json.encode: indent must be non-negative, got {}", indent)
+                })?),
+            };
+            x.to_json_with(&JsonOptions { sort_keys, indent })
         }
 
         fn decode<'v>(
             #[starlark(require = pos)] x: &str,
             heap: &'v Heap,
         ) -> anyhow::Result<Value<'v>> {
-            serde_to_starlark(serde_json::from_str(x)?, heap)
+            let mut deserializer = serde_json::Deserializer::from_str(x);
+            let res = JsonDecoder { heap, depth: 0 }.deserialize(&mut deserializer)?;
+            deserializer.end()?;
+            Ok(res)
         }
     }
 
@@ -97,6 +178,34 @@ mod tests {
         a.eq("'[10]'", "json.encode([10])");
     }
 
+    #[test]
+    fn test_json_encode_options() {
+        let a = Assert::new();
+        a.eq(
+            r#"'{"b":[{"d":1,"c":2}],"a":3}'"#,
+            "json.encode({'b': [{'d': 1, 'c': 2}], 'a': 3})",
+        );
+        a.eq(
+            r#"'{"a":3,"b":[{"c":2,"d":1}]}'"#,
+            "json.encode({'b': [{'d': 1, 'c': 2}], 'a': 3}, sort_keys = True)",
+        );
+        // Starlark string ordering compares code points.
+        a.eq(
+            r#"'{"B":1,"a":2,"é":3}'"#,
+            "json.encode({'é': 3, 'a': 2, 'B': 1}, sort_keys = True)",
+        );
+        a.eq(
+            r#"'{\n  "a": [\n    1\n  ],\n  "b": {}\n}'"#,
+            "json.encode({'a': [1], 'b': {}}, indent = 2)",
+        );
+        a.eq(r#"'[\n1\n]'"#, "json.encode([1], indent = 0)");
+        a.fail("json.encode([1], indent = -1)", "non-negative");
+        a.fail(
+            "json.encode({(1, 2): 3}, sort_keys = True)",
+            "key must be a string",
+        );
+    }
+
     #[test]
     fn test_json_decode() {
         let a = Assert::new();
@@ -110,5 +219,37 @@ mod tests {
             "123456789123456789123456789",
             "json.decode('123456789123456789123456789')",
         );
+        a.eq(
+            "[-1, 9223372036854775807, 18446744073709551615]",
+            "json.decode('[-1, 9223372036854775807, 18446744073709551615]')",
+        );
+        a.eq(
+            "['int', 'float']",
+            "[type(x) for x in json.decode('[-7, -7.5]')]",
+        );
+        a.eq(
+            "[-123456789123456789123456789, 1e20, 1.5e-7]",
+            "json.decode('[-123456789123456789123456789, 1E20, 15e-8]')",
+        );
+        a.eq(
+            "{'a': {'b': ['c']}}",
+            "json.decode(' {\"a\" : {\"b\": [\"c\"]}} ')",
+        );
+        a.fail("json.decode('[1, 2')", "EOF");
+        a.fail("json.decode('1 2')", "trailing characters");
+        a.fail("json.decode('[' * 200 + ']' * 200)", "recursion limit");
+    }
+
+    #[test]
+    fn test_json_decode_out_of_range() {
+        let a = Assert::new();
+        // Floats too large for `f64` become infinite, as in Python.
+        a.eq(
+            "[float('inf'), float('-inf')]",
+            "json.decode('[1e400, -1e400]')",
+        );
+        a.eq("0.0", "json.decode('1e-400')");
+        a.eq("int('1' + '0' * 400)", "json.decode('1' + '0' * 400)");
+        a.eq("int('-1' + '0' * 400)", "json.decode('-1' + '0' * 400)");
     }
 }
@@ -89,6 +89,9 @@ pub(crate) fn list_methods(builder: &mut MethodsBuilder) {
     /// `L.extend(x)` appends the elements of `x`, which must be iterable, to
     /// the list L, and returns `None`.
     ///
+    /// `x` may be any iterable, such as a tuple, a range or a dict (giving its keys).
+    /// `L.extend(L)` appends the elements L had before the call, doubling it.
+    ///
     /// `extend` fails if `x` is not iterable, or if the list L is frozen or has
     /// active iterators.
     ///
@@ -99,7 +102,8 @@ pub(crate) fn list_methods(builder: &mut MethodsBuilder) {
     /// x = []
     /// x.extend([1, 2, 3])
     /// x.extend(["foo"])
-    /// x == [1, 2, 3, "foo"]
+    /// x.extend(range(2))
+    /// x == [1, 2, 3, "foo", 0, 1]
     /// # "#);
     /// ```
     fn extend<'v>(
@@ -147,6 +151,8 @@ pub(crate) fn list_methods(builder: &mut MethodsBuilder) {
     /// x.index("a", 2) == 3   # banAna
     /// # and
     /// x.index("a", -2) == 5  # bananA
+    /// # and
+    /// x.index("n", 0, 3) == 2  # baNana
     /// # )"#);
     /// ```
     #[starlark(speculative_exec_safe)]
@@ -314,6 +320,7 @@ pub(crate) fn list_methods(builder: &mut MethodsBuilder) {
 #[cfg(test)]
 mod tests {
     use crate::assert;
+    use crate::assert::Assert;
 
     #[test]
     fn test_error_codes() {
@@ -325,10 +332,54 @@ mod tests {
 
     #[test]
     fn test_index() {
+        assert::pass(
+            r#"
+x = ["b", "a", "n", "a", "n", "a"]
+assert_eq(x.index("a", x.index("a") + 1), 3)
+assert_eq(x.index("n", 0, 3), 2)
+assert_eq(x.index("a", -3, -1), 3)
+assert_eq(x.index("a", None, None), 1)
+assert_eq(x.index("a", -100, 100), 1)
+"#,
+        );
+        assert::fail("['b', 'a', 'n'].index('a', 2, 3)", "not found");
+        assert::fail("['b', 'a', 'n'].index('a', 10)", "not found");
         // Should fail, but should not panic.
         assert::fail("[True].index(True, 1, 0)", "not found");
     }
 
+    #[test]
+    fn test_extend() {
+        assert::pass(
+            r#"
+x = [1]
+x.extend((2, 3))
+x.extend(range(4, 6))
+x.extend({"a": 1, "b": 2})
+x.extend({"c": 3}.keys())
+assert_eq(x, [1, 2, 3, 4, 5, "a", "b", "c"])
+x.extend(x)
+assert_eq(x, [1, 2, 3, 4, 5, "a", "b", "c"] * 2)
+y = []
+y.extend(y)
+assert_eq(y, [])
+"#,
+        );
+        assert::fail("[].extend(1)", "not supported");
+        assert::fail("[].extend('abc')", "not supported");
+
+        let mut a = Assert::new();
+        a.module("x", "frozen_list = [1]");
+        a.fail(
+            "load('x','frozen_list')\nfrozen_list.extend([2])",
+            "Immutable",
+        );
+        a.fail(
+            "load('x','frozen_list')\nfrozen_list.extend(frozen_list)",
+            "Immutable",
+        );
+    }
+
     #[test]
     fn recursive_list() {
         assert::is_true(
@@ -0,0 +1,220 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! The `math` module, with functions operating on ints and floats.
+
+use num_bigint::BigInt;
+use num_traits::FromPrimitive;
+
+use crate as starlark;
+use crate::environment::GlobalsBuilder;
+use crate::values::bigint::StarlarkBigInt;
+use crate::values::num::Num;
+use crate::values::Heap;
+use crate::values::UnpackValue;
+use crate::values::Value;
+
+#[derive(Debug, thiserror::Error)]
+enum MathError {
+    #[error("math domain error: `math.{0}` is not defined for {1}")]
+    Domain(&'static str, String),
+    #[error("math range error: result of `math.{0}` is too large")]
+    Range(&'static str),
+    #[error("`math.{0}` cannot convert {1} to integer")]
+    NotFinite(&'static str, f64),
+}
+
+/// Round a float to an int with `round`, keeping ints as is.
+fn round_to_int<'v>(
+    name: &'static str,
+    x: Value<'v>,
+    round: fn(f64) -> f64,
+    heap: &'v Heap,
+) -> anyhow::Result<Value<'v>> {
+    match Num::unpack_param(x)? {
+        Num::Float(f) => match BigInt::from_f64(round(f)) {
+            Some(i) => Ok(StarlarkBigInt::alloc_bigint(i, heap)),
+            None => Err(MathError::NotFinite(name, f).into()),
+        },
+        Num::Int(_) | Num::BigInt(_) => Ok(x),
+    }
+}
+
+pub(crate) fn math(builder: &mut GlobalsBuilder) {
+    #[starlark_module]
+    fn math_members(builder: &mut GlobalsBuilder) {
+        /// The square root of `x`.
+        ///
+        /// Fails if `x` is negative. `math.sqrt(float("nan"))` is `nan`.
+        ///
+        /// ```
+        /// # starlark::assert::all_true(r#"
+        /// math.sqrt(16) == 4.0
+        /// math.sqrt(2.25) == 1.5
+        /// # "#);
+        /// ```
+        fn sqrt(#[starlark(require = pos)] x: Num) -> anyhow::Result<f64> {
+            let f = x.as_float();
+            if f < 0.0 {
+                return Err(MathError::Domain("sqrt", f.to_string()).into());
+            }
+            Ok(f.sqrt())
+        }
+
+        /// The largest int less than or equal to `x`.
+        ///
+        /// Ints are returned unchanged, large floats give large ints.
+        /// Fails if `x` is infinite or `nan`.
+        ///
+        /// ```
+        /// # starlark::assert::all_true(r#"
+        /// math.floor(2.7) == 2
+        /// math.floor(-2.2) == -3
+        /// math.floor(5) == 5
+        /// math.floor(1e20) == 100000000000000000000
+        /// # "#);
+        /// ```
+        fn floor<'v>(
+            #[starlark(require = pos)] x: Value<'v>,
+            heap: &'v Heap,
+        ) -> anyhow::Result<Value<'v>> {
+            round_to_int("floor", x, f64::floor, heap)
+        }
+
+        /// The smallest int greater than or equal to `x`.
+        ///
+        /// Ints are returned unchanged, large floats give large ints.
+        /// Fails if `x` is infinite or `nan`.
+        ///
+        /// ```
+        /// # starlark::assert::all_true(r#"
+        /// math.ceil(2.2) == 3
+        /// math.ceil(-2.7) == -2
+        /// math.ceil(5) == 5
+        /// # "#);
+        /// ```
+        fn ceil<'v>(
+            #[starlark(require = pos)] x: Value<'v>,
+            heap: &'v Heap,
+        ) -> anyhow::Result<Value<'v>> {
+            round_to_int("ceil", x, f64::ceil, heap)
+        }
+
+        /// `x` raised to the power `y`, as a float.
+        ///
+        /// Fails if the result is not a real number (a negative `x` with
+        /// a fractional `y`, or zero to a negative power),
+        /// or if finite arguments overflow to infinity.
+        /// `nan` arguments give `nan`, except `math.pow(1, y)` and `math.pow(x, 0)` are `1.0`.
+        ///
+        /// ```
+        /// # starlark::assert::all_true(r#"
+        /// math.pow(2, 10) == 1024.0
+        /// math.pow(4, 0.5) == 2.0
+        /// math.pow(2, -1) == 0.5
+        /// # "#);
+        /// ```
+        fn pow(
+            #[starlark(require = pos)] x: Num,
+            #[starlark(require = pos)] y: Num,
+        ) -> anyhow::Result<f64> {
+            let (x, y) = (x.as_float(), y.as_float());
+            let r = x.powf(y);
+            if x.is_finite() && y.is_finite() {
+                if r.is_nan() || (x == 0.0 && y < 0.0) {
+                    return Err(MathError::Domain("pow", format!("{} and {}", x, y)).into());
+                }
+                if r.is_infinite() {
+                    return Err(MathError::Range("pow").into());
+                }
+            }
+            Ok(r)
+        }
+
+        /// The logarithm of `x` to the given `base`, by default the natural logarithm.
+        ///
+        /// Fails if `x` is not positive, or if `base` is not positive or is `1`.
+        /// `math.log(float("nan"))` is `nan`.
+        ///
+        /// ```
+        /// # starlark::assert::all_true(r#"
+        /// math.log(1) == 0.0
+        /// math.log(math.e) == 1.0
+        /// math.log(8, 2) == 3.0
+        /// # "#);
+        /// ```
+        fn log(
+            #[starlark(require = pos)] x: Num,
+            #[starlark(require = pos)] base: Option<Num>,
+        ) -> anyhow::Result<f64> {
+            let x = x.as_float();
+            if x <= 0.0 {
+                return Err(MathError::Domain("log", x.to_string()).into());
+            }
+            match base {
+                None => Ok(x.ln()),
+                Some(base) => {
+                    let base = base.as_float();
+                    if base <= 0.0 || base == 1.0 {
+                        return Err(MathError::Domain("log", format!("base {}", base)).into());
+                    }
+                    Ok(x.ln() / base.ln())
+                }
+            }
+        }
+    }
+
+    builder.struct_("math", |builder| {
+        math_members(builder);
+        builder.set("pi", std::f64::consts::PI);
+        builder.set("e", std::f64::consts::E);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert;
+
+    #[test]
+    fn test_math() {
+        assert::all_true(
+            r#"
+math.pi > 3.14159 and math.pi < 3.1416
+math.e > 2.71828 and math.e < 2.7183
+math.sqrt(0) == 0.0
+str(math.sqrt(float("nan"))) == "nan"
+math.floor(-0.5) == -1
+math.ceil(0.5) == 1
+type(math.floor(2.0)) == "int"
+math.floor(123456789123456789123) == 123456789123456789123
+math.ceil(-1e20) == -100000000000000000000
+math.pow(2, 0.5) == math.sqrt(2)
+math.pow(float("inf"), 2) == float("inf")
+math.log(100, 10) == 2.0
+"#,
+        );
+        assert::fail("math.sqrt(-1)", "math domain error");
+        assert::fail("math.floor(float('inf'))", "cannot convert inf to integer");
+        assert::fail("math.ceil(float('nan'))", "cannot convert NaN to integer");
+        assert::fail("math.floor('1')", "int or float");
+        assert::fail("math.pow(-8, 1.0 / 3)", "math domain error");
+        assert::fail("math.pow(0, -1)", "math domain error");
+        assert::fail("math.pow(10, 400)", "math range error");
+        assert::fail("math.log(0)", "math domain error");
+        assert::fail("math.log(10, 1)", "math domain error");
+    }
+}
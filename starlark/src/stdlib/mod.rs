@@ -20,18 +20,27 @@
 
 use crate::environment::GlobalsBuilder;
 
+pub(crate) mod assert;
 pub(crate) mod breakpoint;
+pub(crate) mod bytes;
 pub(crate) mod dict;
+pub(crate) mod encoding;
 pub(crate) mod enumeration;
 pub(crate) mod extra;
 mod funcs;
 pub(crate) mod json;
 use gazebo::prelude::*;
 pub(crate) mod list;
+pub(crate) mod math;
 pub(crate) mod record;
+pub(crate) mod set;
 pub(crate) mod string;
 pub(crate) mod structs;
+#[cfg(feature = "toml")]
+pub(crate) mod toml;
 pub(crate) mod util;
+#[cfg(feature = "yaml")]
+pub(crate) mod yaml;
 
 pub use extra::PrintHandler;
 
@@ -76,8 +85,29 @@ pub enum LibraryExtension {
     Breakpoint,
     /// Add a function `json()` which will generate JSON for a module.
     Json,
-    /// Add a function `abs()` which will take the absolute value of an int.
+    /// Add a function `abs()` which will take the absolute value of an int or float.
     Abs,
+    /// Definitions to support the `bytes` type, the `bytes()` constructor.
+    Bytes,
+    /// Add a `math` module with `sqrt`, `floor`, `ceil`, `pow`, `log` and the constants `pi` and `e`.
+    Math,
+    /// Definitions to support the `set` type, the `set()` constructor.
+    SetType,
+    /// Add the functions `assert_eq(a, b)`, `assert_ne(a, b)`, `assert_true(x)` and `assert_false(x)`
+    /// for tests written in Starlark. On failure they show the `repr` of the values, and like `fail`,
+    /// they take an optional `msg` to describe the failure.
+    Assert,
+    /// Add `base64` and `hex` modules, whose `encode` functions turn `bytes` or strings into text,
+    /// and whose `decode` functions turn that text back into `bytes`.
+    Encoding,
+    /// Add a `toml` module with `decode` and `encode`, converting between TOML documents and dicts.
+    /// Only available with the `toml` feature.
+    #[cfg(feature = "toml")]
+    Toml,
+    /// Add a `yaml` module with `decode` and `encode`, converting between the common subset of YAML
+    /// (maps, sequences and scalars) and Starlark values. Only available with the `yaml` feature.
+    #[cfg(feature = "yaml")]
+    Yaml,
     // Make sure if you add anything new, you add it to `all` below.
 }
 
@@ -100,6 +130,15 @@ impl LibraryExtension {
             Breakpoint,
             Json,
             Abs,
+            Bytes,
+            Math,
+            SetType,
+            Assert,
+            Encoding,
+            #[cfg(feature = "toml")]
+            Toml,
+            #[cfg(feature = "yaml")]
+            Yaml,
         ]
     }
 
@@ -121,6 +160,15 @@ impl LibraryExtension {
             Breakpoint => breakpoint::global(builder),
             Json => json::json(builder),
             Abs => extra::abs(builder),
+            Bytes => bytes::global(builder),
+            Math => math::math(builder),
+            SetType => set::global(builder),
+            Assert => assert::assert(builder),
+            Encoding => encoding::encoding(builder),
+            #[cfg(feature = "toml")]
+            Toml => toml::toml(builder),
+            #[cfg(feature = "yaml")]
+            Yaml => yaml::yaml(builder),
         }
     }
 }
@@ -0,0 +1,152 @@
+/*
+ * Copyright 2018 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+//! The `set()` constructor and methods for the `set` type.
+
+use crate as starlark;
+use crate::environment::GlobalsBuilder;
+use crate::environment::MethodsBuilder;
+use crate::values::none::NoneType;
+use crate::values::set::Set;
+use crate::values::Heap;
+use crate::values::Value;
+
+#[starlark_module]
+pub fn global(builder: &mut GlobalsBuilder) {
+    /// set: construct a set.
+    ///
+    /// `set(x)` returns a new set with the elements of the iterable `x`, in the order
+    /// they first appear. `set()` returns a new empty set. The elements must be hashable.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// len(set()) == 0
+    /// list(set("abca".elems())) == ["a", "b", "c"]
+    /// set([1, 2]) == set([2, 1])
+    /// # "#);
+    /// ```
+    #[starlark(type = Set::TYPE)]
+    fn set<'v>(
+        #[starlark(require = pos)] x: Option<Value<'v>>,
+        heap: &'v Heap,
+    ) -> anyhow::Result<Set<'v>> {
+        let mut res = Set::default();
+        if let Some(x) = x {
+            for v in x.iterate(heap)? {
+                res.insert_hashed(v.get_hashed()?);
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[starlark_module]
+pub(crate) fn set_methods(registry: &mut MethodsBuilder) {
+    /// set.add: add an element to a set.
+    ///
+    /// `S.add(x)` adds `x` to the end of the set `S`, unless it is already present.
+    /// It fails if the set is frozen or if there are active iterators.
+    ///
+    /// ```
+    /// # starlark::assert::is_true(r#"
+    /// x = set([1, 2])
+    /// x.add(3)
+    /// x.add(1)
+    /// list(x) == [1, 2, 3]
+    /// # "#);
+    /// ```
+    fn add<'v>(
+        this: Value<'v>,
+        #[starlark(require = pos)] x: Value<'v>,
+    ) -> anyhow::Result<NoneType> {
+        let x = x.get_hashed()?;
+        Set::from_value_mut(this)?.insert_hashed(x);
+        Ok(NoneType)
+    }
+
+    /// set.discard: remove an element from a set.
+    ///
+    /// `S.discard(x)` removes `x` from the set `S` if it is present, keeping the order
+    /// of the other elements. It fails if the set is frozen or if there are active iterators.
+    ///
+    /// ```
+    /// # starlark::assert::is_true(r#"
+    /// x = set([1, 2, 3])
+    /// x.discard(2)
+    /// x.discard(4)
+    /// list(x) == [1, 3]
+    /// # "#);
+    /// ```
+    fn discard<'v>(
+        this: Value<'v>,
+        #[starlark(require = pos)] x: Value<'v>,
+    ) -> anyhow::Result<NoneType> {
+        let x = x.get_hashed()?;
+        Set::from_value_mut(this)?.remove_hashed(x);
+        Ok(NoneType)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use indexmap::IndexSet;
+
+    use crate::assert;
+    use crate::assert::Assert;
+    use crate::values::Heap;
+
+    #[test]
+    fn test_set() {
+        assert::pass(
+            r#"
+x = set([3, 1, 3])
+assert_eq(repr(x), "set([3, 1])")
+assert_eq(repr(set()), "set()")
+assert_eq(type(x), "set")
+assert_true(1 in x and 2 not in x)
+x.add(2)
+assert_eq(list(x), [3, 1, 2])
+assert_eq([y for y in x | set([5, 1])], [3, 1, 2, 5])
+assert_eq(list(x & set([2, 3])), [3, 2])
+assert_eq(list(x - set([1])), [3, 2])
+assert_eq(list(x ^ set([2, 4])), [3, 1, 4])
+assert_eq(x, set([1, 2, 3]))
+assert_ne(x, [3, 1, 2])
+assert_false(set())
+"#,
+        );
+        assert::fail("set([[]])", "not hashable");
+        assert::fail("set([1]) | [1]", "not supported");
+        assert::fail("x = set([1])\nfor y in x:\n    x.add(2)", "mutate");
+        let mut a = Assert::new();
+        a.module("m", "x = set([1])");
+        a.fail("load('m', 'x')\nx.discard(1)", "Immutable");
+        a.eq("[1]", "load('m', 'x')\nlist(x | set())");
+    }
+
+    #[test]
+    fn test_alloc_set() {
+        let heap = Heap::new();
+        let x = heap.alloc(IndexSet::from_iter(["b", "a", "b"]));
+        assert_eq!(x.to_repr(), r#"set(["b", "a"])"#);
+        let x = heap.alloc(IndexSet::from_iter([(1, "x"), (2, "y")]));
+        assert_eq!(x.to_repr(), r#"set([(1, "x"), (2, "y")])"#);
+        let x = heap.alloc(BTreeSet::from_iter([2, 1]));
+        assert_eq!(x.to_repr(), "set([1, 2])");
+    }
+}
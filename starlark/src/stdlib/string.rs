@@ -22,10 +22,13 @@ use std::cmp;
 use gazebo::prelude::*;
 
 use crate as starlark;
+use crate::collections::SmallMap;
 use crate::environment::MethodsBuilder;
 use crate::eval::Arguments;
 use crate::eval::Evaluator;
 use crate::stdlib::string::fast_string::convert_str_indices;
+use crate::values::dict::Dict;
+use crate::values::dict::DictRef;
 use crate::values::none::NoneOr;
 use crate::values::string::fast_string;
 use crate::values::string::interpolation;
@@ -269,6 +272,58 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
         }
     }
 
+    /// [string.expandtabs](
+    /// https://docs.python.org/3/library/stdtypes.html#str.expandtabs
+    /// ): replace tabs with spaces. _Not part of standard Starlark._
+    ///
+    /// `S.expandtabs(tabsize = 8)` returns a copy of S where each tab character is replaced
+    /// by one or more spaces, up to the next column which is a multiple of `tabsize`.
+    /// The column is reset to zero after each newline or carriage return, and every other
+    /// character, including multi-byte ones, counts as one column.
+    /// If `tabsize` is zero or negative, tabs are removed.
+    ///
+    /// Examples:
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// "01\t012\t0123\t01234".expandtabs() == "01      012     0123    01234"
+    /// "01\t012\t0123\t01234".expandtabs(4) == "01  012 0123    01234"
+    /// "a\tb".expandtabs(0) == "ab"
+    /// # "#);
+    /// ```
+    #[starlark(speculative_exec_safe)]
+    fn expandtabs<'v>(
+        this: StringValue<'v>,
+        #[starlark(default = 8)] tabsize: i32,
+        heap: &'v Heap,
+    ) -> anyhow::Result<StringValue<'v>> {
+        if !this.contains('\t') {
+            return Ok(this);
+        }
+        let mut res = String::with_capacity(this.len());
+        let mut column = 0;
+        for c in this.chars() {
+            match c {
+                '\t' => {
+                    if tabsize > 0 {
+                        let n = tabsize as usize - column % tabsize as usize;
+                        res.extend(std::iter::repeat(' ').take(n));
+                        column += n;
+                    }
+                }
+                '\n' | '\r' => {
+                    res.push(c);
+                    column = 0;
+                }
+                c => {
+                    res.push(c);
+                    column += 1;
+                }
+            }
+        }
+        Ok(heap.alloc_str(&res))
+    }
+
     /// [string.find](
     /// https://github.com/google/skylark/blob/3705afa472e466b8b061cce44b47c9ddc6db696d/doc/spec.md#string·find
     /// ): find a substring in a string.
@@ -338,6 +393,9 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
     /// If all the numeric field names form the sequence 0, 1, 2, and so on,
     /// they may be omitted and those values will be implied; however,
     /// the explicit and implicit forms may not be mixed.
+    /// The field name may be followed by any number of `.attr` attribute
+    /// accesses and `[key]` index accesses, where a key made of digits is
+    /// an integer index and anything else is a string key.
     ///
     /// The *conversion* specifies how to convert an argument value `x` to a
     /// string. It may be either `!r`, which converts the value using
@@ -345,8 +403,11 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
     /// the default.
     ///
     /// The *format specifier*, after a colon, specifies field width,
-    /// alignment, padding, and numeric precision.
-    /// Currently it must be empty, but it is reserved for future use.
+    /// alignment, padding, and numeric precision, following Python's
+    /// `[[fill]align][sign][#][0][width][.precision][type]` mini-language.
+    /// The alignment is one of `<`, `>`, `^` or `=`, the sign one of `+`, `-`
+    /// or space, and the type one of `s`, `d`, `x`, `X`, `o`, `b`, `e`, `E`,
+    /// `f`, `F`, `g`, `G` or `%`. Digit grouping is not supported.
     ///
     /// Examples:
     ///
@@ -357,6 +418,9 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
     /// "a{}b{}c".format(1, 2) == "a1b2c"
     /// "({1}, {0})".format("zero", "one") == "(one, zero)"
     /// "Is {0!r} {0!s}?".format("heterological") == "Is \"heterological\" heterological?"
+    /// "{0[1]} {x[k]}".format(["a", "b"], x={"k": "v"}) == "b v"
+    /// "{:>8.2f}|{:<4}|{:^5}".format(3.14159, "ab", "c") == "    3.14|ab  |  c  |"
+    /// "{:+d} {:#x} {:05}".format(5, 255, -42) == "+5 0xff -0042"
     /// # "#);
     /// ```
     #[starlark(speculative_exec_safe)]
@@ -745,6 +809,98 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
         }
     }
 
+    /// [string.maketrans](
+    /// https://docs.python.org/3/library/stdtypes.html#str.maketrans
+    /// ): build a translation table for [`translate`](#stringtranslate).
+    /// _Not part of standard Starlark._
+    ///
+    /// The receiver string is ignored, so it is usually called as `"".maketrans(...)`.
+    /// The table is a dict mapping codepoints (as returned by `ord`) to
+    /// codepoints, strings, or `None` to delete the character.
+    ///
+    /// With one argument, it must be a dict whose keys are single-character strings or
+    /// codepoints, and whose values are strings, codepoints or `None`.
+    /// With two arguments, they must be strings of equal length, and each character of the
+    /// first is mapped to the character at the same position in the second.
+    /// The optional third argument is a string whose characters are mapped to `None`.
+    ///
+    /// Examples:
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// "".maketrans("ab", "xy") == {97: 120, 98: 121}
+    /// "".maketrans("a", "x", "c") == {97: 120, 99: None}
+    /// "".maketrans({"a": "xyz", 98: None}) == {97: "xyz", 98: None}
+    /// # "#);
+    /// ```
+    #[starlark(speculative_exec_safe)]
+    fn maketrans<'v>(
+        #[starlark(this)] _this: &str,
+        #[starlark(require = pos)] x: Value<'v>,
+        #[starlark(require = pos)] y: Option<&str>,
+        #[starlark(require = pos)] z: Option<&str>,
+    ) -> anyhow::Result<Dict<'v>> {
+        let mut table = SmallMap::new();
+        match y {
+            None => {
+                if z.is_some() {
+                    return Err(anyhow::anyhow!(
+                        "maketrans() with three arguments requires the first two to be strings"
+                    ));
+                }
+                let x = match Dict::from_value(x) {
+                    Some(x) => x,
+                    None => {
+                        return Err(anyhow::anyhow!(
+                            "maketrans() with one argument requires a dict, got `{}`",
+                            x.get_type()
+                        ));
+                    }
+                };
+                for (k, v) in x.iter() {
+                    let k = match (k.unpack_int(), k.unpack_str()) {
+                        (Some(_), _) => k,
+                        (_, Some(s)) if s.chars().count() == 1 => {
+                            Value::new_int(u32::from(s.chars().next().unwrap()) as i32)
+                        }
+                        _ => {
+                            return Err(anyhow::anyhow!(
+                                "maketrans() keys must be strings of length 1 or ints, got `{}`",
+                                k.to_repr()
+                            ));
+                        }
+                    };
+                    table.insert_hashed(k.get_hashed()?, v);
+                }
+            }
+            Some(y) => {
+                let x = match x.unpack_str() {
+                    Some(x) => x,
+                    None => {
+                        return Err(anyhow::anyhow!(
+                            "maketrans() first argument must be a string if there is a second argument, got `{}`",
+                            x.get_type()
+                        ));
+                    }
+                };
+                if x.chars().count() != y.chars().count() {
+                    return Err(anyhow::anyhow!(
+                        "maketrans() first two arguments must have equal length"
+                    ));
+                }
+                for (a, b) in x.chars().zip(y.chars()) {
+                    let a = Value::new_int(u32::from(a) as i32);
+                    table.insert_hashed(a.get_hashed()?, Value::new_int(u32::from(b) as i32));
+                }
+                for c in z.unwrap_or_default().chars() {
+                    let c = Value::new_int(u32::from(c) as i32);
+                    table.insert_hashed(c.get_hashed()?, Value::new_none());
+                }
+            }
+        }
+        Ok(Dict::new(table))
+    }
+
     /// [string.partition](
     /// https://github.com/google/skylark/blob/3705afa472e466b8b061cce44b47c9ddc6db696d/doc/spec.md#string·partition
     /// ): partition a string in 3 components
@@ -1139,12 +1295,13 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
     /// "one\n\ntwo".splitlines() == ["one", "", "two"]
     /// "one\n\ntwo".splitlines(True) == ["one\n", "\n", "two"]
     /// "a\nb".splitlines() == ["a", "b"]
+    /// "a\r\nb\rc\n".splitlines(keepends = True) == ["a\r\n", "b\r", "c\n"]
     /// # "#);
     /// ```
     #[starlark(speculative_exec_safe)]
     fn splitlines<'v>(
         this: &str,
-        #[starlark(require = pos, default = false)] keepends: bool,
+        #[starlark(default = false)] keepends: bool,
         heap: &'v Heap,
     ) -> anyhow::Result<Value<'v>> {
         let mut s = this;
@@ -1272,6 +1429,62 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
         Ok(result)
     }
 
+    /// [string.translate](
+    /// https://docs.python.org/3/library/stdtypes.html#str.translate
+    /// ): map each character through a translation table.
+    /// _Not part of standard Starlark._
+    ///
+    /// `S.translate(table)` returns a copy of S where each character is looked up
+    /// by its codepoint in the dict `table`, usually created with [`maketrans`](#stringmaketrans).
+    /// Characters missing from the table are kept, characters mapped to `None` are deleted,
+    /// and characters mapped to a codepoint or a string are replaced by it.
+    ///
+    /// Examples:
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// "a-b c".translate("".maketrans("- ", "__")) == "a_b_c"
+    /// "hello".translate("".maketrans("", "", "lo")) == "he"
+    /// "abc".translate({97: "AA", 98: None, 99: 100}) == "AAd"
+    /// # "#);
+    /// ```
+    #[starlark(speculative_exec_safe)]
+    fn translate<'v>(
+        this: &str,
+        #[starlark(require = pos)] table: DictRef<'v>,
+    ) -> anyhow::Result<String> {
+        let mut res = String::with_capacity(this.len());
+        for c in this.chars() {
+            let k = Value::new_int(u32::from(c) as i32);
+            match table.get_hashed(k.get_hashed()?) {
+                None => res.push(c),
+                Some(v) if v.is_none() => {}
+                Some(v) => {
+                    if let Some(s) = v.unpack_str() {
+                        res.push_str(s);
+                    } else if let Some(i) = v.unpack_int() {
+                        match std::char::from_u32(i as u32) {
+                            Some(c) => res.push(c),
+                            None => {
+                                return Err(anyhow::anyhow!(
+                                    "translate() mapping of {} is {}, which is not a valid codepoint",
+                                    k,
+                                    i
+                                ));
+                            }
+                        }
+                    } else {
+                        return Err(anyhow::anyhow!(
+                            "translate() mapping must be a string, an int or None, got `{}`",
+                            v.get_type()
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(res)
+    }
+
     /// [string.upper](
     /// https://github.com/google/skylark/blob/3705afa472e466b8b061cce44b47c9ddc6db696d/doc/spec.md#string·upper
     /// ): convert a string to all uppercase.
@@ -1305,6 +1518,7 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
     /// "Hello, World!".removeprefix("Hello") == ", World!"
     /// "Hello, World!".removeprefix("Goodbye") == "Hello, World!"
     /// "Hello".removeprefix("Hello") == ""
+    /// "Hello".removeprefix("") == "Hello"
     /// # "#);
     /// ```
     #[starlark(speculative_exec_safe)]
@@ -1323,9 +1537,9 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
 
     /// [string.removesuffix](
     /// https://docs.python.org/3.9/library/stdtypes.html#str.removesuffix
-    /// ): remove a prefix from a string. _Not part of standard Starlark._
+    /// ): remove a suffix from a string. _Not part of standard Starlark._
     ///
-    /// If the string starts with the prefix string, return `string[len(prefix):]`.
+    /// If the string ends with the suffix string, return `string[:len(string) - len(suffix)]`.
     /// Otherwise, return a copy of the original string:
     ///
     /// Examples:
@@ -1335,6 +1549,7 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
     /// "Hello, World!".removesuffix("World!") == "Hello, "
     /// "Hello, World!".removesuffix("World") == "Hello, World!"
     /// "Hello".removesuffix("Hello") == ""
+    /// "Hello".removesuffix("") == "Hello"
     /// # "#);
     /// ```
     #[starlark(speculative_exec_safe)]
@@ -1378,4 +1593,70 @@ mod tests {
         assert::is_true("type('foo'.elems()) != type([])");
         assert::is_true("type('foo'.codepoints()) != type([])");
     }
+
+    #[test]
+    fn test_splitlines() {
+        assert::all_true(
+            r#"
+"".splitlines() == []
+"".splitlines(True) == []
+"\n".splitlines() == [""]
+"a\n".splitlines() == ["a"]
+"a\n".splitlines(True) == ["a\n"]
+"a\n\n".splitlines() == ["a", ""]
+"a\r\nb\rc\nd".splitlines() == ["a", "b", "c", "d"]
+"a\r\nb\rc\nd".splitlines(keepends = True) == ["a\r\n", "b\r", "c\n", "d"]
+"a\n\rb".splitlines() == ["a", "", "b"]
+"#,
+        );
+    }
+
+    #[test]
+    fn test_expandtabs() {
+        assert::all_true(
+            r#"
+"".expandtabs() == ""
+"no tabs".expandtabs() == "no tabs"
+"\t".expandtabs() == "        "
+"\t".expandtabs(tabsize = 3) == "   "
+"ab\tc\nd\te".expandtabs(4) == "ab  c\nd   e"
+"a\r\tb".expandtabs(2) == "a\r  b"
+"日本\tx".expandtabs(4) == "日本  x"
+"\t\t".expandtabs(-1) == ""
+"#,
+        );
+    }
+
+    #[test]
+    fn test_translate() {
+        assert::all_true(
+            r#"
+"".translate({}) == ""
+"abc".translate({}) == "abc"
+"x.y/z".translate("".maketrans("./", "__")) == "x_y_z"
+"x.y/z".translate("".maketrans("", "", "./")) == "xyz"
+"日本".translate("".maketrans({"日": "sun", ord("本"): "book"})) == "sunbook"
+"aaa".translate({ord("a"): ""}) == ""
+"#,
+        );
+        assert::fail(r#""".maketrans("ab", "x")"#, "equal length");
+        assert::fail(r#""".maketrans({"ab": "x"})"#, "strings of length 1");
+        assert::fail(r#""".maketrans("a")"#, "requires a dict");
+        assert::fail(r#""a".translate({97: 1.5})"#, "string, an int or None");
+        assert::fail(r#""a".translate({97: -1})"#, "not a valid codepoint");
+    }
+
+    #[test]
+    fn test_remove_affix() {
+        assert::all_true(
+            r#"
+"//foo:bar".removeprefix("//") == "foo:bar"
+"//foo:bar".removesuffix(":bar") == "//foo"
+"".removeprefix("") == ""
+"".removesuffix("x") == ""
+"abc".removeprefix("abcd") == "abc"
+"abc".removesuffix("zabc") == "abc"
+"#,
+        );
+    }
 }
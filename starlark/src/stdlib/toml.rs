@@ -0,0 +1,185 @@
+/*
+ * Copyright 2018 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! The `toml` module, to convert between TOML documents and Starlark values.
+
+use crate as starlark;
+use crate::collections::SmallMap;
+use crate::environment::GlobalsBuilder;
+use crate::values::dict::Dict;
+use crate::values::list::List;
+use crate::values::num::Num;
+use crate::values::structs::Struct;
+use crate::values::tuple::Tuple;
+use crate::values::Heap;
+use crate::values::Value;
+
+#[derive(Debug, thiserror::Error)]
+enum TomlError {
+    #[error("`toml.encode` expects a dict at the top level, got value of type `{0}`")]
+    NotATable(&'static str),
+    #[error("`toml.encode` does not support values of type `{0}`")]
+    Unsupported(&'static str),
+    #[error("`toml.encode` expects string keys, got key of type `{0}`")]
+    NotAStringKey(&'static str),
+    #[error("`toml.encode` only supports 64-bit integers, got `{0}`")]
+    IntegerTooLarge(String),
+}
+
+/// Allocate the Starlark equivalent of a TOML value. Tables become dicts,
+/// and datetimes become strings in their TOML syntax.
+fn alloc_toml<'v>(x: &toml::Value, heap: &'v Heap) -> Value<'v> {
+    match x {
+        toml::Value::String(x) => heap.alloc_str(x).to_value(),
+        toml::Value::Integer(x) => heap.alloc(*x),
+        toml::Value::Float(x) => heap.alloc(*x),
+        toml::Value::Boolean(x) => Value::new_bool(*x),
+        toml::Value::Datetime(x) => heap.alloc_str(&x.to_string()).to_value(),
+        toml::Value::Array(xs) => heap.alloc_list_iter(xs.iter().map(|x| alloc_toml(x, heap))),
+        toml::Value::Table(xs) => {
+            let mut mp = SmallMap::with_capacity(xs.len());
+            for (k, v) in xs {
+                mp.insert_hashed(heap.alloc_str(k).get_hashed_value(), alloc_toml(v, heap));
+            }
+            heap.alloc(Dict::new(mp))
+        }
+    }
+}
+
+/// Convert a Starlark value to a TOML value. Ints become TOML integers,
+/// so must fit in an `i64`, and dicts and structs become tables.
+fn to_toml(x: Value) -> anyhow::Result<toml::Value> {
+    fn table<'v>(xs: impl Iterator<Item = (Value<'v>, Value<'v>)>) -> anyhow::Result<toml::Value> {
+        let mut res = toml::value::Table::new();
+        for (k, v) in xs {
+            let k = k
+                .unpack_str()
+                .ok_or_else(|| TomlError::NotAStringKey(k.get_type()))?;
+            res.insert(k.to_owned(), to_toml(v)?);
+        }
+        Ok(toml::Value::Table(res))
+    }
+
+    fn array<'v>(xs: impl Iterator<Item = Value<'v>>) -> anyhow::Result<toml::Value> {
+        Ok(toml::Value::Array(
+            xs.map(to_toml).collect::<anyhow::Result<_>>()?,
+        ))
+    }
+
+    if let Some(x) = x.unpack_bool() {
+        Ok(toml::Value::Boolean(x))
+    } else if let Some(x) = x.unpack_num() {
+        match x {
+            Num::Int(x) => Ok(toml::Value::Integer(x as i64)),
+            Num::BigInt(x) => match x.to_i64() {
+                Some(x) => Ok(toml::Value::Integer(x)),
+                None => Err(TomlError::IntegerTooLarge(x.to_string()).into()),
+            },
+            Num::Float(x) => Ok(toml::Value::Float(x)),
+        }
+    } else if let Some(x) = x.unpack_str() {
+        Ok(toml::Value::String(x.to_owned()))
+    } else if let Some(xs) = List::from_value(x) {
+        array(xs.iter())
+    } else if let Some(xs) = Tuple::from_value(x) {
+        array(xs.iter())
+    } else if let Some(xs) = Dict::from_value(x) {
+        table(xs.iter())
+    } else if let Some(xs) = Struct::from_value(x) {
+        table(xs.iter().map(|(k, v)| (k.to_value(), v)))
+    } else {
+        Err(TomlError::Unsupported(x.get_type()).into())
+    }
+}
+
+pub(crate) fn toml(builder: &mut GlobalsBuilder) {
+    #[starlark_module]
+    fn toml_members(builder: &mut GlobalsBuilder) {
+        /// Parse a TOML document into a dict. Arrays become lists, and
+        /// dates and times become strings, e.g. `"1979-05-27T07:32:00Z"`.
+        fn decode<'v>(
+            #[starlark(require = pos)] x: &str,
+            heap: &'v Heap,
+        ) -> anyhow::Result<Value<'v>> {
+            Ok(alloc_toml(&toml::from_str(x)?, heap))
+        }
+
+        /// Write a dict as a TOML document. Keys are written in sorted order,
+        /// ints must fit in 64 bits, and `None` values are not supported.
+        fn encode(#[starlark(require = pos)] x: Value) -> anyhow::Result<String> {
+            // Going through `toml::Value` writes plain values before nested tables,
+            // as TOML requires, whatever the order in the dict.
+            match to_toml(x)? {
+                t @ toml::Value::Table(_) => Ok(toml::to_string(&t)?),
+                _ => Err(TomlError::NotATable(x.get_type()).into()),
+            }
+        }
+    }
+
+    builder.struct_("toml", toml_members);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert::Assert;
+
+    #[test]
+    fn test_toml_decode() {
+        let a = Assert::new();
+        a.eq(
+            r#"{"package": {"name": "x", "version": 3, "ratio": 0.5, "dev": False, "authors": ["a", "b"]}}"#,
+            r#"toml.decode('[package]\nname = "x"\nversion = 3\nratio = 0.5\ndev = false\nauthors = ["a", "b"]')"#,
+        );
+        a.eq(
+            r#"{"a": [{"x": 1}, {"x": 2}]}"#,
+            r#"toml.decode('[[a]]\nx = 1\n[[a]]\nx = 2')"#,
+        );
+        a.eq(
+            r#"{"t": "1979-05-27T07:32:00Z", "d": "1979-05-27"}"#,
+            "toml.decode('t = 1979-05-27T07:32:00Z\\nd = 1979-05-27')",
+        );
+        a.fail("toml.decode('x = ')", "expected a value");
+    }
+
+    #[test]
+    fn test_toml_encode() {
+        let a = Assert::new();
+        a.eq(
+            r#"'a = 1\nb = ["x", "y"]\n\n[c]\nd = true\n'"#,
+            r#"toml.encode({"c": {"d": True}, "b": ["x", "y"], "a": 1})"#,
+        );
+        a.eq(
+            r#"{"a": [{"x": 1.5}], "b": {"c": "d"}}"#,
+            r#"toml.decode(toml.encode({"a": [{"x": 1.5}], "b": {"c": "d"}}))"#,
+        );
+        a.fail("toml.encode([1])", "expects a dict");
+        a.eq(
+            r#"'big = 10000000000\nsmall = -1\n'"#,
+            r#"toml.encode({"big": 10000000000, "small": -1})"#,
+        );
+        a.eq(r#"'[s]\nx = 1\n'"#, r#"toml.encode({"s": struct(x = 1)})"#);
+        a.fail(
+            "toml.encode({'a': None})",
+            "does not support values of type `NoneType`",
+        );
+        a.fail("toml.encode({1: 2})", "expects string keys");
+        a.fail(
+            "toml.encode({'a': 100000000000000000000})",
+            "only supports 64-bit integers, got `100000000000000000000`",
+        );
+    }
+}
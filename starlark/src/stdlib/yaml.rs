@@ -0,0 +1,292 @@
+/*
+ * Copyright 2018 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! The `yaml` module, to convert between a subset of YAML and Starlark values.
+//!
+//! Only maps, sequences and scalars are supported. Anchors, aliases and tags are rejected,
+//! and plain scalars are resolved as in the YAML 1.2 core schema, with decimal ints only.
+
+use std::str::FromStr;
+
+use num_bigint::BigInt;
+use yaml_rust::parser::Event;
+use yaml_rust::parser::EventReceiver;
+use yaml_rust::parser::Parser;
+use yaml_rust::scanner::TScalarStyle;
+use yaml_rust::scanner::TokenType;
+use yaml_rust::yaml::Hash;
+use yaml_rust::Yaml;
+use yaml_rust::YamlEmitter;
+
+use crate as starlark;
+use crate::collections::SmallMap;
+use crate::environment::GlobalsBuilder;
+use crate::values::bigint::StarlarkBigInt;
+use crate::values::dict::Dict;
+use crate::values::list::List;
+use crate::values::num::Num;
+use crate::values::recursive_repr_or_json_guard::json_stack_push;
+use crate::values::tuple::Tuple;
+use crate::values::Heap;
+use crate::values::UnpackValue;
+use crate::values::Value;
+
+#[derive(Debug, thiserror::Error)]
+enum YamlError {
+    #[error("YAML anchors and aliases are not supported")]
+    Anchor,
+    #[error("YAML tags are not supported, got `{0}`")]
+    Tag(String),
+    #[error("`yaml.encode` does not support values of type `{0}`")]
+    Unsupported(&'static str),
+    #[error("Cycle detected when encoding value of type `{0}` to YAML")]
+    Cycle(&'static str),
+}
+
+/// A sequence or mapping which has been started but not yet ended.
+enum Collection<'v> {
+    Sequence(Vec<Value<'v>>),
+    /// The entries so far, and the key of the next entry once it has been read.
+    Mapping(SmallMap<Value<'v>, Value<'v>>, Option<Value<'v>>),
+}
+
+/// Builds a Starlark value from the events of a single YAML document.
+struct Decoder<'v> {
+    heap: &'v Heap,
+    stack: Vec<Collection<'v>>,
+    result: Option<Value<'v>>,
+    /// The first error, after which all events are ignored.
+    error: Option<anyhow::Error>,
+}
+
+impl<'v> Decoder<'v> {
+    fn push(&mut self, x: Value<'v>) -> anyhow::Result<()> {
+        match self.stack.last_mut() {
+            None => self.result = Some(x),
+            Some(Collection::Sequence(xs)) => xs.push(x),
+            Some(Collection::Mapping(mp, key)) => match key.take() {
+                None => *key = Some(x),
+                Some(k) => {
+                    mp.insert_hashed(k.get_hashed()?, x);
+                }
+            },
+        }
+        Ok(())
+    }
+
+    fn scalar(&self, x: &str, style: TScalarStyle) -> anyhow::Result<Value<'v>> {
+        if style != TScalarStyle::Plain {
+            return Ok(self.heap.alloc_str(x).to_value());
+        }
+        Ok(match x {
+            "" | "~" | "null" | "Null" | "NULL" => Value::new_none(),
+            "true" | "True" | "TRUE" => Value::new_bool(true),
+            "false" | "False" | "FALSE" => Value::new_bool(false),
+            ".inf" | ".Inf" | ".INF" | "+.inf" | "+.Inf" | "+.INF" => {
+                self.heap.alloc(f64::INFINITY)
+            }
+            "-.inf" | "-.Inf" | "-.INF" => self.heap.alloc(f64::NEG_INFINITY),
+            ".nan" | ".NaN" | ".NAN" => self.heap.alloc(f64::NAN),
+            _ => {
+                let numeric = x.bytes().any(|c| c.is_ascii_digit())
+                    && x.bytes().all(|c| b"0123456789+-.eE".contains(&c));
+                if !numeric {
+                    self.heap.alloc_str(x).to_value()
+                } else if let Ok(i) = BigInt::from_str(x) {
+                    StarlarkBigInt::alloc_bigint(i, self.heap)
+                } else if let Ok(f) = f64::from_str(x) {
+                    self.heap.alloc(f)
+                } else {
+                    self.heap.alloc_str(x).to_value()
+                }
+            }
+        })
+    }
+
+    fn event(&mut self, ev: Event) -> anyhow::Result<()> {
+        match ev {
+            Event::Alias(_) => return Err(YamlError::Anchor.into()),
+            Event::Scalar(x, style, anchor, tag) => {
+                if anchor != 0 {
+                    return Err(YamlError::Anchor.into());
+                }
+                if let Some(TokenType::Tag(handle, suffix)) = tag {
+                    return Err(YamlError::Tag(format!("{}{}", handle, suffix)).into());
+                }
+                let x = self.scalar(&x, style)?;
+                self.push(x)?;
+            }
+            Event::SequenceStart(anchor) | Event::MappingStart(anchor) if anchor != 0 => {
+                return Err(YamlError::Anchor.into());
+            }
+            Event::SequenceStart(_) => self.stack.push(Collection::Sequence(Vec::new())),
+            Event::MappingStart(_) => self.stack.push(Collection::Mapping(SmallMap::new(), None)),
+            Event::SequenceEnd | Event::MappingEnd => {
+                let x = match self.stack.pop() {
+                    Some(Collection::Sequence(xs)) => self.heap.alloc_list(&xs),
+                    Some(Collection::Mapping(mp, _)) => self.heap.alloc(Dict::new(mp)),
+                    // The parser only produces balanced events.
+                    None => unreachable!("unbalanced YAML events"),
+                };
+                self.push(x)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+impl<'v> EventReceiver for Decoder<'v> {
+    fn on_event(&mut self, ev: Event) {
+        if self.error.is_none() {
+            if let Err(e) = self.event(ev) {
+                self.error = Some(e);
+            }
+        }
+    }
+}
+
+/// Convert a Starlark value to YAML, rejecting anything outside the subset `yaml.decode` produces.
+fn to_yaml(x: Value) -> anyhow::Result<Yaml> {
+    if x.is_none() {
+        return Ok(Yaml::Null);
+    }
+    if let Some(x) = x.unpack_bool() {
+        return Ok(Yaml::Boolean(x));
+    }
+    if let Some(n) = Num::unpack_value(x) {
+        return Ok(match n {
+            Num::Int(i) => Yaml::Integer(i as i64),
+            // Written as is, so it is read back as an int.
+            Num::BigInt(b) => Yaml::Real(b.to_string()),
+            Num::Float(f) if f.is_nan() => Yaml::Real(".nan".to_owned()),
+            Num::Float(f) if f.is_infinite() => {
+                Yaml::Real(if f > 0.0 { ".inf" } else { "-.inf" }.to_owned())
+            }
+            // The `repr` always has a `.` or an exponent, so it is read back as a float.
+            Num::Float(_) => Yaml::Real(x.to_repr()),
+        });
+    }
+    if let Some(x) = x.unpack_str() {
+        return Ok(Yaml::String(x.to_owned()));
+    }
+    let _guard = json_stack_push(x).map_err(|_| YamlError::Cycle(x.get_type()))?;
+    if let Some(xs) = List::from_value(x) {
+        Ok(Yaml::Array(
+            xs.iter().map(to_yaml).collect::<anyhow::Result<_>>()?,
+        ))
+    } else if let Some(xs) = Tuple::from_value(x) {
+        Ok(Yaml::Array(
+            xs.iter().map(to_yaml).collect::<anyhow::Result<_>>()?,
+        ))
+    } else if let Some(xs) = Dict::from_value(x) {
+        let mut res = Hash::new();
+        for (k, v) in xs.iter() {
+            res.insert(to_yaml(k)?, to_yaml(v)?);
+        }
+        Ok(Yaml::Hash(res))
+    } else {
+        Err(YamlError::Unsupported(x.get_type()).into())
+    }
+}
+
+pub(crate) fn yaml(builder: &mut GlobalsBuilder) {
+    #[starlark_module]
+    fn yaml_members(builder: &mut GlobalsBuilder) {
+        /// Parse the first document of a YAML string. Mappings become dicts and sequences become lists.
+        /// Plain scalars become `None`, bools, ints or floats where they match the YAML syntax for them,
+        /// and strings otherwise.
+        fn decode<'v>(
+            #[starlark(require = pos)] x: &str,
+            heap: &'v Heap,
+        ) -> anyhow::Result<Value<'v>> {
+            let mut decoder = Decoder {
+                heap,
+                stack: Vec::new(),
+                result: None,
+                error: None,
+            };
+            Parser::new(x.chars()).load(&mut decoder, false)?;
+            match decoder.error {
+                Some(e) => Err(e),
+                None => Ok(decoder.result.unwrap_or_else(Value::new_none)),
+            }
+        }
+
+        /// Write a value as a YAML document, starting with `---`.
+        /// Only `None`, bools, ints, floats, strings, lists, tuples and dicts are supported.
+        fn encode(#[starlark(require = pos)] x: Value) -> anyhow::Result<String> {
+            let mut res = String::new();
+            YamlEmitter::new(&mut res).dump(&to_yaml(x)?)?;
+            Ok(res)
+        }
+    }
+
+    builder.struct_("yaml", yaml_members);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert::Assert;
+
+    #[test]
+    fn test_yaml_decode() {
+        let a = Assert::new();
+        a.eq(
+            r#"{"name": "x", "tags": ["a", "b"], "deps": {"y": 1}}"#,
+            r#"yaml.decode('name: x\ntags:\n  - a\n  - b\ndeps: {y: 1}\n')"#,
+        );
+        a.eq(
+            r#"[None, None, True, False, 1, -2, 123456789123456789123456789, 1.5, 1e3, "1.2.3", "yes", "true", "x y"]"#,
+            r#"yaml.decode('[~, null, true, False, 1, -2, 123456789123456789123456789, 1.5, 1e3, 1.2.3, yes, "true", x y]')"#,
+        );
+        a.is_true("yaml.decode('.inf') > 1e308");
+        a.is_true("yaml.decode('-.inf') < -1e308");
+        a.is_true("yaml.decode('.nan') != yaml.decode('.nan')");
+        a.eq("{1: 'a'}", "yaml.decode('1: a')");
+        a.eq("None", "yaml.decode('')");
+        a.fail("yaml.decode('a: &x 1\\nb: *x')", "anchors and aliases");
+        a.fail("yaml.decode('a: !custom 1')", "tags are not supported");
+        a.fail("yaml.decode('[1, 2')", "at line 1");
+        a.fail("yaml.decode('[a]: 1')", "not hashable");
+    }
+
+    #[test]
+    fn test_yaml_encode() {
+        let a = Assert::new();
+        a.eq(
+            r#"'---\nb: 1\na:\n  - x\n  - "true"'"#,
+            "yaml.encode({'b': 1, 'a': ['x', 'true']})",
+        );
+        a.pass(
+            r#"
+x = {
+    "a": [None, True, 1, 2.0, -1.5, "", "1", "x: y", "a\nb"],
+    2: {"c": [1]},
+    "big": 123456789123456789123456789,
+}
+assert_eq(yaml.decode(yaml.encode(x)), x)
+assert_eq(yaml.decode(yaml.encode((1, "x"))), [1, "x"])
+"#,
+        );
+        a.fail(
+            "yaml.encode(set([1]))",
+            "does not support values of type `set`",
+        );
+        a.fail("x = [1]\nx.append(x)\nyaml.encode(x)", "Cycle detected");
+    }
+}
@@ -182,8 +182,17 @@ pub(crate) enum AssignP<P: AstPayload> {
     // as these have the same semantics in Starlark.
     Tuple(Vec<AstAssignP<P>>),
     ArrayIndirection(Box<(AstExprP<P>, AstExprP<P>)>),
+    // `a[b:c:d]`, only valid in plain assignment, not in `+=` and friends.
+    Slice(
+        Box<AstExprP<P>>,
+        Option<Box<AstExprP<P>>>,
+        Option<Box<AstExprP<P>>>,
+        Option<Box<AstExprP<P>>>,
+    ),
     Dot(Box<AstExprP<P>>, AstString),
     Identifier(AstAssignIdentP<P>),
+    // `*x` in `a, *x, b = ...`, only occurs directly inside `Tuple`.
+    Starred(Box<AstAssignP<P>>),
 }
 
 /// Identifier in assign position.
@@ -264,6 +273,8 @@ pub(crate) enum StmtP<P: AstPayload> {
     Expression(AstExprP<P>),
     Assign(AstAssignP<P>, Box<AstExprP<P>>),
     AssignModify(AstAssignP<P>, AssignOp, Box<AstExprP<P>>),
+    /// `del x, y[i], z[i:j]`, only identifiers, indexing, slices and tuples of these.
+    Del(AstAssignP<P>),
     Statements(Vec<AstStmtP<P>>),
     If(AstExprP<P>, Box<AstStmtP<P>>),
     IfElse(AstExprP<P>, Box<(AstStmtP<P>, AstStmtP<P>)>),
@@ -482,7 +493,22 @@ impl Display for Assign {
             }
             Assign::Dot(e, s) => write!(f, "{}.{}", e.node, s.node),
             Assign::ArrayIndirection(box (e, i)) => write!(f, "{}[{}]", e.node, i.node),
+            Assign::Slice(e, i1, i2, i3) => {
+                write!(f, "{}[", e.node)?;
+                if let Some(x) = i1 {
+                    write!(f, "{}", x.node)?
+                }
+                f.write_str(":")?;
+                if let Some(x) = i2 {
+                    write!(f, "{}", x.node)?
+                }
+                if let Some(x) = i3 {
+                    write!(f, ":{}", x.node)?
+                }
+                f.write_str("]")
+            }
             Assign::Identifier(s) => write!(f, "{}", s.node),
+            Assign::Starred(x) => write!(f, "*{}", x.node),
         }
     }
 }
@@ -550,6 +576,7 @@ impl Stmt {
             Stmt::Expression(e) => writeln!(f, "{}{}", tab, e.node),
             Stmt::Assign(l, r) => writeln!(f, "{}{} = {}", tab, l.node, r.node),
             Stmt::AssignModify(l, op, r) => writeln!(f, "{}{}{}{}", tab, l.node, op, r.node),
+            Stmt::Del(x) => writeln!(f, "{}del {}", tab, x.node),
             Stmt::Statements(v) => {
                 for s in v {
                     s.node.fmt_with_tab(f, tab.clone())?;
@@ -0,0 +1,1091 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Print an [`AstModule`] back to source in a canonical layout,
+//! see [`AstModule::to_formatted_string`].
+//!
+//! The layout is decided by the AST, with a few hints taken from the original source:
+//! collections and argument lists written over several lines stay one element per line,
+//! parentheses around tuples are kept, at most one blank line is kept between statements,
+//! and comments are kept next to the statement or element they were written next to.
+//! Formatting the output again does not change it.
+
+use gazebo::prelude::*;
+
+use crate::codemap::CodeMap;
+use crate::codemap::Pos;
+use crate::codemap::Span;
+use crate::syntax::ast::Argument;
+use crate::syntax::ast::Assign;
+use crate::syntax::ast::AssignOp;
+use crate::syntax::ast::AstArgument;
+use crate::syntax::ast::AstAssign;
+use crate::syntax::ast::AstExpr;
+use crate::syntax::ast::AstLiteral;
+use crate::syntax::ast::AstParameter;
+use crate::syntax::ast::AstStmt;
+use crate::syntax::ast::AstString;
+use crate::syntax::ast::BinOp;
+use crate::syntax::ast::Clause;
+use crate::syntax::ast::Expr;
+use crate::syntax::ast::ForClause;
+use crate::syntax::ast::Parameter;
+use crate::syntax::ast::Stmt;
+use crate::syntax::AstModule;
+
+/// The quote character used for string literals.
+#[derive(Debug, Clone, Copy, Dupe, PartialEq, Eq)]
+pub enum QuoteStyle {
+    /// `"x"`.
+    Double,
+    /// `'x'`.
+    Single,
+}
+
+/// Options for [`AstModule::to_formatted_string`].
+#[derive(Debug, Clone)]
+pub struct FormatOptions {
+    /// Number of spaces for each level of indentation. Defaults to 4.
+    pub indent: usize,
+    /// Quotes for string literals. Strings containing only the other quote character
+    /// use the other quote instead, and raw or triple-quoted strings are printed as written.
+    /// Defaults to [`QuoteStyle::Double`].
+    pub quote: QuoteStyle,
+    /// Whether to put a comma after the last element of collections and argument lists
+    /// printed over several lines. Defaults to `true`.
+    pub trailing_commas: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            indent: 4,
+            quote: QuoteStyle::Double,
+            trailing_commas: true,
+        }
+    }
+}
+
+impl AstModule {
+    /// Print the module back to source in a canonical layout.
+    ///
+    /// Indentation, spacing, quotes and parentheses are normalized, while comments
+    /// are preserved. Collections and argument lists are printed on one line, unless they
+    /// spanned several lines in the original source, in which case they get one element
+    /// per line. The result parses to the same AST, and formatting it again returns it unchanged.
+    pub fn to_formatted_string(&self, options: FormatOptions) -> String {
+        let mut printer = Printer {
+            codemap: &self.codemap,
+            options: &options,
+            comments: find_comments(self.codemap.source()).into_map(|span| (span, false)),
+            next_comment: 0,
+            last: None,
+            out: String::new(),
+        };
+        printer.suite(&self.statement, 0, self.codemap.full_span().end());
+        // Every comment should have been printed by now, but never lose any.
+        while let Some((span, text)) = printer.next_comment() {
+            printer.item(span.begin(), span.end(), &text, 0);
+        }
+        printer.out
+    }
+}
+
+/// Find the spans of all comments in `source`, from `#` to the end of the line.
+fn find_comments(source: &str) -> Vec<Span> {
+    let span = |begin: usize, end: usize| Span::new(Pos::new(begin as u32), Pos::new(end as u32));
+    // The delimiters are all ASCII, so we can scan bytes even in UTF-8.
+    let bytes = source.as_bytes();
+    let mut res = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'#' => {
+                let end = source[i..].find('\n').map_or(source.len(), |x| i + x);
+                res.push(span(i, end));
+                i = end;
+            }
+            q @ (b'"' | b'\'') => {
+                let triple = bytes[i..].starts_with(&[q, q, q]);
+                i += if triple { 3 } else { 1 };
+                while i < bytes.len() {
+                    if bytes[i] == b'\\' {
+                        i += 2;
+                    } else if triple && bytes[i..].starts_with(&[q, q, q]) {
+                        i += 3;
+                        break;
+                    } else if !triple && (bytes[i] == q || bytes[i] == b'\n') {
+                        i += 1;
+                        break;
+                    } else {
+                        i += 1;
+                    }
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    res
+}
+
+// Precedence of expressions, higher binds tighter. Follows the grammar.
+const PREC_TEST: u8 = 0; // `lambda`, `x if c else y`
+const PREC_OR: u8 = 1;
+const PREC_AND: u8 = 2;
+const PREC_NOT: u8 = 3;
+const PREC_COMPARE: u8 = 4;
+const PREC_BIT_OR: u8 = 5;
+const PREC_BIT_XOR: u8 = 6;
+const PREC_BIT_AND: u8 = 7;
+const PREC_SHIFT: u8 = 8;
+const PREC_ARITH: u8 = 9;
+const PREC_PRODUCT: u8 = 10;
+const PREC_UNARY: u8 = 11;
+const PREC_PRIMARY: u8 = 12;
+
+fn bin_op_prec(op: BinOp) -> u8 {
+    match op {
+        BinOp::Or => PREC_OR,
+        BinOp::And => PREC_AND,
+        BinOp::Equal
+        | BinOp::NotEqual
+        | BinOp::Less
+        | BinOp::Greater
+        | BinOp::LessOrEqual
+        | BinOp::GreaterOrEqual
+        | BinOp::In
+        | BinOp::NotIn => PREC_COMPARE,
+        BinOp::BitOr => PREC_BIT_OR,
+        BinOp::BitXor => PREC_BIT_XOR,
+        BinOp::BitAnd => PREC_BIT_AND,
+        BinOp::LeftShift | BinOp::RightShift => PREC_SHIFT,
+        BinOp::Add | BinOp::Subtract => PREC_ARITH,
+        BinOp::Multiply | BinOp::Percent | BinOp::Divide | BinOp::FloorDivide => PREC_PRODUCT,
+    }
+}
+
+fn expr_prec(x: &Expr) -> u8 {
+    match x {
+        Expr::Lambda(..) | Expr::If(..) => PREC_TEST,
+        Expr::Op(_, op, _) => bin_op_prec(*op),
+        Expr::Not(..) => PREC_NOT,
+        Expr::Minus(..) | Expr::Plus(..) | Expr::BitNot(..) => PREC_UNARY,
+        _ => PREC_PRIMARY,
+    }
+}
+
+fn assign_op_str(op: AssignOp) -> &'static str {
+    match op {
+        AssignOp::Add => " += ",
+        AssignOp::Subtract => " -= ",
+        AssignOp::Multiply => " *= ",
+        AssignOp::Divide => " /= ",
+        AssignOp::FloorDivide => " //= ",
+        AssignOp::Percent => " %= ",
+        AssignOp::BitAnd => " &= ",
+        AssignOp::BitOr => " |= ",
+        AssignOp::BitXor => " ^= ",
+        AssignOp::LeftShift => " <<= ",
+        AssignOp::RightShift => " >>= ",
+    }
+}
+
+/// Collect the statements of a block, flattening `a; b` lists.
+fn flatten<'a>(x: &'a AstStmt, res: &mut Vec<&'a AstStmt>) {
+    match &x.node {
+        Stmt::Statements(xs) => xs.iter().for_each(|x| flatten(x, res)),
+        _ => res.push(x),
+    }
+}
+
+/// The brackets around the elements of a collection, and how to lay them out.
+struct Brackets {
+    open: &'static str,
+    close: &'static str,
+    /// Comments inside the collection start here, usually at the opening bracket.
+    begin: Pos,
+    /// The end of the closing bracket, if known.
+    end: Option<Pos>,
+    /// Print one element per line.
+    multiline: bool,
+    /// A single element needs a trailing comma.
+    tuple: bool,
+}
+
+impl Brackets {
+    /// Indentation of the elements.
+    fn item_indent(&self, indent: usize) -> usize {
+        if self.multiline {
+            indent + 1
+        } else {
+            indent
+        }
+    }
+}
+
+struct Printer<'a> {
+    codemap: &'a CodeMap,
+    options: &'a FormatOptions,
+    /// All comments in source order, with whether they have been printed.
+    comments: Vec<(Span, bool)>,
+    /// No comment before this index is left to print.
+    next_comment: usize,
+    /// End of the last line printed in the current block, to preserve blank lines.
+    last: Option<Pos>,
+    out: String,
+}
+
+impl<'a> Printer<'a> {
+    fn line(&self, pos: Pos) -> usize {
+        self.codemap.find_line(pos)
+    }
+
+    fn column(&self, pos: Pos) -> u32 {
+        pos.get() - self.codemap.line_span(self.line(pos)).begin().get()
+    }
+
+    fn source(&self, span: Span) -> &'a str {
+        self.codemap.source_span(span)
+    }
+
+    fn is_multiline(&self, begin: Pos, end: Pos) -> bool {
+        begin < end && self.source(Span::new(begin, end)).contains('\n')
+    }
+
+    fn indentation(&self, indent: usize) -> String {
+        " ".repeat(indent * self.options.indent)
+    }
+
+    /// Position of the `(` directly before `pos`, ignoring whitespace and comments.
+    fn open_paren(&self, pos: Pos) -> Option<Pos> {
+        let source = self.codemap.source();
+        let mut i = pos.get() as usize;
+        while let Some(c) = source[..i].chars().next_back() {
+            if c.is_whitespace() || c == '\\' {
+                i -= c.len_utf8();
+            } else if let Some((span, _)) = self
+                .comments
+                .iter()
+                .find(|(span, _)| span.end().get() as usize == i)
+            {
+                i = span.begin().get() as usize;
+            } else if c == '(' {
+                return Some(Pos::new((i - 1) as u32));
+            } else {
+                return None;
+            }
+        }
+        None
+    }
+
+    /// Position of the keyword `kw` between `begin` and `end`, outside comments.
+    fn keyword(&self, begin: Pos, end: Pos, kw: &str) -> Pos {
+        self.source(Span::new(begin, end))
+            .match_indices(kw)
+            .map(|(i, _)| begin + i as u32)
+            .find(|pos| !self.comments.iter().any(|(span, _)| span.contains(*pos)))
+            .unwrap_or(end)
+    }
+
+    /// Index of the first comment not yet printed which starts at or after `pos`.
+    fn find_comment(&mut self, pos: Pos) -> Option<usize> {
+        while matches!(self.comments.get(self.next_comment), Some((_, true))) {
+            self.next_comment += 1;
+        }
+        let i = self.comments[self.next_comment..]
+            .iter()
+            .position(|(span, printed)| !printed && span.begin() >= pos)?;
+        Some(self.next_comment + i)
+    }
+
+    /// Mark the comment at index `i` as printed, returning its span and text.
+    fn take_comment(&mut self, i: usize) -> (Span, String) {
+        let span = self.comments[i].0;
+        self.comments[i].1 = true;
+        (span, self.source(span).trim_end().to_owned())
+    }
+
+    /// Take the first comment not yet printed.
+    fn next_comment(&mut self) -> Option<(Span, String)> {
+        let i = self.find_comment(Pos::new(0))?;
+        Some(self.take_comment(i))
+    }
+
+    /// Take the first comment not yet printed which starts between `begin` and `end`.
+    fn comment_between(&mut self, begin: Pos, end: Pos) -> Option<(Span, String)> {
+        let i = self.find_comment(begin)?;
+        if self.comments[i].0.begin() < end {
+            Some(self.take_comment(i))
+        } else {
+            None
+        }
+    }
+
+    /// Take the first comment not yet printed after `pos` if it is on the same line,
+    /// and before `limit`.
+    fn comment_after(&mut self, pos: Pos, limit: Pos) -> Option<(Span, String)> {
+        let i = self.find_comment(pos)?;
+        let begin = self.comments[i].0.begin();
+        if begin < limit && self.line(begin) == self.line(pos) {
+            Some(self.take_comment(i))
+        } else {
+            None
+        }
+    }
+
+    /// Print a line of the current block, which spans `begin` to `end` in the source.
+    fn item(&mut self, begin: Pos, end: Pos, text: &str, indent: usize) {
+        if let Some(last) = self.last {
+            let blank = (self.line(last) + 1..self.line(begin))
+                .any(|line| self.codemap.source_line(line).trim().is_empty());
+            if blank {
+                self.out.push('\n');
+            }
+        }
+        self.out.push_str(&self.indentation(indent));
+        self.out.push_str(text);
+        self.out.push('\n');
+        self.last = Some(end);
+    }
+
+    /// Print the comments before `pos` on their own lines.
+    fn comments_before(&mut self, pos: Pos, indent: usize) {
+        while let Some((span, text)) = self.comment_between(Pos::new(0), pos) {
+            self.item(span.begin(), span.end(), &text, indent);
+        }
+    }
+
+    /// Print the trailing comment after `pos` on the last printed line.
+    fn trailing_comment(&mut self, pos: Pos, limit: Pos) {
+        if let Some((span, text)) = self.comment_after(pos, limit) {
+            self.out.pop();
+            self.out.push_str("  ");
+            self.out.push_str(&text);
+            self.out.push('\n');
+            self.last = Some(span.end());
+        }
+    }
+
+    /// Print a simple statement or the header of a compound statement,
+    /// which spans from `begin` to `end` in the source.
+    /// Comments inside it which were not printed as part of `text` go on the lines above.
+    fn header(&mut self, begin: Pos, end: Pos, text: &str, indent: usize, limit: Pos) {
+        while let Some((_, comment)) = self.comment_between(Pos::new(0), end) {
+            self.item(begin, begin, &comment, indent);
+        }
+        self.item(begin, end, text, indent);
+        self.trailing_comment(end, limit);
+    }
+
+    /// Print the statements of a block, followed by the comments which belong to it:
+    /// those before `limit` indented at least as much as the block.
+    fn suite(&mut self, suite: &AstStmt, indent: usize, limit: Pos) {
+        let mut stmts = Vec::new();
+        flatten(suite, &mut stmts);
+        self.last = None;
+        for (i, stmt) in stmts.iter().enumerate() {
+            let limit = stmts.get(i + 1).map_or(limit, |x| x.span.begin());
+            self.stmt(stmt, indent, limit);
+        }
+        let column = stmts.first().map_or(0, |x| self.column(x.span.begin()));
+        while let Some(i) = self.find_comment(Pos::new(0)) {
+            let begin = self.comments[i].0.begin();
+            if begin >= limit || self.column(begin) < column {
+                break;
+            }
+            let (span, text) = self.take_comment(i);
+            self.item(span.begin(), span.end(), &text, indent);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &AstStmt, indent: usize, limit: Pos) {
+        let begin = stmt.span.begin();
+        self.comments_before(begin, indent);
+        match &stmt.node {
+            Stmt::If(..) | Stmt::IfElse(..) => self.if_stmt("if", begin, stmt, indent, limit),
+            Stmt::For(var, box (over, body)) => {
+                let text = format!(
+                    "for {} in {}:",
+                    self.assign(var, true, indent),
+                    self.expr(over, PREC_TEST, indent)
+                );
+                self.header(begin, over.span.end(), &text, indent, limit);
+                self.suite(body, indent + 1, limit);
+            }
+            Stmt::Def(name, params, ret, body, _) => {
+                let mut end = params.last().map_or(name.span.end(), |x| x.span.end());
+                let params = self.params(name.span.end(), end, params, indent);
+                let mut text = format!("def {}{}", name.node.0, params);
+                if let Some(ret) = ret {
+                    text.push_str(" -> ");
+                    text.push_str(&self.expr(ret, PREC_TEST, indent));
+                    end = ret.span.end();
+                }
+                text.push(':');
+                self.header(begin, end, &text, indent, limit);
+                self.suite(body, indent + 1, limit);
+            }
+            _ => {
+                let text = self.simple_stmt(stmt, indent);
+                self.header(begin, stmt.span.end(), &text, indent, limit);
+            }
+        }
+    }
+
+    /// Print an `if` statement, or an `elif` branch when `begin` is the `elif` keyword.
+    fn if_stmt(&mut self, keyword: &str, begin: Pos, stmt: &AstStmt, indent: usize, limit: Pos) {
+        let (cond, then, els) = match &stmt.node {
+            Stmt::If(cond, box then) => (cond, then, None),
+            Stmt::IfElse(cond, box (then, els)) => (cond, then, Some(els)),
+            _ => unreachable!("not an if statement"),
+        };
+        let text = format!("{} {}:", keyword, self.expr(cond, PREC_TEST, indent));
+        self.header(begin, cond.span.end(), &text, indent, limit);
+        let els = match els {
+            None => return self.suite(then, indent + 1, limit),
+            Some(els) => els,
+        };
+        let elif = matches!(els.node, Stmt::If(..) | Stmt::IfElse(..));
+        let kw = if elif { "elif" } else { "else" };
+        let kw_pos = self.keyword(then.span.end(), els.span.begin(), kw);
+        self.suite(then, indent + 1, kw_pos);
+        self.comments_before(kw_pos, indent);
+        if elif {
+            self.if_stmt("elif", kw_pos, els, indent, limit);
+        } else {
+            let end = kw_pos + kw.len() as u32;
+            self.item(kw_pos, end, "else:", indent);
+            self.trailing_comment(end, limit);
+            self.suite(els, indent + 1, limit);
+        }
+    }
+
+    fn simple_stmt(&mut self, stmt: &AstStmt, indent: usize) -> String {
+        match &stmt.node {
+            Stmt::Break => "break".to_owned(),
+            Stmt::Continue => "continue".to_owned(),
+            Stmt::Pass => "pass".to_owned(),
+            Stmt::Return(None) => "return".to_owned(),
+            Stmt::Return(Some(x)) => format!("return {}", self.expr_top(x, indent)),
+            Stmt::Expression(x) => self.expr_top(x, indent),
+            Stmt::Assign(lhs, rhs) => format!(
+                "{} = {}",
+                self.assign(lhs, true, indent),
+                self.expr_top(rhs, indent)
+            ),
+            Stmt::AssignModify(lhs, op, rhs) => format!(
+                "{}{}{}",
+                self.assign(lhs, true, indent),
+                assign_op_str(*op),
+                self.expr_top(rhs, indent)
+            ),
+            Stmt::Del(x) => format!("del {}", self.assign(x, true, indent)),
+            Stmt::Load(load) => {
+                let mut items = vec![(load.node.module.span, self.string(&load.node.module))];
+                for (local, their) in &load.node.args {
+                    let their_str = self.string(their);
+                    if local.node.0 == their.node && local.span == their.span {
+                        items.push((their.span, their_str));
+                    } else {
+                        items.push((
+                            local.span.merge(their.span),
+                            format!("{} = {}", local.node.0, their_str),
+                        ));
+                    }
+                }
+                let brackets = Brackets {
+                    open: "(",
+                    close: ")",
+                    begin: load.span.begin(),
+                    end: Some(load.span.end()),
+                    multiline: self.is_multiline(load.span.begin(), load.span.end()),
+                    tuple: false,
+                };
+                format!("load{}", self.collection(brackets, items, indent))
+            }
+            Stmt::If(..)
+            | Stmt::IfElse(..)
+            | Stmt::For(..)
+            | Stmt::Def(..)
+            | Stmt::Statements(..) => unreachable!("not a simple statement"),
+        }
+    }
+
+    /// Print a string literal. Raw and triple-quoted strings are kept as written.
+    fn string(&self, x: &AstString) -> String {
+        let source = self.source(x.span);
+        let quoted = source.trim_start_matches(|c| c == 'r' || c == 'R');
+        if quoted.len() != source.len() || quoted.starts_with("\"\"\"") || quoted.starts_with("'''")
+        {
+            return source.to_owned();
+        }
+        let (preferred, other) = match self.options.quote {
+            QuoteStyle::Double => ('"', '\''),
+            QuoteStyle::Single => ('\'', '"'),
+        };
+        let quote = if x.node.contains(preferred) && !x.node.contains(other) {
+            other
+        } else {
+            preferred
+        };
+        let mut res = String::with_capacity(x.node.len() + 2);
+        res.push(quote);
+        for c in x.node.chars() {
+            match c {
+                '\n' => res.push_str("\\n"),
+                '\r' => res.push_str("\\r"),
+                '\t' => res.push_str("\\t"),
+                '\\' => res.push_str("\\\\"),
+                c if c == quote => {
+                    res.push('\\');
+                    res.push(c);
+                }
+                c if c < ' ' || c == '\x7f' => res.push_str(&format!("\\x{:02x}", c as u32)),
+                c => res.push(c),
+            }
+        }
+        res.push(quote);
+        res
+    }
+
+    /// Print the `items` of a collection, which were formatted at
+    /// [`Brackets::item_indent`], with the comments between them.
+    fn collection(
+        &mut self,
+        brackets: Brackets,
+        items: Vec<(Span, String)>,
+        indent: usize,
+    ) -> String {
+        let mut res = brackets.open.to_owned();
+        if !brackets.multiline {
+            res.push_str(&items.map(|(_, x)| x.as_str()).join(", "));
+            if brackets.tuple && items.len() == 1 {
+                res.push(',');
+            }
+            res.push_str(brackets.close);
+            return res;
+        }
+        let item_indentation = self.indentation(indent + 1);
+        let file_end = self.codemap.full_span().end();
+        for (i, (span, item)) in items.iter().enumerate() {
+            while let Some((_, comment)) = self.comment_between(brackets.begin, span.begin()) {
+                res.push('\n');
+                res.push_str(&item_indentation);
+                res.push_str(&comment);
+            }
+            res.push('\n');
+            res.push_str(&item_indentation);
+            res.push_str(item);
+            if i + 1 < items.len() || self.options.trailing_commas || brackets.tuple {
+                res.push(',');
+            }
+            let limit = match items.get(i + 1) {
+                Some((next, _)) => next.begin(),
+                None => brackets.end.unwrap_or(file_end),
+            };
+            if let Some((_, comment)) = self.comment_after(span.end(), limit) {
+                res.push_str("  ");
+                res.push_str(&comment);
+            }
+        }
+        if let Some(end) = brackets.end {
+            while let Some((_, comment)) = self.comment_between(brackets.begin, end) {
+                res.push('\n');
+                res.push_str(&item_indentation);
+                res.push_str(&comment);
+            }
+        }
+        if res.len() == brackets.open.len() {
+            // Nothing inside, so nothing to split over lines.
+            res.push_str(brackets.close);
+            return res;
+        }
+        res.push('\n');
+        res.push_str(&self.indentation(indent));
+        res.push_str(brackets.close);
+        res
+    }
+
+    fn exprs(&mut self, brackets: Brackets, xs: &[AstExpr], indent: usize) -> String {
+        let item_indent = brackets.item_indent(indent);
+        let items = xs.map(|x| (x.span, self.expr(x, PREC_TEST, item_indent)));
+        self.collection(brackets, items, indent)
+    }
+
+    /// Print the parameters of a `def`, which span `begin` to `end` in the source.
+    fn params(&mut self, begin: Pos, end: Pos, params: &[AstParameter], indent: usize) -> String {
+        let brackets = Brackets {
+            open: "(",
+            close: ")",
+            begin,
+            end: None,
+            multiline: self.is_multiline(begin, end),
+            tuple: false,
+        };
+        let item_indent = brackets.item_indent(indent);
+        let items = params.map(|x| (x.span, self.param(&x.node, item_indent)));
+        self.collection(brackets, items, indent)
+    }
+
+    fn param(&mut self, x: &Parameter, indent: usize) -> String {
+        let (prefix, name, ty, default) = match x {
+            Parameter::Normal(name, ty) => ("", name, ty, None),
+            Parameter::WithDefaultValue(name, ty, default) => ("", name, ty, Some(default)),
+            Parameter::NoArgs => return "*".to_owned(),
+            Parameter::Args(name, ty) => ("*", name, ty, None),
+            Parameter::KwArgs(name, ty) => ("**", name, ty, None),
+        };
+        let mut res = format!("{}{}", prefix, name.node.0);
+        if let Some(ty) = ty {
+            res.push_str(": ");
+            res.push_str(&self.expr(ty, PREC_TEST, indent));
+        }
+        if let Some(default) = default {
+            res.push_str(" = ");
+            res.push_str(&self.expr(default, PREC_TEST, indent));
+        }
+        res
+    }
+
+    fn arg(&mut self, x: &AstArgument, indent: usize) -> String {
+        match &x.node {
+            Argument::Positional(x) => self.expr(x, PREC_TEST, indent),
+            Argument::Named(name, x) => {
+                format!("{} = {}", name.node, self.expr(x, PREC_TEST, indent))
+            }
+            Argument::Args(x) => format!("*{}", self.expr(x, PREC_TEST, indent)),
+            Argument::KwArgs(x) => format!("**{}", self.expr(x, PREC_TEST, indent)),
+        }
+    }
+
+    /// Print an assignment target. At the `top` of a statement or `for`,
+    /// tuples only get parentheses if they had them in the source.
+    fn assign(&mut self, x: &AstAssign, top: bool, indent: usize) -> String {
+        match &x.node {
+            Assign::Tuple(xs) => {
+                let items = xs.map(|x| (x.span, self.assign(x, false, indent)));
+                let list = self.source(x.span).starts_with('[')
+                    && xs.first().map_or(true, |y| y.span.begin() > x.span.begin());
+                let (open, close) = if list {
+                    ("[", "]")
+                } else if top && self.open_paren(x.span.begin()).is_none() {
+                    ("", "")
+                } else {
+                    ("(", ")")
+                };
+                let brackets = Brackets {
+                    open,
+                    close,
+                    begin: x.span.begin(),
+                    end: None,
+                    multiline: false,
+                    tuple: !list,
+                };
+                self.collection(brackets, items, indent)
+            }
+            Assign::ArrayIndirection(box (array, index)) => format!(
+                "{}[{}]",
+                self.primary(array, indent),
+                self.expr_top(index, indent)
+            ),
+            Assign::Slice(array, start, stop, step) => {
+                let mut res = format!("{}[", self.primary(array, indent));
+                if let Some(start) = start {
+                    res.push_str(&self.expr(start, PREC_TEST, indent));
+                }
+                res.push(':');
+                if let Some(stop) = stop {
+                    res.push_str(&self.expr(stop, PREC_TEST, indent));
+                }
+                if let Some(step) = step {
+                    res.push(':');
+                    res.push_str(&self.expr(step, PREC_TEST, indent));
+                }
+                res.push(']');
+                res
+            }
+            Assign::Dot(object, attr) => format!("{}.{}", self.primary(object, indent), attr.node),
+            Assign::Identifier(x) => x.node.0.clone(),
+            Assign::Starred(x) => format!("*{}", self.assign(x, false, indent)),
+        }
+    }
+
+    /// Print an expression where tuples don't need parentheses:
+    /// they only get them if they had them in the source, or span several lines.
+    fn expr_top(&mut self, x: &AstExpr, indent: usize) -> String {
+        match &x.node {
+            Expr::Tuple(xs)
+                if !xs.is_empty()
+                    && self.open_paren(x.span.begin()).is_none()
+                    && !self.is_multiline(x.span.begin(), x.span.end()) =>
+            {
+                let brackets = Brackets {
+                    open: "",
+                    close: "",
+                    begin: x.span.begin(),
+                    end: None,
+                    multiline: false,
+                    tuple: true,
+                };
+                self.exprs(brackets, xs, indent)
+            }
+            _ => self.expr(x, PREC_TEST, indent),
+        }
+    }
+
+    /// Print an expression, in parentheses if it binds less tightly than `prec`.
+    fn expr(&mut self, x: &AstExpr, prec: u8, indent: usize) -> String {
+        let res = self.expr_inner(x, indent);
+        if expr_prec(&x.node) < prec {
+            format!("({})", res)
+        } else {
+            res
+        }
+    }
+
+    /// Brackets of a list, dict or argument list, which span `begin` to `end` in the source.
+    fn brackets(&self, open: &'static str, close: &'static str, begin: Pos, end: Pos) -> Brackets {
+        Brackets {
+            open,
+            close,
+            begin,
+            end: Some(end),
+            multiline: self.is_multiline(begin, end),
+            tuple: false,
+        }
+    }
+
+    fn expr_inner(&mut self, x: &AstExpr, indent: usize) -> String {
+        match &x.node {
+            Expr::Tuple(xs) if xs.is_empty() => "()".to_owned(),
+            Expr::Tuple(xs) => {
+                let begin = self.open_paren(x.span.begin()).unwrap_or(x.span.begin());
+                let brackets = Brackets {
+                    open: "(",
+                    close: ")",
+                    begin,
+                    end: None,
+                    multiline: self.is_multiline(begin, x.span.end()),
+                    tuple: true,
+                };
+                self.exprs(brackets, xs, indent)
+            }
+            Expr::Dot(object, attr) => format!("{}.{}", self.primary(object, indent), attr.node),
+            Expr::Call(f, args) => {
+                let f_str = self.primary(f, indent);
+                let brackets = self.brackets("(", ")", f.span.end(), x.span.end());
+                let item_indent = brackets.item_indent(indent);
+                let items = args.map(|arg| (arg.span, self.arg(arg, item_indent)));
+                format!("{}{}", f_str, self.collection(brackets, items, indent))
+            }
+            Expr::ArrayIndirection(box (array, index)) => format!(
+                "{}[{}]",
+                self.primary(array, indent),
+                self.expr_top(index, indent)
+            ),
+            Expr::Slice(array, start, stop, step) => {
+                let mut res = format!("{}[", self.primary(array, indent));
+                if let Some(start) = start {
+                    res.push_str(&self.expr(start, PREC_TEST, indent));
+                }
+                res.push(':');
+                if let Some(stop) = stop {
+                    res.push_str(&self.expr(stop, PREC_TEST, indent));
+                }
+                if let Some(step) = step {
+                    res.push(':');
+                    res.push_str(&self.expr(step, PREC_TEST, indent));
+                }
+                res.push(']');
+                res
+            }
+            Expr::Identifier(name, _) => name.node.clone(),
+            Expr::Lambda(params, body, _) => {
+                let params = params.map(|x| self.param(&x.node, indent)).join(", ");
+                let body = self.expr(body, PREC_TEST, indent);
+                if params.is_empty() {
+                    format!("lambda: {}", body)
+                } else {
+                    format!("lambda {}: {}", params, body)
+                }
+            }
+            Expr::Literal(AstLiteral::String(x)) => self.string(x),
+            Expr::Literal(AstLiteral::Int(x)) => self.source(x.span).to_owned(),
+            Expr::Literal(AstLiteral::Float(x)) => self.source(x.span).to_owned(),
+            Expr::Not(x) => format!("not {}", self.expr(x, PREC_NOT, indent)),
+            Expr::Minus(x) => format!("-{}", self.expr(x, PREC_UNARY, indent)),
+            Expr::Plus(x) => format!("+{}", self.expr(x, PREC_UNARY, indent)),
+            Expr::BitNot(x) => format!("~{}", self.expr(x, PREC_UNARY, indent)),
+            Expr::Op(lhs, op, rhs) => {
+                let prec = bin_op_prec(*op);
+                // Comparisons don't chain, other operators are left associative.
+                let lhs_prec = if prec == PREC_COMPARE { prec + 1 } else { prec };
+                format!(
+                    "{}{}{}",
+                    self.expr(lhs, lhs_prec, indent),
+                    op,
+                    self.expr(rhs, prec + 1, indent)
+                )
+            }
+            Expr::If(box (cond, then, els)) => format!(
+                "{} if {} else {}",
+                self.expr(then, PREC_OR, indent),
+                self.expr(cond, PREC_OR, indent),
+                self.expr(els, PREC_TEST, indent)
+            ),
+            Expr::List(xs) => {
+                let brackets = self.brackets("[", "]", x.span.begin(), x.span.end());
+                self.exprs(brackets, xs, indent)
+            }
+            Expr::Dict(xs) => {
+                let brackets = self.brackets("{", "}", x.span.begin(), x.span.end());
+                let item_indent = brackets.item_indent(indent);
+                let items = xs.map(|(k, v)| {
+                    let k_str = self.expr(k, PREC_TEST, item_indent);
+                    let v_str = self.expr(v, PREC_TEST, item_indent);
+                    (k.span.merge(v.span), format!("{}: {}", k_str, v_str))
+                });
+                self.collection(brackets, items, indent)
+            }
+            Expr::ListComprehension(x, for_, clauses) => format!(
+                "[{}{}]",
+                self.expr(x, PREC_TEST, indent),
+                self.clauses(for_, clauses, indent)
+            ),
+            Expr::DictComprehension(box (k, v), for_, clauses) => format!(
+                "{{{}: {}{}}}",
+                self.expr(k, PREC_TEST, indent),
+                self.expr(v, PREC_TEST, indent),
+                self.clauses(for_, clauses, indent)
+            ),
+        }
+    }
+
+    /// Print the object of `.x`, `(...)` or `[...]`.
+    fn primary(&mut self, x: &AstExpr, indent: usize) -> String {
+        match &x.node {
+            // `1.x` would lex as a float.
+            Expr::Literal(AstLiteral::Int(_)) => format!("({})", self.expr_inner(x, indent)),
+            _ => self.expr(x, PREC_PRIMARY, indent),
+        }
+    }
+
+    fn clauses(&mut self, for_: &ForClause, clauses: &[Clause], indent: usize) -> String {
+        let mut res = self.for_clause(for_, indent);
+        for clause in clauses {
+            match clause {
+                Clause::For(x) => res.push_str(&self.for_clause(x, indent)),
+                Clause::If(x) => {
+                    res.push_str(" if ");
+                    res.push_str(&self.expr(x, PREC_OR, indent));
+                }
+            }
+        }
+        res
+    }
+
+    fn for_clause(&mut self, x: &ForClause, indent: usize) -> String {
+        format!(
+            " for {} in {}",
+            self.assign(&x.var, true, indent),
+            self.expr(&x.over, PREC_OR, indent)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::syntax::AstModule;
+    use crate::syntax::Dialect;
+    use crate::syntax::FormatOptions;
+    use crate::syntax::QuoteStyle;
+
+    fn format_with(program: &str, options: FormatOptions) -> String {
+        let ast = AstModule::parse("x.star", program.to_owned(), &Dialect::Extended).unwrap();
+        let res = ast.to_formatted_string(options.clone());
+        let again = AstModule::parse("x.star", res.clone(), &Dialect::Extended)
+            .unwrap_or_else(|e| panic!("formatted program does not parse: {}\n{}", e, res));
+        assert_eq!(res, again.to_formatted_string(options), "not idempotent");
+        res
+    }
+
+    fn format(program: &str) -> String {
+        format_with(program, FormatOptions::default())
+    }
+
+    #[test]
+    fn test_format_spacing() {
+        assert_eq!(
+            r#"x = [1, 2, {"a": 3}]
+y = f(x, z = 1, *args, **kwargs)
+a, b = b, a
+c = (1, 2)
+d = (1,)
+e = x[1:2] + x[::2] + x[a, b]
+f = lambda x, y = 1: x + y
+g = [x for x in y if x]
+h += 1
+"#,
+            format(
+                "x=[1,2,{'a':3}]\ny=f( x,z=1 ,*args,**kwargs )\na,b=b,a\nc=(1,2)\nd=(1,)\n\
+                 e=x[1:2]+x[::2]+x[a,b]\nf=lambda x,y=1:x+y\ng=[x for x in y if x]\nh+=1\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_format_parens() {
+        assert_eq!(
+            "x = (a + b) * c - (d - e)\ny = not a == b and (c or d)\nz = (1).real\nw = (a if b else c)(d)\n",
+            format("x = ((a + b)) * c - (d - e)\ny = (not a == b) and (c or d)\nz = (1).real\nw = (a if b else c)(d)\n")
+        );
+    }
+
+    #[test]
+    fn test_format_indentation() {
+        assert_eq!(
+            r#"def f(x):
+    if x:
+        return 1
+    elif x == 2:
+        pass
+    else:
+        for y in x:
+            continue
+    return None
+"#,
+            format(
+                "def f(x):\n  if x: return 1\n  elif x == 2:\n   pass\n  else:\n        for y in x: continue\n  return None\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_format_comments() {
+        let program = r#"# Header comment.
+
+load("a.star", "b", c = "d")  # load
+
+# About f.
+def f(
+    x,  # the x
+    # the y
+    y = [
+        1,  # one
+        # end of y
+    ],
+):  # unusual place
+    if x:  # is x
+        pass
+        # end of if
+    # before else
+    else:
+        pass
+
+    # end of f
+
+# Trailing comment.
+"#;
+        assert_eq!(
+            r#"# Header comment.
+
+load("a.star", "b", c = "d")  # load
+
+# About f.
+def f(
+    x,  # the x
+    # the y
+    y = [
+        1,  # one
+        # end of y
+    ],
+):
+    # unusual place
+    if x:  # is x
+        pass
+        # end of if
+    # before else
+    else:
+        pass
+
+    # end of f
+
+# Trailing comment.
+"#,
+            format(program)
+        );
+    }
+
+    #[test]
+    fn test_format_comments_inside_expressions() {
+        // Comments which can't stay where they are move above the statement.
+        assert_eq!(
+            "# inside\nx = a + b\n",
+            format("x = (a +  # inside\n  b)\n")
+        );
+    }
+
+    #[test]
+    fn test_format_multiline() {
+        assert_eq!(
+            r#"x = [
+    1,
+    (
+        2,
+        3,
+    ),
+]
+y = f(
+    1,
+    [2],
+)
+"#,
+            format("x = [1, (2,\n 3)]\ny = f(1,\n      [2])\n")
+        );
+    }
+
+    #[test]
+    fn test_format_blank_lines() {
+        assert_eq!(
+            "x = 1\n\ny = 2\n\ndef f():\n    a = 1\n\n    b = 2\n",
+            format("\n\nx = 1\n\n\n\ny = 2\n\ndef f():\n\n    a = 1\n\n\n    b = 2\n\n\n")
+        );
+    }
+
+    #[test]
+    fn test_format_strings() {
+        assert_eq!(
+            "x = [\"a\", 'b\"', \"\\n\\x01\", r'\\d', '''a\"b\"''']\n",
+            format("x = ['a', 'b\"', '\\n\\x01', r'\\d', '''a\"b\"''']\n")
+        );
+        let single = FormatOptions {
+            quote: QuoteStyle::Single,
+            indent: 2,
+            trailing_commas: false,
+        };
+        assert_eq!(
+            "def f():\n  return [\n    'a',\n    \"b'\",\n    [\n      1,\n      2\n    ]\n  ]\n",
+            format_with("def f():\n return [\"a\", \"b'\", [1,\n 2]]\n", single)
+        );
+    }
+
+    #[test]
+    fn test_format_empty() {
+        assert_eq!("", format(""));
+        assert_eq!("# Just a comment.\n", format("\n# Just a comment.\n\n"));
+    }
+}
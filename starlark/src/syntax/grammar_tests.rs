@@ -15,11 +15,15 @@
  * limitations under the License.
  */
 
+use std::ops::Range;
+
 use gazebo::prelude::*;
 
 use crate::assert;
 use crate::assert::Assert;
 use crate::syntax::ast::Stmt;
+use crate::syntax::AstModule;
+use crate::syntax::Dialect;
 
 #[test]
 fn test_empty() {
@@ -301,3 +305,80 @@ fn test_bad_assignment() {
     assert::parse_fail("[!x or y!] = 1");
     assert::parse_fail("![x]! += 1");
 }
+
+#[test]
+fn test_starred_assignment() {
+    assert_eq!(assert::parse("a, *b, c = x"), "(a, *b, c) = x\n");
+    assert_eq!(assert::parse("*a, b = x"), "(*a, b) = x\n");
+    assert_eq!(assert::parse("a, *b = x"), "(a, *b) = x\n");
+    assert_eq!(assert::parse("*a, = x"), "(*a,) = x\n");
+    assert_eq!(assert::parse("a, *b.c[0], = x"), "(a, *b.c[0]) = x\n");
+    assert::parse_fail("!*a! = x");
+    assert::parse_fail("a, *b, !*!c = x");
+    assert::parse_fail("a, *b !+=! x");
+    assert::parse_fail("a, *!f()! = x");
+}
+
+#[test]
+fn test_del() {
+    assert_eq!(
+        assert::parse("def f():\n    del x, y[0], z[1:]\n"),
+        "def f():\n  del (x, y[0], z[1:])\n"
+    );
+    assert::parse_fail("def f():\n    del !x.y!\n");
+    assert::parse_fail("def f():\n    del !f()!\n");
+    assert::parse_fail("!del x!\n");
+}
+
+#[test]
+fn test_slice_assignment() {
+    assert_eq!(assert::parse("a[1:2] = x"), "a[1:2] = x\n");
+    assert_eq!(assert::parse("a[::2] = x"), "a[::2] = x\n");
+    assert_eq!(assert::parse("a.b[i:] = x"), "a.b[i:] = x\n");
+    assert::parse_fail("!a[1:2]! += x");
+}
+
+#[test]
+fn test_reparse() {
+    fn check(before: &str, range: Range<usize>, replacement: &str) {
+        let mut module =
+            AstModule::parse("test.star", before.to_owned(), &Dialect::Extended).unwrap();
+        module.reparse(range.clone(), replacement).unwrap();
+        let mut after = before.to_owned();
+        after.replace_range(range, replacement);
+        let expected = AstModule::parse("test.star", after.clone(), &Dialect::Extended).unwrap();
+        assert_eq!(after, module.codemap.source());
+        // The debug output includes spans, so this checks offsets too.
+        assert_eq!(
+            format!("{:?}", expected.statement),
+            format!("{:?}", module.statement)
+        );
+    }
+
+    let src = "x = 1\n\ndef f(a):\n    return a\n\ny = f(x)\n";
+    // Edit within the last statement.
+    check(src, 37..38, "x + 100");
+    // Edit within a `def`.
+    check(src, 28..29, "a * 2");
+    // Edit within the first statement.
+    check(src, 4..5, "[1, 2]");
+    // Insert a new statement between others.
+    check(src, 6..6, "z = 3\n");
+    // Extend a `def` at the end of the file, which can't be parsed on its own.
+    check("def f():\n    pass\n", 18..18, "    return 1\n");
+    // Join lines with a trailing `\`.
+    check("x = 1 \\\n\n", 8..8, "+ 2");
+    // Delete trailing statements, so the module ends earlier.
+    check(src, 31..40, "");
+    check(src, 7..40, "");
+    check("x = 1\ny = [\n    2,\n]\n", 6..21, "z = 3\n");
+}
+
+#[test]
+fn test_reparse_error() {
+    let mut module =
+        AstModule::parse("test.star", "x = 1\ny = 2\n".to_owned(), &Dialect::Extended).unwrap();
+    assert!(module.reparse(6..7, "(").is_err());
+    assert!(module.reparse(6..100, "").is_err());
+    assert_eq!("x = 1\ny = 2\n", module.codemap.source());
+}
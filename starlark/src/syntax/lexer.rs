@@ -544,9 +544,7 @@ pub enum Token {
     #[token("r\"")]
     RawDoubleQuote,
 
-    #[regex(
-        "as|import|is|class|nonlocal|del|raise|except|try|finally|while|from|with|global|yield"
-    )]
+    #[regex("as|import|is|class|nonlocal|raise|except|try|finally|while|from|with|global|yield")]
     Reserved, // One of the reserved keywords
 
     #[regex(
@@ -603,6 +601,8 @@ pub enum Token {
     Return,
     #[token("lambda")]
     Lambda,
+    #[token("del")]
+    Del,
     // Symbols
     #[token(",")]
     Comma,
@@ -743,6 +743,7 @@ impl Display for Token {
             Token::Elif => write!(f, "keyword 'elif'"),
             Token::Return => write!(f, "keyword 'return'"),
             Token::Lambda => write!(f, "keyword 'lambda'"),
+            Token::Del => write!(f, "keyword 'del'"),
             Token::Comma => write!(f, "symbol ','"),
             Token::Semicolon => write!(f, "symbol ';'"),
             Token::Colon => write!(f, "symbol ':'"),
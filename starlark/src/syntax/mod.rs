@@ -20,6 +20,14 @@
 pub use ast::AstModule;
 pub use dialect::Dialect;
 pub use dialect::DialectTypes;
+pub use format::FormatOptions;
+pub use format::QuoteStyle;
+
+pub use crate::analysis::Completion;
+pub use crate::analysis::CompletionKind;
+pub use crate::analysis::Definition;
+pub use crate::analysis::DottedDefinition;
+pub use crate::analysis::IdentifierDefinition;
 
 #[cfg(test)]
 mod grammar_tests;
@@ -31,6 +39,7 @@ mod testcases;
 pub(crate) mod ast;
 pub(crate) mod cursors;
 mod dialect;
+mod format;
 pub(crate) mod lexer;
 pub(crate) mod payload_map;
 pub(crate) mod validate;
@@ -17,13 +17,17 @@
 
 use std::fmt::Write;
 use std::fs;
+use std::ops::Range;
 use std::path::Path;
 
 use gazebo::prelude::*;
 use lalrpop_util as lu;
+use thiserror::Error;
 
 use crate::codemap::CodeMap;
+use crate::codemap::ColumnUnit;
 use crate::codemap::FileSpan;
+use crate::codemap::LineCol;
 use crate::codemap::Pos;
 use crate::codemap::Span;
 use crate::errors::Diagnostic;
@@ -35,6 +39,12 @@ use crate::syntax::grammar::StarlarkParser;
 use crate::syntax::lexer::Lexer;
 use crate::syntax::lexer::Token;
 
+#[derive(Error, Debug)]
+enum ReparseError {
+    #[error("Edit range {0}..{1} is not valid for a source of length {2}")]
+    InvalidRange(usize, usize, usize),
+}
+
 fn one_of(expected: &[String]) -> String {
     let mut result = String::new();
     for (i, e) in expected.iter().enumerate() {
@@ -132,7 +142,10 @@ impl AstModule {
     /// assert_eq!(err.span.unwrap().to_string(), "filename:2:11");
     /// ```
     pub fn parse(filename: &str, content: String, dialect: &Dialect) -> anyhow::Result<Self> {
-        let codemap = CodeMap::new(filename.to_owned(), content);
+        Self::parse_codemap(CodeMap::new(filename.to_owned(), content), dialect)
+    }
+
+    fn parse_codemap(codemap: CodeMap, dialect: &Dialect) -> anyhow::Result<Self> {
         let lexer = Lexer::new(codemap.source(), dialect, codemap.dupe());
         match StarlarkParser::new().parse(&codemap, dialect, lexer) {
             Ok(v) => Ok(AstModule::create(codemap, v, dialect)?),
@@ -140,6 +153,86 @@ impl AstModule {
         }
     }
 
+    /// Apply an edit to the source of this module, replacing the bytes in `range`
+    /// with `replacement`, and update the AST to match.
+    ///
+    /// Top-level statements which end on a line before the edit are kept, and only
+    /// the remainder of the module is parsed again, which makes edits near the end
+    /// of large files cheap. The resulting module is the same as if the edited source
+    /// was passed to [`parse`](AstModule::parse), including all spans.
+    /// If the edited source does not parse, the error is returned and the module is left unchanged.
+    pub fn reparse(&mut self, range: Range<usize>, replacement: &str) -> anyhow::Result<()> {
+        let old = self.codemap.source();
+        if old.get(range.clone()).is_none() {
+            return Err(ReparseError::InvalidRange(range.start, range.end, old.len()).into());
+        }
+        let mut content = String::with_capacity(old.len() - range.len() + replacement.len());
+        content.push_str(&old[..range.start]);
+        content.push_str(replacement);
+        content.push_str(&old[range.end..]);
+        let codemap = CodeMap::new(self.codemap.filename().to_owned(), content);
+
+        if let Some((keep, offset)) = self.reusable_prefix(range.start) {
+            // A tail without any tokens has a default span, so can't give the module end.
+            if let Some(tail) = Self::parse_from(&codemap, offset, &self.dialect)
+                .filter(|tail| tail.span.end().get() as usize >= offset)
+            {
+                // The module span runs from its first token to its last token, which
+                // is in the tail, so don't keep the old end if the edit removed code.
+                let span = Span::new(self.statement.span.begin(), tail.span.end());
+                match (&mut self.statement.node, tail.node) {
+                    (Stmt::Statements(stmts), Stmt::Statements(tail)) => {
+                        stmts.truncate(keep);
+                        stmts.extend(tail);
+                        self.statement.span = span;
+                        self.codemap = codemap;
+                        return Ok(());
+                    }
+                    _ => unreachable!("top-level statement is always `Statements`"),
+                }
+            }
+        }
+        // Either nothing could be reused, or the remainder doesn't parse on its own
+        // (e.g. it continues an earlier block), so parse everything to get it right.
+        *self = Self::parse_codemap(codemap, &self.dialect)?;
+        Ok(())
+    }
+
+    /// The number of top-level statements not affected by an edit at `pos`,
+    /// and the offset at which parsing must resume after them.
+    fn reusable_prefix(&self, pos: usize) -> Option<(usize, usize)> {
+        let source = self.codemap.source();
+        let stmts = match &self.statement.node {
+            Stmt::Statements(stmts) => stmts,
+            _ => return None,
+        };
+        let line_start = source[..pos].rfind('\n').map_or(0, |x| x + 1);
+        let mut res = None;
+        for (i, stmt) in stmts.iter().enumerate() {
+            let end = stmt.span.end().get() as usize;
+            let line_end = match source[end..].find('\n') {
+                Some(x) => end + x,
+                None => break,
+            };
+            // A `\` after the statement could join the next line onto it.
+            if line_end >= line_start || source[end..line_end].contains('\\') {
+                break;
+            }
+            res = Some((i + 1, line_end + 1));
+        }
+        res
+    }
+
+    /// Parse the source of `codemap` starting at `offset`, with spans relative to the whole file.
+    /// Errors are discarded, as they will be reported by parsing the whole file.
+    fn parse_from(codemap: &CodeMap, offset: usize, dialect: &Dialect) -> Option<AstStmt> {
+        let lexer = Lexer::new(&codemap.source()[offset..], dialect, codemap.dupe())
+            .map(|x| x.map(|(l, t, r)| (l + offset, t, r + offset)));
+        let stmt = StarlarkParser::new().parse(codemap, dialect, lexer).ok()?;
+        Stmt::validate(codemap, &stmt, dialect).ok()?;
+        Some(stmt)
+    }
+
     /// Return the file names of all the `load` statements in the module.
     /// If the [`Dialect`] had [`enable_load`](Dialect::enable_load) set to [`false`] this will be an empty list.
     pub fn loads(&self) -> Vec<&str> {
@@ -166,4 +259,46 @@ impl AstModule {
     pub(crate) fn file_span(&self, x: Span) -> FileSpan {
         self.codemap.file_span(x)
     }
+
+    /// The line and column of the byte `offset` in the source of this module,
+    /// with the column counted in Unicode scalar values (`char`s).
+    ///
+    /// Offsets past the end of the source give the end of the source,
+    /// and offsets in the middle of a UTF-8 character give the start of that character.
+    ///
+    /// ```
+    /// use starlark::codemap::LineCol;
+    /// use starlark::syntax::{AstModule, Dialect};
+    ///
+    /// let ast = AstModule::parse("x.star", "x = 1\ny = '°'\n".to_owned(), &Dialect::Standard).unwrap();
+    /// let line_col = ast.offset_to_line_col(11);
+    /// assert_eq!(LineCol { line: 1, column: 5 }, line_col);
+    /// assert_eq!("2:6", line_col.to_string());
+    /// assert_eq!(11, ast.line_col_to_offset(line_col));
+    /// ```
+    pub fn offset_to_line_col(&self, offset: usize) -> LineCol {
+        self.codemap.offset_to_line_col(offset, ColumnUnit::Char)
+    }
+
+    /// Like [`offset_to_line_col`](AstModule::offset_to_line_col), but with the column
+    /// counted in UTF-16 code units, as used by the Language Server Protocol.
+    pub fn offset_to_line_col_utf16(&self, offset: usize) -> LineCol {
+        self.codemap.offset_to_line_col(offset, ColumnUnit::Utf16)
+    }
+
+    /// The byte offset of a line and column in the source of this module,
+    /// with the column counted in Unicode scalar values (`char`s).
+    /// The inverse of [`offset_to_line_col`](AstModule::offset_to_line_col).
+    ///
+    /// Columns past the end of a line give the end of that line, not counting the line
+    /// terminator, and lines past the end of the source give the end of the source.
+    pub fn line_col_to_offset(&self, line_col: LineCol) -> usize {
+        self.codemap.line_col_to_offset(line_col, ColumnUnit::Char)
+    }
+
+    /// Like [`line_col_to_offset`](AstModule::line_col_to_offset), but with the column
+    /// counted in UTF-16 code units, as used by the Language Server Protocol.
+    pub fn line_col_utf16_to_offset(&self, line_col: LineCol) -> usize {
+        self.codemap.line_col_to_offset(line_col, ColumnUnit::Utf16)
+    }
 }
@@ -68,6 +68,7 @@ impl<A: AstPayload> StmtP<A> {
             StmtP::AssignModify(lhs, op, rhs) => {
                 StmtP::AssignModify(lhs.into_map_payload(f), op, box rhs.into_map_payload(f))
             }
+            StmtP::Del(x) => StmtP::Del(x.into_map_payload(f)),
             StmtP::Statements(stmts) => {
                 StmtP::Statements(stmts.into_map(|s| s.into_map_payload(f)))
             }
@@ -166,8 +167,15 @@ impl<A: AstPayload> AssignP<A> {
                 array.into_map_payload(f),
                 index.into_map_payload(f),
             )),
+            AssignP::Slice(x, a, b, c) => AssignP::Slice(
+                box x.into_map_payload(f),
+                a.map(|e| box e.into_map_payload(f)),
+                b.map(|e| box e.into_map_payload(f)),
+                c.map(|e| box e.into_map_payload(f)),
+            ),
             AssignP::Dot(object, field) => AssignP::Dot(box object.into_map_payload(f), field),
             AssignP::Identifier(ident) => AssignP::Identifier(ident.into_map_payload(f)),
+            AssignP::Starred(box x) => AssignP::Starred(box x.into_map_payload(f)),
         }
     }
 }
@@ -16,6 +16,7 @@
  */
 
 use crate::assert;
+use crate::syntax::FormatOptions;
 
 macro_rules! testcases_parse {
     ($($x:expr)*) => {
@@ -215,3 +216,22 @@ fn parsing_testcases() {
         assert::parse(content);
     }
 }
+
+#[test]
+fn formatting_testcases() {
+    for (name, content) in TESTCASE_FILES {
+        let formatted = assert::parse_ast(content).to_formatted_string(FormatOptions::default());
+        assert_eq!(
+            assert::parse(content),
+            assert::parse(&formatted),
+            "formatting changed the AST of {}",
+            name
+        );
+        assert_eq!(
+            formatted,
+            assert::parse_ast(&formatted).to_formatted_string(FormatOptions::default()),
+            "formatting {} is not idempotent",
+            name
+        );
+    }
+}
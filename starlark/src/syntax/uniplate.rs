@@ -92,6 +92,7 @@ impl<P: AstPayload> StmtP<P> {
                 lhs.visit_expr(|x| f(Visit::Expr(x)));
                 f(Visit::Expr(rhs));
             }
+            StmtP::Del(x) => x.visit_expr(|x| f(Visit::Expr(x))),
             StmtP::Load(..) => {}
         }
     }
@@ -136,6 +137,7 @@ impl<P: AstPayload> StmtP<P> {
                 lhs.visit_expr_mut(|x| f(VisitMut::Expr(x)));
                 f(VisitMut::Expr(rhs));
             }
+            StmtP::Del(x) => x.visit_expr_mut(|x| f(VisitMut::Expr(x))),
             StmtP::Load(..) => {}
         }
     }
@@ -356,11 +358,18 @@ impl<P: AstPayload> AssignP<P> {
         fn recurse<'a, P: AstPayload>(x: &'a AssignP<P>, f: &mut impl FnMut(&'a AstExprP<P>)) {
             match x {
                 AssignP::Tuple(xs) => xs.iter().for_each(|x| recurse(x, f)),
+                AssignP::Starred(x) => recurse(x, f),
                 AssignP::Dot(a, _) => f(a),
                 AssignP::ArrayIndirection(box (a, b)) => {
                     f(a);
                     f(b);
                 }
+                AssignP::Slice(a, b, c, d) => {
+                    f(a);
+                    b.iter().for_each(|x| f(x));
+                    c.iter().for_each(|x| f(x));
+                    d.iter().for_each(|x| f(x));
+                }
                 AssignP::Identifier(..) => {}
             }
         }
@@ -374,11 +383,18 @@ impl<P: AstPayload> AssignP<P> {
         ) {
             match x {
                 AssignP::Tuple(ref mut xs) => xs.iter_mut().for_each(|x| recurse(&mut *x, f)),
+                AssignP::Starred(ref mut x) => recurse(&mut *x, f),
                 AssignP::Dot(a, _) => f(a),
                 AssignP::ArrayIndirection(box (a, b)) => {
                     f(a);
                     f(b);
                 }
+                AssignP::Slice(a, b, c, d) => {
+                    f(a);
+                    b.iter_mut().for_each(|x| f(x));
+                    c.iter_mut().for_each(|x| f(x));
+                    d.iter_mut().for_each(|x| f(x));
+                }
                 AssignP::Identifier(..) => {}
             }
         }
@@ -396,6 +412,7 @@ impl<P: AstPayload> AssignP<P> {
             match x {
                 AssignP::Identifier(x) => f(x),
                 AssignP::Tuple(xs) => xs.iter().for_each(|x| recurse(x, f)),
+                AssignP::Starred(x) => recurse(x, f),
                 _ => {}
             }
         }
@@ -413,6 +430,7 @@ impl<P: AstPayload> AssignP<P> {
             match x {
                 AssignP::Identifier(x) => f(x),
                 AssignP::Tuple(xs) => xs.iter_mut().for_each(|x| recurse(x, f)),
+                AssignP::Starred(x) => recurse(x, f),
                 _ => {}
             }
         }
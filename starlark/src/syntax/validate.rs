@@ -18,6 +18,7 @@
 //! AST for parsed starlark files.
 
 use std::collections::HashSet;
+use std::iter;
 
 use gazebo::prelude::*;
 use thiserror::Error;
@@ -55,10 +56,18 @@ enum ValidateError {
     NoTopLevelIf,
     #[error("`for` cannot be used outside `def` in this dialect")]
     NoTopLevelFor,
-    #[error("left-hand-side of assignment must take the form `a`, `a.b` or `a[b]`")]
+    #[error("left-hand-side of assignment must take the form `a`, `a.b`, `a[b]` or `a[b:c]`")]
     InvalidLhs,
     #[error("left-hand-side of modifying assignment cannot be a list or tuple")]
     InvalidModifyLhs,
+    #[error("left-hand-side of modifying assignment cannot be a slice")]
+    InvalidModifySliceLhs,
+    #[error("starred assignment target must be in a list or tuple")]
+    StarredOutsideTuple,
+    #[error("target of `del` must take the form `a`, `a[b]` or `a[b:c]`")]
+    InvalidDel,
+    #[error("`del` of a variable cannot be used outside of a `def` function")]
+    DelVariableOutsideDef,
 }
 
 #[derive(Eq, PartialEq, Ord, PartialOrd)]
@@ -267,6 +276,7 @@ impl Stmt {
                 }
                 Expr::Dot(a, b) => Assign::Dot(a, b),
                 Expr::ArrayIndirection(box (a, b)) => Assign::ArrayIndirection(box (a, b)),
+                Expr::Slice(a, b, c, d) => Assign::Slice(a, b, c, d),
                 Expr::Identifier(x, ()) => Assign::Identifier(x.into_map(|s| AssignIdentP(s, ()))),
                 _ => {
                     return Err(Diagnostic::new(ValidateError::InvalidLhs, x.span, codemap));
@@ -275,6 +285,51 @@ impl Stmt {
         })
     }
 
+    /// Validate the targets of a `del` statement.
+    pub(crate) fn check_del(codemap: &CodeMap, x: AstExpr) -> anyhow::Result<AstAssign> {
+        Ok(Spanned {
+            span: x.span,
+            node: match x.node {
+                Expr::Tuple(xs) | Expr::List(xs) => {
+                    Assign::Tuple(xs.into_try_map(|x| Self::check_del(codemap, x))?)
+                }
+                Expr::ArrayIndirection(box (a, b)) => Assign::ArrayIndirection(box (a, b)),
+                Expr::Slice(a, b, c, d) => Assign::Slice(a, b, c, d),
+                Expr::Identifier(x, ()) => Assign::Identifier(x.into_map(|s| AssignIdentP(s, ()))),
+                _ => {
+                    return Err(Diagnostic::new(ValidateError::InvalidDel, x.span, codemap));
+                }
+            },
+        })
+    }
+
+    /// Validate the left-hand-side of an assignment with a starred target,
+    /// e.g. `first, *rest, last = ...`. The starred target binds a list.
+    pub(crate) fn check_starred_assign(
+        codemap: &CodeMap,
+        before: Vec<AstExpr>,
+        starred: AstAssign,
+        after: Vec<AstExpr>,
+        trailing_comma: bool,
+    ) -> anyhow::Result<Assign> {
+        if before.is_empty() && after.is_empty() && !trailing_comma {
+            return Err(Diagnostic::new(
+                ValidateError::StarredOutsideTuple,
+                starred.span,
+                codemap,
+            ));
+        }
+        let before = before.into_try_map(|x| Self::check_assign(codemap, x))?;
+        let after = after.into_try_map(|x| Self::check_assign(codemap, x))?;
+        Ok(Assign::Tuple(
+            before
+                .into_iter()
+                .chain(iter::once(starred))
+                .chain(after)
+                .collect(),
+        ))
+    }
+
     pub(crate) fn check_assignment(
         codemap: &CodeMap,
         lhs: AstExpr,
@@ -291,6 +346,13 @@ impl Stmt {
                         codemap,
                     ));
                 }
+                Expr::Slice(..) => {
+                    return Err(Diagnostic::new(
+                        ValidateError::InvalidModifySliceLhs,
+                        lhs.span,
+                        codemap,
+                    ));
+                }
                 _ => {}
             }
         }
@@ -311,6 +373,12 @@ impl Stmt {
         // Inside a def, we allow return.
         // All load's must occur at the top-level.
         // At the top-level we only allow for/if when the dialect permits it.
+        fn binds_name(x: &AstAssign) -> bool {
+            let mut res = false;
+            x.visit_lvalue(|_| res = true);
+            res
+        }
+
         fn f(
             codemap: &CodeMap,
             dialect: &Dialect,
@@ -343,6 +411,9 @@ impl Stmt {
                 Stmt::Continue if !inside_for => err(ValidateError::ContinueOutsideLoop),
                 Stmt::Return(_) if !inside_def => err(ValidateError::ReturnOutsideDef),
                 Stmt::Load(..) if !top_level => err(ValidateError::LoadNotTop),
+                Stmt::Del(x) if !inside_def && binds_name(x) => {
+                    err(ValidateError::DelVariableOutsideDef)
+                }
                 _ => stmt.node.visit_stmt_result(|x| {
                     f(codemap, dialect, x, top_level, inside_for, inside_def)
                 }),
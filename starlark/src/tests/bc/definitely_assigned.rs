@@ -34,6 +34,30 @@ def f(a):
     a.eq("33", "load('a.star', 'f')\nf(33)");
 }
 
+#[test]
+fn test_del_local_is_not_definitely_assigned() {
+    let mut a = Assert::new();
+    a.module(
+        "a.star",
+        r"
+def f(x):
+  noop(x)
+  del x
+  return x
+def g(xs):
+  y = 1
+  for x in xs:
+    noop(y)
+    del y
+",
+    );
+    a.fail("load('a.star', 'f')\nf(1)", "referenced before assignment");
+    a.fail(
+        "load('a.star', 'g')\ng([1, 2])",
+        "referenced before assignment",
+    );
+}
+
 #[test]
 fn test_definitely_assigned_slot_range_in_list() {
     bc_golden_test(
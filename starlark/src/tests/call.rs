@@ -103,6 +103,30 @@ def f3(a, b, c):
     assert::fail(&f("f3(a=1, b=2, c=3, d=4)"), "extra named");
 }
 
+#[test]
+fn test_max_callstack_size() {
+    let program = |n: u32| {
+        format!(
+            "
+def rec(n):
+    if n == 0:
+        return 0
+    return rec(n - 1) + 1
+rec({}) == {}",
+            n, n
+        )
+    };
+    let mut a = Assert::new();
+    a.setup_eval(|eval| eval.set_max_callstack_size(10).unwrap());
+    a.is_true(&program(5));
+    a.fail(&program(20), "call stack exceeded 10 frames");
+
+    assert::fail(&program(100), "Starlark call stack overflow");
+    let mut a = Assert::new();
+    a.setup_eval(|eval| eval.set_max_callstack_size(200).unwrap());
+    a.is_true(&program(100));
+}
+
 #[test]
 fn test_repeated_parameters() {
     // Starlark requires both these types of errors are _static_ errors
@@ -296,3 +320,24 @@ G_F_PTR = g([])
         frame_native_size,
     );
 }
+
+#[test]
+fn test_kwargs_order() {
+    // `**kwargs` is in call-site order, followed by entries of any `**dict` argument.
+    assert::pass(
+        r#"
+def f(a = 0, **kwargs):
+    return kwargs.keys()
+
+assert_eq(f(z=1, b=2, m=3), ["z", "b", "m"])
+assert_eq(f(z=1, a=2, b=3), ["z", "b"])
+assert_eq(f(y=1, **{"x": 2, "w": 3}), ["y", "x", "w"])
+assert_eq(f(**{"q": 1, "a": 2, "p": 3}), ["q", "p"])
+
+def g(**kwargs):
+    return kwargs.keys()
+
+assert_eq(g(c=1, b=2, a=3), ["c", "b", "a"])
+"#,
+    );
+}
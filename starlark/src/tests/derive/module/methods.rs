@@ -25,6 +25,7 @@ use crate::environment::MethodsStatic;
 use crate::values::AllocFrozenValue;
 use crate::values::FrozenHeap;
 use crate::values::FrozenValue;
+use crate::values::Heap;
 use crate::values::StarlarkValue;
 use crate::values::Value;
 use crate::values::ValueLike;
@@ -64,3 +65,58 @@ fn test_receiver_can_be_named_anything() {
     a.globals_add(|g| g.set("x", g.alloc(Applaud { value: 10 })));
     a.eq("13", "x.test_method(this=3)");
 }
+
+#[derive(Debug, derive_more::Display, ProvidesStaticType, NoSerialize)]
+#[display(fmt = "proxy")]
+struct Proxy;
+
+#[starlark_module]
+fn proxy_methods(builder: &mut MethodsBuilder) {
+    fn get(#[starlark(this)] _this: Value) -> anyhow::Result<i32> {
+        Ok(1)
+    }
+
+    fn other(#[starlark(this)] _this: Value) -> anyhow::Result<i32> {
+        Ok(2)
+    }
+}
+
+impl<'v> StarlarkValue<'v> for Proxy {
+    starlark_type!("proxy");
+
+    fn get_methods() -> Option<&'static Methods> {
+        static RES: MethodsStatic = MethodsStatic::new();
+        RES.methods(proxy_methods)
+    }
+
+    fn bind_method(
+        &self,
+        _this: Value<'v>,
+        method_name: &str,
+        heap: &'v Heap,
+    ) -> Option<Value<'v>> {
+        match method_name {
+            "get" => Some(heap.alloc("bound get")),
+            _ => None,
+        }
+    }
+}
+
+impl AllocFrozenValue for Proxy {
+    fn alloc_frozen_value(self, heap: &FrozenHeap) -> FrozenValue {
+        heap.alloc_simple(self)
+    }
+}
+
+#[test]
+fn test_bind_method() {
+    let mut a = Assert::new();
+    a.globals_add(|g| g.set("x", g.alloc(Proxy)));
+    a.eq("'bound get'", "x.get");
+    a.eq("'bound get'", "getattr(x, 'get')");
+    a.eq("'bound get'", "(lambda: x)().get");
+    // Calls don't bind the method.
+    a.eq("1", "x.get()");
+    a.eq("2", "x.other()");
+    a.eq("2", "getattr(x, 'other')()");
+}
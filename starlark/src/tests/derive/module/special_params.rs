@@ -18,8 +18,10 @@
 use crate as starlark;
 use crate::assert::Assert;
 use crate::environment::GlobalsBuilder;
+use crate::eval::Evaluator;
 use crate::values::Heap;
 use crate::values::StringValue;
+use crate::values::Value;
 
 #[starlark_module]
 fn functions(builder: &mut GlobalsBuilder) {
@@ -29,6 +31,16 @@ fn functions(builder: &mut GlobalsBuilder) {
     ) -> anyhow::Result<StringValue<'v>> {
         Ok(starlark_heap.alloc_str_concat(heap, "!"))
     }
+
+    fn heap_and_eval<'v>(
+        heap: &'v Heap,
+        f: Value<'v>,
+        eval: &mut Evaluator<'v, '_>,
+    ) -> anyhow::Result<Value<'v>> {
+        let x = heap.alloc("x");
+        let y = f.invoke_pos(&[x], eval)?;
+        Ok(heap.alloc((x, y)))
+    }
 }
 
 #[test]
@@ -37,3 +49,10 @@ fn test_non_standard_param_names() {
     a.globals_add(functions);
     a.eq("'x!'", "non_standard_heap_name('x')");
 }
+
+#[test]
+fn test_heap_and_eval() {
+    let mut a = Assert::new();
+    a.globals_add(functions);
+    a.eq("('x', 'xx')", "heap_and_eval(lambda x: x + x)");
+}
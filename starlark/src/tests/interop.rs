@@ -20,6 +20,7 @@
 #![allow(clippy::significant_drop_in_scrutinee)] // FIXME?
 
 use std::cell::RefCell;
+use std::cmp::Ordering;
 use std::sync::Arc;
 use std::sync::Mutex;
 
@@ -33,15 +34,23 @@ use crate::assert::Assert;
 use crate::collections::SmallMap;
 use crate::environment::GlobalsBuilder;
 use crate::environment::Module;
+use crate::errors::Diagnostic;
+use crate::eval::compiler::EvalException;
+use crate::eval::Arguments;
+use crate::eval::CallStack;
 use crate::eval::Evaluator;
 use crate::syntax::AstModule;
 use crate::syntax::Dialect;
 use crate::values::any::StarlarkAny;
+use crate::values::demand::Demand;
 use crate::values::none::NoneType;
 use crate::values::Freeze;
+use crate::values::Heap;
 use crate::values::NoSerialize;
 use crate::values::StarlarkValue;
+use crate::values::UnpackValue;
 use crate::values::Value;
+use crate::values::ValueError;
 use crate::values::ValueLike;
 
 #[test]
@@ -258,3 +267,591 @@ fun
         .unwrap();
     assert_eq!(v.unpack_str(), Some("(8, \"hello\", 1)"))
 }
+
+#[test]
+fn test_to_json_value() {
+    let v = assert::pass(
+        r#"
+{
+    "a": [1, 2.5, "x", None, True],
+    "b": (1, {"c": []}),
+    "big": json.decode("123456789123456789123456789"),
+}
+"#,
+    );
+    assert_eq!(
+        serde_json::json!({
+            "a": [1, 2.5, "x", null, true],
+            "b": [1, {"c": []}],
+            "big": "123456789123456789123456789",
+        }),
+        v.value().to_json_value().unwrap()
+    );
+
+    let v = assert::pass("{(1, 2): 3}");
+    assert!(v.value().to_json_value().is_err());
+}
+
+#[test]
+fn test_write_json() {
+    let v = assert::pass(r#"{"a": [1, 2.5, "x\ny", None, True], "b": (1, {"c": []})}"#);
+    let mut out = Vec::new();
+    v.value().write_json(&mut out).unwrap();
+    assert_eq!(
+        v.value().to_json().unwrap(),
+        String::from_utf8(out).unwrap()
+    );
+
+    let v = assert::pass("[1, {(1, 2): 3}]");
+    assert!(v.value().write_json(&mut Vec::new()).is_err());
+}
+
+#[test]
+fn test_alloc_json() {
+    let heap = Heap::new();
+    let json = serde_json::json!({"a": [1, -2, 2.5, "x", null, false], "b": {}});
+    let v = heap.alloc_json(&json);
+    assert_eq!(
+        r#"{"a": [1, -2, 2.5, "x", None, False], "b": {}}"#,
+        v.to_repr()
+    );
+    assert_eq!(json, v.to_json_value().unwrap());
+}
+
+#[test]
+fn test_invoke_method() {
+    // A proxy which handles every method call itself, without `get_attr`.
+    #[derive(Debug, Display, ProvidesStaticType, NoSerialize)]
+    #[display(fmt = "proxy")]
+    struct Proxy;
+    starlark_simple_value!(Proxy);
+
+    impl<'v> StarlarkValue<'v> for Proxy {
+        starlark_type!("proxy");
+
+        fn invoke_method(
+            &self,
+            _me: Value<'v>,
+            name: &str,
+            args: &Arguments<'v, '_>,
+            eval: &mut Evaluator<'v, '_>,
+        ) -> Option<anyhow::Result<Value<'v>>> {
+            if name == "unhandled" {
+                return None;
+            }
+            let heap = eval.heap();
+            Some(
+                args.positional1(heap)
+                    .map(|x| heap.alloc(format!("{}({})", name, x.to_str()))),
+            )
+        }
+    }
+
+    #[starlark_module]
+    fn module(builder: &mut GlobalsBuilder) {
+        fn proxy() -> anyhow::Result<Proxy> {
+            Ok(Proxy)
+        }
+    }
+
+    let mut a = Assert::new();
+    a.globals_add(module);
+    a.eq("'fetch(1)'", "proxy().fetch(1)");
+    a.eq("'store(x)'", "p = proxy()\np.store('x')");
+    a.fail("proxy().fetch()", "Wrong number of positional");
+    a.fail("proxy().unhandled(1)", "has no attribute");
+    a.fail("getattr(proxy(), 'fetch')", "has no attribute");
+}
+
+/// Each frame of the error call stack, as the function called and the line it was called from.
+fn error_call_stack_lines(call_stack: &CallStack) -> Vec<(&str, usize)> {
+    call_stack
+        .frames()
+        .iter()
+        .map(|x| {
+            let location = x.location.as_ref().unwrap().resolve();
+            assert_eq!("assert.bzl", location.file);
+            (x.name.as_str(), location.span.begin_line + 1)
+        })
+        .collect()
+}
+
+#[test]
+fn test_error_call_stack() {
+    let err = assert::fail(
+        r#"
+def f():
+    fail("oops")
+def g():
+    f()
+g()
+"#,
+        "oops",
+    );
+    let err = EvalException(err);
+    assert_eq!(
+        vec![("g", 6), ("f", 5), ("fail", 3)],
+        error_call_stack_lines(err.call_stack())
+    );
+    let err = EvalException(anyhow::anyhow!("oops"));
+    assert!(err.call_stack().is_empty());
+}
+
+#[test]
+fn test_error_call_stack_native_callback() {
+    // Functions called by a native function are located at the native function call.
+    let err = assert::fail(
+        r#"
+def key(x):
+    return int(x)
+sorted(["1", "x"], key = key)
+"#,
+        "not a valid number",
+    );
+    let diag = err.downcast_ref::<Diagnostic>().unwrap();
+    assert_eq!(3, diag.span.as_ref().unwrap().resolve_span().begin_line + 1);
+    assert_eq!(
+        vec![("sorted", 4), ("key", 4), ("int", 3)],
+        error_call_stack_lines(&diag.call_stack)
+    );
+
+    let err = assert::fail("\nsorted(['x'], key = int)", "not a valid number");
+    let diag = err.downcast_ref::<Diagnostic>().unwrap();
+    assert_eq!(2, diag.span.as_ref().unwrap().resolve_span().begin_line + 1);
+    assert_eq!(
+        vec![("sorted", 2), ("int", 2)],
+        error_call_stack_lines(&diag.call_stack)
+    );
+}
+
+#[test]
+fn test_fail_location() {
+    let err = assert::fail(
+        r#"
+x = {"a": [1]}
+def check():
+    if len(x) < 2: fail("bad value:", x, "len", len(x))
+check()
+"#,
+        r#"fail: bad value: {"a": [1]} len 1"#,
+    );
+    let diag = err.downcast_ref::<Diagnostic>().unwrap();
+    let span = diag.span.as_ref().unwrap();
+    assert_eq!(4, span.resolve_span().begin_line + 1);
+    assert_eq!(
+        r#"fail("bad value:", x, "len", len(x))"#,
+        span.source_span()
+    );
+}
+
+#[test]
+fn test_heap_finalizer() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let module = Module::new();
+    for name in ["first", "second"] {
+        let log = log.clone();
+        module
+            .heap()
+            .register_finalizer(move || log.lock().unwrap().push(name));
+    }
+    module.set("x", module.heap().alloc("value"));
+    assert!(log.lock().unwrap().is_empty());
+    // Freezing the module drops its heap.
+    let frozen = module.freeze().unwrap();
+    assert_eq!(vec!["second", "first"], *log.lock().unwrap());
+    assert_eq!(Some("value"), frozen.get("x").unwrap().unpack_str());
+}
+
+#[test]
+fn test_any_all_custom_truthiness() {
+    /// `None` panics when asked for truthiness, to check short-circuiting.
+    #[derive(Debug, Display, ProvidesStaticType, NoSerialize)]
+    #[display(fmt = "{:?}", self)]
+    struct Truthy(Option<bool>);
+
+    starlark_simple_value!(Truthy);
+
+    impl<'v> StarlarkValue<'v> for Truthy {
+        starlark_type!("truthy");
+
+        fn to_bool(&self) -> bool {
+            self.0
+                .expect("to_bool must not be called after a decisive element")
+        }
+    }
+
+    let mut a = Assert::new();
+    a.globals_add(|builder| {
+        builder.set("yes", Truthy(Some(true)));
+        builder.set("no", Truthy(Some(false)));
+        builder.set("bomb", Truthy(None));
+    });
+    a.all_true(
+        r#"
+bool(yes) and not bool(no)
+all([yes, yes])
+not all([yes, no])
+not all([no, bomb])
+any([no, yes])
+not any([no, no])
+any([yes, bomb])
+"#,
+    );
+}
+
+#[test]
+fn test_reflected_operators() {
+    #[derive(Debug, Display, ProvidesStaticType, NoSerialize)]
+    #[display(fmt = "meters({})", _0)]
+    struct Meters(i32);
+
+    starlark_simple_value!(Meters);
+
+    impl<'v> StarlarkValue<'v> for Meters {
+        starlark_type!("meters");
+
+        fn mul(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+            match other.unpack_int() {
+                Some(x) => Ok(heap.alloc(Meters(self.0 * x))),
+                None => ValueError::unsupported_with(self, "*", other),
+            }
+        }
+
+        fn rsub(&self, lhs: Value<'v>, heap: &'v Heap) -> Option<anyhow::Result<Value<'v>>> {
+            let lhs = lhs.unpack_int()?;
+            Some(Ok(heap.alloc(Meters(lhs - self.0))))
+        }
+
+        fn rbit_or(&self, lhs: Value<'v>, _heap: &'v Heap) -> Option<anyhow::Result<Value<'v>>> {
+            lhs.unpack_str().map(|_| Ok(Value::new_bool(true)))
+        }
+
+        fn div(&self, other: Value<'v>, _heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+            match other.downcast_ref::<Meters>() {
+                Some(Meters(0)) => Err(ValueError::DivisionByZero.into()),
+                _ => ValueError::unsupported_with(self, "/", other),
+            }
+        }
+
+        fn rdiv(&self, _lhs: Value<'v>, _heap: &'v Heap) -> Option<anyhow::Result<Value<'v>>> {
+            Some(Ok(Value::new_none()))
+        }
+    }
+
+    let mut a = Assert::new();
+    a.globals_add(|builder| {
+        builder.set("m", Meters(3));
+        builder.set("zero", Meters(0));
+    });
+    a.all_true(
+        r#"
+str(m * 2) == "meters(6)"
+str(2 * m) == "meters(6)"
+str(10 - m) == "meters(7)"
+"x" | m
+1 / m == None
+"#,
+    );
+    // Only falls back when the left operand doesn't support the operation.
+    a.fail("m / zero", "divide by zero");
+    // Falls back to the error of the left operand.
+    a.fail("'x' - m", "not supported");
+    a.fail("1 | m", "not supported");
+    a.fail("m - 1", "not supported");
+}
+
+#[test]
+fn test_incomparable() {
+    /// Sets of flags, ordered by inclusion.
+    #[derive(Debug, Display, ProvidesStaticType, NoSerialize)]
+    #[display(fmt = "flags({})", _0)]
+    struct Flags(u8);
+
+    starlark_simple_value!(Flags);
+
+    impl<'v> StarlarkValue<'v> for Flags {
+        starlark_type!("flags");
+
+        fn equals(&self, other: Value<'v>) -> anyhow::Result<bool> {
+            Ok(other.downcast_ref::<Flags>().map(|x| x.0) == Some(self.0))
+        }
+
+        fn compare(&self, other: Value<'v>) -> anyhow::Result<Ordering> {
+            let other = match other.downcast_ref::<Flags>() {
+                Some(other) => other.0,
+                None => return ValueError::unsupported_with(self, "compare", other),
+            };
+            let union = self.0 | other;
+            if self.0 == other {
+                Ok(Ordering::Equal)
+            } else if union == other {
+                Ok(Ordering::Less)
+            } else if union == self.0 {
+                Ok(Ordering::Greater)
+            } else {
+                Err(anyhow::anyhow!(
+                    "{} and flags({}) are incomparable",
+                    self,
+                    other
+                ))
+            }
+        }
+    }
+
+    let mut a = Assert::new();
+    a.globals_add(|builder| {
+        builder.set("a", Flags(1));
+        builder.set("ab", Flags(3));
+        builder.set("b", Flags(2));
+    });
+    a.all_true(
+        r#"
+a < ab
+ab >= b
+a != b
+not (a == b)
+a in [b, a]
+sorted([ab, a]) == [a, ab]
+"#,
+    );
+    a.fail("a < b", "flags(1) and flags(2) are incomparable");
+    a.fail("a >= b", "incomparable");
+    a.fail("sorted([a, b])", "incomparable");
+    a.fail("max(a, b)", "incomparable");
+}
+
+#[test]
+fn test_is_in_custom_container() {
+    /// Contains the multiples of a number, without storing them.
+    #[derive(Debug, Display, ProvidesStaticType, NoSerialize)]
+    #[display(fmt = "multiples({})", _0)]
+    struct Multiples(i32);
+
+    starlark_simple_value!(Multiples);
+
+    impl<'v> StarlarkValue<'v> for Multiples {
+        starlark_type!("multiples");
+
+        fn is_in(&self, other: Value<'v>) -> anyhow::Result<bool> {
+            Ok(other.unpack_int().map_or(false, |x| x % self.0 == 0))
+        }
+    }
+
+    let mut a = Assert::new();
+    a.globals_add(|builder| builder.set("threes", Multiples(3)));
+    a.all_true(
+        r#"
+9 in threes
+2147483646 in threes
+10 not in threes
+"x" not in threes
+[x for x in range(7) if x in threes] == [0, 3, 6]
+"#,
+    );
+    a.fail("1 in 2", "not supported");
+}
+
+#[test]
+fn test_at2() {
+    /// A grid where `g[i, j]` is `10 * i + j`, with no one-index subscript.
+    /// Storing is only accepted for `g[i, j] += 1`.
+    #[derive(Debug, Display, ProvidesStaticType, NoSerialize)]
+    #[display(fmt = "grid")]
+    struct Grid;
+
+    starlark_simple_value!(Grid);
+
+    impl<'v> StarlarkValue<'v> for Grid {
+        starlark_type!("grid");
+
+        fn at2(
+            &self,
+            index0: Value<'v>,
+            index1: Value<'v>,
+            _heap: &'v Heap,
+        ) -> anyhow::Result<Value<'v>> {
+            let i = i32::unpack_param(index0)?;
+            let j = i32::unpack_param(index1)?;
+            Ok(Value::new_int(10 * i + j))
+        }
+
+        fn set_at(&self, index: Value<'v>, new_value: Value<'v>) -> anyhow::Result<()> {
+            let (i, j) = <(i32, i32)>::unpack_param(index)?;
+            assert_eq!(Some(10 * i + j + 1), new_value.unpack_int());
+            Ok(())
+        }
+    }
+
+    let mut a = Assert::new();
+    a.globals_add(|builder| builder.set("grid", Grid));
+    a.all_true(
+        r#"
+grid[1, 2] == 12
+grid[(3, 4)] == 34
+[grid[i, i] for i in range(3)] == [0, 11, 22]
+"#,
+    );
+    a.fail("t = (1, 2)\ngrid[t]", "not supported");
+    // Augmented assignment reads with `at2` and stores with a tuple key.
+    a.pass("g = grid\ng[1, 2] += 1");
+    a.pass("d = {(1, 2): 3}\nd[1, 2] += 4\nassert_eq(d, {(1, 2): 7})");
+    // The default `at2` is `at` with a tuple.
+    a.is_true("{(1, 2): 3}[1, 2] == 3");
+    a.fail("[1, 2][0, 1]", "not supported");
+}
+
+#[test]
+fn test_request_ref() {
+    trait Shape {
+        fn area(&self) -> i32;
+    }
+
+    unsafe impl<'v> ProvidesStaticType for dyn Shape + 'v {
+        type StaticType = dyn Shape + 'static;
+    }
+
+    #[derive(Debug, Display, ProvidesStaticType, NoSerialize)]
+    #[display(fmt = "square")]
+    struct Square(i32);
+    starlark_simple_value!(Square);
+
+    #[derive(Debug, Display, ProvidesStaticType, NoSerialize)]
+    #[display(fmt = "rect")]
+    struct Rect(i32, i32);
+    starlark_simple_value!(Rect);
+
+    impl Shape for Square {
+        fn area(&self) -> i32 {
+            self.0 * self.0
+        }
+    }
+
+    impl Shape for Rect {
+        fn area(&self) -> i32 {
+            self.0 * self.1
+        }
+    }
+
+    impl<'v> StarlarkValue<'v> for Square {
+        starlark_type!("square");
+
+        fn provide(&'v self, demand: &mut Demand<'_, 'v>) {
+            demand.provide_ref::<dyn Shape>(self);
+        }
+    }
+
+    impl<'v> StarlarkValue<'v> for Rect {
+        starlark_type!("rect");
+
+        fn provide(&'v self, demand: &mut Demand<'_, 'v>) {
+            // Only the requested type is provided.
+            demand
+                .provide_ref::<Rect>(self)
+                .provide_ref::<dyn Shape>(self);
+        }
+    }
+
+    #[starlark_module]
+    fn module(builder: &mut GlobalsBuilder) {
+        fn square(x: i32) -> anyhow::Result<Square> {
+            Ok(Square(x))
+        }
+
+        fn rect(x: i32, y: i32) -> anyhow::Result<Rect> {
+            Ok(Rect(x, y))
+        }
+
+        fn area(x: Value) -> anyhow::Result<i32> {
+            match x.request_ref::<dyn Shape>() {
+                Some(shape) => Ok(shape.area()),
+                None => Err(anyhow::anyhow!("not a shape: {}", x.get_type())),
+            }
+        }
+    }
+
+    let mut a = Assert::new();
+    a.globals_add(module);
+    a.eq("9", "area(square(3))");
+    a.eq("[6, 4]", "[area(s) for s in [rect(2, 3), square(2)]]");
+    a.fail("area([])", "not a shape: list");
+
+    let heap = Heap::new();
+    assert_eq!(
+        Some(3),
+        heap.alloc(Rect(2, 3)).request_ref::<Rect>().map(|r| r.1)
+    );
+    assert!(heap.alloc(Square(1)).request_ref::<Rect>().is_none());
+    assert!(heap.alloc(1).request_ref::<dyn Shape>().is_none());
+}
+
+#[test]
+fn test_request_value() {
+    /// A native handle attached to several value types.
+    #[derive(Debug, PartialEq, ProvidesStaticType)]
+    struct Handle(u32);
+
+    #[derive(ProvidesStaticType)]
+    struct Expensive;
+
+    #[derive(Debug, Display, ProvidesStaticType, NoSerialize)]
+    #[display(fmt = "resource")]
+    struct Resource(u32);
+    starlark_simple_value!(Resource);
+
+    impl<'v> StarlarkValue<'v> for Resource {
+        starlark_type!("resource");
+
+        fn provide(&'v self, demand: &mut Demand<'_, 'v>) {
+            demand
+                .provide_value(Handle(self.0))
+                .provide_value_with::<Expensive>(|| panic!("not requested"));
+        }
+    }
+
+    let heap = Heap::new();
+    let resource = heap.alloc(Resource(7));
+    assert_eq!(Some(Handle(7)), resource.request_value::<Handle>());
+    // Values and references are requested separately.
+    assert!(resource.request_ref::<Handle>().is_none());
+    assert!(resource.request_ref::<Resource>().is_none());
+    assert!(heap.alloc("x").request_value::<Handle>().is_none());
+}
+
+#[test]
+fn test_extra_mut() {
+    #[derive(Default, ProvidesStaticType)]
+    struct Counter(u32);
+
+    #[starlark_module]
+    fn module(builder: &mut GlobalsBuilder) {
+        fn bump<'v>(eval: &mut Evaluator<'v, '_>) -> anyhow::Result<NoneType> {
+            eval.get_extra_mut::<Counter>().unwrap().0 += 1;
+            Ok(NoneType)
+        }
+
+        fn has_extra<'v>(eval: &mut Evaluator<'v, '_>) -> anyhow::Result<bool> {
+            Ok(eval.get_extra::<Counter>().is_some())
+        }
+    }
+
+    let mut counter = Counter::default();
+    let modu = Module::new();
+    let globals = GlobalsBuilder::new().with(module).build();
+    let mut eval = Evaluator::new(&modu);
+    eval.set_extra_mut(&mut counter);
+    let res = eval
+        .eval_module(
+            AstModule::parse(
+                "a",
+                "bump(); bump(); has_extra()".to_owned(),
+                &Dialect::Standard,
+            )
+            .unwrap(),
+            &globals,
+        )
+        .unwrap();
+    // Only `extra_mut` was set.
+    assert_eq!(Some(false), res.unpack_bool());
+    drop(eval);
+    assert_eq!(2, counter.0);
+}
@@ -27,6 +27,7 @@ mod freeze_access_value;
 mod go;
 mod interop;
 mod opt;
+mod repr_rand;
 mod runtime;
 mod type_annot;
 mod uncategorized;
@@ -0,0 +1,125 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Random tests that `repr` of JSON-like values evaluates back to an equal value.
+
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+
+use crate::collections::SmallMap;
+use crate::environment::Globals;
+use crate::environment::Module;
+use crate::eval::Evaluator;
+use crate::syntax::AstModule;
+use crate::syntax::Dialect;
+use crate::values::dict::Dict;
+use crate::values::Heap;
+use crate::values::Value;
+
+const RANDOM_ITERATIONS: usize = 2000;
+
+fn random_string(rng: &mut SmallRng) -> String {
+    let len = rng.gen_range(0..8);
+    (0..len)
+        .map(|_| match rng.gen_range(0..5) {
+            0 => rng.gen_range(' '..='~'),
+            1 => rng.gen_range('\0'..' '),
+            2 => ['"', '\'', '\\', '\x7f'][rng.gen_range(0..4)],
+            3 => rng.gen_range('\u{80}'..='\u{ff}'),
+            _ => rng.gen(),
+        })
+        .collect()
+}
+
+fn random_float(rng: &mut SmallRng) -> f64 {
+    match rng.gen_range(0..3) {
+        0 => rng.gen_range(-1000..1000) as f64 / 8.0,
+        1 => rng.gen::<f64>() * 10f64.powi(rng.gen_range(-20..20)),
+        _ => loop {
+            let f = f64::from_bits(rng.gen());
+            if f.is_finite() {
+                break f;
+            }
+        },
+    }
+}
+
+/// Generate a random value: scalars with `max_depth == 0`, possibly nested containers otherwise.
+fn random_value<'v>(rng: &mut SmallRng, heap: &'v Heap, max_depth: usize) -> Value<'v> {
+    let choices = if max_depth == 0 { 5 } else { 8 };
+    match rng.gen_range(0..choices) {
+        0 => Value::new_none(),
+        1 => Value::new_bool(rng.gen()),
+        2 => match rng.gen_range(0..3) {
+            0 => heap.alloc(rng.gen::<i32>()),
+            1 => heap.alloc(rng.gen::<i64>()),
+            _ => heap.alloc(rng.gen::<i128>()),
+        },
+        3 => heap.alloc(random_float(rng)),
+        4 => heap.alloc(random_string(rng)),
+        5 | 6 => {
+            // Include singleton tuples often, they need the trailing comma.
+            let len = rng.gen_range(0..4);
+            let xs: Vec<Value> = (0..len)
+                .map(|_| random_value(rng, heap, max_depth - 1))
+                .collect();
+            if rng.gen() {
+                heap.alloc_list(&xs)
+            } else {
+                heap.alloc_tuple(&xs)
+            }
+        }
+        _ => {
+            let len = rng.gen_range(0..4);
+            let mut content = SmallMap::new();
+            for _ in 0..len {
+                let k = random_value(rng, heap, 0);
+                let v = random_value(rng, heap, max_depth - 1);
+                content.insert_hashed(k.get_hashed().unwrap(), v);
+            }
+            heap.alloc(Dict::new(content))
+        }
+    }
+}
+
+#[test]
+fn test_repr_round_trip_random() {
+    let mut rng = SmallRng::seed_from_u64(17);
+    let heap = Heap::new();
+    for _ in 0..RANDOM_ITERATIONS {
+        let value = random_value(&mut rng, &heap, 3);
+        let repr = value.to_repr();
+
+        let module = Module::new();
+        let mut eval = Evaluator::new(&module);
+        let ast = AstModule::parse("repr.star", repr.clone(), &Dialect::Extended)
+            .unwrap_or_else(|e| panic!("repr `{}` does not parse: {}", repr, e));
+        let res = eval
+            .eval_module(ast, &Globals::standard())
+            .unwrap_or_else(|e| panic!("repr `{}` does not evaluate: {}", repr, e));
+
+        assert!(
+            res.equals(value).unwrap(),
+            "repr `{}` evaluates to `{}`",
+            repr,
+            res
+        );
+        // Also catches things `==` ignores, like the sign of zero.
+        assert_eq!(repr, res.to_repr());
+    }
+}
@@ -19,20 +19,28 @@
 
 use std::fmt::Write;
 use std::mem;
+use std::sync::atomic::AtomicBool;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
+use std::sync::Arc;
 
 use derive_more::Display;
+use gazebo::dupe::Dupe;
 use once_cell::sync::Lazy;
 
 use crate as starlark;
 use crate::assert;
 use crate::assert::Assert;
+use crate::environment::Globals;
 use crate::environment::GlobalsBuilder;
+use crate::environment::Module;
 use crate::eval::Evaluator;
+use crate::syntax::AstModule;
+use crate::syntax::Dialect;
 use crate::values::any::StarlarkAny;
 use crate::values::FrozenHeap;
 use crate::values::Heap;
+use crate::values::Value;
 
 #[test]
 fn test_garbage_collect() {
@@ -46,6 +54,22 @@ assert_eq(y, str(x))
     );
 }
 
+#[test]
+fn test_heap_allocated_bytes() {
+    let heap = Heap::new();
+    let empty = heap.allocated_bytes();
+    heap.alloc_str(&"x".repeat(10_000));
+    heap.alloc_list(&[Value::new_none()]);
+    let allocated = heap.allocated_bytes();
+    assert!(allocated >= empty + 10_000, "{} {}", empty, allocated);
+    assert_eq!(allocated, heap.peak_allocated_bytes());
+
+    // Nothing is retained, so the collection frees everything, but the peak remains.
+    unsafe { heap.garbage_collect(|_| {}) };
+    assert!(heap.allocated_bytes() < allocated);
+    assert_eq!(allocated, heap.peak_allocated_bytes());
+}
+
 #[test]
 fn test_deallocation() {
     // Check that we really do deallocate values we create
@@ -188,6 +212,85 @@ def expensive(n):
     assert!(!a.pass(&code).unpack_bool().unwrap());
 }
 
+#[test]
+fn test_instruction_budget() {
+    let mut a = Assert::new();
+    a.setup_eval(|eval| eval.set_instruction_budget(1000));
+    a.pass("x = 0\nfor i in range(10):\n    x += i\nassert_eq(x, 45)");
+    a.fail(
+        "def f():\n    for i in range(1000000):\n        pass\nf()",
+        "Instruction budget exhausted",
+    );
+}
+
+#[test]
+fn test_instruction_budget_frozen_def() {
+    // Functions from a module frozen without a budget still consume the budget.
+    let mut a = Assert::new();
+    a.module("m", "def f():\n    for i in range(1000000):\n        pass");
+    a.setup_eval(|eval| eval.set_instruction_budget(1000));
+    a.fail("load('m', 'f')\nf()", "Instruction budget exhausted");
+}
+
+#[test]
+fn test_interrupt_flag() {
+    let flag = Arc::new(AtomicBool::new(false));
+    let mut a = Assert::new();
+    let setup_flag = flag.dupe();
+    a.setup_eval(move |eval| eval.set_interrupt_flag(setup_flag.dupe()));
+    a.pass("x = 0\nfor i in range(10):\n    x += i\nassert_eq(x, 45)");
+    flag.store(true, Ordering::Relaxed);
+    a.fail("def f():\n    pass\nf()", "Evaluation interrupted");
+}
+
+#[test]
+fn test_interrupt_flag_from_other_thread() {
+    let flag = Arc::new(AtomicBool::new(false));
+    let module = Module::new();
+    let globals = Globals::standard();
+    let mut eval = Evaluator::new(&module);
+    eval.set_interrupt_flag(flag.dupe());
+    let ast = AstModule::parse(
+        "a.star",
+        "def f():\n    for i in range(1000000000):\n        pass\nf()".to_owned(),
+        &Dialect::Standard,
+    )
+    .unwrap();
+    let setter = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        flag.store(true, Ordering::Relaxed);
+    });
+    let err = eval.eval_module(ast, &globals).unwrap_err();
+    assert!(
+        err.to_string().contains("Evaluation interrupted"),
+        "{}",
+        err
+    );
+    setter.join().unwrap();
+}
+
+#[test]
+fn test_instruction_budget_deterministic() {
+    fn remaining() -> u64 {
+        let module = Module::new();
+        let globals = Globals::standard();
+        let mut eval = Evaluator::new(&module);
+        eval.set_instruction_budget(10000);
+        let ast = AstModule::parse(
+            "a.star",
+            "def f(n):\n    return [i * 2 for i in range(n)]\nf(10)".to_owned(),
+            &Dialect::Standard,
+        )
+        .unwrap();
+        eval.eval_module(ast, &globals).unwrap();
+        eval.instruction_budget().unwrap()
+    }
+
+    let r = remaining();
+    assert!(r < 10000);
+    assert_eq!(r, remaining());
+}
+
 #[test]
 fn test_callstack() {
     // Make sure that even for native functions that fail, the
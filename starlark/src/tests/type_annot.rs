@@ -50,3 +50,22 @@ fn test_types_enable() {
     a.dialect(&dialect);
     a.fail(PROGRAM, "does not match the type annotation");
 }
+
+#[test]
+fn test_types_runtime_type_checking() {
+    let mut dialect = Dialect::Standard;
+    dialect.enable_types = DialectTypes::ParseOnly;
+    let mut a = Assert::new();
+    a.dialect(&dialect);
+    a.setup_eval(|eval| eval.enable_runtime_type_checking(true));
+    a.fail(PROGRAM, "does not match the type annotation");
+    a.fail(
+        "def f() -> str.type: return 1\nf()",
+        "does not match the type annotation",
+    );
+    a.pass("def f(x: int.type) -> str.type: return str(x)\nf(1)");
+
+    a.setup_eval(|eval| eval.enable_runtime_type_checking(false));
+    a.pass(PROGRAM);
+    a.pass("def f() -> str.type: return 1\nf()");
+}
@@ -20,16 +20,19 @@ use std::fmt::Write;
 
 use derive_more::Display;
 use gazebo::any::ProvidesStaticType;
+use gazebo::dupe::Dupe;
 
 use crate as starlark;
 use crate::assert;
 use crate::assert::Assert;
 use crate::collections::SmallMap;
+use crate::environment::FrozenModule;
 use crate::environment::Globals;
 use crate::environment::GlobalsBuilder;
 use crate::environment::Module;
 use crate::errors::Diagnostic;
 use crate::eval::Evaluator;
+use crate::eval::FileLoader;
 use crate::syntax::AstModule;
 use crate::syntax::Dialect;
 use crate::values::Freeze;
@@ -351,6 +354,74 @@ assert_eq(repr(s2), "$[2, 1, 3]")
     );
 }
 
+#[test]
+fn test_bitwise_custom() {
+    // A fixed-width integer participates in bitwise operators by overriding them.
+    #[derive(Debug, Display, Clone, Copy, ProvidesStaticType, NoSerialize)]
+    #[display(fmt = "byte({})", _0)]
+    struct Byte(u8);
+    starlark_simple_value!(Byte);
+
+    fn unpack_byte(x: Value) -> anyhow::Result<u8> {
+        match Byte::from_value(x) {
+            Some(x) => Ok(x.0),
+            None => Ok(u8::try_from(i32::unpack_param(x)?)?),
+        }
+    }
+
+    impl<'v> StarlarkValue<'v> for Byte {
+        starlark_type!("byte");
+        fn collect_repr(&self, collector: &mut String) {
+            write!(collector, "{}", self).unwrap()
+        }
+        fn bit_and(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+            Ok(heap.alloc(Byte(self.0 & unpack_byte(other)?)))
+        }
+        fn bit_or(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+            Ok(heap.alloc(Byte(self.0 | unpack_byte(other)?)))
+        }
+        fn bit_xor(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+            Ok(heap.alloc(Byte(self.0 ^ unpack_byte(other)?)))
+        }
+        fn bit_not(&self, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+            Ok(heap.alloc(Byte(!self.0)))
+        }
+        fn left_shift(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+            Ok(heap.alloc(Byte(self.0.wrapping_shl(unpack_byte(other)? as u32))))
+        }
+        fn right_shift(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+            Ok(heap.alloc(Byte(self.0.wrapping_shr(unpack_byte(other)? as u32))))
+        }
+    }
+
+    #[starlark_module]
+    fn module(build: &mut GlobalsBuilder) {
+        fn byte(x: i32) -> anyhow::Result<Byte> {
+            Ok(Byte(u8::try_from(x)?))
+        }
+    }
+
+    let mut a = Assert::new();
+    a.globals_add(module);
+    a.pass(
+        r#"
+b = byte(0xf0)
+assert_eq(repr(b & 0x3c), "byte(48)")
+assert_eq(repr(b | byte(0x0f)), "byte(255)")
+assert_eq(repr(b ^ 0xff), "byte(15)")
+assert_eq(repr(~b), "byte(15)")
+assert_eq(repr(b << 1), "byte(224)")
+assert_eq(repr(b >> 4), "byte(15)")
+x = byte(1)
+x |= 2
+x <<= 2
+assert_eq(repr(x), "byte(12)")
+"#,
+    );
+    a.fail("byte(1) & 256", "out of range");
+    a.fail("1 & byte(1)", "not supported");
+}
+
 #[test]
 fn test_compound_assignment() {
     assert::pass(
@@ -367,6 +438,90 @@ assert_eq(x, 9)"#,
     );
 }
 
+#[test]
+fn test_starred_assignment() {
+    assert::pass(
+        r#"
+first, *rest, last = [1, 2, 3, 4]
+assert_eq((first, rest, last), (1, [2, 3], 4))
+*init, last = "abc".elems()
+assert_eq((init, last), (["a", "b"], "c"))
+first, *rest = (1,)
+assert_eq((first, rest), (1, []))
+*all, = range(3)
+assert_eq(all, [0, 1, 2])
+a, *b, c = {"x": 1, "y": 2}
+assert_eq((a, b, c), ("x", [], "y"))
+x = {}
+x["k"], *x["rest"] = [1, 2, 3]
+assert_eq(x, {"k": 1, "rest": [2, 3]})
+def f(xs):
+    head, *tail = xs
+    return head, tail
+assert_eq(f([1, 2]), (1, [2]))
+"#,
+    );
+    assert::fail("a, *b, c = [1]", "Unpacked 1 values but expected at least 2");
+    assert::fail("a, *b = 1", "not supported");
+}
+
+#[test]
+fn test_del() {
+    assert::pass(
+        r#"
+xs = [0, 1, 2, 3, 4, 5]
+del xs[1]
+assert_eq(xs, [0, 2, 3, 4, 5])
+del xs[-1], xs[0]
+assert_eq(xs, [2, 3, 4])
+ys = list(range(10))
+del ys[2:5]
+assert_eq(ys, [0, 1, 5, 6, 7, 8, 9])
+del ys[::2]
+assert_eq(ys, [1, 6, 8])
+del ys[:]
+assert_eq(ys, [])
+d = {"a": 1, "b": 2}
+del d["a"]
+assert_eq(d, {"b": 2})
+def f(x):
+    y = x
+    del x
+    x = y + 1
+    return x
+assert_eq(f(1), 2)
+def g():
+    x = 1
+    h = lambda: x
+    del x
+    return h
+assert_eq(type(g()), "function")
+"#,
+    );
+    assert::fail("xs = [1]\ndel xs[1]", "out of bound");
+    assert::fail("d = {}\ndel d['x']", "Key `\"x\"` was not found");
+    assert::fail("t = (1, 2)\ndel t[0]", "not supported");
+    assert::fails(
+        "def f():\n    x = 1\n    del x\n    return x\nf()",
+        &["referenced before assignment", "`x`"],
+    );
+    assert::fails(
+        "def f():\n    del x\nf()",
+        &["referenced before assignment", "`x`"],
+    );
+    assert::fail(
+        "def f():\n    x = 1\n    h = lambda: x\n    del x\n    return h()\nf()",
+        "referenced before assignment",
+    );
+    assert::fail("x = 1\ndel x", "cannot be used outside of a `def`");
+    assert::fail("x = [1]\ndef f():\n    del x.y\n", "target of `del`");
+    let mut a = Assert::new();
+    a.module("m", "frozen_list = [1, 2]\nfrozen_dict = {1: 2}");
+    a.fail("load('m', 'frozen_list')\ndel frozen_list[0]", "Immutable");
+    a.fail("load('m', 'frozen_list')\ndel frozen_list[:1]", "Immutable");
+    a.fail("load('m', 'frozen_dict')\ndel frozen_dict[1]", "Immutable");
+}
+
 #[test]
 fn test_static_name_checks() {
     let a = Assert::new();
@@ -497,6 +652,87 @@ fn test_load_reexport() {
     );
 }
 
+#[test]
+fn test_load_validate() -> anyhow::Result<()> {
+    struct AllowlistLoader {
+        module: FrozenModule,
+        seen: RefCell<Vec<(String, String)>>,
+    }
+
+    impl FileLoader for AllowlistLoader {
+        fn load(&self, _path: &str) -> anyhow::Result<FrozenModule> {
+            Ok(self.module.dupe())
+        }
+
+        fn validate_load(&self, path: &str, symbols: &[(&str, &str)]) -> anyhow::Result<()> {
+            for (their, our) in symbols {
+                self.seen
+                    .borrow_mut()
+                    .push(((*their).to_owned(), (*our).to_owned()));
+                if *their != "allowed" {
+                    return Err(anyhow::anyhow!("`{}` may not load `{}`", path, their));
+                }
+            }
+            Ok(())
+        }
+    }
+
+    let globals = Globals::standard();
+    let module = Module::new();
+    module.set("allowed", Value::new_int(1));
+    module.set("forbidden", Value::new_int(2));
+    let loader = AllowlistLoader {
+        module: module.freeze()?,
+        seen: RefCell::new(Vec::new()),
+    };
+
+    let run = |program: &str| {
+        let module = Module::new();
+        let mut eval = Evaluator::new(&module);
+        eval.set_loader(&loader);
+        let ast = AstModule::parse("a.star", program.to_owned(), &Dialect::Standard).unwrap();
+        eval.eval_module(ast, &globals).map(|_| ())
+    };
+
+    run("load('lib', x = 'allowed')")?;
+    assert_eq!(
+        vec![("allowed".to_owned(), "x".to_owned())],
+        *loader.seen.borrow()
+    );
+    let err = run("load('lib', 'forbidden')").unwrap_err();
+    assert!(err.to_string().contains("`lib` may not load `forbidden`"));
+    Ok(())
+}
+
+#[test]
+fn test_eval_statements_incrementally() -> anyhow::Result<()> {
+    let globals = Globals::standard();
+    let module = Module::new();
+    {
+        let mut eval = Evaluator::new(&module);
+        let mut repl = |line: &str| -> anyhow::Result<String> {
+            let ast = AstModule::parse("repl", line.to_owned(), &Dialect::Extended)?;
+            Ok(eval.eval_statements_incrementally(ast, &globals)?.to_repr())
+        };
+
+        assert_eq!("None", repl("\"not a docstring\"\nx = 1")?);
+        assert_eq!("2", repl("x + 1")?);
+        repl("def f():\n    return x * 10")?;
+        assert_eq!("10", repl("f()")?);
+        // Redefinitions are visible to functions defined earlier.
+        repl("x = 5")?;
+        assert_eq!("50", repl("f()")?);
+        assert_eq!("[5]", repl("y = [x]\ny")?);
+        // Errors don't lose the existing bindings.
+        assert!(repl("undefined").is_err());
+        assert_eq!("5", repl("x")?);
+    }
+
+    assert_eq!(Some(5), module.get("x").and_then(|x| x.unpack_int()));
+    assert!(module.freeze()?.documentation().is_none());
+    Ok(())
+}
+
 #[test]
 fn test_module_visibility_preserved_by_evaluator() -> anyhow::Result<()> {
     // Make sure that when we use a module in the evaluator, the entering / exiting the
@@ -0,0 +1,185 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Copy values between heaps.
+
+use crate::collections::Hashed;
+use crate::collections::SmallMap;
+use crate::collections::SmallSet;
+use crate::values::bigint::StarlarkBigInt;
+use crate::values::dict::Dict;
+use crate::values::float::StarlarkFloat;
+use crate::values::layout::pointer::RawPointer;
+use crate::values::list::List;
+use crate::values::tuple::Tuple;
+use crate::values::Heap;
+use crate::values::Value;
+use crate::values::ValueLike;
+
+#[derive(Debug, thiserror::Error)]
+enum DeepCloneError {
+    #[error("Cannot deep clone value of type `{0}`")]
+    UnsupportedType(String),
+    #[error("Cycle detected when deep cloning value of type `{0}`")]
+    Cycle(String),
+}
+
+struct DeepCloner<'d> {
+    heap: &'d Heap,
+    /// Containers currently being cloned, used to detect cycles.
+    stack: SmallSet<RawPointer>,
+}
+
+impl<'d> DeepCloner<'d> {
+    fn clone_container<'v>(
+        &mut self,
+        value: Value<'v>,
+        f: impl FnOnce(&mut Self) -> anyhow::Result<Value<'d>>,
+    ) -> anyhow::Result<Value<'d>> {
+        if !self.stack.insert(value.ptr_value()) {
+            return Err(DeepCloneError::Cycle(value.get_type().to_owned()).into());
+        }
+        let res = f(self);
+        self.stack.pop();
+        res
+    }
+
+    fn clone_all<'v>(
+        &mut self,
+        values: impl Iterator<Item = Value<'v>>,
+    ) -> anyhow::Result<Vec<Value<'d>>> {
+        values.map(|x| self.clone_value(x)).collect()
+    }
+
+    fn clone_value<'v>(&mut self, value: Value<'v>) -> anyhow::Result<Value<'d>> {
+        // Frozen values are immutable, so they are shared rather than copied.
+        if let Some(x) = value.unpack_int() {
+            Ok(Value::new_int(x))
+        } else if let Some(x) = value.unpack_frozen() {
+            Ok(x.to_value())
+        } else if let Some(x) = value.unpack_str() {
+            Ok(self.heap.alloc_str(x).to_value())
+        } else if let Some(x) = value.downcast_ref::<StarlarkFloat>() {
+            Ok(self.heap.alloc(x.0))
+        } else if let Some(x) = value.downcast_ref::<StarlarkBigInt>() {
+            Ok(StarlarkBigInt::alloc_bigint(x.get().clone(), self.heap))
+        } else if let Some(x) = List::from_value(value) {
+            self.clone_container(value, |this| {
+                let content = this.clone_all(x.iter())?;
+                Ok(this.heap.alloc_list(&content))
+            })
+        } else if let Some(x) = Tuple::from_value(value) {
+            self.clone_container(value, |this| {
+                let content = this.clone_all(x.iter())?;
+                Ok(this.heap.alloc_tuple(&content))
+            })
+        } else if let Some(x) = Dict::from_value(value) {
+            self.clone_container(value, |this| {
+                let mut content = SmallMap::with_capacity(x.len());
+                for (k, v) in x.iter_hashed() {
+                    let k = Hashed::new_unchecked(k.hash(), this.clone_value(*k.key())?);
+                    let v = this.clone_value(v)?;
+                    content.insert_hashed(k, v);
+                }
+                Ok(this.heap.alloc(Dict::new(content)))
+            })
+        } else {
+            Err(DeepCloneError::UnsupportedType(value.get_type().to_owned()).into())
+        }
+    }
+}
+
+pub(crate) fn deep_clone_into<'v, 'd>(
+    value: Value<'v>,
+    heap: &'d Heap,
+) -> anyhow::Result<Value<'d>> {
+    DeepCloner {
+        heap,
+        stack: SmallSet::new(),
+    }
+    .clone_value(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::values::dict::Dict;
+    use crate::values::list::List;
+    use crate::values::FrozenHeap;
+    use crate::values::Heap;
+    use crate::values::Value;
+
+    #[test]
+    fn test_deep_clone_into() {
+        let src = Heap::new();
+        let dst = Heap::new();
+        let v = src.alloc((vec![1, 2], "test", 1.5));
+        let v = src.alloc((v, u64::MAX, src.alloc_list(&[src.alloc("x")])));
+        let cloned = v.deep_clone_into(&dst).unwrap();
+        drop(src);
+        assert_eq!(
+            "(([1, 2], \"test\", 1.5), 18446744073709551615, [\"x\"])",
+            cloned.to_repr()
+        );
+    }
+
+    #[test]
+    fn test_deep_clone_into_dict() {
+        let src = Heap::new();
+        let dst = Heap::new();
+        let mut d = Dict::default();
+        d.insert_hashed(
+            src.alloc("a").get_hashed().unwrap(),
+            src.alloc_list(&[Value::new_int(1)]),
+        );
+        d.insert_hashed(Value::new_int(2).get_hashed().unwrap(), src.alloc(("b",)));
+        let cloned = src.alloc(d).deep_clone_into(&dst).unwrap();
+        drop(src);
+        assert_eq!("{\"a\": [1], 2: (\"b\",)}", cloned.to_repr());
+        assert_eq!(
+            Some(Value::new_int(2).get_hashed().unwrap().hash()),
+            Dict::from_value(cloned)
+                .unwrap()
+                .iter_hashed()
+                .nth(1)
+                .map(|(k, _)| k.hash())
+        );
+    }
+
+    #[test]
+    fn test_deep_clone_into_frozen() {
+        let frozen_heap = FrozenHeap::new();
+        let frozen = frozen_heap.alloc(vec!["x", "y"]).to_value();
+        let src = Heap::new();
+        let v = src.alloc_list(&[frozen, Value::new_none(), Value::new_bool(true)]);
+        let dst = Heap::new();
+        let cloned = v.deep_clone_into(&dst).unwrap();
+        drop(src);
+        assert_eq!("[[\"x\", \"y\"], None, True]", cloned.to_repr());
+        let cloned = List::from_value(cloned).unwrap();
+        assert!(cloned.content()[0].ptr_eq(frozen));
+    }
+
+    #[test]
+    fn test_deep_clone_into_cycle() {
+        let heap = Heap::new();
+        let list = heap.alloc_list(&[]);
+        let outer = heap.alloc_list(&[list]);
+        List::from_value_mut(list).unwrap().push(outer, &heap);
+        let err = outer.deep_clone_into(&Heap::new()).unwrap_err();
+        assert!(err.to_string().contains("Cycle detected"), "{}", err);
+    }
+}
@@ -0,0 +1,155 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Requesting data of other types from a value, without knowing its concrete type.
+//!
+//! A [`StarlarkValue`](crate::values::StarlarkValue) answers requests in
+//! [`provide`](crate::values::StarlarkValue::provide), and callers make them with
+//! [`Value::request_value`](crate::values::Value::request_value) for owned data
+//! (e.g. a native handle) or [`Value::request_ref`](crate::values::Value::request_ref)
+//! for references. The latter lets several value types share behavior defined by
+//! an embedder's Rust trait:
+//!
+//! ```
+//! # use std::fmt;
+//! # use std::fmt::Display;
+//! use gazebo::any::ProvidesStaticType;
+//! use starlark::starlark_simple_value;
+//! use starlark::starlark_type;
+//! use starlark::values::demand::Demand;
+//! use starlark::values::Heap;
+//! use starlark::values::NoSerialize;
+//! use starlark::values::StarlarkValue;
+//!
+//! trait Area {
+//!     fn area(&self) -> f64;
+//! }
+//!
+//! // Requests are keyed by the type with lifetimes erased.
+//! unsafe impl<'v> ProvidesStaticType for dyn Area + 'v {
+//!     type StaticType = dyn Area + 'static;
+//! }
+//!
+//! #[derive(Debug, ProvidesStaticType, NoSerialize)]
+//! struct Square(f64);
+//! # impl Display for Square {
+//! #     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+//! #         write!(f, "square")
+//! #     }
+//! # }
+//! starlark_simple_value!(Square);
+//!
+//! impl Area for Square {
+//!     fn area(&self) -> f64 {
+//!         self.0 * self.0
+//!     }
+//! }
+//!
+//! impl<'v> StarlarkValue<'v> for Square {
+//!     starlark_type!("square");
+//!
+//!     fn provide(&'v self, demand: &mut Demand<'_, 'v>) {
+//!         demand.provide_ref::<dyn Area>(self);
+//!     }
+//! }
+//!
+//! let heap = Heap::new();
+//! let square = heap.alloc(Square(3.0));
+//! assert_eq!(Some(9.0), square.request_ref::<dyn Area>().map(|x| x.area()));
+//! assert!(heap.alloc(1.5).request_ref::<dyn Area>().is_none());
+//! ```
+
+use std::any::TypeId;
+use std::marker::PhantomData;
+
+use gazebo::any::ProvidesStaticType;
+
+/// A request for a value or a reference of a single type, answered by
+/// [`StarlarkValue::provide`](crate::values::StarlarkValue::provide).
+///
+/// Types are matched by their [`ProvidesStaticType::StaticType`], so as with
+/// [`AnyLifetime`](gazebo::any::AnyLifetime), any lifetimes in them are taken to be `'v`.
+pub struct Demand<'a, 'v> {
+    /// `TypeId` of the requested type with lifetimes erased,
+    /// `T` for values and `&T` for references.
+    type_id: TypeId,
+    /// Points to the `Option<T>` or `Option<&'v T>` result.
+    result: *mut (),
+    _marker: PhantomData<(&'a mut (), &'v ())>,
+}
+
+impl<'a, 'v> Demand<'a, 'v> {
+    /// Answer the request with `value` if a `T` was requested with
+    /// [`Value::request_value`](crate::values::Value::request_value),
+    /// otherwise do nothing. Calls can be chained to provide several types.
+    pub fn provide_value<T: ProvidesStaticType + 'v>(&mut self, value: T) -> &mut Self {
+        self.provide_value_with(|| value)
+    }
+
+    /// Like [`provide_value`](Demand::provide_value), but only computes the value
+    /// if it was requested.
+    pub fn provide_value_with<T: ProvidesStaticType + 'v>(
+        &mut self,
+        f: impl FnOnce() -> T,
+    ) -> &mut Self {
+        // SAFETY: the type id is that of `request_value::<T>`.
+        unsafe { self.provide_impl(TypeId::of::<T::StaticType>(), f) }
+    }
+
+    /// Answer the request with `value` if a `&T` was requested with
+    /// [`Value::request_ref`](crate::values::Value::request_ref),
+    /// otherwise do nothing. Calls can be chained to provide several types.
+    pub fn provide_ref<T: ProvidesStaticType + ?Sized + 'v>(&mut self, value: &'v T) -> &mut Self {
+        // SAFETY: the type id is that of `request_ref::<T>`.
+        unsafe { self.provide_impl(TypeId::of::<&'static T::StaticType>(), || value) }
+    }
+
+    /// Caller must guarantee a request with `type_id` has a result of type `Option<R>`.
+    unsafe fn provide_impl<R>(&mut self, type_id: TypeId, f: impl FnOnce() -> R) -> &mut Self {
+        if self.type_id == type_id {
+            // The result was created for a type with the same `StaticType`,
+            // so it is `Option<R>` up to lifetimes, which are all `'v`.
+            *(self.result as *mut Option<R>) = Some(f());
+        }
+        self
+    }
+}
+
+/// Make a request for a `T` and let `provide` answer it.
+pub(crate) fn request_value<'v, T: ProvidesStaticType + 'v>(
+    provide: impl FnOnce(&mut Demand<'_, 'v>),
+) -> Option<T> {
+    request_impl(TypeId::of::<T::StaticType>(), provide)
+}
+
+/// Make a request for a `&'v T` and let `provide` answer it.
+pub(crate) fn request_ref<'v, T: ProvidesStaticType + ?Sized + 'v>(
+    provide: impl FnOnce(&mut Demand<'_, 'v>),
+) -> Option<&'v T> {
+    request_impl(TypeId::of::<&'static T::StaticType>(), provide)
+}
+
+fn request_impl<'v, R>(type_id: TypeId, provide: impl FnOnce(&mut Demand<'_, 'v>)) -> Option<R> {
+    let mut result: Option<R> = None;
+    let mut demand = Demand {
+        type_id,
+        result: &mut result as *mut Option<R> as *mut (),
+        _marker: PhantomData,
+    };
+    provide(&mut demand);
+    result
+}
@@ -487,14 +487,31 @@ impl Function {
                 ds
             })
             .unwrap_or_default();
-        let ret = self
-            .ret
+        format!(
+            "def {}{}{}:\n{}    pass",
+            name,
+            params,
+            self.render_ret(),
+            docstring
+        )
+    }
+
+    fn render_ret(&self) -> String {
+        self.ret
             .typ
             .as_ref()
             .map(|t| format!(" -> {}", t.raw_type))
-            .unwrap_or_default();
+            .unwrap_or_default()
+    }
 
-        format!("def {}{}{}:\n{}    pass", name, params, ret, docstring)
+    /// The signature of the function on a single line, e.g. `name(x, y = 1) -> int`.
+    pub(crate) fn render_signature(&self, name: &str) -> String {
+        format!(
+            "{}({}){}",
+            name,
+            self.params.iter().map(Param::render_as_code).join(", "),
+            self.render_ret()
+        )
     }
 
     /// Parses function documentation out of a docstring
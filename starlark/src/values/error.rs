@@ -114,4 +114,16 @@ impl ValueError {
     ) -> anyhow::Result<T> {
         Self::unsupported_owned(left.get_type(), op, Some(right.get_type()))
     }
+
+    /// Is the error one created by [`unsupported`](ValueError::unsupported)
+    /// or [`unsupported_with`](ValueError::unsupported_with).
+    pub(crate) fn is_unsupported(e: &anyhow::Error) -> bool {
+        matches!(
+            e.downcast_ref::<ValueError>(),
+            Some(
+                ValueError::OperationNotSupported { .. }
+                    | ValueError::OperationNotSupportedBinary { .. }
+            )
+        )
+    }
 }
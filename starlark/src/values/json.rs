@@ -0,0 +1,93 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Options for converting values to JSON.
+
+use serde::ser::SerializeMap;
+use serde::ser::SerializeSeq;
+use serde::Serialize;
+use serde::Serializer;
+use serde_json::ser::PrettyFormatter;
+
+use crate::values::Value;
+
+/// Options for [`Value::to_json_with`](crate::values::Value::to_json_with).
+///
+/// The default options produce the same output as
+/// [`Value::to_json`](crate::values::Value::to_json).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JsonOptions {
+    /// Write object keys in sorted order, rather than in dict order.
+    /// Keys are compared as Starlark strings.
+    pub sort_keys: bool,
+    /// Write each array element and object entry on its own line,
+    /// indented by this many spaces per level. Without it, the output has no whitespace.
+    pub indent: Option<usize>,
+}
+
+/// A JSON value which serializes with sorted object keys.
+struct SortedKeys<'a>(&'a serde_json::Value);
+
+impl<'a> Serialize for SortedKeys<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            serde_json::Value::Array(xs) => {
+                let mut seq = serializer.serialize_seq(Some(xs.len()))?;
+                for x in xs {
+                    seq.serialize_element(&SortedKeys(x))?;
+                }
+                seq.end()
+            }
+            serde_json::Value::Object(xs) => {
+                let mut entries: Vec<_> = xs.iter().collect();
+                // Keys are unique, so the order is fully determined.
+                entries.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (k, v) in entries {
+                    map.serialize_entry(k, &SortedKeys(v))?;
+                }
+                map.end()
+            }
+            x => x.serialize(serializer),
+        }
+    }
+}
+
+fn write_json<T: Serialize>(x: &T, indent: Option<usize>) -> anyhow::Result<String> {
+    match indent {
+        None => Ok(serde_json::to_string(x)?),
+        Some(indent) => {
+            let indent = " ".repeat(indent);
+            let mut res = Vec::new();
+            let formatter = PrettyFormatter::with_indent(indent.as_bytes());
+            x.serialize(&mut serde_json::Serializer::with_formatter(
+                &mut res, formatter,
+            ))?;
+            // The serializer only writes valid UTF-8.
+            Ok(String::from_utf8(res)?)
+        }
+    }
+}
+
+pub(crate) fn to_json_with(x: Value, options: &JsonOptions) -> anyhow::Result<String> {
+    if options.sort_keys {
+        // Go through `serde_json::Value`, which has the same conversion as `to_json`.
+        write_json(&SortedKeys(&x.to_json_value()?), options.indent)
+    } else {
+        write_json(&x, options.indent)
+    }
+}
@@ -34,6 +34,7 @@ use crate::collections::StarlarkHasher;
 use crate::eval::compiler::def::FrozenDef;
 use crate::private::Private;
 use crate::values::basic::StarlarkValueBasic;
+use crate::values::bigint::StarlarkBigInt;
 use crate::values::bool::StarlarkBool;
 use crate::values::float::StarlarkFloat;
 use crate::values::layout::heap::repr::AValueForward;
@@ -256,6 +257,10 @@ pub(crate) fn float_avalue<'v>(x: StarlarkFloat) -> impl AValue<'v, ExtraElem =
     AValueImpl(Direct, x)
 }
 
+pub(crate) fn bigint_avalue<'v>(x: StarlarkBigInt) -> impl AValue<'v, ExtraElem = ()> {
+    AValueImpl(Direct, x)
+}
+
 // A type where the second element is in control of what instances are in scope
 pub(crate) struct Direct;
 
@@ -344,6 +349,36 @@ impl<'v> AValue<'v> for AValueImpl<Direct, StarlarkFloat> {
     }
 }
 
+impl<'v> AValue<'v> for AValueImpl<Direct, StarlarkBigInt> {
+    type StarlarkValue = StarlarkBigInt;
+
+    type ExtraElem = ();
+
+    fn extra_len(&self) -> usize {
+        0
+    }
+
+    fn offset_of_extra() -> usize {
+        mem::size_of::<Self>()
+    }
+
+    unsafe fn heap_freeze(
+        me: *mut AValueRepr<Self>,
+        freezer: &Freezer,
+    ) -> anyhow::Result<FrozenValue> {
+        Self::heap_freeze_simple_impl(me, freezer)
+    }
+
+    unsafe fn heap_copy(me: *mut AValueRepr<Self>, tracer: &Tracer<'v>) -> Value<'v> {
+        Self::heap_copy_impl(me, tracer, |_v, _tracer| {})
+    }
+
+    fn get_hash(&self) -> anyhow::Result<StarlarkHashValue> {
+        // Same hash as `int` and `float` values, so equal numbers hash equally.
+        Ok(Num::BigInt(&self.1).get_hash())
+    }
+}
+
 pub(crate) type StarlarkStrAValue = AValueImpl<Direct, StarlarkStr>;
 
 impl<'v> AValue<'v> for AValueImpl<Direct, StarlarkStr> {
@@ -634,8 +669,8 @@ impl<'v, T: Debug + 'static> AValue<'v> for AValueImpl<Direct, AnyArray<T>> {
 }
 
 impl<Mode, C> AValueImpl<Mode, C> {
-    /// `heap_freeze` implementation for simple `StarlarkValue` and `StarlarkFloat`
-    /// (`StarlarkFloat` is logically a simple type, but it is not considered simple type).
+    /// `heap_freeze` implementation for simple `StarlarkValue`, `StarlarkFloat`
+    /// and `StarlarkBigInt` (which are logically simple types, but are not considered simple types).
     unsafe fn heap_freeze_simple_impl<'v>(
         me: *mut AValueRepr<Self>,
         freezer: &Freezer,
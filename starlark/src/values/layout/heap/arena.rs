@@ -140,6 +140,16 @@ impl<'c> Iterator for ChunkIter<'c> {
 }
 
 impl Arena {
+    /// Arena whose first chunks can hold at least `bytes` bytes in total.
+    /// The capacity is split evenly between the drop and non-drop arenas.
+    pub(crate) fn with_capacity(bytes: usize) -> Self {
+        let non_drop = bytes / 2;
+        Arena {
+            non_drop: Bump::with_capacity(non_drop),
+            drop: Bump::with_capacity(bytes - non_drop),
+        }
+    }
+
     /// Number of allocated bytes plus padding size.
     pub fn allocated_bytes(&self) -> usize {
         // This overestimates the allocates size, see `Bump::allocated_bytes()`:
@@ -505,6 +515,17 @@ mod tests {
         assert_eq!(to_repr(res[2]), "hello");
     }
 
+    #[test]
+    fn test_with_capacity() {
+        let mut arena = Arena::with_capacity(100000);
+        assert!(arena.available_bytes() >= 100000);
+        let before = arena.drop.iter_allocated_chunks().count();
+        for i in 0..100 {
+            arena.alloc(mk_str(&i.to_string()));
+        }
+        assert_eq!(before, arena.drop.iter_allocated_chunks().count());
+    }
+
     #[test]
     fn test_allocated_summary() {
         let arena = Arena::default();
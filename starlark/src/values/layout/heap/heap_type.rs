@@ -32,6 +32,7 @@ use std::mem::MaybeUninit;
 use std::ops::Deref;
 use std::ptr;
 use std::slice;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Instant;
 use std::usize;
@@ -39,15 +40,20 @@ use std::usize;
 use either::Either;
 use gazebo::cast;
 use gazebo::prelude::*;
+use num_bigint::BigInt;
 use once_cell::sync::Lazy;
 
 use crate::collections::Hashed;
+use crate::collections::SmallMap;
 use crate::collections::StarlarkHashValue;
 use crate::eval::compiler::def::FrozenDef;
 use crate::values::any::StarlarkAny;
 use crate::values::array::Array;
+use crate::values::bigint::StarlarkBigInt;
+use crate::values::dict::Dict;
 use crate::values::layout::avalue::any_array_avalue;
 use crate::values::layout::avalue::array_avalue;
+use crate::values::layout::avalue::bigint_avalue;
 use crate::values::layout::avalue::complex;
 use crate::values::layout::avalue::complex_no_freeze;
 use crate::values::layout::avalue::float_avalue;
@@ -74,10 +80,12 @@ use crate::values::layout::static_string::constant_string;
 use crate::values::layout::typed::string::StringValueLike;
 use crate::values::layout::value::FrozenValue;
 use crate::values::layout::value::Value;
+use crate::values::lazy_list::LazyList;
 use crate::values::string::intern::interner::FrozenStringInterner;
 use crate::values::string::StarlarkStr;
 use crate::values::types::float::StarlarkFloat;
 use crate::values::AllocFrozenValue;
+use crate::values::AllocValue;
 use crate::values::ComplexValue;
 use crate::values::FrozenRef;
 use crate::values::FrozenStringValue;
@@ -99,6 +107,18 @@ pub struct Heap {
     /// Peak memory seen when a garbage collection takes place (may be lower than currently allocated)
     peak_allocated: Cell<usize>,
     arena: FastCell<Arena>,
+    /// Callbacks registered with [`Heap::register_finalizer`].
+    finalizers: RefCell<Vec<Box<dyn FnOnce() + Send + 'static>>>,
+}
+
+impl Drop for Heap {
+    fn drop(&mut self) {
+        // Run before the fields are dropped, so the arena values are still alive.
+        // Most recently registered first, like the destructors of local variables.
+        while let Some(f) = self.finalizers.get_mut().pop() {
+            f();
+        }
+    }
 }
 
 impl Debug for Heap {
@@ -219,6 +239,15 @@ impl FrozenHeap {
         Self::default()
     }
 
+    /// Create a new [`FrozenHeap`] with `bytes` bytes of memory reserved up front,
+    /// split evenly between two arenas. See [`Heap::with_capacity`] for how the hint is used.
+    pub fn with_capacity(bytes: usize) -> Self {
+        Self {
+            arena: Arena::with_capacity(bytes),
+            ..Self::default()
+        }
+    }
+
     /// After all values have been allocated, convert the [`FrozenHeap`] into a
     /// [`FrozenHeapRef`] which can be [`clone`](Clone::clone)d, shared between threads,
     /// and ensures the underlying values allocated on the [`FrozenHeap`] remain valid.
@@ -273,8 +302,12 @@ impl FrozenHeap {
         self.alloc_str_impl(x, StarlarkStr::UNINIT_HASH)
     }
 
-    /// Intern string.
-    pub(crate) fn alloc_str_intern(&self, s: &str) -> FrozenStringValue {
+    /// Allocate a string on this heap, reusing a previously interned string
+    /// with the same content if there is one.
+    ///
+    /// Interned strings live as long as the heap. This is useful for frequently
+    /// repeated identifiers like struct field names.
+    pub fn alloc_str_intern(&self, s: &str) -> FrozenStringValue {
         if let Some(s) = constant_string(s) {
             s
         } else {
@@ -322,7 +355,13 @@ impl FrozenHeap {
         self.alloc_raw(float_avalue(f))
     }
 
-    pub(crate) fn alloc_simple_typed<T: StarlarkValue<'static>>(
+    pub(crate) fn alloc_bigint(&self, b: StarlarkBigInt) -> FrozenValue {
+        self.alloc_raw(bigint_avalue(b))
+    }
+
+    /// Allocate a simple [`StarlarkValue`] on this heap, like
+    /// [`alloc_simple`](FrozenHeap::alloc_simple), but return the value with its type.
+    pub fn alloc_simple_typed<T: StarlarkValue<'static>>(
         &self,
         val: T,
     ) -> FrozenValueTyped<'static, T> {
@@ -487,8 +526,31 @@ impl Heap {
         Self::default()
     }
 
+    /// Create a new [`Heap`] with `bytes` bytes of memory reserved up front.
+    ///
+    /// The heap allocates memory in chunks, and the hint is the total size of the first chunks.
+    /// Values which need dropping and values which don't (e.g. strings) live in separate arenas,
+    /// and the hint is split evenly between them, so the heap holds `bytes` bytes of values
+    /// (as measured by [`allocated_bytes`](Heap::allocated_bytes)) without allocating more memory
+    /// only if the values are split evenly too, and at least `bytes / 2` bytes in any case.
+    /// Once a chunk is full the next one is allocated as usual, so the hint is never a limit.
+    /// After a garbage collection the live values are copied into a default-sized arena,
+    /// so the hint only affects the memory allocated before the first collection.
+    /// Creating many heaps with a large hint allocates that memory for each of them up front.
+    pub fn with_capacity(bytes: usize) -> Self {
+        Self {
+            peak_allocated: Cell::new(0),
+            arena: FastCell::new(Arena::with_capacity(bytes)),
+            finalizers: RefCell::new(Vec::new()),
+        }
+    }
+
     /// Number of bytes allocated on this heap, not including any memory
     /// represented by [`extra_memory`](crate::values::StarlarkValue::extra_memory).
+    ///
+    /// This counts the arena memory holding live values, including value headers
+    /// and string contents. It decreases after a garbage collection,
+    /// see [`peak_allocated_bytes`](Heap::peak_allocated_bytes) for the high-water mark.
     pub fn allocated_bytes(&self) -> usize {
         self.arena.borrow().allocated_bytes()
     }
@@ -504,6 +566,24 @@ impl Heap {
         self.arena.borrow().available_bytes()
     }
 
+    /// Register a callback to run when this heap is dropped.
+    ///
+    /// Values on the heap are already dropped with the heap, so a plain [`Drop`]
+    /// implementation is usually enough. A finalizer is useful for releasing
+    /// resources shared by many values, e.g. an OS handle referenced from a pool.
+    ///
+    /// Ordering guarantees:
+    ///
+    /// * Finalizers run in reverse order of registration.
+    /// * Finalizers run before any value on the heap is dropped.
+    /// * When a [`Module`](crate::environment::Module) is frozen, its heap is dropped
+    ///   only after every value has been through [`Freeze`](crate::values::Freeze),
+    ///   so finalizers observe the state after freezing.
+    /// * Garbage collection does not run finalizers.
+    pub fn register_finalizer(&self, f: impl FnOnce() + Send + 'static) {
+        self.finalizers.borrow_mut().push(Box::new(f));
+    }
+
     fn alloc_raw<'v, 'v2: 'v2>(&'v self, x: impl AValue<'v2, ExtraElem = ()>) -> Value<'v> {
         let arena = self.arena.borrow();
         let v: &AValueRepr<_> = arena.alloc(x);
@@ -626,6 +706,56 @@ impl Heap {
         list.to_value()
     }
 
+    /// Allocate a list-like value whose elements are produced by calling `f` each time
+    /// it is iterated, so they are never all held in memory at once.
+    /// See [`LazyList`] for details.
+    pub fn alloc_lazy_list<'v, F, I>(&'v self, f: F) -> Value<'v>
+    where
+        F: Fn() -> I + Send + Sync + 'static,
+        I: Iterator + 'static,
+        I::Item: for<'a> AllocValue<'a>,
+    {
+        self.alloc_simple(LazyList::new(f))
+    }
+
+    /// Allocate the Starlark equivalent of a parsed JSON value: `null`, booleans,
+    /// strings, arrays and objects become `None`, `bool`, `str`, `list` and `dict`.
+    /// Numbers which are integers become `int` (big if necessary), others become `float`.
+    pub fn alloc_json<'v>(&'v self, x: &serde_json::Value) -> Value<'v> {
+        match x {
+            serde_json::Value::Null => Value::new_none(),
+            serde_json::Value::Bool(x) => Value::new_bool(*x),
+            serde_json::Value::Number(x) => {
+                if let Some(x) = x.as_i64() {
+                    self.alloc(x)
+                } else if let Some(x) = x.as_u64() {
+                    self.alloc(x)
+                } else if let Ok(x) = BigInt::from_str(&x.to_string()) {
+                    // Only when `serde_json` keeps the number text
+                    // (feature `arbitrary_precision`), larger integers are exact.
+                    StarlarkBigInt::alloc_bigint(x, self)
+                } else {
+                    // `as_f64` rejects numbers out of range,
+                    // which parse to infinity, as in Python.
+                    self.alloc(x.as_f64().unwrap_or_else(|| {
+                        f64::from_str(&x.to_string()).expect("JSON number is a valid float")
+                    }))
+                }
+            }
+            serde_json::Value::String(x) => self.alloc_str(x).to_value(),
+            serde_json::Value::Array(xs) => {
+                self.alloc_list_iter(xs.iter().map(|x| self.alloc_json(x)))
+            }
+            serde_json::Value::Object(xs) => {
+                let mut mp = SmallMap::with_capacity(xs.len());
+                for (k, v) in xs {
+                    mp.insert_hashed(self.alloc_str(k).get_hashed_value(), self.alloc_json(v));
+                }
+                self.alloc(Dict::new(mp))
+            }
+        }
+    }
+
     /// Allocate a list by concatenating two slices.
     pub(crate) fn alloc_list_concat<'v>(&'v self, a: &[Value<'v>], b: &[Value<'v>]) -> Value<'v> {
         let array = self.alloc_array(a.len() + b.len());
@@ -644,6 +774,10 @@ impl Heap {
         self.alloc_raw(float_avalue(f))
     }
 
+    pub(crate) fn alloc_bigint<'v>(&'v self, b: StarlarkBigInt) -> Value<'v> {
+        self.alloc_raw(bigint_avalue(b))
+    }
+
     /// Allocate a simple [`StarlarkValue`] on this heap.
     ///
     /// Simple value is any starlark value which:
@@ -653,6 +787,42 @@ impl Heap {
         self.alloc_raw(simple(x))
     }
 
+    /// Allocate a simple [`StarlarkValue`] on this heap, like
+    /// [`alloc_simple`](Heap::alloc_simple), but return the value with its type,
+    /// without checking it as [`ValueTyped::new`] would.
+    ///
+    /// ```
+    /// use derive_more::Display;
+    /// use gazebo::any::ProvidesStaticType;
+    /// use starlark::starlark_type;
+    /// use starlark::values::Heap;
+    /// use starlark::values::NoSerialize;
+    /// use starlark::values::StarlarkValue;
+    /// use starlark::values::ValueTyped;
+    ///
+    /// #[derive(Debug, Display, ProvidesStaticType, NoSerialize)]
+    /// #[display(fmt = "meters({})", _0)]
+    /// struct Meters(u32);
+    ///
+    /// impl<'v> StarlarkValue<'v> for Meters {
+    ///     starlark_type!("meters");
+    /// }
+    ///
+    /// let heap = Heap::new();
+    /// let x: ValueTyped<Meters> = heap.alloc_simple_typed(Meters(3));
+    /// assert_eq!(3, x.as_ref().0);
+    /// assert_eq!("meters(3)", x.to_value().to_str());
+    /// ```
+    pub fn alloc_simple_typed<'v, T>(&'v self, x: T) -> ValueTyped<'v, T>
+    where
+        // A single bound for all lifetimes, since separate `StarlarkValue<'static>`
+        // and `StarlarkValue<'v>` bounds are ambiguous.
+        T: for<'a> StarlarkValue<'a>,
+    {
+        // SAFETY: we've just allocated `T`.
+        unsafe { ValueTyped::new_unchecked(self.alloc_simple(x)) }
+    }
+
     /// Allocate a [`ComplexValue`] on the [`Heap`].
     pub fn alloc_complex<'v, T>(&'v self, x: T) -> Value<'v>
     where
@@ -662,6 +832,17 @@ impl Heap {
         self.alloc_raw(complex(x))
     }
 
+    /// Allocate a [`ComplexValue`] on the [`Heap`], like [`alloc_complex`](Heap::alloc_complex),
+    /// but return the value with its type, without checking it as [`ValueTyped::new`] would.
+    pub fn alloc_complex_typed<'v, T>(&'v self, x: T) -> ValueTyped<'v, T>
+    where
+        T: ComplexValue<'v>,
+        T::Frozen: StarlarkValue<'static>,
+    {
+        // SAFETY: we've just allocated `T`.
+        unsafe { ValueTyped::new_unchecked(self.alloc_complex(x)) }
+    }
+
     /// Allocate a value which can be traced (garbage collected), but cannot be frozen.
     pub fn alloc_complex_no_freeze<'v, T>(&'v self, x: T) -> Value<'v>
     where
@@ -704,7 +885,9 @@ impl Heap {
         self.arena.set(tracer.arena);
     }
 
-    /// Obtain a summary of how much memory is currently allocated by this heap.
+    /// Obtain a summary of how much memory is currently allocated by this heap,
+    /// grouped by type. Values which are no longer reachable are included until
+    /// the next garbage collection. The summary can be printed as a table.
     pub fn allocated_summary(&self) -> HeapSummary {
         self.arena.borrow().allocated_summary()
     }
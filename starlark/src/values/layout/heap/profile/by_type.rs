@@ -16,6 +16,8 @@
  */
 
 use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Display;
 
 use starlark_map::small_map::SmallMap;
 
@@ -25,6 +27,9 @@ use crate::values::layout::heap::profile::alloc_counts::AllocCounts;
 /// Information about the data stored on a heap. Accessible through
 /// the function `allocated_summary` available on [`Heap`](crate::values::Heap)
 /// and [`FrozenHeap`](crate::values::FrozenHeap)
+///
+/// The [`Display`] instance prints a table of types, largest total size first.
+/// Call it after a garbage collection to see which types account for live memory.
 pub struct HeapSummary {
     /// For each type, give the (number of entries, size of all entries).
     /// The size may be approximate as it includes information from
@@ -63,4 +68,61 @@ impl HeapSummary {
         }
         HeapSummary { summary }
     }
+
+    /// Entries sorted by descending size, then by type name.
+    fn sorted(&self) -> Vec<(&'static str, AllocCounts)> {
+        let mut entries: Vec<_> = self.summary.iter().map(|(k, v)| (*k, *v)).collect();
+        entries.sort_by(|(k1, v1), (k2, v2)| v2.bytes.cmp(&v1.bytes).then_with(|| k1.cmp(k2)));
+        entries
+    }
+}
+
+impl Display for HeapSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entries = self.sorted();
+        let total = self.total();
+        let width = entries
+            .iter()
+            .map(|(k, _)| k.len())
+            .chain(["TOTAL".len()])
+            .max()
+            .unwrap_or_default();
+        writeln!(f, "{:<width$} {:>10} {:>12}", "TYPE", "COUNT", "BYTES")?;
+        for (name, counts) in entries {
+            writeln!(
+                f,
+                "{:<width$} {:>10} {:>12}",
+                name, counts.count, counts.bytes
+            )?;
+        }
+        writeln!(
+            f,
+            "{:<width$} {:>10} {:>12}",
+            "TOTAL", total.count, total.bytes
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::values::layout::heap::profile::alloc_counts::AllocCounts;
+    use crate::values::layout::heap::profile::by_type::HeapSummary;
+
+    #[test]
+    fn test_display() {
+        let mut summary = HeapSummary::default();
+        let mut add = |t, count, bytes| summary.add(t, AllocCounts { bytes, count });
+        add("str", 1, 10);
+        add("list", 2, 48);
+        add("str", 2, 20);
+        assert_eq!(
+            "\
+TYPE       COUNT        BYTES
+list           2           48
+str            3           30
+TOTAL          5           78
+",
+            summary.to_string()
+        );
+    }
 }
@@ -125,6 +125,17 @@ impl<'v, T: StarlarkValue<'v>> Serialize for FrozenValueTyped<'v, T> {
     }
 }
 
+#[derive(Debug, thiserror::Error)]
+enum ValueTypedError {
+    #[error("expected \"{0}\", got \"{1}\"")]
+    TypeMismatch(&'static str, &'static str),
+}
+
+#[cold]
+fn type_mismatch_error<'v, T: StarlarkValue<'v>>(value: Value) -> anyhow::Error {
+    ValueTypedError::TypeMismatch(T::get_type_value_static().as_str(), value.get_type()).into()
+}
+
 impl<'v, T: StarlarkValue<'v>> ValueTyped<'v, T> {
     /// Downcast.
     pub fn new(value: Value<'v>) -> Option<ValueTyped<'v, T>> {
@@ -132,7 +143,21 @@ impl<'v, T: StarlarkValue<'v>> ValueTyped<'v, T> {
         Some(ValueTyped(value, marker::PhantomData))
     }
 
+    /// Downcast, returning an error describing expected and actual types on failure.
+    pub fn try_new(value: Value<'v>) -> anyhow::Result<ValueTyped<'v, T>> {
+        match ValueTyped::new(value) {
+            Some(v) => Ok(v),
+            None => Err(type_mismatch_error::<T>(value)),
+        }
+    }
+
     /// Construct typed value without checking the value is of type `<T>`.
+    ///
+    /// This is a zero-cost conversion (the type is only checked in debug builds),
+    /// intended for code which already knows the concrete type of the value.
+    /// Caller must guarantee the value is of type `<T>`, otherwise
+    /// dereferencing the result is undefined behavior.
+    #[inline]
     pub unsafe fn new_unchecked(value: Value<'v>) -> ValueTyped<'v, T> {
         debug_assert!(value.downcast_ref::<T>().is_some());
         ValueTyped(value, marker::PhantomData)
@@ -174,6 +199,13 @@ impl<'v, T: StarlarkValue<'v>> ValueTyped<'v, T> {
 
 impl<'v, T: StarlarkValue<'v>> FrozenValueTyped<'v, T> {
     /// Construct `FrozenValueTyped` without checking that the value is of correct type.
+    ///
+    /// This is a zero-cost conversion (the type is only checked in debug builds),
+    /// intended for code which already knows the concrete type of the value,
+    /// for example, because the value was just allocated with [`FrozenHeap::alloc_simple`].
+    /// Caller must guarantee the value is of type `<T>`, otherwise
+    /// dereferencing the result is undefined behavior.
+    #[inline]
     pub unsafe fn new_unchecked(value: FrozenValue) -> FrozenValueTyped<'v, T> {
         debug_assert!(value.downcast_ref::<T>().is_some());
         FrozenValueTyped(value, marker::PhantomData)
@@ -185,6 +217,14 @@ impl<'v, T: StarlarkValue<'v>> FrozenValueTyped<'v, T> {
         Some(FrozenValueTyped(value, marker::PhantomData))
     }
 
+    /// Downcast, returning an error describing expected and actual types on failure.
+    pub fn try_new(value: FrozenValue) -> anyhow::Result<FrozenValueTyped<'v, T>> {
+        match FrozenValueTyped::new(value) {
+            Some(v) => Ok(v),
+            None => Err(type_mismatch_error::<T>(value.to_value())),
+        }
+    }
+
     pub(crate) fn new_repr<A: AValue<'v, StarlarkValue = T>>(
         repr: &'v AValueRepr<A>,
     ) -> FrozenValueTyped<'v, T> {
@@ -329,14 +369,60 @@ impl<'v, T: StarlarkValue<'v>> AllocFrozenValue for FrozenValueTyped<'v, T> {
 
 #[cfg(test)]
 mod tests {
+    use crate::collections::SmallMap;
+    use crate::values::float::StarlarkFloat;
     use crate::values::int::PointerI32;
+    use crate::values::record::Record;
+    use crate::values::string::StarlarkStr;
+    use crate::values::structs::Struct;
+    use crate::values::FrozenHeap;
     use crate::values::FrozenValue;
     use crate::values::FrozenValueTyped;
+    use crate::values::Heap;
     use crate::values::StarlarkValue;
+    use crate::values::ValueTyped;
 
     #[test]
     fn int() {
         let v = FrozenValueTyped::<PointerI32>::new(FrozenValue::new_int(17)).unwrap();
         assert_eq!(17, v.as_ref().to_int().unwrap());
     }
+
+    #[test]
+    fn try_new_error() {
+        let err = FrozenValueTyped::<StarlarkStr>::try_new(FrozenValue::new_int(17)).unwrap_err();
+        assert_eq!(r#"expected "string", got "int""#, err.to_string());
+
+        let heap = Heap::new();
+        let err = ValueTyped::<Record>::try_new(heap.alloc_list(&[])).unwrap_err();
+        assert_eq!(r#"expected "record", got "list""#, err.to_string());
+    }
+
+    #[test]
+    fn serialize() {
+        let heap = Heap::new();
+        let v = ValueTyped::<StarlarkStr>::new(heap.alloc("x")).unwrap();
+        assert_eq!("\"x\"", serde_json::to_string(&v).unwrap());
+        let v = FrozenValueTyped::<PointerI32>::new(FrozenValue::new_int(17)).unwrap();
+        assert_eq!("17", serde_json::to_string(&v).unwrap());
+    }
+
+    #[test]
+    fn alloc_typed() {
+        let heap = Heap::new();
+        let v: ValueTyped<StarlarkFloat> = heap.alloc_simple_typed(StarlarkFloat(1.5));
+        assert_eq!(1.5, v.as_ref().0);
+        assert_eq!("1.5", v.to_value().to_repr());
+
+        let mut fields = SmallMap::new();
+        fields.insert(heap.alloc_str("x"), heap.alloc(1));
+        let v: ValueTyped<Struct> = heap.alloc_complex_typed(Struct::new(fields));
+        assert_eq!(1, v.as_ref().fields.len());
+        assert_eq!("struct(x=1)", v.to_value().to_repr());
+
+        let frozen_heap = FrozenHeap::new();
+        let v: FrozenValueTyped<StarlarkFloat> = frozen_heap.alloc_simple_typed(StarlarkFloat(2.5));
+        assert_eq!(2.5, v.as_ref().0);
+        assert_eq!("2.5", v.to_value().to_repr());
+    }
 }
@@ -34,6 +34,7 @@ use std::cmp::Ordering;
 use std::fmt;
 use std::fmt::Debug;
 use std::fmt::Display;
+use std::io::Write;
 
 use either::Either;
 use gazebo::any::AnyLifetime;
@@ -59,6 +60,8 @@ use crate::eval::Arguments;
 use crate::eval::Evaluator;
 use crate::eval::ParametersSpec;
 use crate::sealed::Sealed;
+use crate::values::deep_clone::deep_clone_into;
+use crate::values::demand;
 use crate::values::dict::FrozenDict;
 use crate::values::docs::DocItem;
 use crate::values::enumeration::EnumType;
@@ -68,6 +71,7 @@ use crate::values::function::FrozenBoundMethod;
 use crate::values::function::NativeFunction;
 use crate::values::function::FUNCTION_TYPE;
 use crate::values::int::PointerI32;
+use crate::values::json::to_json_with;
 use crate::values::layout::avalue::basic_ref;
 use crate::values::layout::avalue::AValue;
 use crate::values::layout::avalue::StarlarkStrAValue;
@@ -102,6 +106,7 @@ use crate::values::FrozenRef;
 use crate::values::FrozenStringValue;
 use crate::values::FrozenValueTyped;
 use crate::values::Heap;
+use crate::values::JsonOptions;
 use crate::values::StarlarkValue;
 use crate::values::StringValue;
 use crate::values::UnpackValue;
@@ -216,6 +221,18 @@ pub struct FrozenValue(pub(crate) FrozenPointer<'static>);
 unsafe impl Send for FrozenValue {}
 unsafe impl Sync for FrozenValue {}
 
+/// The result of a binary operation, or if the left operand doesn't support it,
+/// the result of the reflected operation of the right operand, if that is defined.
+fn or_reflected<'v>(
+    res: anyhow::Result<Value<'v>>,
+    reflected: impl FnOnce() -> Option<anyhow::Result<Value<'v>>>,
+) -> anyhow::Result<Value<'v>> {
+    match res {
+        Err(e) if ValueError::is_unsupported(&e) => reflected().unwrap_or(Err(e)),
+        res => res,
+    }
+}
+
 impl<'v> Value<'v> {
     #[inline]
     pub(crate) fn new_ptr(x: &'v AValueHeader, is_str: bool) -> Self {
@@ -271,6 +288,17 @@ impl<'v> Value<'v> {
         Self(x.0.to_pointer())
     }
 
+    /// Is this value frozen, i.e. not allocated on a mutable [`Heap`].
+    ///
+    /// Values allocated on a [`FrozenHeap`](crate::values::FrozenHeap) (including everything
+    /// reachable from a frozen module), as well as ints, `None`, booleans and other static values,
+    /// are frozen, and remain valid as long as their frozen heap is kept alive.
+    /// Equivalent to `self.unpack_frozen().is_some()`.
+    #[inline]
+    pub fn is_frozen(self) -> bool {
+        !self.0.is_unfrozen()
+    }
+
     /// Obtain the underlying [`FrozenValue`] from inside the [`Value`], if it is one.
     #[inline]
     pub fn unpack_frozen(self) -> Option<FrozenValue> {
@@ -421,6 +449,23 @@ impl<'v> Value<'v> {
         self.get_ref().get_hash()
     }
 
+    /// Request a value of type `T` from the underlying [`StarlarkValue`],
+    /// e.g. a native handle or a source location attached to many value types.
+    /// Returns [`None`] unless the value answers the request in
+    /// [`StarlarkValue::provide`].
+    pub fn request_value<T: ProvidesStaticType + 'v>(self) -> Option<T> {
+        demand::request_value(|demand| self.get_ref().provide(demand))
+    }
+
+    /// Request a reference of type `T` from the underlying [`StarlarkValue`],
+    /// usually a trait object shared by several value types.
+    /// Returns [`None`] unless the value answers the request in
+    /// [`StarlarkValue::provide`]. Unlike [`downcast_ref`](ValueLike::downcast_ref),
+    /// the caller does not need to know the concrete type of the value.
+    pub fn request_ref<T: ProvidesStaticType + ?Sized + 'v>(self) -> Option<&'v T> {
+        demand::request_ref(|demand| self.get_ref().provide(demand))
+    }
+
     /// Are two [`Value`]s equal, looking at only their underlying pointer. This function is
     /// low-level and provides two guarantees.
     ///
@@ -487,6 +532,16 @@ impl<'v> Value<'v> {
         self.get_ref().at(index, heap)
     }
 
+    /// `x[index0, index1]`.
+    pub fn at2(
+        self,
+        index0: Value<'v>,
+        index1: Value<'v>,
+        heap: &'v Heap,
+    ) -> anyhow::Result<Value<'v>> {
+        self.get_ref().at2(index0, index1, heap)
+    }
+
     /// `x[start:stop:stride]`.
     pub fn slice(
         self,
@@ -518,44 +573,68 @@ impl<'v> Value<'v> {
         self.get_ref().minus(heap)
     }
 
-    /// `x - other`.
+    /// `x - other`, using [`rsub`](StarlarkValue::rsub) of `other`
+    /// if `x` doesn't support it.
     pub fn sub(self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
-        self.get_ref().sub(other, heap)
+        or_reflected(self.get_ref().sub(other, heap), || {
+            other.get_ref().rsub(self, heap)
+        })
     }
 
-    /// `x * other`.
+    /// `x * other`, using [`rmul`](StarlarkValue::rmul) of `other`
+    /// if `x` doesn't support it.
     pub fn mul(self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
-        self.get_ref().mul(other, heap)
+        or_reflected(self.get_ref().mul(other, heap), || {
+            other.get_ref().rmul(self, heap)
+        })
     }
 
-    /// `x % other`.
+    /// `x % other`, using [`rpercent`](StarlarkValue::rpercent) of `other`
+    /// if `x` doesn't support it.
     pub fn percent(self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
-        self.get_ref().percent(other, heap)
+        or_reflected(self.get_ref().percent(other, heap), || {
+            other.get_ref().rpercent(self, heap)
+        })
     }
 
-    /// `x / other`.
+    /// `x / other`, using [`rdiv`](StarlarkValue::rdiv) of `other`
+    /// if `x` doesn't support it.
     pub fn div(self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
-        self.get_ref().div(other, heap)
+        or_reflected(self.get_ref().div(other, heap), || {
+            other.get_ref().rdiv(self, heap)
+        })
     }
 
-    /// `x // other`.
+    /// `x // other`, using [`rfloor_div`](StarlarkValue::rfloor_div) of `other`
+    /// if `x` doesn't support it.
     pub fn floor_div(self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
-        self.get_ref().floor_div(other, heap)
+        or_reflected(self.get_ref().floor_div(other, heap), || {
+            other.get_ref().rfloor_div(self, heap)
+        })
     }
 
-    /// `x & other`.
+    /// `x & other`, using [`rbit_and`](StarlarkValue::rbit_and) of `other`
+    /// if `x` doesn't support it.
     pub fn bit_and(self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
-        self.get_ref().bit_and(other, heap)
+        or_reflected(self.get_ref().bit_and(other, heap), || {
+            other.get_ref().rbit_and(self, heap)
+        })
     }
 
-    /// `x | other`.
+    /// `x | other`, using [`rbit_or`](StarlarkValue::rbit_or) of `other`
+    /// if `x` doesn't support it.
     pub fn bit_or(self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
-        self.get_ref().bit_or(other, heap)
+        or_reflected(self.get_ref().bit_or(other, heap), || {
+            other.get_ref().rbit_or(self, heap)
+        })
     }
 
-    /// `x ^ other`.
+    /// `x ^ other`, using [`rbit_xor`](StarlarkValue::rbit_xor) of `other`
+    /// if `x` doesn't support it.
     pub fn bit_xor(self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
-        self.get_ref().bit_xor(other, heap)
+        or_reflected(self.get_ref().bit_xor(other, heap), || {
+            other.get_ref().rbit_xor(self, heap)
+        })
     }
 
     /// `~x`.
@@ -563,14 +642,20 @@ impl<'v> Value<'v> {
         self.get_ref().bit_not(heap)
     }
 
-    /// `x << other`.
+    /// `x << other`, using [`rleft_shift`](StarlarkValue::rleft_shift) of `other`
+    /// if `x` doesn't support it.
     pub fn left_shift(self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
-        self.get_ref().left_shift(other, heap)
+        or_reflected(self.get_ref().left_shift(other, heap), || {
+            other.get_ref().rleft_shift(self, heap)
+        })
     }
 
-    /// `x >> other`.
+    /// `x >> other`, using [`rright_shift`](StarlarkValue::rright_shift) of `other`
+    /// if `x` doesn't support it.
     pub fn right_shift(self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
-        self.get_ref().right_shift(other, heap)
+        or_reflected(self.get_ref().right_shift(other, heap), || {
+            other.get_ref().rright_shift(self, heap)
+        })
     }
 
     pub(crate) fn invoke_with_loc(
@@ -598,12 +683,16 @@ impl<'v> Value<'v> {
     }
 
     /// Invoke self with given arguments.
+    ///
+    /// This is a call made by a native function (e.g. the `key` of `sorted`),
+    /// so it is attributed to the location where that native function was called.
     pub(crate) fn invoke(
         self,
         args: &Arguments<'v, '_>,
         eval: &mut Evaluator<'v, '_>,
     ) -> anyhow::Result<Value<'v>> {
-        self.invoke_with_loc(None, args, eval)
+        let location = eval.call_stack.top_span();
+        self.invoke_with_loc(location, args, eval)
     }
 
     /// Invoke a function with only positional arguments.
@@ -685,6 +774,36 @@ impl<'v> Value<'v> {
         s
     }
 
+    /// The `str()` of this value, same as [`to_str`](Value::to_str).
+    ///
+    /// Strings are returned as they are, other values are converted with `repr()`.
+    ///
+    /// ```
+    /// # use starlark::values::Heap;
+    /// let heap = Heap::new();
+    /// let s = heap.alloc("x");
+    /// assert_eq!("x", s.to_display_string());
+    /// assert_eq!("\"x\"", s.to_repr_string());
+    /// assert_eq!("[\"x\"]", heap.alloc(vec!["x"]).to_display_string());
+    /// ```
+    pub fn to_display_string(self) -> String {
+        self.to_str()
+    }
+
+    /// The `repr()` of this value, same as [`to_repr`](Value::to_repr).
+    /// Unlike [`to_display_string`](Value::to_display_string), strings are quoted.
+    pub fn to_repr_string(self) -> String {
+        self.to_repr()
+    }
+
+    /// Append the `repr()` of this value to `buffer`.
+    ///
+    /// Equivalent to `buffer.push_str(&self.to_repr())`, but without allocating
+    /// a temporary string, so a single buffer can be reused for many values.
+    pub fn collect_repr_into(self, buffer: &mut String) {
+        self.collect_repr(buffer)
+    }
+
     pub(crate) fn name_for_call_stack(self) -> String {
         self.get_ref().name_for_call_stack(self)
     }
@@ -696,6 +815,47 @@ impl<'v> Value<'v> {
         serde_json::to_string(&self).map_err(|e| anyhow::anyhow!(e))
     }
 
+    /// Convert the value to JSON, with options for key order and indentation.
+    ///
+    /// ```
+    /// use starlark::values::Heap;
+    /// use starlark::values::JsonOptions;
+    ///
+    /// let heap = Heap::new();
+    /// let x = heap.alloc_json(&serde_json::json!({"a": [1]}));
+    /// let options = JsonOptions {
+    ///     sort_keys: true,
+    ///     indent: Some(2),
+    /// };
+    /// assert_eq!(
+    ///     "{\n  \"a\": [\n    1\n  ]\n}",
+    ///     x.to_json_with(&options).unwrap()
+    /// );
+    /// ```
+    ///
+    /// Return an error if the value or any contained value does not support conversion to JSON.
+    pub fn to_json_with(self, options: &JsonOptions) -> anyhow::Result<String> {
+        to_json_with(self, options)
+    }
+
+    /// Write the value as JSON to `writer`, producing the same output as
+    /// [`to_json`](Value::to_json), but without building the whole string in memory.
+    ///
+    /// Return an error if the value or any contained value does not support conversion to JSON,
+    /// or if writing fails. Output written before the error is left in `writer`.
+    pub fn write_json(self, writer: &mut dyn Write) -> anyhow::Result<()> {
+        serde_json::to_writer(writer, &self).map_err(|e| anyhow::anyhow!(e))
+    }
+
+    /// Convert the value to a [`serde_json::Value`], with the same conversion as
+    /// [`to_json`](Value::to_json), but without producing an intermediate string.
+    ///
+    /// Ints too large for a JSON number are converted to strings.
+    /// Return an error if the value or any contained value does not support conversion to JSON.
+    pub fn to_json_value(self) -> anyhow::Result<serde_json::Value> {
+        serde_json::to_value(self).map_err(|e| anyhow::anyhow!(e))
+    }
+
     /// Forwards to [`StarlarkValue::set_attr`].
     pub fn set_attr(self, attribute: &str, alloc_value: Value<'v>) -> anyhow::Result<()> {
         self.get_ref().set_attr(attribute, alloc_value)
@@ -706,12 +866,55 @@ impl<'v> Value<'v> {
         self.get_ref().set_at(index, alloc_value)
     }
 
+    /// Forwards to [`StarlarkValue::set_slice`].
+    pub fn set_slice(
+        self,
+        start: Option<Value<'v>>,
+        stop: Option<Value<'v>>,
+        stride: Option<Value<'v>>,
+        alloc_value: Value<'v>,
+        heap: &'v Heap,
+    ) -> anyhow::Result<()> {
+        self.get_ref()
+            .set_slice(start, stop, stride, alloc_value, heap)
+    }
+
+    /// Forwards to [`StarlarkValue::del_at`].
+    pub fn del_at(self, index: Value<'v>) -> anyhow::Result<()> {
+        self.get_ref().del_at(index)
+    }
+
+    /// Forwards to [`StarlarkValue::del_slice`].
+    pub fn del_slice(
+        self,
+        start: Option<Value<'v>>,
+        stop: Option<Value<'v>>,
+        stride: Option<Value<'v>>,
+        heap: &'v Heap,
+    ) -> anyhow::Result<()> {
+        self.get_ref().del_slice(start, stop, stride, heap)
+    }
+
     /// Forwards to [`StarlarkValue::documentation`].
     pub fn documentation(self) -> Option<DocItem> {
         self.get_ref().documentation()
     }
 
     /// Return the contents of an iterable collection, as an owned vector.
+    ///
+    /// This is the simplest way to iterate a value from Rust, as the result
+    /// does not borrow the value or the heap. Fails for values which are not iterable.
+    ///
+    /// ```
+    /// use starlark::values::Heap;
+    ///
+    /// let heap = Heap::new();
+    /// let list = heap.alloc(vec![1, 2, 3]);
+    /// let items = list.iterate_collect(&heap).unwrap();
+    /// let ints: Vec<_> = items.iter().map(|x| x.unpack_int()).collect();
+    /// assert_eq!(vec![Some(1), Some(2), Some(3)], ints);
+    /// assert!(heap.alloc(1).iterate_collect(&heap).is_err());
+    /// ```
     pub fn iterate_collect(self, heap: &'v Heap) -> anyhow::Result<Vec<Value<'v>>> {
         // You might reasonably think this is mostly called on lists (I think it is),
         // and thus that a fast-path here would speed things up. But in my experiments
@@ -742,7 +945,22 @@ impl<'v> Value<'v> {
         self.get_ref().iterate(heap)
     }
 
+    /// Recursively copy this value into another heap.
+    ///
+    /// Lists, dicts, tuples, strings and numbers are copied,
+    /// frozen values are shared rather than copied, so the frozen heaps
+    /// they belong to must be kept alive as long as the result is used.
+    /// Fails for other types of values and for cyclic structures.
+    pub fn deep_clone_into<'d>(self, heap: &'d Heap) -> anyhow::Result<Value<'d>> {
+        deep_clone_into(self, heap)
+    }
+
     /// Get the [`Hashed`] version of this [`Value`].
+    ///
+    /// The result can be reused for multiple lookups with the same key
+    /// (e.g. [`Dict::get_hashed`](crate::values::dict::Dict::get_hashed),
+    /// [`Dict::insert_hashed`](crate::values::dict::Dict::insert_hashed))
+    /// to avoid recomputing the hash.
     #[inline]
     pub fn get_hashed(self) -> anyhow::Result<Hashed<Self>> {
         ValueLike::get_hashed(self)
@@ -755,6 +973,20 @@ impl<'v> Value<'v> {
         ValueLike::equals(self, other)
     }
 
+    /// Are two hashed values equal, as by [`Value::equals`].
+    ///
+    /// Values with different hashes are rejected without comparing their contents,
+    /// so when comparing the same values many times (e.g. deduplicating),
+    /// compute their hashes once with [`Value::get_hashed`] and compare with this function.
+    #[inline]
+    pub fn equals_hashed(x: Hashed<Value<'v>>, y: Hashed<Value<'v>>) -> anyhow::Result<bool> {
+        if x.hash() != y.hash() {
+            Ok(false)
+        } else {
+            x.key().equals(*y.key())
+        }
+    }
+
     /// How are two values comparable. For values of different types will return [`Err`].
     #[inline]
     pub fn compare(self, other: Value<'v>) -> anyhow::Result<Ordering> {
@@ -786,7 +1018,11 @@ impl<'v> Value<'v> {
         if let Some(methods) = aref.get_methods() {
             let attribute = Hashed::new(attribute);
             if let Some(v) = methods.get_hashed(attribute) {
-                return Ok(Some(MaybeUnboundValue::new(v).bind(self, heap)?));
+                return Ok(Some(MaybeUnboundValue::new(v).bind(
+                    self,
+                    attribute.key(),
+                    heap,
+                )?));
             }
             Ok(aref.get_attr_hashed(attribute, heap))
         } else {
@@ -1245,9 +1481,11 @@ where
 
 #[cfg(test)]
 mod tests {
+    use crate::collections::Hashed;
     use crate::values::none::NoneType;
     use crate::values::string::StarlarkStr;
     use crate::values::types::int::PointerI32;
+    use crate::values::FrozenHeap;
     use crate::values::Heap;
     use crate::values::Value;
     use crate::values::ValueLike;
@@ -1274,4 +1512,46 @@ mod tests {
         assert_eq!(17, integer.downcast_ref::<PointerI32>().unwrap().get());
         assert!(none.downcast_ref::<PointerI32>().is_none());
     }
+
+    #[test]
+    fn test_collect_repr_into() {
+        let heap = Heap::new();
+        let mut buffer = String::new();
+        for x in [heap.alloc("x"), heap.alloc(vec![1, 2]), Value::new_none()] {
+            x.collect_repr_into(&mut buffer);
+            buffer.push(';');
+        }
+        assert_eq!(buffer, "\"x\";[1, 2];None;");
+    }
+
+    #[test]
+    fn test_is_frozen() {
+        let heap = Heap::new();
+        let frozen_heap = FrozenHeap::new();
+        assert!(Value::new_none().is_frozen());
+        assert!(Value::new_int(17).is_frozen());
+        assert!(Value::new_bool(true).is_frozen());
+        assert!(frozen_heap.alloc("frozen").to_value().is_frozen());
+        assert!(!heap.alloc("unfrozen").is_frozen());
+        assert!(!heap.alloc(vec![1, 2]).is_frozen());
+    }
+
+    #[test]
+    fn test_equals_hashed() -> anyhow::Result<()> {
+        let heap = Heap::new();
+        fn hashed<'v>(x: Value<'v>) -> Hashed<Value<'v>> {
+            x.get_hashed().unwrap()
+        }
+        let one = hashed(Value::new_int(1));
+        let one_float = hashed(heap.alloc(1.0));
+        let two = hashed(Value::new_int(2));
+        let tuple1 = hashed(heap.alloc((1, "x")));
+        let tuple2 = hashed(heap.alloc((1, "x")));
+        assert!(Value::equals_hashed(one, one)?);
+        assert!(Value::equals_hashed(one, one_float)?);
+        assert!(!Value::equals_hashed(one, two)?);
+        assert!(Value::equals_hashed(tuple1, tuple2)?);
+        assert!(!Value::equals_hashed(tuple1, one)?);
+        Ok(())
+    }
 }
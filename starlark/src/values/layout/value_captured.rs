@@ -58,6 +58,10 @@ impl<'v> ValueCaptured<'v> {
         debug_assert!(value.downcast_ref::<FrozenValueCaptured>().is_none());
         self.0.set(Some(value));
     }
+
+    pub(crate) fn clear(&self) {
+        self.0.set(None);
+    }
 }
 
 impl<'v> Freeze for ValueCaptured<'v> {
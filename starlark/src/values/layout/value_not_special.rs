@@ -81,7 +81,7 @@ impl FrozenValueNotSpecial {
     ) -> anyhow::Result<Value<'v>> {
         eval.with_call_stack(self.to_value(), Some(location), |eval| {
             self.get_ref()
-                .invoke_method(self.to_value(), this, args, eval)
+                .invoke_as_method(self.to_value(), this, args, eval)
         })
     }
 }
@@ -36,6 +36,7 @@ use crate::environment::Methods;
 use crate::eval::Arguments;
 use crate::eval::Evaluator;
 use crate::private::Private;
+use crate::values::demand::Demand;
 use crate::values::docs::DocItem;
 use crate::values::layout::avalue::AValue;
 use crate::values::layout::avalue::BlackHole;
@@ -268,6 +269,11 @@ impl<'v> AValueDyn<'v> {
         (self.vtable.starlark_value.documentation)(StarlarkValueRawPtr::new(self.value))
     }
 
+    #[inline]
+    pub(crate) fn provide(self, demand: &mut Demand<'_, 'v>) {
+        (self.vtable.starlark_value.provide)(StarlarkValueRawPtr::new(self.value), demand)
+    }
+
     #[inline]
     pub(crate) fn get_methods(self) -> Option<&'static Methods> {
         (self.vtable.starlark_value.get_methods)()
@@ -278,6 +284,16 @@ impl<'v> AValueDyn<'v> {
         (self.vtable.starlark_value.at)(StarlarkValueRawPtr::new(self.value), index, heap)
     }
 
+    #[inline]
+    pub(crate) fn at2(
+        self,
+        index0: Value<'v>,
+        index1: Value<'v>,
+        heap: &'v Heap,
+    ) -> anyhow::Result<Value<'v>> {
+        (self.vtable.starlark_value.at2)(StarlarkValueRawPtr::new(self.value), index0, index1, heap)
+    }
+
     #[inline]
     pub(crate) fn is_in(self, collection: Value<'v>) -> anyhow::Result<bool> {
         (self.vtable.starlark_value.is_in)(StarlarkValueRawPtr::new(self.value), collection)
@@ -314,6 +330,21 @@ impl<'v> AValueDyn<'v> {
         )
     }
 
+    #[inline]
+    pub(crate) fn bind_method(
+        self,
+        this: Value<'v>,
+        method_name: &str,
+        heap: &'v Heap,
+    ) -> Option<Value<'v>> {
+        (self.vtable.starlark_value.bind_method)(
+            StarlarkValueRawPtr::new(self.value),
+            this,
+            method_name,
+            heap,
+        )
+    }
+
     #[inline]
     pub(crate) fn has_attr(self, name: &str) -> bool {
         (self.vtable.starlark_value.has_attr)(StarlarkValueRawPtr::new(self.value), name)
@@ -408,6 +439,84 @@ impl<'v> AValueDyn<'v> {
         (self.vtable.starlark_value.radd)(StarlarkValueRawPtr::new(self.value), other, heap)
     }
 
+    #[inline]
+    pub(crate) fn rsub(self, lhs: Value<'v>, heap: &'v Heap) -> Option<anyhow::Result<Value<'v>>> {
+        (self.vtable.starlark_value.rsub)(StarlarkValueRawPtr::new(self.value), lhs, heap)
+    }
+
+    #[inline]
+    pub(crate) fn rmul(self, lhs: Value<'v>, heap: &'v Heap) -> Option<anyhow::Result<Value<'v>>> {
+        (self.vtable.starlark_value.rmul)(StarlarkValueRawPtr::new(self.value), lhs, heap)
+    }
+
+    #[inline]
+    pub(crate) fn rdiv(self, lhs: Value<'v>, heap: &'v Heap) -> Option<anyhow::Result<Value<'v>>> {
+        (self.vtable.starlark_value.rdiv)(StarlarkValueRawPtr::new(self.value), lhs, heap)
+    }
+
+    #[inline]
+    pub(crate) fn rpercent(
+        self,
+        lhs: Value<'v>,
+        heap: &'v Heap,
+    ) -> Option<anyhow::Result<Value<'v>>> {
+        (self.vtable.starlark_value.rpercent)(StarlarkValueRawPtr::new(self.value), lhs, heap)
+    }
+
+    #[inline]
+    pub(crate) fn rfloor_div(
+        self,
+        lhs: Value<'v>,
+        heap: &'v Heap,
+    ) -> Option<anyhow::Result<Value<'v>>> {
+        (self.vtable.starlark_value.rfloor_div)(StarlarkValueRawPtr::new(self.value), lhs, heap)
+    }
+
+    #[inline]
+    pub(crate) fn rbit_and(
+        self,
+        lhs: Value<'v>,
+        heap: &'v Heap,
+    ) -> Option<anyhow::Result<Value<'v>>> {
+        (self.vtable.starlark_value.rbit_and)(StarlarkValueRawPtr::new(self.value), lhs, heap)
+    }
+
+    #[inline]
+    pub(crate) fn rbit_or(
+        self,
+        lhs: Value<'v>,
+        heap: &'v Heap,
+    ) -> Option<anyhow::Result<Value<'v>>> {
+        (self.vtable.starlark_value.rbit_or)(StarlarkValueRawPtr::new(self.value), lhs, heap)
+    }
+
+    #[inline]
+    pub(crate) fn rbit_xor(
+        self,
+        lhs: Value<'v>,
+        heap: &'v Heap,
+    ) -> Option<anyhow::Result<Value<'v>>> {
+        (self.vtable.starlark_value.rbit_xor)(StarlarkValueRawPtr::new(self.value), lhs, heap)
+    }
+
+    #[inline]
+    pub(crate) fn rleft_shift(
+        self,
+        lhs: Value<'v>,
+        heap: &'v Heap,
+    ) -> Option<anyhow::Result<Value<'v>>> {
+        (self.vtable.starlark_value.rleft_shift)(StarlarkValueRawPtr::new(self.value), lhs, heap)
+    }
+
+    #[inline]
+    pub(crate) fn rright_shift(
+        self,
+        lhs: Value<'v>,
+        heap: &'v Heap,
+    ) -> Option<anyhow::Result<Value<'v>>> {
+        (self.vtable.starlark_value.rright_shift)(StarlarkValueRawPtr::new(self.value), lhs, heap)
+    }
+
     #[inline]
     pub(crate) fn sub(self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         (self.vtable.starlark_value.sub)(StarlarkValueRawPtr::new(self.value), other, heap)
@@ -487,14 +596,14 @@ impl<'v> AValueDyn<'v> {
     }
 
     #[inline]
-    pub(crate) fn invoke_method(
+    pub(crate) fn invoke_as_method(
         self,
         me: Value<'v>,
         this: Value<'v>,
         args: &Arguments<'v, '_>,
         eval: &mut Evaluator<'v, '_>,
     ) -> anyhow::Result<Value<'v>> {
-        (self.vtable.starlark_value.invoke_method)(
+        (self.vtable.starlark_value.invoke_as_method)(
             StarlarkValueRawPtr::new(self.value),
             me,
             this,
@@ -504,6 +613,23 @@ impl<'v> AValueDyn<'v> {
         )
     }
 
+    #[inline]
+    pub(crate) fn invoke_method(
+        self,
+        me: Value<'v>,
+        name: &str,
+        args: &Arguments<'v, '_>,
+        eval: &mut Evaluator<'v, '_>,
+    ) -> Option<anyhow::Result<Value<'v>>> {
+        (self.vtable.starlark_value.invoke_method)(
+            StarlarkValueRawPtr::new(self.value),
+            me,
+            name,
+            args,
+            eval,
+        )
+    }
+
     #[inline]
     pub(crate) fn name_for_call_stack(self, me: Value<'v>) -> String {
         (self.vtable.starlark_value.name_for_call_stack)(StarlarkValueRawPtr::new(self.value), me)
@@ -523,6 +649,47 @@ impl<'v> AValueDyn<'v> {
         (self.vtable.starlark_value.set_at)(StarlarkValueRawPtr::new(self.value), index, new_value)
     }
 
+    #[inline]
+    pub(crate) fn set_slice(
+        self,
+        start: Option<Value<'v>>,
+        stop: Option<Value<'v>>,
+        stride: Option<Value<'v>>,
+        new_value: Value<'v>,
+        heap: &'v Heap,
+    ) -> anyhow::Result<()> {
+        (self.vtable.starlark_value.set_slice)(
+            StarlarkValueRawPtr::new(self.value),
+            start,
+            stop,
+            stride,
+            new_value,
+            heap,
+        )
+    }
+
+    #[inline]
+    pub(crate) fn del_at(self, index: Value<'v>) -> anyhow::Result<()> {
+        (self.vtable.starlark_value.del_at)(StarlarkValueRawPtr::new(self.value), index)
+    }
+
+    #[inline]
+    pub(crate) fn del_slice(
+        self,
+        start: Option<Value<'v>>,
+        stop: Option<Value<'v>>,
+        stride: Option<Value<'v>>,
+        heap: &'v Heap,
+    ) -> anyhow::Result<()> {
+        (self.vtable.starlark_value.del_slice)(
+            StarlarkValueRawPtr::new(self.value),
+            start,
+            stop,
+            stride,
+            heap,
+        )
+    }
+
     #[inline]
     pub(crate) fn set_attr(self, attribute: &str, new_value: Value<'v>) -> anyhow::Result<()> {
         (self.vtable.starlark_value.set_attr)(
@@ -44,12 +44,14 @@ pub use crate::values::alloc_value::AllocValue;
 pub use crate::values::error::ValueError;
 pub use crate::values::freeze::Freeze;
 pub use crate::values::frozen_ref::FrozenRef;
+pub use crate::values::json::JsonOptions;
 pub use crate::values::layout::heap::heap_type::Freezer;
 pub use crate::values::layout::heap::heap_type::FrozenHeap;
 pub use crate::values::layout::heap::heap_type::FrozenHeapRef;
 pub use crate::values::layout::heap::heap_type::Heap;
 pub use crate::values::layout::heap::heap_type::Tracer;
 pub use crate::values::layout::heap::profile::aggregated::AggregateHeapProfileInfo;
+pub use crate::values::layout::heap::profile::by_type::HeapSummary;
 pub use crate::values::layout::identity::ValueIdentity;
 pub use crate::values::layout::static_string::constant_string;
 pub use crate::values::layout::static_string::StarlarkStrNRepr;
@@ -68,17 +70,21 @@ pub use crate::values::traits::ComplexValue;
 pub use crate::values::traits::StarlarkValue;
 pub use crate::values::types::any;
 pub use crate::values::types::array;
+pub use crate::values::types::bigint;
 pub use crate::values::types::bool;
+pub use crate::values::types::bytes;
 pub use crate::values::types::dict;
 pub use crate::values::types::enumeration;
 pub use crate::values::types::float;
 pub use crate::values::types::function;
 pub use crate::values::types::int;
+pub use crate::values::types::lazy_list;
 pub use crate::values::types::list;
 pub use crate::values::types::none;
 pub use crate::values::types::range;
 pub use crate::values::types::record;
 pub use crate::values::types::regex;
+pub use crate::values::types::set;
 pub use crate::values::types::string;
 pub use crate::values::types::structs;
 pub use crate::values::types::tuple;
@@ -91,12 +97,15 @@ mod comparison;
 // Submodules
 mod alloc_value;
 pub(crate) mod basic;
+mod deep_clone;
+pub mod demand;
 pub mod docs;
 pub(crate) mod error;
 mod freeze;
 pub(crate) mod frozen_ref;
 mod index;
 pub(crate) mod iter;
+mod json;
 pub(crate) mod layout;
 pub(crate) mod num;
 mod owned;
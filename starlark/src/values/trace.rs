@@ -158,6 +158,10 @@ unsafe impl<'v> Trace<'v> for FrozenValue {
     fn trace(&mut self, _tracer: &Tracer<'v>) {}
 }
 
+unsafe impl<'v> Trace<'v> for () {
+    fn trace(&mut self, _tracer: &Tracer<'v>) {}
+}
+
 unsafe impl<'v> Trace<'v> for String {
     fn trace(&mut self, _tracer: &Tracer<'v>) {}
 }
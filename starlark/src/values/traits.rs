@@ -42,6 +42,7 @@ use crate::environment::Methods;
 use crate::eval::Arguments;
 use crate::eval::Evaluator;
 use crate::private::Private;
+use crate::values::demand::Demand;
 use crate::values::docs::DocItem;
 use crate::values::error::ControlError;
 use crate::values::function::FUNCTION_TYPE;
@@ -265,6 +266,15 @@ pub trait StarlarkValue<'v>: 'v + ProvidesStaticType + Debug + Display + Seriali
         Self::get_methods().map(|methods| methods.documentation())
     }
 
+    /// Answer requests for data of other types, made with [`Value::request_value`]
+    /// or [`Value::request_ref`] without knowing the concrete type of this value.
+    /// Call [`Demand::provide_value`] or [`Demand::provide_ref`] for each type
+    /// this value can supply, e.g. a native handle or a trait object implemented by
+    /// several value types. The default implementation provides nothing.
+    ///
+    /// See the [`demand`](crate::values::demand) module for an example.
+    fn provide(&'v self, _demand: &mut Demand<'_, 'v>) {}
+
     /// Return a string representation of self, as returned by the `repr()` function.
     /// Defaults to the `Display` instance - which should be fine for nearly all types.
     /// In many cases the `repr()` representation will also be a Starlark expression
@@ -354,6 +364,14 @@ pub trait StarlarkValue<'v>: 'v + ProvidesStaticType + Debug + Display + Seriali
     /// Compare `self` with `other`.
     /// This method returns a result of type [`Ordering`], or an [`Err`]
     /// if the two types differ.
+    ///
+    /// This implements `<`, `<=`, `>` and `>=`, and the ordering used by
+    /// functions like `sorted`, `min` and `max`. An error is reported as the
+    /// failure of the expression, so a partially ordered type should return an error
+    /// for values which can't be ordered, rather than any [`Ordering`].
+    /// `==` and `!=` only use [`equals`](StarlarkValue::equals), so such values
+    /// can still be checked for equality.
+    /// When this returns [`Ordering::Equal`], `equals` should return `true`.
     fn compare(&self, other: Value<'v>) -> anyhow::Result<Ordering> {
         ValueError::unsupported_with(self, "compare", other)
     }
@@ -374,7 +392,7 @@ pub trait StarlarkValue<'v>: 'v + ProvidesStaticType + Debug + Display + Seriali
     /// This is an internal operation, and does not need to be implemented
     /// or used outside of the Starlark crate.
     #[doc(hidden)]
-    fn invoke_method(
+    fn invoke_as_method(
         &self,
         _me: Value<'v>,
         _this: Value<'v>,
@@ -382,7 +400,7 @@ pub trait StarlarkValue<'v>: 'v + ProvidesStaticType + Debug + Display + Seriali
         _eval: &mut Evaluator<'v, '_>,
         _sealed: Private,
     ) -> anyhow::Result<Value<'v>> {
-        unreachable!("invoke_method should only be invoked for method or attribute");
+        unreachable!("invoke_as_method should only be invoked for method or attribute");
     }
 
     /// Return the result of `a[index]` if `a` is indexable.
@@ -390,6 +408,21 @@ pub trait StarlarkValue<'v>: 'v + ProvidesStaticType + Debug + Display + Seriali
         ValueError::unsupported_with(self, "[]", index)
     }
 
+    /// Return the result of `a[index0, index1]` if `a` is indexable by two parameters,
+    /// e.g. a matrix.
+    ///
+    /// The default implementation calls [`at`](StarlarkValue::at) with the tuple `(index0, index1)`.
+    /// This method is only used when the subscript is written as two expressions,
+    /// so `t = (i, j); a[t]` still calls `at` with a tuple.
+    fn at2(
+        &self,
+        index0: Value<'v>,
+        index1: Value<'v>,
+        heap: &'v Heap,
+    ) -> anyhow::Result<Value<'v>> {
+        self.at(heap.alloc_tuple(&[index0, index1]), heap)
+    }
+
     /// Extract a slice of the underlying object if the object is indexable. The
     /// result will be object between `start` and `stop` (both of them are
     /// added length() if negative and then clamped between 0 and length()).
@@ -475,6 +508,23 @@ pub trait StarlarkValue<'v>: 'v + ProvidesStaticType + Debug + Display + Seriali
         self.get_attr(attribute.key(), heap)
     }
 
+    /// Called when a method from [`get_methods`](StarlarkValue::get_methods) is accessed
+    /// without being called, e.g. `f = x.method`, with `this` being the value itself.
+    ///
+    /// Return `Some` to use the returned value instead of a newly allocated bound method,
+    /// e.g. a cached one or a different callable. The default implementation returns `None`.
+    /// Calls like `x.method()` don't create a bound method and don't use this.
+    ///
+    /// As with [`get_attr`](StarlarkValue::get_attr), this must have no side effects.
+    fn bind_method(
+        &self,
+        _this: Value<'v>,
+        _method_name: &str,
+        _heap: &'v Heap,
+    ) -> Option<Value<'v>> {
+        None
+    }
+
     /// Return true if an attribute of name `attribute` exists for the current
     /// value.
     ///
@@ -494,8 +544,31 @@ pub trait StarlarkValue<'v>: 'v + ProvidesStaticType + Debug + Display + Seriali
         Vec::new()
     }
 
+    /// Called for a method call expression `x.name(args)` before the attribute is looked up.
+    ///
+    /// Return `None` (the default) to evaluate the call as usual, by looking up `name`
+    /// with [`get_attr`](StarlarkValue::get_attr) and calling the result. Returning `Some`
+    /// handles the call directly, which lets types with dynamic methods (e.g. proxies)
+    /// avoid allocating a bound callable per attribute. Attribute access without a call,
+    /// such as `getattr(x, "name")`, still goes through `get_attr`, so implementations
+    /// should keep the two consistent.
+    fn invoke_method(
+        &self,
+        _me: Value<'v>,
+        _name: &str,
+        _args: &Arguments<'v, '_>,
+        _eval: &mut Evaluator<'v, '_>,
+    ) -> Option<anyhow::Result<Value<'v>>> {
+        None
+    }
+
     /// Tell whether `other` is in the current value, if it is a container.
     ///
+    /// This implements `other in self` and `other not in self` for all types,
+    /// so a custom container can use its own lookup. There is no fallback to
+    /// [`iterate`](StarlarkValue::iterate): the default implementation fails,
+    /// even for iterable values.
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -571,6 +644,12 @@ pub trait StarlarkValue<'v>: 'v + ProvidesStaticType + Debug + Display + Seriali
         ValueError::unsupported_with(self, "-", other)
     }
 
+    /// `lhs - self`, called when `lhs` doesn't support `-` with this value.
+    /// Should return [`None`] to fail with the error from `lhs`.
+    fn rsub(&self, _lhs: Value<'v>, _heap: &'v Heap) -> Option<anyhow::Result<Value<'v>>> {
+        None
+    }
+
     /// Multiply the current value with `other`.
     ///
     /// # Examples
@@ -587,6 +666,12 @@ pub trait StarlarkValue<'v>: 'v + ProvidesStaticType + Debug + Display + Seriali
         ValueError::unsupported_with(self, "*", other)
     }
 
+    /// `lhs * self`, called when `lhs` doesn't support `*` with this value.
+    /// Should return [`None`] to fail with the error from `lhs`.
+    fn rmul(&self, _lhs: Value<'v>, _heap: &'v Heap) -> Option<anyhow::Result<Value<'v>>> {
+        None
+    }
+
     /// Divide the current value by `other`. Always results in a float value.
     ///
     /// # Examples
@@ -601,6 +686,12 @@ pub trait StarlarkValue<'v>: 'v + ProvidesStaticType + Debug + Display + Seriali
         ValueError::unsupported_with(self, "/", other)
     }
 
+    /// `lhs / self`, called when `lhs` doesn't support `/` with this value.
+    /// Should return [`None`] to fail with the error from `lhs`.
+    fn rdiv(&self, _lhs: Value<'v>, _heap: &'v Heap) -> Option<anyhow::Result<Value<'v>>> {
+        None
+    }
+
     /// Apply the percent operator between the current value and `other`. Usually used on
     /// strings, as per
     /// [the Starlark spec](https://github.com/google/skylark/blob/a0e5de7e63b47e716cca7226662a4c95d47bf873/doc/spec.md#string-interpolation).
@@ -631,6 +722,12 @@ pub trait StarlarkValue<'v>: 'v + ProvidesStaticType + Debug + Display + Seriali
         ValueError::unsupported_with(self, "%", other)
     }
 
+    /// `lhs % self`, called when `lhs` doesn't support `%` with this value.
+    /// Should return [`None`] to fail with the error from `lhs`.
+    fn rpercent(&self, _lhs: Value<'v>, _heap: &'v Heap) -> Option<anyhow::Result<Value<'v>>> {
+        None
+    }
+
     /// Floor division between the current value and `other`.
     ///
     /// # Examples
@@ -652,11 +749,32 @@ pub trait StarlarkValue<'v>: 'v + ProvidesStaticType + Debug + Display + Seriali
         ValueError::unsupported_with(self, "//", other)
     }
 
+    /// `lhs // self`, called when `lhs` doesn't support `//` with this value.
+    /// Should return [`None`] to fail with the error from `lhs`.
+    fn rfloor_div(&self, _lhs: Value<'v>, _heap: &'v Heap) -> Option<anyhow::Result<Value<'v>>> {
+        None
+    }
+
     /// Bitwise `&` operator.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # starlark::assert::all_true(r#"
+    /// 0xbeef & 0x0ff0 == 0x0ee0
+    /// 5 & 3 == 1
+    /// # "#);
+    /// ```
     fn bit_and(&self, other: Value<'v>, _heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         ValueError::unsupported_with(self, "&", other)
     }
 
+    /// `lhs & self`, called when `lhs` doesn't support `&` with this value.
+    /// Should return [`None`] to fail with the error from `lhs`.
+    fn rbit_and(&self, _lhs: Value<'v>, _heap: &'v Heap) -> Option<anyhow::Result<Value<'v>>> {
+        None
+    }
+
     /// Bitwise `|` operator.
     ///
     /// # Examples
@@ -673,27 +791,94 @@ pub trait StarlarkValue<'v>: 'v + ProvidesStaticType + Debug + Display + Seriali
         ValueError::unsupported_with(self, "|", other)
     }
 
+    /// `lhs | self`, called when `lhs` doesn't support `|` with this value.
+    /// Should return [`None`] to fail with the error from `lhs`.
+    fn rbit_or(&self, _lhs: Value<'v>, _heap: &'v Heap) -> Option<anyhow::Result<Value<'v>>> {
+        None
+    }
+
     /// Bitwise `^` operator.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # starlark::assert::all_true(r#"
+    /// 0xb00f ^ 0x0ee0 == 0xbeef
+    /// 5 ^ 3 == 6
+    /// # "#);
+    /// ```
     fn bit_xor(&self, other: Value<'v>, _heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         ValueError::unsupported_with(self, "^", other)
     }
 
+    /// `lhs ^ self`, called when `lhs` doesn't support `^` with this value.
+    /// Should return [`None`] to fail with the error from `lhs`.
+    fn rbit_xor(&self, _lhs: Value<'v>, _heap: &'v Heap) -> Option<anyhow::Result<Value<'v>>> {
+        None
+    }
+
     /// Bitwise `~` operator.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # starlark::assert::all_true(r#"
+    /// ~1 == -2
+    /// ~0 == -1
+    /// # "#);
+    /// ```
     fn bit_not(&self, _heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         ValueError::unsupported(self, "~")
     }
 
     /// Bitwise `<<` operator.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # starlark::assert::all_true(r#"
+    /// 1 << 4 == 16
+    /// -1 << 2 == -4
+    /// # "#);
+    /// ```
     fn left_shift(&self, other: Value<'v>, _heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         ValueError::unsupported_with(self, "<<", other)
     }
 
+    /// `lhs << self`, called when `lhs` doesn't support `<<` with this value.
+    /// Should return [`None`] to fail with the error from `lhs`.
+    fn rleft_shift(&self, _lhs: Value<'v>, _heap: &'v Heap) -> Option<anyhow::Result<Value<'v>>> {
+        None
+    }
+
     /// Bitwise `>>` operator.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # starlark::assert::all_true(r#"
+    /// 16 >> 4 == 1
+    /// -16 >> 2 == -4
+    /// # "#);
+    /// ```
     fn right_shift(&self, other: Value<'v>, _heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         ValueError::unsupported_with(self, ">>", other)
     }
 
-    /// Called when exporting a value under a specific name,
+    /// `lhs >> self`, called when `lhs` doesn't support `>>` with this value.
+    /// Should return [`None`] to fail with the error from `lhs`.
+    fn rright_shift(&self, _lhs: Value<'v>, _heap: &'v Heap) -> Option<anyhow::Result<Value<'v>>> {
+        None
+    }
+
+    /// Called when a value is assigned to a top-level variable of a module,
+    /// with the name of that variable.
+    ///
+    /// This is how Bazel-style `rule()` or `provider()` values learn the name
+    /// they are exported under, e.g. after `MyInfo = provider()` the provider can
+    /// report itself as `MyInfo`. The default implementation does nothing.
+    /// It may be called more than once (e.g. `x = v; y = x`), and is not called for
+    /// assignments to local variables or values nested inside other values.
     fn export_as(&self, _variable_name: &str, _eval: &mut Evaluator<'v, '_>) {
         // Most data types ignore how they are exported
         // but rules/providers like to use it as a helpful hint for users
@@ -713,6 +898,58 @@ pub trait StarlarkValue<'v>: 'v + ProvidesStaticType + Debug + Display + Seriali
         Err(ValueError::CannotMutateImmutableValue.into())
     }
 
+    /// Replace the slice `[start:stop:stride]` with the contents of the iterable `new_value`.
+    ///
+    /// With a stride of 1 the number of elements may change, otherwise the iterable
+    /// must have as many elements as the slice.
+    ///
+    /// ```rust
+    /// # starlark::assert::is_true(r#"
+    /// v = [1, 2, 3, 4]
+    /// v[1:3] = ["a"]
+    /// v[::2] = [0, 0]
+    /// v == [0, "a", 0]
+    /// # "#);
+    /// ```
+    fn set_slice(
+        &self,
+        _start: Option<Value<'v>>,
+        _stop: Option<Value<'v>>,
+        _stride: Option<Value<'v>>,
+        _new_value: Value<'v>,
+        _heap: &'v Heap,
+    ) -> anyhow::Result<()> {
+        ValueError::unsupported(self, "[::]=")
+    }
+
+    /// Remove the value at `index` (e.g. `del a[index]`).
+    ///
+    /// ```rust
+    /// # starlark::assert::is_true(r#"
+    /// v = [1, 2, 3]
+    /// d = {"a": 1, "b": 2}
+    /// def f():
+    ///     del v[0]
+    ///     del d["a"]
+    /// f()
+    /// v == [2, 3] and d == {"b": 2}
+    /// # "#);
+    /// ```
+    fn del_at(&self, _index: Value<'v>) -> anyhow::Result<()> {
+        ValueError::unsupported(self, "del []")
+    }
+
+    /// Remove the slice `[start:stop:stride]` (e.g. `del a[start:stop:stride]`).
+    fn del_slice(
+        &self,
+        _start: Option<Value<'v>>,
+        _stop: Option<Value<'v>>,
+        _stride: Option<Value<'v>>,
+        _heap: &'v Heap,
+    ) -> anyhow::Result<()> {
+        ValueError::unsupported(self, "del [::]")
+    }
+
     /// Set the attribute named `attribute` of the current value to
     /// `value` (e.g. `a.attribute = value`).
     fn set_attr(&self, attribute: &str, _new_value: Value<'v>) -> anyhow::Result<()> {
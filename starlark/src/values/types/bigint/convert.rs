@@ -97,6 +97,54 @@ impl AllocFrozenValue for i64 {
     }
 }
 
+impl StarlarkTypeRepr for u128 {
+    fn starlark_type_repr() -> String {
+        i32::starlark_type_repr()
+    }
+}
+
+impl<'v> AllocValue<'v> for u128 {
+    fn alloc_value(self, heap: &'v Heap) -> Value<'v> {
+        match i32::try_from(self) {
+            Ok(x) => Value::new_int(x),
+            Err(_) => StarlarkBigInt::alloc_bigint(self.into(), heap),
+        }
+    }
+}
+
+impl AllocFrozenValue for u128 {
+    fn alloc_frozen_value(self, heap: &FrozenHeap) -> FrozenValue {
+        match i32::try_from(self) {
+            Ok(x) => FrozenValue::new_int(x),
+            Err(_) => StarlarkBigInt::alloc_bigint_frozen(self.into(), heap),
+        }
+    }
+}
+
+impl StarlarkTypeRepr for i128 {
+    fn starlark_type_repr() -> String {
+        i32::starlark_type_repr()
+    }
+}
+
+impl<'v> AllocValue<'v> for i128 {
+    fn alloc_value(self, heap: &'v Heap) -> Value<'v> {
+        match i32::try_from(self) {
+            Ok(x) => Value::new_int(x),
+            Err(_) => StarlarkBigInt::alloc_bigint(self.into(), heap),
+        }
+    }
+}
+
+impl AllocFrozenValue for i128 {
+    fn alloc_frozen_value(self, heap: &FrozenHeap) -> FrozenValue {
+        match i32::try_from(self) {
+            Ok(x) => FrozenValue::new_int(x),
+            Err(_) => StarlarkBigInt::alloc_bigint_frozen(self.into(), heap),
+        }
+    }
+}
+
 impl StarlarkTypeRepr for usize {
     fn starlark_type_repr() -> String {
         i32::starlark_type_repr()
@@ -163,6 +211,18 @@ impl<'v> UnpackValue<'v> for i64 {
     }
 }
 
+impl<'v> UnpackValue<'v> for u128 {
+    fn unpack_value(value: Value<'v>) -> Option<u128> {
+        value.unpack_integer()
+    }
+}
+
+impl<'v> UnpackValue<'v> for i128 {
+    fn unpack_value(value: Value<'v>) -> Option<i128> {
+        value.unpack_integer()
+    }
+}
+
 impl<'v> UnpackValue<'v> for usize {
     fn unpack_value(value: Value<'v>) -> Option<usize> {
         value.unpack_integer()
@@ -82,6 +82,28 @@ impl StarlarkBigInt {
         &self.value
     }
 
+    /// Convert to `i32` if the value fits.
+    ///
+    /// `StarlarkBigInt` is always outside of `i32` range, so this normally returns `None`.
+    pub fn try_to_i32(&self) -> Option<i32> {
+        self.value.to_i32()
+    }
+
+    /// Convert to `i64` if the value fits.
+    pub fn to_i64(&self) -> Option<i64> {
+        self.value.to_i64()
+    }
+
+    /// Convert to `u64` if the value fits.
+    pub fn to_u64(&self) -> Option<u64> {
+        self.value.to_u64()
+    }
+
+    /// Convert to the nearest `f64`, possibly losing precision or producing infinity.
+    pub fn to_f64_lossy(&self) -> f64 {
+        self.to_f64()
+    }
+
     pub(crate) fn to_f64(&self) -> f64 {
         // `to_f64` is infallible.
         self.value.to_f64().unwrap()
@@ -89,14 +111,14 @@ impl StarlarkBigInt {
 
     pub(crate) fn alloc_bigint<'v>(value: BigInt, heap: &'v Heap) -> Value<'v> {
         match Self::try_from_bigint(value) {
-            Ok(bigint) => heap.alloc_simple(bigint),
+            Ok(bigint) => heap.alloc_bigint(bigint),
             Err(i) => Value::new_int(i),
         }
     }
 
     pub(crate) fn alloc_bigint_frozen(value: BigInt, heap: &FrozenHeap) -> FrozenValue {
         match Self::try_from_bigint(value) {
-            Ok(bigint) => heap.alloc_simple(bigint),
+            Ok(bigint) => heap.alloc_bigint(bigint),
             Err(i) => FrozenValue::new_int(i),
         }
     }
@@ -413,7 +435,10 @@ mod tests {
     use crate::collections::StarlarkHasher;
     use crate::values::float::StarlarkFloat;
     use crate::values::types::bigint::StarlarkBigInt;
+    use crate::values::Heap;
     use crate::values::StarlarkValue;
+    use crate::values::UnpackValue;
+    use crate::values::ValueLike;
 
     #[test]
     fn test_parse() {
@@ -600,6 +625,42 @@ mod tests {
         assert::eq("10.0", "10.0 % 100000000000000000000");
     }
 
+    #[test]
+    fn test_floor_div_percent_mixed_signs() {
+        // Floored division: the remainder takes the sign of the divisor,
+        // for every combination of small and big operands.
+        assert::pass(
+            r#"
+cases = [
+    (600000000000000000007, 200000000000000000000, 3, 7),
+    (600000000000000000007, -200000000000000000000, -4, -199999999999999999993),
+    (-600000000000000000007, 200000000000000000000, -4, 199999999999999999993),
+    (-600000000000000000007, -200000000000000000000, 3, -7),
+    (600000000000000000007, 3, 200000000000000000002, 1),
+    (600000000000000000007, -3, -200000000000000000003, -2),
+    (-600000000000000000007, 3, -200000000000000000003, 2),
+    (-600000000000000000007, -3, 200000000000000000002, -1),
+    (7, 200000000000000000000, 0, 7),
+    (7, -200000000000000000000, -1, -199999999999999999993),
+    (-7, 200000000000000000000, -1, 199999999999999999993),
+    (-7, -200000000000000000000, 0, -7),
+    (7, -2, -4, -1),
+    (-7, 2, -4, 1),
+    (-7, -2, 3, -1),
+    (-2147483647 - 1, -1, 2147483648, 0),
+]
+def check():
+    for a, b, q, r in cases:
+        assert_eq(a // b, q)
+        assert_eq(a % b, r)
+        assert_eq(q * b + r, a)
+check()
+"#,
+        );
+        assert::fail("600000000000000000000 // 0", "Cannot divide by zero");
+        assert::fail("600000000000000000000 % 0", "Cannot divide by zero");
+    }
+
     #[test]
     fn test_bit_and_big() {
         assert::eq(
@@ -776,5 +837,54 @@ mod tests {
             .write_hash(&mut hash2)
             .unwrap();
         assert_eq!(hash1.finish(), hash2.finish());
+        assert::all_true(
+            r#"
+hash(10000000000) == hash(10000000000.0)
+{10000000000: 1}[10000000000.0] == 1
+{1e20: 1}[100000000000000000000] == 1
+"#,
+        );
+    }
+
+    #[test]
+    fn test_alloc_unpack_128() {
+        let heap = Heap::new();
+        let v = heap.alloc(u128::MAX);
+        assert_eq!("340282366920938463463374607431768211455", v.to_str());
+        assert_eq!(Some(u128::MAX), u128::unpack_value(v));
+        assert_eq!(None, i128::unpack_value(v));
+        let v = heap.alloc(i128::MIN);
+        assert_eq!(Some(i128::MIN), i128::unpack_value(v));
+        assert_eq!(None, u128::unpack_value(v));
+        let v = heap.alloc(17u128);
+        assert_eq!(Some(17), v.unpack_int());
+    }
+
+    #[test]
+    fn test_unpack_64() {
+        let heap = Heap::new();
+        let v = heap.alloc(i64::MIN);
+        assert!(v.downcast_ref::<StarlarkBigInt>().is_some());
+        assert_eq!(Some(i64::MIN), i64::unpack_value(v));
+        assert_eq!(None, u64::unpack_value(v));
+        let v = heap.alloc(u64::MAX);
+        assert_eq!(Some(u64::MAX), u64::unpack_value(v));
+        assert_eq!(None, i64::unpack_value(v));
+        let v = heap.alloc(u128::from(u64::MAX) + 1);
+        assert_eq!(None, u64::unpack_value(v));
+        assert_eq!(Some(-3), i64::unpack_value(heap.alloc(-3)));
+        assert_eq!(None, u64::unpack_value(heap.alloc(-3)));
+    }
+
+    #[test]
+    fn test_conversions() {
+        let big = StarlarkBigInt::unchecked_new(BigInt::from(i64::MIN));
+        assert_eq!(None, big.try_to_i32());
+        assert_eq!(Some(i64::MIN), big.to_i64());
+        assert_eq!(None, big.to_u64());
+        assert_eq!(i64::MIN as f64, big.to_f64_lossy());
+        let big = StarlarkBigInt::unchecked_new(BigInt::from(u64::MAX));
+        assert_eq!(None, big.to_i64());
+        assert_eq!(Some(u64::MAX), big.to_u64());
     }
 }
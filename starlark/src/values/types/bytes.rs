@@ -0,0 +1,256 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! The bytes type, an immutable sequence of bytes created with `bytes()`.
+//!
+//! Unlike strings, bytes can hold arbitrary binary data which is not valid UTF-8.
+//! Indexing a bytes value gives an `int`, slicing gives another bytes value.
+//!
+//! ```
+//! # starlark::assert::all_true(r#"
+//! bytes("hello") == bytes([104, 101, 108, 108, 111])
+//! len(bytes("√")) == 3
+//! bytes("abc")[1] == 98
+//! bytes("abc")[1:] == bytes("bc")
+//! bytes("ab") + bytes("c") == bytes("abc")
+//! # "#);
+//! ```
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Write;
+use std::hash::Hash;
+
+use gazebo::any::ProvidesStaticType;
+
+use crate as starlark;
+use crate::collections::StarlarkHasher;
+use crate::values::index::apply_slice;
+use crate::values::index::convert_index;
+use crate::values::type_repr::StarlarkTypeRepr;
+use crate::values::AllocValue;
+use crate::values::Heap;
+use crate::values::StarlarkValue;
+use crate::values::UnpackValue;
+use crate::values::Value;
+use crate::values::ValueError;
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum BytesError {
+    #[error("bytes() expects ints in the range 0-255, got `{0}`")]
+    InvalidByte(String),
+}
+
+/// Representation of the `bytes` type.
+#[derive(
+    Clone,
+    Default,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    ProvidesStaticType,
+    NoSerialize,
+    StarlarkDocs
+)]
+#[starlark_docs_attrs(builtin = "extension")]
+pub struct StarlarkBytes(Box<[u8]>);
+
+starlark_simple_value!(StarlarkBytes);
+
+impl StarlarkBytes {
+    /// The result of calling `type()` on bytes.
+    pub const TYPE: &'static str = "bytes";
+
+    /// Create a new bytes value.
+    pub fn new(bytes: impl Into<Box<[u8]>>) -> Self {
+        Self(bytes.into())
+    }
+
+    /// The contents of this bytes value.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for StarlarkBytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self::new(bytes)
+    }
+}
+
+impl Display for StarlarkBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("b\"")?;
+        for b in self.0.iter() {
+            match *b {
+                b'"' => f.write_str("\\\"")?,
+                b'\\' => f.write_str("\\\\")?,
+                b'\n' => f.write_str("\\n")?,
+                b'\r' => f.write_str("\\r")?,
+                b'\t' => f.write_str("\\t")?,
+                b if b.is_ascii_graphic() || b == b' ' => f.write_char(b as char)?,
+                b => write!(f, "\\x{:02x}", b)?,
+            }
+        }
+        f.write_char('"')
+    }
+}
+
+impl<'v> StarlarkValue<'v> for StarlarkBytes {
+    starlark_type!(StarlarkBytes::TYPE);
+
+    fn collect_repr(&self, s: &mut String) {
+        write!(s, "{}", self).unwrap()
+    }
+
+    fn to_bool(&self) -> bool {
+        !self.0.is_empty()
+    }
+
+    fn write_hash(&self, hasher: &mut StarlarkHasher) -> anyhow::Result<()> {
+        self.0.hash(hasher);
+        Ok(())
+    }
+
+    fn equals(&self, other: Value<'v>) -> anyhow::Result<bool> {
+        match StarlarkBytes::from_value(other) {
+            None => Ok(false),
+            Some(other) => Ok(self == other),
+        }
+    }
+
+    fn compare(&self, other: Value<'v>) -> anyhow::Result<Ordering> {
+        match StarlarkBytes::from_value(other) {
+            None => ValueError::unsupported_with(self, "cmp()", other),
+            Some(other) => Ok(self.cmp(other)),
+        }
+    }
+
+    fn at(&self, index: Value, _heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        let i = convert_index(index, self.0.len() as i32)? as usize;
+        Ok(Value::new_int(self.0[i] as i32))
+    }
+
+    fn length(&self) -> anyhow::Result<i32> {
+        Ok(self.0.len() as i32)
+    }
+
+    fn is_in(&self, other: Value<'v>) -> anyhow::Result<bool> {
+        // Like Python, `x in b` works for both a single byte and a subsequence.
+        if let Some(needle) = StarlarkBytes::from_value(other) {
+            Ok(needle.0.is_empty() || self.0.windows(needle.0.len()).any(|w| w == &*needle.0))
+        } else if let Some(i) = other.unpack_int() {
+            Ok(u8::try_from(i).map_or(false, |b| self.0.contains(&b)))
+        } else {
+            ValueError::unsupported_with(self, "in", other)
+        }
+    }
+
+    fn slice(
+        &self,
+        start: Option<Value>,
+        stop: Option<Value>,
+        stride: Option<Value>,
+        heap: &'v Heap,
+    ) -> anyhow::Result<Value<'v>> {
+        Ok(heap.alloc(StarlarkBytes::from(apply_slice(
+            &self.0, start, stop, stride,
+        )?)))
+    }
+
+    fn add(&self, other: Value<'v>, heap: &'v Heap) -> Option<anyhow::Result<Value<'v>>> {
+        let other = StarlarkBytes::from_value(other)?;
+        let mut res = Vec::with_capacity(self.0.len() + other.0.len());
+        res.extend_from_slice(&self.0);
+        res.extend_from_slice(&other.0);
+        Some(Ok(heap.alloc(StarlarkBytes::from(res))))
+    }
+}
+
+impl StarlarkTypeRepr for Vec<u8> {
+    fn starlark_type_repr() -> String {
+        StarlarkBytes::get_type_starlark_repr()
+    }
+}
+
+impl<'v> AllocValue<'v> for Vec<u8> {
+    fn alloc_value(self, heap: &'v Heap) -> Value<'v> {
+        heap.alloc(StarlarkBytes::from(self))
+    }
+}
+
+impl<'v> UnpackValue<'v> for Vec<u8> {
+    fn unpack_value(value: Value<'v>) -> Option<Self> {
+        Some(StarlarkBytes::from_value(value)?.0.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert;
+    use crate::values::bytes::StarlarkBytes;
+    use crate::values::Heap;
+    use crate::values::UnpackValue;
+
+    #[test]
+    fn test_bytes() {
+        assert::pass(
+            r#"
+b = bytes("a\"\n\x00é")
+assert_eq(repr(b), 'b"a\\"\\n\\x00\\xc3\\xa9"')
+assert_eq(type(b), "bytes")
+assert_eq(len(b), 6)
+assert_eq(b[0], 97)
+assert_eq(b[-1], 0xa9)
+assert_eq(b[1:3], bytes("\"\n"))
+assert_eq(b[::-1][0], 0xa9)
+assert_eq(bytes([1, 2]) + bytes([3]), bytes([1, 2, 3]))
+assert_eq(bytes(bytes("x")), bytes("x"))
+assert_true(bytes("abc") < bytes("abd"))
+assert_true(98 in bytes("abc"))
+assert_true(bytes("bc") in bytes("abc"))
+assert_eq({bytes("k"): 1}[bytes("k")], 1)
+assert_true(not bytes(""))
+assert_true(bytes("x") != "x")
+"#,
+        );
+        assert::fail("bytes([256])", "0-255");
+        assert::fail("bytes(['a'])", "0-255");
+        assert::fail("bytes('abc')[3]", "out of bound");
+        assert::fail("bytes('abc') + 'd'", "not supported");
+    }
+
+    #[test]
+    fn test_alloc_unpack_vec_u8() {
+        let heap = Heap::new();
+        let v = heap.alloc(vec![0u8, 255]);
+        assert_eq!("b\"\\x00\\xff\"", v.to_repr());
+        assert_eq!(Some(vec![0u8, 255]), Vec::<u8>::unpack_value(v));
+        assert_eq!(None, Vec::<u8>::unpack_value(heap.alloc(vec![0, 255])));
+    }
+
+    #[test]
+    fn test_display_escapes() {
+        // Not valid UTF-8, with control characters, DEL and a high byte.
+        let b = StarlarkBytes::new(vec![b'a', 0x01, b' ', 0x7f, 0x80, 0xff, b'~']);
+        assert_eq!(r#"b"a\x01 \x7f\x80\xff~""#, b.to_string());
+    }
+}
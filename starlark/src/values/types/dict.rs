@@ -21,6 +21,7 @@ use std::any::TypeId;
 use std::cell::Ref;
 use std::cell::RefCell;
 use std::cell::RefMut;
+use std::cmp::Ordering;
 use std::fmt;
 use std::fmt::Debug;
 use std::fmt::Display;
@@ -294,6 +295,26 @@ impl<'v> Dict<'v> {
         self.content.iter_hashed().map(|(l, r)| (l.copied(), *r))
     }
 
+    /// Iterate through the key/value pairs in the dictionary ordered by keys.
+    ///
+    /// Keys are ordered using Starlark comparison, so this fails
+    /// if some keys are not comparable with each other (e.g. `int` and `str`).
+    pub fn iter_sorted(
+        &self,
+    ) -> anyhow::Result<impl ExactSizeIterator<Item = (Value<'v>, Value<'v>)>> {
+        let mut entries: Vec<(Value<'v>, Value<'v>)> = self.iter().collect();
+        let mut compare_ok = Ok(());
+        entries.sort_by(|x, y| match x.0.compare(y.0) {
+            Ok(r) => r,
+            Err(e) => {
+                compare_ok = Err(e);
+                Ordering::Equal // does not matter
+            }
+        });
+        compare_ok?;
+        Ok(entries.into_iter())
+    }
+
     /// Iterator over keys.
     pub fn keys<'a>(&'a self) -> impl Iterator<Item = Value<'v>> + 'a {
         self.content.keys().copied()
@@ -397,7 +418,12 @@ impl FrozenDict {
     /// Get the value associated with a particular key. Will be [`Err`] if the key is not hashable,
     /// and otherwise [`Some`] if the key exists in the dictionary and [`None`] otherwise.
     pub fn get<'v>(&self, key: Value<'v>) -> anyhow::Result<Option<FrozenValue>> {
-        Ok(self.content.get_hashed(key.get_hashed()?.borrow()).copied())
+        Ok(self.get_hashed(key.get_hashed()?))
+    }
+
+    /// Lookup the value by the given prehashed key.
+    pub fn get_hashed<'v>(&self, key: Hashed<Value<'v>>) -> Option<FrozenValue> {
+        self.content.get_hashed(key.borrow()).copied()
     }
 
     /// Get the value associated with a particular string. Equivalent to allocating the
@@ -418,6 +444,8 @@ impl<'v> Freeze for DictGen<RefCell<Dict<'v>>> {
 trait DictLike<'v>: Debug {
     fn content(&self) -> ARef<SmallMap<Value<'v>, Value<'v>>>;
     fn set_at(&self, index: Hashed<Value<'v>>, value: Value<'v>) -> anyhow::Result<()>;
+    /// Remove the key, returning the value if it was present.
+    fn del_at(&self, index: Hashed<Value<'v>>) -> anyhow::Result<Option<Value<'v>>>;
 }
 
 impl<'v> DictLike<'v> for RefCell<Dict<'v>> {
@@ -434,6 +462,13 @@ impl<'v> DictLike<'v> for RefCell<Dict<'v>> {
             Err(_) => Err(ValueError::MutationDuringIteration.into()),
         }
     }
+
+    fn del_at(&self, index: Hashed<Value<'v>>) -> anyhow::Result<Option<Value<'v>>> {
+        match self.try_borrow_mut() {
+            Ok(mut xs) => Ok(xs.remove_hashed(index)),
+            Err(_) => Err(ValueError::MutationDuringIteration.into()),
+        }
+    }
 }
 
 impl<'v> DictLike<'v> for FrozenDict {
@@ -444,6 +479,10 @@ impl<'v> DictLike<'v> for FrozenDict {
     fn set_at(&self, _index: Hashed<Value<'v>>, _value: Value<'v>) -> anyhow::Result<()> {
         Err(ValueError::CannotMutateImmutableValue.into())
     }
+
+    fn del_at(&self, _index: Hashed<Value<'v>>) -> anyhow::Result<Option<Value<'v>>> {
+        Err(ValueError::CannotMutateImmutableValue.into())
+    }
 }
 
 pub(crate) fn dict_methods() -> Option<&'static Methods> {
@@ -539,6 +578,13 @@ where
         self.0.set_at(index, alloc_value)
     }
 
+    fn del_at(&self, index: Value<'v>) -> anyhow::Result<()> {
+        match self.0.del_at(index.get_hashed()?)? {
+            Some(_) => Ok(()),
+            None => Err(ValueError::KeyNotFound(index.to_repr()).into()),
+        }
+    }
+
     fn bit_or(&self, rhs: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         let rhs = Dict::from_value(rhs)
             .map_or_else(|| ValueError::unsupported_with(self, "|", rhs), Ok)?;
@@ -704,6 +750,39 @@ b1 and b2 and b3
         Ok(())
     }
 
+    #[test]
+    fn test_reuse_hashed_key() -> anyhow::Result<()> {
+        let heap = Heap::new();
+        let key = heap.alloc((1, "x")).get_hashed()?;
+        let mut d1 = Dict::default();
+        let mut d2 = Dict::default();
+        d1.insert_hashed(key, Value::new_int(1));
+        d2.insert_hashed(key, Value::new_int(2));
+        assert_eq!(d1.get_hashed(key).unwrap().unpack_int(), Some(1));
+        assert_eq!(d2.get_hashed(key).unwrap().unpack_int(), Some(2));
+        assert_eq!(d1.remove_hashed(key).unwrap().unpack_int(), Some(1));
+        assert_eq!(d1.get_hashed(key), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_iter_sorted() -> anyhow::Result<()> {
+        let heap = Heap::new();
+        let mut d = Dict::default();
+        for (k, v) in [("b", 1), ("c", 2), ("a", 3)] {
+            d.insert_hashed(heap.alloc(k).get_hashed()?, Value::new_int(v));
+        }
+        let sorted: Vec<String> = d
+            .iter_sorted()?
+            .map(|(k, v)| format!("{}={}", k.to_str(), v))
+            .collect();
+        assert_eq!(vec!["a=3", "b=1", "c=2"], sorted);
+
+        d.insert_hashed(Value::new_int(1).get_hashed()?, Value::new_int(4));
+        assert!(d.iter_sorted().is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_repr_cycle() {
         assert::eq("d = {}; d[17] = d; repr(d)", "'{17: {...}}'");
@@ -152,6 +152,26 @@ pub(crate) fn write_compact<W: fmt::Write>(
     }
 }
 
+/// Like [`write_compact`], but in scientific notation writes as many digits as are needed
+/// to parse the output back to the same float, e.g. `1.2345678e+20` rather than `1.234568e+20`.
+pub(crate) fn write_shortest<W: fmt::Write>(output: &mut W, f: f64) -> fmt::Result {
+    if f.is_finite() && f != 0.0 && (f.abs().log10().floor() as i32).abs() >= WRITE_PRECISION as i32
+    {
+        // Rust writes the shortest representation which parses back to `f`, e.g. `1.5e-7`,
+        // so only the exponent needs adjusting to our `1.5e-07` style.
+        let s = format!("{:e}", f);
+        let (mantissa, exponent) = s.split_once('e').unwrap();
+        write!(
+            output,
+            "{}e{:+03}",
+            mantissa,
+            exponent.parse::<i32>().unwrap()
+        )
+    } else {
+        write_compact(output, f, 'e')
+    }
+}
+
 /// Runtime representation of Starlark `float` type.
 #[derive(Clone, Dupe, Copy, Debug, ProvidesStaticType, Serialize, StarlarkDocs)]
 #[starlark_docs_attrs(builtin = "standard")]
@@ -237,7 +257,7 @@ where
 
 impl Display for StarlarkFloat {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write_compact(f, self.0, 'e')
+        write_shortest(f, self.0)
     }
 }
 
@@ -405,6 +425,26 @@ mod tests {
         assert_eq!(compact(1e300), "1e+300");
     }
 
+    fn shortest(f: f64) -> String {
+        let mut buf = String::new();
+        write_shortest(&mut buf, f).unwrap();
+        buf
+    }
+
+    #[test]
+    fn test_write_shortest() {
+        assert_eq!(shortest(f64::NAN), "nan");
+        assert_eq!(shortest(0f64), "0.0");
+        assert_eq!(shortest(-0f64), "-0.0");
+        assert_eq!(shortest(std::f64::consts::PI), "3.141592653589793");
+        assert_eq!(shortest(123456.5), "123456.5");
+        assert_eq!(shortest(1e10), "1e+10");
+        assert_eq!(shortest(1234567.8), "1.2345678e+06");
+        assert_eq!(shortest(-1.23456789e-145), "-1.23456789e-145");
+        assert_eq!(shortest(f64::MAX), "1.7976931348623157e+308");
+        assert_eq!(shortest(5e-324), "5e-324");
+    }
+
     #[test]
     fn test_arithmetic_operators() {
         assert::all_true(
@@ -421,6 +461,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_str_round_trip() {
+        assert::pass(
+            r#"
+for x in [float("inf"), float("-inf"), float("nan"), 0.0, -0.0, 1.5, 1e300, -1e-300, 1.23456789e20]:
+    s = str(x)
+    assert_eq(str(float(s)), s)
+    assert_eq(repr(float(repr(x))), repr(x))
+assert_eq(str([float("inf"), float("nan")]), "[+inf, nan]")
+assert_eq(float("Infinity"), float("+inf"))
+assert_eq(str(float("NaN")), "nan")
+"#,
+        );
+    }
+
     #[test]
     fn test_dictionary_key() {
         assert::pass(
@@ -278,7 +278,7 @@ starlark_simple_value!(NativeMethod);
 impl<'v> StarlarkValue<'v> for NativeMethod {
     starlark_type!("native_method");
 
-    fn invoke_method(
+    fn invoke_as_method(
         &self,
         _me: Value<'v>,
         this: Value<'v>,
@@ -319,7 +319,7 @@ impl NativeAttribute {
 impl<'v> StarlarkValue<'v> for NativeAttribute {
     starlark_type!("attribute");
 
-    fn invoke_method(
+    fn invoke_as_method(
         &self,
         _me: Value<'v>,
         this: Value<'v>,
@@ -373,7 +373,7 @@ where
         args: &Arguments<'v, '_>,
         eval: &mut Evaluator<'v, '_>,
     ) -> anyhow::Result<Value<'v>> {
-        self.method.invoke_method(
+        self.method.invoke_as_method(
             self.method.to_value(),
             self.this.to_value(),
             args,
@@ -0,0 +1,200 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! A type [`LazyList`] whose elements are produced on demand by a Rust iterator.
+//!
+//! Module functions producing a large sequence can return a [`LazyList`] instead of
+//! a [`Vec`], and the elements will be allocated one at a time as Starlark iterates over them.
+//! Alternatively, a lazy list can be allocated directly with [`Heap::alloc_lazy_list`].
+//!
+//! ```
+//! #![feature(box_syntax)]
+//! #[macro_use]
+//! extern crate starlark;
+//! # fn main() {
+//! use starlark::assert::Assert;
+//! use starlark::environment::GlobalsBuilder;
+//! use starlark::values::lazy_list::LazyList;
+//!
+//! #[starlark_module]
+//! fn globals(builder: &mut GlobalsBuilder) {
+//!     fn naturals() -> anyhow::Result<LazyList> {
+//!         Ok(LazyList::new(|| 0..))
+//!     }
+//! }
+//!
+//! let mut a = Assert::new();
+//! a.globals_add(globals);
+//! a.pass(r#"
+//! total = 0
+//! for x in naturals():
+//!     if x == 10:
+//!         break
+//!     total += x
+//! assert_eq(total, 45)
+//! "#);
+//! # }
+//! ```
+
+use std::fmt;
+use std::fmt::Debug;
+use std::fmt::Display;
+
+use gazebo::any::ProvidesStaticType;
+
+use crate as starlark;
+use crate::values::AllocValue;
+use crate::values::Heap;
+use crate::values::StarlarkValue;
+use crate::values::Value;
+
+/// Type-erased function producing the elements of a [`LazyList`].
+trait LazyListGen: Send + Sync + 'static {
+    fn elements<'v>(&self, heap: &'v Heap) -> Box<dyn Iterator<Item = Value<'v>> + 'v>;
+}
+
+struct LazyListFn<F>(F);
+
+impl<F, I> LazyListGen for LazyListFn<F>
+where
+    F: Fn() -> I + Send + Sync + 'static,
+    I: Iterator + 'static,
+    I::Item: for<'a> AllocValue<'a>,
+{
+    fn elements<'v>(&self, heap: &'v Heap) -> Box<dyn Iterator<Item = Value<'v>> + 'v> {
+        box (self.0)().map(move |x| heap.alloc(x))
+    }
+}
+
+/// A sequence whose elements are produced by a Rust iterator each time it is iterated,
+/// so they are never all held in memory at once. Constructed with [`new`](LazyList::new).
+///
+/// A lazy list can be iterated (e.g. in a `for` loop, or converted with `list()`),
+/// but does not support indexing or `len()`.
+#[derive(ProvidesStaticType, NoSerialize)]
+pub struct LazyList(Box<dyn LazyListGen>);
+
+impl LazyList {
+    /// The result of calling `type()` on a lazy list.
+    pub const TYPE: &'static str = "lazy_list";
+
+    /// Create a lazy list, calling `f` to obtain a fresh iterator every time it is iterated.
+    pub fn new<F, I>(f: F) -> Self
+    where
+        F: Fn() -> I + Send + Sync + 'static,
+        I: Iterator + 'static,
+        I::Item: for<'a> AllocValue<'a>,
+    {
+        LazyList(box LazyListFn(f))
+    }
+}
+
+impl Debug for LazyList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyList").finish_non_exhaustive()
+    }
+}
+
+impl Display for LazyList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<{}>", LazyList::TYPE)
+    }
+}
+
+impl<'v> AllocValue<'v> for LazyList {
+    fn alloc_value(self, heap: &'v Heap) -> Value<'v> {
+        heap.alloc_simple(self)
+    }
+}
+
+impl<'v> StarlarkValue<'v> for LazyList {
+    starlark_type!(LazyList::TYPE);
+
+    fn iterate<'a>(
+        &'a self,
+        heap: &'v Heap,
+    ) -> anyhow::Result<Box<dyn Iterator<Item = Value<'v>> + 'a>>
+    where
+        'v: 'a,
+    {
+        Ok(self.0.elements(heap))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    use gazebo::dupe::Dupe;
+
+    use crate as starlark;
+    use crate::assert::Assert;
+    use crate::environment::GlobalsBuilder;
+    use crate::values::lazy_list::LazyList;
+    use crate::values::Heap;
+
+    #[test]
+    fn test_lazy_list() {
+        #[starlark_module]
+        fn module(builder: &mut GlobalsBuilder) {
+            fn squares(n: i32) -> anyhow::Result<LazyList> {
+                Ok(LazyList::new(move || (0..n).map(|x| x * x)))
+            }
+        }
+
+        let mut a = Assert::new();
+        a.globals_add(module);
+        a.pass(
+            r#"
+xs = squares(4)
+assert_eq(type(xs), "lazy_list")
+assert_eq(list(xs), [0, 1, 4, 9])
+# Iterating again starts from the beginning.
+assert_eq([x for x in xs if x % 2 == 0], [0, 4])
+"#,
+        );
+        a.fail("len(squares(3))", "not supported");
+        a.fail("squares(3)[0]", "not supported");
+    }
+
+    #[test]
+    fn test_alloc_lazy_list_streams() {
+        let produced = Arc::new(AtomicUsize::new(0));
+        let heap = Heap::new();
+        let xs = heap.alloc_lazy_list({
+            let produced = produced.dupe();
+            move || {
+                let produced = produced.dupe();
+                (0..).map(move |x| {
+                    produced.fetch_add(1, Ordering::SeqCst);
+                    format!("item{}", x)
+                })
+            }
+        });
+        assert_eq!(0, produced.load(Ordering::SeqCst));
+        let first: Vec<String> = xs
+            .iterate(&heap)
+            .unwrap()
+            .take(3)
+            .map(|x| x.unpack_str().unwrap().to_owned())
+            .collect();
+        assert_eq!(vec!["item0", "item1", "item2"], first);
+        assert_eq!(3, produced.load(Ordering::SeqCst));
+    }
+}
@@ -48,12 +48,14 @@ use crate::values::comparison::equals_slice;
 use crate::values::error::ValueError;
 use crate::values::index::apply_slice;
 use crate::values::index::convert_index;
+use crate::values::index::convert_slice_indices;
 use crate::values::type_repr::StarlarkTypeRepr;
 use crate::values::AllocFrozenValue;
 use crate::values::AllocValue;
 use crate::values::FrozenHeap;
 use crate::values::FrozenStringValue;
 use crate::values::FrozenValue;
+use crate::values::FrozenValueTyped;
 use crate::values::Heap;
 use crate::values::StarlarkValue;
 use crate::values::UnpackValue;
@@ -257,6 +259,19 @@ impl<'v> List<'v> {
     pub(crate) fn remove(&self, index: usize) -> Value<'v> {
         self.content.get().remove(index)
     }
+
+    /// Replace the elements in `start..stop` with `values`.
+    pub(crate) fn splice(&self, start: usize, stop: usize, values: &[Value<'v>], heap: &'v Heap) {
+        let content = self.content();
+        let new_content: Vec<Value<'v>> = content[..start]
+            .iter()
+            .chain(values)
+            .chain(&content[stop..])
+            .copied()
+            .collect();
+        self.clear();
+        self.extend(new_content, heap);
+    }
 }
 
 impl<'v> Deref for ListRef<'v> {
@@ -403,12 +418,39 @@ impl FrozenList {
     {
         self.content().iter().map(|e| e.to_value())
     }
+
+    /// Iterate over the elements in the list, all of which must be of type `T`.
+    /// Every element is checked before this function returns, failing on the first
+    /// element of another type, so the iterator doesn't need to check again.
+    pub fn downcast_frozen<'a, 'v, T: StarlarkValue<'v>>(
+        &'a self,
+    ) -> anyhow::Result<impl ExactSizeIterator<Item = FrozenValueTyped<'v, T>> + 'a>
+    where
+        'v: 'a,
+    {
+        for x in self.content() {
+            FrozenValueTyped::<T>::try_new(*x)?;
+        }
+        // SAFETY: we've just checked the type of every element.
+        Ok(self
+            .content()
+            .iter()
+            .map(|x| unsafe { FrozenValueTyped::new_unchecked(*x) }))
+    }
 }
 
 // This trait need to be `pub(crate)` because `ListGen<T>` is.
 pub(crate) trait ListLike<'v>: Debug {
     fn content(&self) -> &[Value<'v>];
     fn set_at(&self, i: usize, v: Value<'v>) -> anyhow::Result<()>;
+    fn splice(
+        &self,
+        start: usize,
+        stop: usize,
+        values: &[Value<'v>],
+        heap: &'v Heap,
+    ) -> anyhow::Result<()>;
+    fn del_at(&self, i: usize) -> anyhow::Result<()>;
     fn iterate<'a>(&'a self) -> Box<dyn Iterator<Item = Value<'v>> + 'a>
     where
         'v: 'a;
@@ -429,6 +471,24 @@ impl<'v> ListLike<'v> for List<'v> {
         Ok(())
     }
 
+    fn splice(
+        &self,
+        start: usize,
+        stop: usize,
+        values: &[Value<'v>],
+        heap: &'v Heap,
+    ) -> anyhow::Result<()> {
+        self.check_can_mutate()?;
+        List::splice(self, start, stop, values, heap);
+        Ok(())
+    }
+
+    fn del_at(&self, i: usize) -> anyhow::Result<()> {
+        self.check_can_mutate()?;
+        self.remove(i);
+        Ok(())
+    }
+
     fn iterate<'a>(&'a self) -> Box<dyn Iterator<Item = Value<'v>> + 'a>
     where
         'v: 'a,
@@ -453,6 +513,20 @@ impl<'v> ListLike<'v> for FrozenList {
         Err(ValueError::CannotMutateImmutableValue.into())
     }
 
+    fn splice(
+        &self,
+        _start: usize,
+        _stop: usize,
+        _values: &[Value<'v>],
+        _heap: &'v Heap,
+    ) -> anyhow::Result<()> {
+        Err(ValueError::CannotMutateImmutableValue.into())
+    }
+
+    fn del_at(&self, _i: usize) -> anyhow::Result<()> {
+        Err(ValueError::CannotMutateImmutableValue.into())
+    }
+
     fn iterate<'a>(&'a self) -> Box<dyn Iterator<Item = Value<'v>> + 'a>
     where
         'v: 'a,
@@ -604,6 +678,65 @@ where
         let i = convert_index(index, self.0.content().len() as i32)? as usize;
         self.0.set_at(i, alloc_value)
     }
+
+    fn set_slice(
+        &self,
+        start: Option<Value<'v>>,
+        stop: Option<Value<'v>>,
+        stride: Option<Value<'v>>,
+        new_value: Value<'v>,
+        heap: &'v Heap,
+    ) -> anyhow::Result<()> {
+        #[derive(thiserror::Error, Debug)]
+        #[error("Cannot assign {0} values to an extended slice of {1} elements")]
+        struct ExtendedSliceLengthError(usize, usize);
+
+        let values = new_value.iterate_collect(heap)?;
+        let len = self.0.content().len() as i32;
+        let (start_i, stop_i, stride_i) = convert_slice_indices(len, start, stop, stride)?;
+        if stride_i == 1 {
+            // An empty slice, e.g. `x[2:1]`, inserts at `start`.
+            let stop_i = cmp::max(start_i, stop_i);
+            return self
+                .0
+                .splice(start_i as usize, stop_i as usize, &values, heap);
+        }
+        let indices = apply_slice(&(0..len).collect::<Vec<_>>(), start, stop, stride)?;
+        if indices.len() != values.len() {
+            return Err(ExtendedSliceLengthError(values.len(), indices.len()).into());
+        }
+        let mut content = self.0.content().to_vec();
+        for (i, v) in indices.into_iter().zip(values) {
+            content[i as usize] = v;
+        }
+        self.0.splice(0, content.len(), &content, heap)
+    }
+
+    fn del_at(&self, index: Value<'v>) -> anyhow::Result<()> {
+        let i = convert_index(index, self.0.content().len() as i32)? as usize;
+        self.0.del_at(i)
+    }
+
+    fn del_slice(
+        &self,
+        start: Option<Value<'v>>,
+        stop: Option<Value<'v>>,
+        stride: Option<Value<'v>>,
+        heap: &'v Heap,
+    ) -> anyhow::Result<()> {
+        let content = self.0.content();
+        let mut deleted = vec![false; content.len()];
+        let indices: Vec<usize> = (0..content.len()).collect();
+        for i in apply_slice(&indices, start, stop, stride)? {
+            deleted[i] = true;
+        }
+        let kept: Vec<Value<'v>> = content
+            .iter()
+            .zip(deleted)
+            .filter_map(|(x, deleted)| if deleted { None } else { Some(*x) })
+            .collect();
+        self.0.splice(0, content.len(), &kept, heap)
+    }
 }
 
 impl<'v, T: ListLike<'v>> Serialize for ListGen<T> {
@@ -685,6 +818,12 @@ impl<'v, V: UnpackValue<'v>> Deref for ListOf<'v, V> {
 mod tests {
     use crate::assert;
     use crate::assert::Assert;
+    use crate::values::list::FrozenList;
+    use crate::values::list::List;
+    use crate::values::string::StarlarkStr;
+    use crate::values::FrozenHeap;
+    use crate::values::Heap;
+    use crate::values::Value;
 
     #[test]
     fn test_to_str() {
@@ -715,6 +854,44 @@ v == [1, 1, [2, 3]]
         );
     }
 
+    #[test]
+    fn test_set_slice() {
+        assert::pass(
+            r#"
+def set_slice(xs, start, stop, step, new):
+    xs = list(xs)
+    xs[start:stop:step] = new
+    return xs
+x = [0, 1, 2, 3, 4]
+assert_eq(set_slice(x, 1, 3, None, ["a"]), [0, "a", 3, 4])
+assert_eq(set_slice(x, 1, 3, None, "abcd".elems()), [0, "a", "b", "c", "d", 3, 4])
+assert_eq(set_slice(x, 3, 1, None, ["a"]), [0, 1, 2, "a", 3, 4])
+assert_eq(set_slice(x, -2, None, None, []), [0, 1, 2])
+assert_eq(set_slice(x, None, None, 2, ["a", "b", "c"]), ["a", 1, "b", 3, "c"])
+assert_eq(set_slice(x, None, None, -2, ["a", "b", "c"]), ["c", 1, "b", 3, "a"])
+"#,
+        );
+        assert::is_true(
+            r#"
+x = [1, 2, 3]
+x[:] = x + x
+x == [1, 2, 3, 1, 2, 3]
+"#,
+        );
+        assert::fail(
+            "x = [1, 2, 3]\nx[::2] = [1]",
+            "Cannot assign 1 values to an extended slice of 2 elements",
+        );
+        assert::fail("x = [1, 2, 3]\nx[1:2] = 1", "not supported");
+        assert::fail("x = (1, 2, 3)\nx[1:2] = []", "not supported");
+        let mut a = Assert::new();
+        a.module("m", "frozen_list = [1, 2]");
+        a.fail(
+            "load('m', 'frozen_list')\nfrozen_list[:1] = []",
+            "Immutable",
+        );
+    }
+
     #[test]
     fn test_arithmetic_on_list() {
         assert::all_true(
@@ -758,4 +935,35 @@ def list_result():
         );
         a.is_true("load('x','list_result')\nx = list_result()\nx += [8]\nx == [1, 2, 4, 8]");
     }
+
+    #[test]
+    fn test_alloc_list_iter() {
+        let heap = Heap::new();
+        let sized = heap.alloc_list_iter((0..100).map(Value::new_int));
+        assert_eq!(100, List::from_value(sized).unwrap().content().len());
+        let filtered = heap.alloc_list_iter((0..100).filter(|x| x % 3 == 0).map(Value::new_int));
+        assert_eq!(34, List::from_value(filtered).unwrap().content().len());
+        assert_eq!("[]", heap.alloc_list_iter(std::iter::empty()).to_repr());
+    }
+
+    #[test]
+    fn test_downcast_frozen() {
+        let heap = FrozenHeap::new();
+        let strings = heap.alloc_list(&[
+            heap.alloc_str("a").to_frozen_value(),
+            heap.alloc_str("b").to_frozen_value(),
+        ]);
+        let strings = FrozenList::from_frozen_value(&strings).unwrap();
+        let res: Vec<&str> = strings
+            .downcast_frozen::<StarlarkStr>()
+            .unwrap()
+            .map(|x| x.as_str())
+            .collect();
+        assert_eq!(vec!["a", "b"], res);
+
+        let mixed = heap.alloc_list(&[heap.alloc_str("a").to_frozen_value(), heap.alloc(1)]);
+        let mixed = FrozenList::from_frozen_value(&mixed).unwrap();
+        let err = mixed.downcast_frozen::<StarlarkStr>().err().unwrap();
+        assert!(err.to_string().contains("actual `int`"), "{}", err);
+    }
 }
@@ -20,17 +20,20 @@ pub mod any_array;
 pub mod array;
 pub mod bigint;
 pub mod bool;
+pub mod bytes;
 pub mod dict;
 pub mod enumeration;
 pub mod float;
 pub mod function;
 pub mod int;
 pub(crate) mod known_methods;
+pub mod lazy_list;
 pub mod list;
 pub mod none;
 pub mod range;
 pub mod record;
 pub mod regex;
+pub mod set;
 pub mod string;
 pub mod structs;
 pub mod tuple;
@@ -74,6 +74,17 @@ impl Range {
         Range { start, stop, step }
     }
 
+    /// The elements of the range, last to first.
+    pub(crate) fn iter_rev(&self) -> anyhow::Result<impl Iterator<Item = i32>> {
+        let start = self.start;
+        let step = self.step.get();
+        // `step * i` may not fit in `i32`, but the element itself does,
+        // so wrapping arithmetic gives the right answer.
+        Ok((0..self.length()?)
+            .rev()
+            .map(move |i| start.wrapping_add(step.wrapping_mul(i))))
+    }
+
     fn equals_range(&self, other: &Range) -> anyhow::Result<bool> {
         let self_length = self.length()?;
         let other_length = other.length()?;
@@ -138,30 +149,25 @@ impl<'v> StarlarkValue<'v> for Range {
             return Ok(0);
         }
 
-        // Convert range and step to `u64`
+        // Distance and step in `i64`, where they can't overflow.
         let (dist, step) = if self.step.get() >= 0 {
-            (
-                self.stop.wrapping_sub(self.start) as u64,
-                self.step.get() as u64,
-            )
+            (self.stop as i64 - self.start as i64, self.step.get() as i64)
         } else {
             (
-                self.start.wrapping_sub(self.stop) as u64,
-                self.step.get().wrapping_neg() as u64,
+                self.start as i64 - self.stop as i64,
+                -(self.step.get() as i64),
             )
         };
-        let i = ((dist - 1) / step + 1) as i32;
-        if i >= 0 {
-            Ok(i)
-        } else {
-            Err(ValueError::IntegerOverflow.into())
-        }
+        i32::try_from((dist - 1) / step + 1).map_err(|_| ValueError::IntegerOverflow.into())
     }
 
     fn at(&self, index: Value, _heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         let index = convert_index(index, self.length()?)?;
-        // Must not overflow if `length` is computed correctly
-        Ok(Value::new_int(self.start + self.step.get() * index))
+        // The element fits in `i32` if `length` is computed correctly,
+        // but `step * index` may not.
+        Ok(Value::new_int(
+            (self.start as i64 + self.step.get() as i64 * index as i64) as i32,
+        ))
     }
 
     fn equals(&self, other: Value) -> anyhow::Result<bool> {
@@ -244,15 +250,12 @@ impl<'v> StarlarkValue<'v> for Range {
             if other < self.start || other >= self.stop {
                 return Ok(false);
             }
-            Ok((other.wrapping_sub(self.start) as u64) % (self.step.get() as u64) == 0)
+            Ok((other as i64 - self.start as i64) % (self.step.get() as i64) == 0)
         } else {
             if other > self.start || other <= self.stop {
                 return Ok(false);
             }
-            Ok(
-                (self.start.wrapping_sub(other) as u64) % (self.step.get().wrapping_neg() as u64)
-                    == 0,
-            )
+            Ok((self.start as i64 - other as i64) % -(self.step.get() as i64) == 0)
         }
     }
 }
@@ -268,6 +271,7 @@ impl PartialEq for Range {
 mod tests {
     use std::num::NonZeroI32;
 
+    use crate::assert;
     use crate::values::range::Range;
     use crate::values::Heap;
     use crate::values::StarlarkValue;
@@ -314,6 +318,8 @@ mod tests {
         assert_eq!(Some(5), range(10, 0, -2).length().ok());
         assert_eq!(Some(5), range(9, 0, -2).length().ok());
         assert_eq!(Some(1), range(4, 14, 10).length().ok());
+        assert_eq!(Some(3), range(i32::MIN, i32::MAX, i32::MAX).length().ok());
+        assert_eq!(Some(3), range(i32::MAX, i32::MIN, -i32::MAX).length().ok());
     }
 
     #[test]
@@ -321,6 +327,34 @@ mod tests {
         assert_eq!(range_stop(0), range(2, 1, 3));
     }
 
+    #[test]
+    fn test_slice_and_in() {
+        assert::all_true(
+            r#"
+5 in range(10)
+10 not in range(10)
+-1 not in range(10)
+4 in range(10, 0, -2)
+5 not in range(10, 0, -2)
+0 not in range(10, 0, -2)
+999999 in range(0, 1000000000, 3)
+"a" not in range(3)
+range(100)[10:20] == range(10, 20)
+repr(range(100)[10:20]) == "range(10, 20)"
+range(100)[::-10] == range(99, -1, -10)
+range(10, 0, -2)[1:] == range(8, 0, -2)
+range(10, 0, -2)[::-1] == range(2, 12, 2)
+range(10)[20:30] == range(0)
+range(10)[5:2] == range(0)
+len(range(10)[-100:100]) == 10
+reversed(range(-2147483647 - 1, 2147483647, 2147483647)) == [2147483646, -1, -2147483648]
+range(-2147483647 - 1, 2147483647, 2147483647)[2] == 2147483646
+2147483646 in range(-2147483647 - 1, 2147483647, 2147483647)
+2147483645 not in range(-2147483647 - 1, 2147483647, 2147483647)
+"#,
+        );
+    }
+
     #[test]
     fn test_range_exhaustive() {
         // The range implementation is fairly hairy. Lots of corner cases etc.
@@ -345,6 +379,10 @@ mod tests {
             for (i, v) in full.iter().enumerate() {
                 assert_eq!(x.at(Value::new_int(i as i32), &heap).unwrap(), *v);
             }
+            assert!(Iterator::eq(
+                x.iter_rev().unwrap().map(Value::new_int),
+                full.iter().rev().copied()
+            ));
         }
 
         // Takes 294^2 steps - but completes instantly
@@ -0,0 +1,490 @@
+/*
+ * Copyright 2018 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+//! The set type, a mutable collection of unique values, which iterates in insertion order.
+//!
+//! Sets are created with the `set()` function, and support `in`, `len`, iteration
+//! and the operators `|` (union), `&` (intersection), `-` (difference) and `^`
+//! (symmetric difference), which all return new sets.
+//!
+//! ```
+//! # starlark::assert::all_true(r#"
+//! list(set([3, 1, 3, 2])) == [3, 1, 2]
+//! 2 in set([1, 2])
+//! set([1, 2]) | set([2, 3]) == set([1, 2, 3])
+//! set([1, 2]) & set([2, 3]) == set([2])
+//! set([1, 2]) - set([2, 3]) == set([1])
+//! set([1, 2]) ^ set([2, 3]) == set([1, 3])
+//! # "#);
+//! ```
+
+use std::cell::RefCell;
+use std::cell::RefMut;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fmt::Debug;
+use std::fmt::Display;
+use std::ops::Deref;
+use std::ops::DerefMut;
+
+use gazebo::any::ProvidesStaticType;
+use gazebo::cell::ARef;
+use gazebo::coerce::coerce;
+use gazebo::coerce::Coerce;
+use indexmap::IndexSet;
+use serde::Serialize;
+
+use crate as starlark;
+use crate::collections::Hashed;
+use crate::collections::SmallMap;
+use crate::environment::Methods;
+use crate::environment::MethodsStatic;
+use crate::values::error::ValueError;
+use crate::values::iter::ARefIterator;
+use crate::values::none::NoneType;
+use crate::values::type_repr::StarlarkTypeRepr;
+use crate::values::AllocFrozenValue;
+use crate::values::AllocValue;
+use crate::values::Freeze;
+use crate::values::Freezer;
+use crate::values::FrozenHeap;
+use crate::values::FrozenValue;
+use crate::values::Heap;
+use crate::values::StarlarkValue;
+use crate::values::Trace;
+use crate::values::UnpackValue;
+use crate::values::Value;
+use crate::values::ValueLike;
+
+#[derive(Clone, Default, Trace, Debug, ProvidesStaticType)]
+struct SetGen<T>(T);
+
+/// Define the set type. See [`Set`] and [`FrozenSet`] as the two possible representations.
+#[derive(Clone, Default, Trace, Debug, ProvidesStaticType)]
+#[repr(transparent)]
+pub struct Set<'v> {
+    /// The elements of the set, which must all be hashable.
+    content: SmallMap<Value<'v>, ()>,
+}
+
+/// Define the set type. See [`Set`] and [`FrozenSet`] as the two possible representations.
+#[derive(Clone, Default, Debug, ProvidesStaticType)]
+#[repr(transparent)]
+pub struct FrozenSet {
+    /// The elements of the set, which must all be hashable.
+    content: SmallMap<FrozenValue, ()>,
+}
+
+unsafe impl<'v> Coerce<Set<'v>> for FrozenSet {}
+
+impl<'v> StarlarkTypeRepr for Set<'v> {
+    fn starlark_type_repr() -> String {
+        Set::TYPE.to_owned()
+    }
+}
+
+impl<'v> AllocValue<'v> for Set<'v> {
+    fn alloc_value(self, heap: &'v Heap) -> Value<'v> {
+        heap.alloc_complex(SetGen(RefCell::new(self)))
+    }
+}
+
+impl AllocFrozenValue for FrozenSet {
+    fn alloc_frozen_value(self, heap: &FrozenHeap) -> FrozenValue {
+        heap.alloc_simple(SetGen(self))
+    }
+}
+
+/// Borrowed `Set`.
+pub struct SetRef<'v> {
+    aref: ARef<'v, Set<'v>>,
+}
+
+/// Mutably borrowed `Set`.
+pub struct SetMut<'v> {
+    aref: RefMut<'v, Set<'v>>,
+}
+
+impl<'v> Deref for SetRef<'v> {
+    type Target = Set<'v>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.aref
+    }
+}
+
+impl<'v> Deref for SetMut<'v> {
+    type Target = Set<'v>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.aref
+    }
+}
+
+impl<'v> DerefMut for SetMut<'v> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.aref
+    }
+}
+
+impl<'v> Set<'v> {
+    /// The result of calling `type()` on sets.
+    pub const TYPE: &'static str = "set";
+
+    /// Downcast the value to a set.
+    pub fn from_value(x: Value<'v>) -> Option<SetRef<'v>> {
+        if x.unpack_frozen().is_some() {
+            x.downcast_ref::<SetGen<FrozenSet>>().map(|x| SetRef {
+                aref: ARef::new_ptr(coerce(&x.0)),
+            })
+        } else {
+            let ptr = x.downcast_ref::<SetGen<RefCell<Set<'v>>>>()?;
+            Some(SetRef {
+                aref: ARef::new_ref(ptr.0.borrow()),
+            })
+        }
+    }
+
+    /// Downcast the value to a mutable set reference.
+    pub fn from_value_mut(x: Value<'v>) -> anyhow::Result<SetMut<'v>> {
+        #[derive(thiserror::Error, Debug)]
+        #[error("Value is not set, value type: `{0}`")]
+        struct NotSetError(&'static str);
+
+        match x.downcast_ref::<SetGen<RefCell<Set<'v>>>>() {
+            None if x.downcast_ref::<SetGen<FrozenSet>>().is_some() => {
+                Err(ValueError::CannotMutateImmutableValue.into())
+            }
+            None => Err(NotSetError(x.get_type()).into()),
+            Some(ptr) => match ptr.0.try_borrow_mut() {
+                Ok(x) => Ok(SetMut { aref: x }),
+                Err(_) => Err(ValueError::MutationDuringIteration.into()),
+            },
+        }
+    }
+
+    /// Number of elements in the set.
+    pub fn len(&self) -> usize {
+        self.content.len()
+    }
+
+    /// Is the set empty?
+    pub fn is_empty(&self) -> bool {
+        self.content.is_empty()
+    }
+
+    /// Iterate through the elements in insertion order.
+    pub fn iter<'a>(&'a self) -> impl ExactSizeIterator<Item = Value<'v>> + 'a {
+        self.content.keys().copied()
+    }
+
+    /// Iterate through the elements in insertion order, retaining their hashes.
+    pub fn iter_hashed<'a>(&'a self) -> impl ExactSizeIterator<Item = Hashed<Value<'v>>> + 'a
+    where
+        'v: 'a,
+    {
+        self.content.iter_hashed().map(|(x, _)| x.copied())
+    }
+
+    /// Does the set contain the given prehashed value?
+    pub fn contains_hashed(&self, x: Hashed<Value<'v>>) -> bool {
+        self.content.contains_key_hashed(x.borrow())
+    }
+
+    /// Add a value to the end of the set, returning `false` if it was already present.
+    pub fn insert_hashed(&mut self, x: Hashed<Value<'v>>) -> bool {
+        self.content.insert_hashed(x, ()).is_none()
+    }
+
+    /// Remove a value from the set, returning `false` if it was not present.
+    /// The order of the remaining elements is unchanged.
+    pub fn remove_hashed(&mut self, x: Hashed<Value<'v>>) -> bool {
+        self.content.remove_hashed(x.borrow()).is_some()
+    }
+
+    /// Remove all elements from the set.
+    pub fn clear(&mut self) {
+        self.content.clear();
+    }
+}
+
+impl<'v> FromIterator<Hashed<Value<'v>>> for Set<'v> {
+    fn from_iter<I: IntoIterator<Item = Hashed<Value<'v>>>>(iter: I) -> Self {
+        Set {
+            content: iter.into_iter().map(|x| (x, ())).collect(),
+        }
+    }
+}
+
+impl FrozenSet {
+    /// Obtain the [`FrozenSet`] pointed at by a [`FrozenValue`].
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    // We need a lifetime because FrozenValue doesn't contain the right lifetime
+    pub fn from_frozen_value(x: &FrozenValue) -> Option<&FrozenSet> {
+        x.downcast_ref::<SetGen<FrozenSet>>().map(|x| &x.0)
+    }
+
+    /// Iterate through the elements in insertion order.
+    pub fn iter<'a>(&'a self) -> impl ExactSizeIterator<Item = FrozenValue> + 'a {
+        self.content.keys().copied()
+    }
+}
+
+impl<'v> StarlarkTypeRepr for SetRef<'v> {
+    fn starlark_type_repr() -> String {
+        Set::starlark_type_repr()
+    }
+}
+
+impl<'v> UnpackValue<'v> for SetRef<'v> {
+    fn expected() -> String {
+        Set::TYPE.to_owned()
+    }
+
+    fn unpack_value(value: Value<'v>) -> Option<SetRef<'v>> {
+        Set::from_value(value)
+    }
+}
+
+impl<'v> Freeze for SetGen<RefCell<Set<'v>>> {
+    type Frozen = SetGen<FrozenSet>;
+    fn freeze(self, freezer: &Freezer) -> anyhow::Result<Self::Frozen> {
+        let content = self.0.into_inner().content.freeze(freezer)?;
+        Ok(SetGen(FrozenSet { content }))
+    }
+}
+
+trait SetLike<'v>: Debug {
+    fn content(&self) -> ARef<Set<'v>>;
+}
+
+impl<'v> SetLike<'v> for RefCell<Set<'v>> {
+    fn content(&self) -> ARef<Set<'v>> {
+        ARef::new_ref(self.borrow())
+    }
+}
+
+impl<'v> SetLike<'v> for FrozenSet {
+    fn content(&self) -> ARef<Set<'v>> {
+        ARef::new_ptr(coerce(self))
+    }
+}
+
+impl<'v, T: SetLike<'v>> Display for SetGen<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let content = self.0.content();
+        if content.is_empty() {
+            return f.write_str("set()");
+        }
+        f.write_str("set([")?;
+        for (i, x) in content.iter().enumerate() {
+            if i != 0 {
+                f.write_str(", ")?;
+            }
+            Display::fmt(&x, f)?;
+        }
+        f.write_str("])")
+    }
+}
+
+pub(crate) fn set_methods() -> Option<&'static Methods> {
+    static RES: MethodsStatic = MethodsStatic::new();
+    RES.methods(crate::stdlib::set::set_methods)
+}
+
+impl<'v, T: SetLike<'v> + 'v> SetGen<T>
+where
+    Self: ProvidesStaticType,
+{
+    /// Combine with another set, keeping the elements of both for which `keep` holds.
+    fn combine(
+        &self,
+        op: &str,
+        rhs: Value<'v>,
+        heap: &'v Heap,
+        keep: impl Fn(bool, bool) -> bool,
+    ) -> anyhow::Result<Value<'v>> {
+        let rhs =
+            Set::from_value(rhs).map_or_else(|| ValueError::unsupported_with(self, op, rhs), Ok)?;
+        let lhs = self.0.content();
+        let res: Set = lhs
+            .iter_hashed()
+            .filter(|x| keep(true, rhs.contains_hashed(*x)))
+            .chain(
+                rhs.iter_hashed()
+                    .filter(|x| !lhs.contains_hashed(*x) && keep(false, true)),
+            )
+            .collect();
+        Ok(heap.alloc(res))
+    }
+}
+
+impl<'v, T: SetLike<'v> + 'v> StarlarkValue<'v> for SetGen<T>
+where
+    Self: ProvidesStaticType,
+{
+    starlark_type!(Set::TYPE);
+
+    fn get_methods() -> Option<&'static Methods> {
+        set_methods()
+    }
+
+    fn collect_repr_cycle(&self, collector: &mut String) {
+        collector.push_str("set(...)");
+    }
+
+    fn to_bool(&self) -> bool {
+        !self.0.content().is_empty()
+    }
+
+    fn equals(&self, other: Value<'v>) -> anyhow::Result<bool> {
+        match Set::from_value(other) {
+            None => Ok(false),
+            Some(other) => {
+                let content = self.0.content();
+                Ok(content.len() == other.len()
+                    && content.iter_hashed().all(|x| other.contains_hashed(x)))
+            }
+        }
+    }
+
+    fn extra_memory(&self) -> usize {
+        self.0.content().content.extra_memory()
+    }
+
+    fn length(&self) -> anyhow::Result<i32> {
+        Ok(self.0.content().len() as i32)
+    }
+
+    fn is_in(&self, other: Value<'v>) -> anyhow::Result<bool> {
+        Ok(self.0.content().contains_hashed(other.get_hashed()?))
+    }
+
+    fn iterate<'a>(
+        &'a self,
+        _heap: &'v Heap,
+    ) -> anyhow::Result<Box<dyn Iterator<Item = Value<'v>> + 'a>>
+    where
+        'v: 'a,
+    {
+        Ok(box ARefIterator::new(self.0.content(), |x| x.iter()))
+    }
+
+    fn with_iterator(
+        &self,
+        _heap: &'v Heap,
+        f: &mut dyn FnMut(&mut dyn Iterator<Item = Value<'v>>) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        f(&mut self.0.content().iter())
+    }
+
+    fn bit_or(&self, rhs: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        self.combine("|", rhs, heap, |_, _| true)
+    }
+
+    fn bit_and(&self, rhs: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        self.combine("&", rhs, heap, |in_lhs, in_rhs| in_lhs && in_rhs)
+    }
+
+    fn sub(&self, rhs: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        self.combine("-", rhs, heap, |in_lhs, in_rhs| in_lhs && !in_rhs)
+    }
+
+    fn bit_xor(&self, rhs: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        self.combine("^", rhs, heap, |in_lhs, in_rhs| in_lhs != in_rhs)
+    }
+}
+
+impl<'v, T: SetLike<'v>> Serialize for SetGen<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_seq(self.0.content().iter())
+    }
+}
+
+/// Rust values which always allocate to hashable Starlark values,
+/// so can be used as the elements of a Rust set allocated as a Starlark set.
+pub trait AllocHashableValue<'v>: AllocValue<'v> {}
+
+macro_rules! impl_alloc_hashable_value {
+    ($($t:ty),*) => {
+        $(impl<'v> AllocHashableValue<'v> for $t {})*
+    };
+}
+
+impl_alloc_hashable_value!(
+    NoneType,
+    bool,
+    i32,
+    u32,
+    i64,
+    u64,
+    i128,
+    u128,
+    isize,
+    usize,
+    f64,
+    char,
+    String,
+    &'_ String,
+    &'_ str
+);
+
+impl<'v, T1: AllocHashableValue<'v>> AllocHashableValue<'v> for (T1,) {}
+
+impl<'v, T1: AllocHashableValue<'v>, T2: AllocHashableValue<'v>> AllocHashableValue<'v>
+    for (T1, T2)
+{
+}
+
+/// Allocate a set holding the given elements, which come from [`AllocHashableValue`] values.
+fn alloc_set<'v>(xs: impl Iterator<Item = Value<'v>>, heap: &'v Heap) -> Value<'v> {
+    let res: Set = xs
+        .map(|x| {
+            x.get_hashed()
+                .expect("AllocHashableValue values are always hashable")
+        })
+        .collect();
+    heap.alloc(res)
+}
+
+impl<V: StarlarkTypeRepr> StarlarkTypeRepr for IndexSet<V> {
+    fn starlark_type_repr() -> String {
+        Set::starlark_type_repr()
+    }
+}
+
+/// Allocates a Starlark set, in the same order.
+impl<'v, V: AllocHashableValue<'v>> AllocValue<'v> for IndexSet<V> {
+    fn alloc_value(self, heap: &'v Heap) -> Value<'v> {
+        alloc_set(self.into_iter().map(|x| x.alloc_value(heap)), heap)
+    }
+}
+
+impl<V: StarlarkTypeRepr> StarlarkTypeRepr for BTreeSet<V> {
+    fn starlark_type_repr() -> String {
+        Set::starlark_type_repr()
+    }
+}
+
+/// Allocates a Starlark set, in sorted order.
+impl<'v, V: AllocHashableValue<'v>> AllocValue<'v> for BTreeSet<V> {
+    fn alloc_value(self, heap: &'v Heap) -> Value<'v> {
+        alloc_set(self.into_iter().map(|x| x.alloc_value(heap)), heap)
+    }
+}
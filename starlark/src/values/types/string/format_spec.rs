@@ -0,0 +1,620 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Format specifications: the `>8.2f` in `"{:>8.2f}".format(x)`,
+//! and the `-8.2f` in `"%-8.2f" % x`.
+//! Based on <https://docs.python.org/3/library/string.html#format-specification-mini-language>
+//! and <https://docs.python.org/3/library/stdtypes.html#printf-style-string-formatting>.
+
+use std::fmt;
+use std::fmt::Write;
+use std::str::FromStr;
+
+use num_bigint::Sign;
+use thiserror::Error;
+
+use crate::values::float;
+use crate::values::num::Num;
+use crate::values::UnpackValue;
+use crate::values::Value;
+use crate::values::ValueError;
+
+#[derive(Debug, Error)]
+enum FormatSpecError {
+    #[error("Invalid format specifier `{0}`")]
+    Invalid(String),
+    #[error("Unsupported format specifier `{0}`: {1} is not supported")]
+    Unsupported(String, &'static str),
+    #[error("Unknown format code `{0}` for value of type `{1}`")]
+    UnknownCode(char, String),
+    #[error("Format specifier `{0}` is not allowed for strings")]
+    NotForString(String),
+    #[error("Precision is not allowed in integer format specifier `{0}`")]
+    IntegerPrecision(String),
+    #[error("Too large width or precision in format specifier `{0}`, the maximum is {1}")]
+    TooLarge(String, usize),
+}
+
+/// Largest width or precision accepted, so a script can't make us allocate
+/// an arbitrary amount of memory for padding or digits.
+const MAX_WIDTH_OR_PRECISION: usize = 1_000_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Align {
+    /// `<`
+    Left,
+    /// `>`
+    Right,
+    /// `^`
+    Center,
+    /// `=`, padding goes between the sign and the digits.
+    AfterSign,
+}
+
+impl Align {
+    fn from_char(c: char) -> Option<Align> {
+        match c {
+            '<' => Some(Align::Left),
+            '>' => Some(Align::Right),
+            '^' => Some(Align::Center),
+            '=' => Some(Align::AfterSign),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SignMode {
+    /// `-`, the default: only negative numbers get a sign.
+    Negative,
+    /// `+`
+    Always,
+    /// ` `, a space in place of the plus sign.
+    Space,
+}
+
+/// A parsed format specification.
+#[derive(Debug, PartialEq)]
+pub(crate) struct FormatSpec<'a> {
+    /// The specification as written, for error messages.
+    text: &'a str,
+    fill: char,
+    align: Option<Align>,
+    sign: SignMode,
+    /// `#`: add `0x`, `0o` or `0b` prefix, keep trailing zeros for `g`.
+    alternate: bool,
+    width: usize,
+    precision: Option<usize>,
+    typ: Option<char>,
+    /// Whether this spec came from the `%` operator rather than `str.format`.
+    printf: bool,
+}
+
+fn parse_usize(text: &str, digits: &str) -> anyhow::Result<usize> {
+    match usize::from_str(digits) {
+        Ok(n) if n <= MAX_WIDTH_OR_PRECISION => Ok(n),
+        // Only digits are passed, so an error means the number overflows.
+        _ => Err(FormatSpecError::TooLarge(text.to_owned(), MAX_WIDTH_OR_PRECISION).into()),
+    }
+}
+
+/// Split off the leading ASCII digits of `s`.
+fn split_digits(s: &str) -> (&str, &str) {
+    s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()))
+}
+
+impl<'a> FormatSpec<'a> {
+    /// Parse the part after `:` in a `str.format` replacement field,
+    /// `[[fill]align][sign][#][0][width][.precision][type]`.
+    pub(crate) fn parse(text: &'a str) -> anyhow::Result<FormatSpec<'a>> {
+        let invalid = || FormatSpecError::Invalid(text.to_owned());
+        let mut spec = FormatSpec {
+            text,
+            fill: ' ',
+            align: None,
+            sign: SignMode::Negative,
+            alternate: false,
+            width: 0,
+            precision: None,
+            typ: None,
+            printf: false,
+        };
+        let mut rest = text;
+
+        let mut chars = rest.chars();
+        let first = chars.next();
+        let second = chars.next();
+        if let Some(align) = second.and_then(Align::from_char) {
+            spec.fill = first.unwrap();
+            spec.align = Some(align);
+            rest = chars.as_str();
+        } else if let Some(align) = first.and_then(Align::from_char) {
+            spec.align = Some(align);
+            rest = &rest[1..];
+        }
+
+        spec.sign = match rest.chars().next() {
+            Some('+') => SignMode::Always,
+            Some(' ') => SignMode::Space,
+            _ => SignMode::Negative,
+        };
+        if let Some(r) = rest.strip_prefix(|c| c == '+' || c == ' ' || c == '-') {
+            rest = r;
+        }
+        if let Some(r) = rest.strip_prefix('#') {
+            spec.alternate = true;
+            rest = r;
+        }
+        if let Some(r) = rest.strip_prefix('0') {
+            if spec.align.is_none() {
+                spec.fill = '0';
+                spec.align = Some(Align::AfterSign);
+            }
+            rest = r;
+        }
+
+        let (width, r) = split_digits(rest);
+        if !width.is_empty() {
+            spec.width = parse_usize(text, width)?;
+        }
+        rest = r;
+        if rest.starts_with(|c| c == ',' || c == '_') {
+            return Err(FormatSpecError::Unsupported(text.to_owned(), "digit grouping").into());
+        }
+        if let Some(r) = rest.strip_prefix('.') {
+            let (precision, r) = split_digits(r);
+            if precision.is_empty() {
+                return Err(invalid().into());
+            }
+            spec.precision = Some(parse_usize(text, precision)?);
+            rest = r;
+        }
+
+        let mut chars = rest.chars();
+        spec.typ = chars.next();
+        if !chars.as_str().is_empty() {
+            return Err(invalid().into());
+        }
+        Ok(spec)
+    }
+
+    /// Parse a `%` conversion, where `text` is the whole conversion including
+    /// the leading `%`, e.g. `%-08.2f`.
+    pub(crate) fn parse_printf(text: &'a str) -> anyhow::Result<FormatSpec<'a>> {
+        let invalid = || FormatSpecError::Invalid(text.to_owned());
+        let mut spec = FormatSpec {
+            text,
+            fill: ' ',
+            align: Some(Align::Right),
+            sign: SignMode::Negative,
+            alternate: false,
+            width: 0,
+            precision: None,
+            typ: None,
+            printf: true,
+        };
+        let mut rest = text.strip_prefix('%').ok_or_else(invalid)?;
+
+        let mut left = false;
+        let mut zero = false;
+        loop {
+            match rest.chars().next() {
+                Some('-') => left = true,
+                Some('0') => zero = true,
+                Some('+') => spec.sign = SignMode::Always,
+                Some(' ') if spec.sign != SignMode::Always => spec.sign = SignMode::Space,
+                Some(' ') => {}
+                Some('#') => spec.alternate = true,
+                _ => break,
+            }
+            rest = &rest[1..];
+        }
+        if left {
+            spec.align = Some(Align::Left);
+        } else if zero {
+            spec.fill = '0';
+            spec.align = Some(Align::AfterSign);
+        }
+
+        let (width, r) = split_digits(rest);
+        if !width.is_empty() {
+            spec.width = parse_usize(text, width)?;
+        }
+        rest = r;
+        if let Some(r) = rest.strip_prefix('.') {
+            let (precision, r) = split_digits(r);
+            // Like C, `%.f` means precision zero.
+            spec.precision = Some(if precision.is_empty() {
+                0
+            } else {
+                parse_usize(text, precision)?
+            });
+            rest = r;
+        }
+
+        let mut chars = rest.chars();
+        spec.typ = chars.next();
+        if spec.typ.is_none() || !chars.as_str().is_empty() {
+            return Err(invalid().into());
+        }
+        Ok(spec)
+    }
+
+    /// Format a value for `str.format` without a `!` conversion.
+    pub(crate) fn format_value(&self, value: Value, out: &mut String) -> anyhow::Result<()> {
+        match value.unpack_num() {
+            Some(num) if self.typ != Some('s') => self.format_num(num, value.get_type(), out),
+            _ => match self.typ {
+                None | Some('s') => match value.unpack_str() {
+                    Some(s) => self.format_str(s, out),
+                    None => self.format_str(&value.to_str(), out),
+                },
+                Some(c) => Err(FormatSpecError::UnknownCode(c, value.get_type().to_owned()).into()),
+            },
+        }
+    }
+
+    /// Format a value for the `%` operator.
+    pub(crate) fn format_printf(&self, value: Value, out: &mut String) -> anyhow::Result<()> {
+        match self.typ {
+            Some('s') => match value.unpack_str() {
+                Some(s) => self.format_str(s, out),
+                None => self.format_str(&value.to_repr(), out),
+            },
+            Some('r') => self.format_str(&value.to_repr(), out),
+            Some(c @ ('d' | 'i' | 'o' | 'x' | 'X')) => {
+                let num = Num::unpack_param(value)?;
+                // Like Python, `%d` truncates floats.
+                let num = match num {
+                    Num::Float(f) => match Num::Float(f.trunc()).as_int() {
+                        Some(i) => Num::Int(i),
+                        None => {
+                            return ValueError::unsupported_owned(
+                                value.get_type(),
+                                &format!("%{}", c),
+                                None,
+                            );
+                        }
+                    },
+                    num => num,
+                };
+                self.format_num(num, value.get_type(), out)
+            }
+            Some('e' | 'E' | 'f' | 'F' | 'g' | 'G') => {
+                self.format_num(Num::unpack_param(value)?, value.get_type(), out)
+            }
+            Some(c) => Err(FormatSpecError::UnknownCode(c, value.get_type().to_owned()).into()),
+            None => Err(FormatSpecError::Invalid(self.text.to_owned()).into()),
+        }
+    }
+
+    /// Format a string, padded and truncated to the precision.
+    pub(crate) fn format_str(&self, s: &str, out: &mut String) -> anyhow::Result<()> {
+        match self.typ {
+            None | Some('s') | Some('r') => {}
+            Some(c) => return Err(FormatSpecError::UnknownCode(c, "string".to_owned()).into()),
+        }
+        let mut spec_align = self.align;
+        let mut fill = self.fill;
+        if self.printf {
+            // `%05s` pads with spaces.
+            if spec_align == Some(Align::AfterSign) {
+                spec_align = Some(Align::Right);
+                fill = ' ';
+            }
+        } else if self.sign != SignMode::Negative
+            || self.alternate
+            || spec_align == Some(Align::AfterSign)
+        {
+            return Err(FormatSpecError::NotForString(self.text.to_owned()).into());
+        }
+        let s = match self.precision {
+            Some(p) => match s.char_indices().nth(p) {
+                Some((i, _)) => &s[..i],
+                None => s,
+            },
+            None => s,
+        };
+        pad(
+            out,
+            fill,
+            spec_align.unwrap_or(Align::Left),
+            self.width,
+            "",
+            s,
+        );
+        Ok(())
+    }
+
+    fn format_num(&self, num: Num, typ_name: &str, out: &mut String) -> anyhow::Result<()> {
+        let is_int = !matches!(num, Num::Float(_));
+        let typ = match self.typ {
+            Some(c) => c,
+            None if is_int => 'd',
+            // Python's `str(x)`, or general format if there is a precision.
+            None => {
+                let f = num.as_float();
+                return match self.precision {
+                    None => {
+                        let body = if f.is_finite() {
+                            float::StarlarkFloat(f.abs()).to_string()
+                        } else {
+                            non_finite(f, false)
+                        };
+                        self.write_num(out, self.sign_str(float_is_negative(f)), "", &body);
+                        Ok(())
+                    }
+                    Some(p) => {
+                        let mut body = general(f.abs(), p, false, self.alternate);
+                        if f.is_finite() && !body.contains(|c| c == '.' || c == 'e') {
+                            body.push_str(".0");
+                        }
+                        self.write_num(out, self.sign_str(float_is_negative(f)), "", &body);
+                        Ok(())
+                    }
+                };
+            }
+        };
+        match typ {
+            'd' | 'i' | 'o' | 'x' | 'X' | 'b' if is_int => {
+                if typ == 'i' && !self.printf {
+                    return Err(FormatSpecError::UnknownCode(typ, typ_name.to_owned()).into());
+                }
+                if self.precision.is_some() && !self.printf {
+                    return Err(FormatSpecError::IntegerPrecision(self.text.to_owned()).into());
+                }
+                let (negative, mut body) = match num {
+                    Num::Int(i) => (i < 0, int_digits((i as i64).unsigned_abs(), typ)),
+                    Num::BigInt(b) => (
+                        b.get().sign() == Sign::Minus,
+                        int_digits(b.get().magnitude(), typ),
+                    ),
+                    Num::Float(_) => unreachable!(),
+                };
+                // For `%`, the precision is the minimum number of digits.
+                if let Some(p) = self.precision {
+                    if body.len() < p {
+                        body.insert_str(0, &"0".repeat(p - body.len()));
+                    }
+                }
+                let prefix = match typ {
+                    _ if !self.alternate => "",
+                    'x' => "0x",
+                    'X' => "0X",
+                    'o' => "0o",
+                    'b' => "0b",
+                    _ => "",
+                };
+                self.write_num(out, self.sign_str(negative), prefix, &body);
+                Ok(())
+            }
+            'e' | 'E' | 'f' | 'F' | 'g' | 'G' | '%' => {
+                let f = num.as_float();
+                let upper = typ.is_ascii_uppercase();
+                let body = if !f.is_finite() {
+                    non_finite(f, upper)
+                } else {
+                    let x = f.abs();
+                    let p = self.precision.unwrap_or(6);
+                    match typ {
+                        'e' | 'E' => exponent(x, p, upper),
+                        'f' | 'F' => format!("{:.*}", p, x),
+                        'g' | 'G' => general(x, p, upper, self.alternate),
+                        _ => format!("{:.*}%", p, x * 100.0),
+                    }
+                };
+                self.write_num(out, self.sign_str(float_is_negative(f)), "", &body);
+                Ok(())
+            }
+            c => Err(FormatSpecError::UnknownCode(c, typ_name.to_owned()).into()),
+        }
+    }
+
+    fn sign_str(&self, negative: bool) -> &'static str {
+        match (negative, self.sign) {
+            (true, _) => "-",
+            (false, SignMode::Negative) => "",
+            (false, SignMode::Always) => "+",
+            (false, SignMode::Space) => " ",
+        }
+    }
+
+    fn write_num(&self, out: &mut String, sign: &str, prefix: &str, body: &str) {
+        let mut sign_prefix = String::with_capacity(sign.len() + prefix.len());
+        sign_prefix.push_str(sign);
+        sign_prefix.push_str(prefix);
+        pad(
+            out,
+            self.fill,
+            self.align.unwrap_or(Align::Right),
+            self.width,
+            &sign_prefix,
+            body,
+        );
+    }
+}
+
+/// Write `prefix` and `body` to `out`, padded with `fill` up to `width` chars.
+fn pad(out: &mut String, fill: char, align: Align, width: usize, prefix: &str, body: &str) {
+    let padding = width.saturating_sub(prefix.chars().count() + body.chars().count());
+    let (left, right) = match align {
+        Align::Left => (0, padding),
+        Align::Right => (padding, 0),
+        Align::Center => (padding / 2, padding - padding / 2),
+        Align::AfterSign => {
+            out.push_str(prefix);
+            out.extend((0..padding).map(|_| fill));
+            out.push_str(body);
+            return;
+        }
+    };
+    out.extend((0..left).map(|_| fill));
+    out.push_str(prefix);
+    out.push_str(body);
+    out.extend((0..right).map(|_| fill));
+}
+
+fn float_is_negative(f: f64) -> bool {
+    f.is_sign_negative() && !f.is_nan()
+}
+
+fn non_finite(f: f64, upper: bool) -> String {
+    match (f.is_nan(), upper) {
+        (true, false) => "nan".to_owned(),
+        (true, true) => "NAN".to_owned(),
+        (false, false) => "inf".to_owned(),
+        (false, true) => "INF".to_owned(),
+    }
+}
+
+fn int_digits<T>(x: T, typ: char) -> String
+where
+    T: fmt::Display + fmt::Octal + fmt::LowerHex + fmt::UpperHex + fmt::Binary,
+{
+    match typ {
+        'o' => format!("{:o}", x),
+        'x' => format!("{:x}", x),
+        'X' => format!("{:X}", x),
+        'b' => format!("{:b}", x),
+        _ => format!("{}", x),
+    }
+}
+
+/// Format non-negative finite `x` as `d.ddde+XX` with `precision` fractional digits.
+fn exponent(x: f64, precision: usize, upper: bool) -> String {
+    // Rust writes `1.5e3`, Python writes `1.5e+03`.
+    let s = format!("{:.*e}", precision, x);
+    let (mantissa, exp) = s.split_once('e').unwrap();
+    let exp = i32::from_str(exp).unwrap();
+    let mut res = String::with_capacity(mantissa.len() + 5);
+    res.push_str(mantissa);
+    res.push(if upper { 'E' } else { 'e' });
+    write!(res, "{:+03}", exp).unwrap();
+    res
+}
+
+/// Format non-negative finite `x` with `precision` significant digits,
+/// in fixed or exponent notation depending on its magnitude.
+fn general(x: f64, precision: usize, upper: bool, alternate: bool) -> String {
+    let p = precision.max(1);
+    let exp = if x == 0.0 {
+        0
+    } else {
+        // The exponent after rounding to `p` significant digits.
+        let s = format!("{:.*e}", p - 1, x);
+        i32::from_str(s.split_once('e').unwrap().1).unwrap()
+    };
+    let mut res = if -4 <= exp && exp < p as i32 {
+        format!("{:.*}", (p as i32 - 1 - exp) as usize, x)
+    } else {
+        exponent(x, p - 1, upper)
+    };
+    if !alternate {
+        let exp_start = res.find(|c| c == 'e' || c == 'E').unwrap_or(res.len());
+        let (mantissa, exp) = res.split_at(exp_start);
+        if mantissa.contains('.') {
+            let mantissa = mantissa.trim_end_matches('0').trim_end_matches('.');
+            res = format!("{}{}", mantissa, exp);
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert;
+
+    #[test]
+    fn test_format_spec() {
+        assert::all_true(
+            r#"
+"{:>8.2f}".format(3.14159) == "    3.14"
+"{:<6}|".format("ab") == "ab    |"
+"{:^6}|".format("ab") == "  ab  |"
+"{:*^7}".format("ab") == "**ab***"
+"{:>5}".format(42) == "   42"
+"{:<5}|".format(42) == "42   |"
+"{:05}".format(-42) == "-0042"
+"{:+d}".format(5) == "+5"
+"{: d}".format(5) == " 5"
+"{:=+6}".format(5) == "+    5"
+"{:x} {:X} {:o} {:b}".format(255, 255, 8, 5) == "ff FF 10 101"
+"{:#x} {:#o} {:#b}".format(-255, 8, 5) == "-0xff 0o10 0b101"
+"{:x}".format(0x123456789abcdef0123) == "123456789abcdef0123"
+"{:e}".format(1234.5) == "1.234500e+03"
+"{:.2E}".format(0.000123) == "1.23E-04"
+"{:g} {:g} {:g}".format(0.0001, 0.00001, 123456789) == "0.0001 1e-05 1.23457e+08"
+"{:g} {:#g}".format(1.5, 1.5) == "1.5 1.50000"
+"{:.1%}".format(0.125) == "12.5%"
+"{:f}".format(1) == "1.000000"
+"{:.3}".format(1.0) == "1.0"
+"{:.3}".format(3.14159) == "3.14"
+"{:8}|".format(1.5) == "     1.5|"
+"{:f} {:F}".format(float("inf"), float("-inf")) == "inf -INF"
+"{:.3}".format("abcdef") == "abc"
+"{0!r:>5}".format("a") == '  "a"'
+"{x:>4}".format(x = True) == "True"
+"#,
+        );
+        assert::fail("'{:,}'.format(1000)", "digit grouping is not supported");
+        assert::fail("'{:f}'.format('a')", "Unknown format code `f`");
+        assert::fail("'{:d}'.format(1.5)", "Unknown format code `d`");
+        assert::fail("'{:+}'.format('a')", "not allowed for strings");
+        assert::fail("'{:.2d}'.format(1)", "Precision is not allowed");
+        assert::fail("'{:5.}'.format(1)", "Invalid format specifier `5.`");
+        assert::fail("'{:ff}'.format(1)", "Invalid format specifier `ff`");
+        assert::fail(
+            "'{:999999999999}'.format(1)",
+            "Too large width or precision",
+        );
+        assert::fail("'{:.1000001f}'.format(1)", "Too large width or precision");
+        assert::fail(
+            "'{:99999999999999999999999}'.format(1)",
+            "Too large width or precision",
+        );
+    }
+
+    #[test]
+    fn test_percent_spec() {
+        assert::all_true(
+            r#"
+"%5d|%-5d|" % (42, 42) == "   42|42   |"
+"%05d" % -3 == "-0003"
+"%+d % d" % (5, 5) == "+5  5"
+"%.3d" % 5 == "005"
+"%#x %#o %5X" % (31, 8, 255) == "0x1f 0o10    FF"
+"%x" % 0x123456789abcdef0123 == "123456789abcdef0123"
+"%X %o" % (-0x123456789abcdef0123, 0o1234567012345670123456701) == "-123456789ABCDEF0123 1234567012345670123456701"
+"%d" % 123456789123456789123 == "123456789123456789123"
+"%8.2f|" % 3.14159 == "    3.14|"
+"%-8.2f|" % 3.14159 == "3.14    |"
+"%.2e" % 1234.5 == "1.23e+03"
+"%.3g" % 0.0001234 == "0.000123"
+"%5s|%-5s|" % ("ab", "ab") == "   ab|ab   |"
+"%05s" % "ab" == "   ab"
+"%.2s" % "abc" == "ab"
+"%6r" % "a" == '   "a"'
+"%5d" % 3.9 == "    3"
+"#,
+        );
+        assert::fail("'%5q' % 1", "Unknown format code `q`");
+        assert::fail("'%5' % 1", "Invalid format specifier `%5`");
+        assert::fail("'%5d' % 'a'", "int or float");
+        assert::fail("'%.999999999999f' % 1", "Too large width or precision");
+        assert::fail("'%1000001d' % 1", "Too large width or precision");
+    }
+}
@@ -66,4 +66,15 @@ mod test {
         let xx2 = intern.intern(Hashed::new("xx"), || heap2.alloc_str("xx"));
         assert!(xx1.to_value().ptr_eq(xx2.to_value()));
     }
+
+    #[test]
+    fn test_frozen_heap_alloc_str_intern() {
+        let heap = FrozenHeap::new();
+        let a1 = heap.alloc_str_intern("field_name");
+        let a2 = heap.alloc_str_intern("field_name");
+        let b = heap.alloc_str_intern("other_field");
+        assert!(a1.to_value().ptr_eq(a2.to_value()));
+        assert!(!a1.to_value().ptr_eq(b.to_value()));
+        assert_eq!("field_name", a2.as_str());
+    }
 }
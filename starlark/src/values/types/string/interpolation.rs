@@ -17,6 +17,7 @@
 
 //! String interpolation-related code.
 //! Based on <https://docs.python.org/3/library/stdtypes.html#printf-style-string-formatting>
+//! and <https://docs.python.org/3/library/string.html#format-string-syntax>
 
 use std::fmt::Write;
 use std::mem;
@@ -24,13 +25,16 @@ use std::str::FromStr;
 
 use gazebo::cast;
 use gazebo::prelude::*;
+use num_bigint::Sign;
 use thiserror::Error;
 
 use crate::collections::string_pool::StringPool;
+use crate::values::bigint::StarlarkBigInt;
 use crate::values::dict::Dict;
 use crate::values::float;
 use crate::values::num;
 use crate::values::num::Num;
+use crate::values::string::format_spec::FormatSpec;
 use crate::values::tuple::Tuple;
 use crate::values::Heap;
 use crate::values::StringValue;
@@ -50,6 +54,19 @@ enum StringInterpolationError {
     NotEnoughParameters,
 }
 
+/// Errors in the field name of a `.format()` replacement field.
+#[derive(Debug, Error)]
+enum FormatFieldError {
+    #[error("Invalid field `{0}` in format string")]
+    InvalidField(String),
+    #[error("Object of type `{typ}` has no attribute `{attr}` in format field `{field}`")]
+    NoAttribute {
+        field: String,
+        typ: String,
+        attr: String,
+    },
+}
+
 pub(crate) fn percent(format: &str, value: Value) -> anyhow::Result<String> {
     // For performance reasons, we treat format as a list of bytes
     // (which is fine, the only thing we care about are '%' and ASCII digits).
@@ -101,39 +118,40 @@ pub(crate) fn percent(format: &str, value: Value) -> anyhow::Result<String> {
                                 }
                                 Some(v) => write!(out, "{}", v).unwrap(),
                             }
+                        } else if let Some(v) = value.downcast_ref::<StarlarkBigInt>() {
+                            write!(out, "{}", v.get()).unwrap()
                         } else {
                             write!(out, "{}", value.to_int()?).unwrap()
                         }
                     }
-                    b'o' => {
-                        let v = next_value()?.to_int()?;
-                        write!(
-                            out,
-                            "{}{:o}",
-                            if v < 0 { "-" } else { "" },
-                            v.wrapping_abs() as u64
-                        )
-                        .unwrap();
-                    }
-                    b'x' => {
-                        let v = next_value()?.to_int()?;
-                        write!(
-                            out,
-                            "{}{:x}",
-                            if v < 0 { "-" } else { "" },
-                            v.wrapping_abs() as u64
-                        )
-                        .unwrap();
-                    }
-                    b'X' => {
-                        let v = next_value()?.to_int()?;
-                        write!(
-                            out,
-                            "{}{:X}",
-                            if v < 0 { "-" } else { "" },
-                            v.wrapping_abs() as u64
-                        )
-                        .unwrap()
+                    b'o' | b'x' | b'X' => {
+                        let value = next_value()?;
+                        let (negative, digits) = match value.downcast_ref::<StarlarkBigInt>() {
+                            Some(v) => (
+                                v.get().sign() == Sign::Minus,
+                                v.get()
+                                    .magnitude()
+                                    .to_str_radix(if c == b'o' { 8 } else { 16 }),
+                            ),
+                            None => {
+                                let v = value.to_int()?;
+                                let abs = (v as i64).unsigned_abs();
+                                let digits = if c == b'o' {
+                                    format!("{:o}", abs)
+                                } else {
+                                    format!("{:x}", abs)
+                                };
+                                (v < 0, digits)
+                            }
+                        };
+                        if negative {
+                            out.push('-');
+                        }
+                        if c == b'X' {
+                            out.push_str(&digits.to_ascii_uppercase());
+                        } else {
+                            out.push_str(&digits);
+                        }
                     }
                     b'e' => {
                         let v = Num::unpack_param(next_value()?)?.as_float();
@@ -155,6 +173,24 @@ pub(crate) fn percent(format: &str, value: Value) -> anyhow::Result<String> {
                         let v = Num::unpack_param(next_value()?)?.as_float();
                         float::write_compact(out, v, 'E').unwrap()
                     }
+                    b'-' | b'+' | b' ' | b'#' | b'.' | b'0'..=b'9' => {
+                        // Flags, width or precision, e.g. `%-8.2f`.
+                        let mut spec = String::from("%");
+                        spec.push(c as char);
+                        loop {
+                            match format.next() {
+                                Some(c @ (b'-' | b'+' | b' ' | b'#' | b'.' | b'0'..=b'9')) => {
+                                    spec.push(c as char)
+                                }
+                                Some(c) => {
+                                    spec.push(c as char);
+                                    break;
+                                }
+                                None => break,
+                            }
+                        }
+                        FormatSpec::parse_printf(&spec)?.format_printf(next_value()?, out)?;
+                    }
                     c => {
                         res.push(b'%');
                         res.push(c);
@@ -420,7 +456,7 @@ pub(crate) fn format<'v>(
         match token {
             FormatToken::Text(text) => result.push_str(text),
             FormatToken::Capture(capture) => {
-                format_capture(capture, &mut args, &kwargs, &mut result)?
+                format_capture(capture, &mut args, &kwargs, &mut result, heap)?
             }
         }
     }
@@ -432,22 +468,26 @@ pub(crate) fn format<'v>(
 fn format_capture<'v, T: Iterator<Item = Value<'v>>>(
     capture: &str,
     args: &mut FormatArgs<'v, T>,
-    kwargs: &Dict,
+    kwargs: &Dict<'v>,
     result: &mut String,
+    heap: &'v Heap,
 ) -> anyhow::Result<()> {
-    let (n, conv) = {
-        if let Some((n, conv)) = capture.split_once('!') {
-            (n, conv)
-        } else {
-            (capture, "s")
-        }
+    let (capture, spec) = match capture.split_once(':') {
+        Some((capture, spec)) if !spec.is_empty() => (capture, Some(FormatSpec::parse(spec)?)),
+        Some((capture, _)) => (capture, None),
+        None => (capture, None),
+    };
+    let (n, conv) = match capture.split_once('!') {
+        Some((n, conv)) => (n, Some(conv)),
+        None => (capture, None),
     };
     let conv_s = |x: Value, result: &mut String| x.collect_str(result);
     let conv_r = |x: Value, result: &mut String| x.collect_repr(result);
-    let conv: &dyn Fn(Value, &mut String) = match conv {
-        "s" => &conv_s,
-        "r" => &conv_r,
-        c => {
+    let conv: Option<&dyn Fn(Value, &mut String)> = match conv {
+        None => None,
+        Some("s") => Some(&conv_s),
+        Some("r") => Some(&conv_r),
+        Some(c) => {
             return Err(anyhow::anyhow!(
                 concat!(
                     "'{}' is not a valid format string specifier, only ",
@@ -457,16 +497,16 @@ fn format_capture<'v, T: Iterator<Item = Value<'v>>>(
             ));
         }
     };
-    if n.is_empty() {
-        conv(args.next_ordered()?, result);
-        Ok(())
-    } else if n.chars().all(|c| c.is_ascii_digit()) {
-        let i = usize::from_str(n).unwrap();
-        conv(args.by_index(i)?, result);
-        Ok(())
+    // The field name is an argument name followed by `.attr` and `[key]` accessors.
+    let (name, accessors) = n.split_at(n.find(|c| c == '.' || c == '[').unwrap_or(n.len()));
+    let value = if name.is_empty() {
+        args.next_ordered()?
+    } else if name.chars().all(|c| c.is_ascii_digit()) {
+        let i = usize::from_str(name).unwrap();
+        args.by_index(i)?
     } else {
-        if let Some(x) = n.chars().find(|c| match c {
-            '.' | ',' | '[' | ']' => true,
+        if let Some(x) = name.chars().find(|c| match c {
+            ',' | ']' => true,
             _ => false,
         }) {
             return Err(anyhow::anyhow!(
@@ -474,14 +514,70 @@ fn format_capture<'v, T: Iterator<Item = Value<'v>>>(
                 x
             ));
         }
-        match kwargs.get_str(n) {
-            None => Err(ValueError::KeyNotFound(n.to_owned()).into()),
-            Some(v) => {
-                conv(v, result);
-                Ok(())
+        match kwargs.get_str(name) {
+            None => return Err(ValueError::KeyNotFound(name.to_owned()).into()),
+            Some(v) => v,
+        }
+    };
+    let value = format_field_access(n, accessors, value, heap)?;
+    match (conv, spec) {
+        (None, None) => conv_s(value, result),
+        (Some(conv), None) => conv(value, result),
+        (None, Some(spec)) => spec.format_value(value, result)?,
+        (Some(conv), Some(spec)) => {
+            let mut s = String::new();
+            conv(value, &mut s);
+            spec.format_str(&s, result)?
+        }
+    }
+    Ok(())
+}
+
+/// Apply the `.attr` and `[key]` accessors of the replacement field `field` to `value`.
+fn format_field_access<'v>(
+    field: &str,
+    mut accessors: &str,
+    mut value: Value<'v>,
+    heap: &'v Heap,
+) -> anyhow::Result<Value<'v>> {
+    let invalid = || FormatFieldError::InvalidField(field.to_owned());
+    while !accessors.is_empty() {
+        if let Some(rest) = accessors.strip_prefix('.') {
+            let (attr, rest) =
+                rest.split_at(rest.find(|c| c == '.' || c == '[').unwrap_or(rest.len()));
+            if attr.is_empty() {
+                return Err(invalid().into());
+            }
+            value = match value.get_attr(attr, heap)? {
+                Some(v) => v,
+                None => {
+                    return Err(FormatFieldError::NoAttribute {
+                        field: field.to_owned(),
+                        typ: value.get_type().to_owned(),
+                        attr: attr.to_owned(),
+                    }
+                    .into());
+                }
+            };
+            accessors = rest;
+        } else if let Some(rest) = accessors.strip_prefix('[') {
+            let (key, rest) = rest.split_once(']').ok_or_else(invalid)?;
+            if key.is_empty() {
+                return Err(invalid().into());
             }
+            // Like Python, a key of digits is an integer index, anything else is a string.
+            let key = if key.chars().all(|c| c.is_ascii_digit()) {
+                Value::new_int(i32::from_str(key).map_err(|_| invalid())?)
+            } else {
+                heap.alloc(key)
+            };
+            value = value.at(key, heap)?;
+            accessors = rest;
+        } else {
+            return Err(invalid().into());
         }
     }
+    Ok(value)
 }
 
 #[cfg(test)]
@@ -496,10 +592,11 @@ mod tests {
     fn format_capture_for_test<'v, T: Iterator<Item = Value<'v>>>(
         capture: &str,
         args: &mut FormatArgs<'v, T>,
-        kwargs: &Dict,
+        kwargs: &Dict<'v>,
+        heap: &'v Heap,
     ) -> anyhow::Result<String> {
         let mut result = String::new();
-        super::format_capture(capture, args, kwargs, &mut result)?;
+        super::format_capture(capture, args, kwargs, &mut result, heap)?;
         Ok(result)
     }
 
@@ -515,32 +612,32 @@ mod tests {
         kwargs.insert_hashed(heap.alloc_str("c").get_hashed(), heap.alloc("z"));
         let kwargs = Dict::new(coerce(kwargs));
         assert_eq!(
-            format_capture_for_test("", &mut args, &kwargs).unwrap(),
+            format_capture_for_test("", &mut args, &kwargs, &heap).unwrap(),
             "1"
         );
         assert_eq!(
-            format_capture_for_test("!s", &mut args, &kwargs).unwrap(),
+            format_capture_for_test("!s", &mut args, &kwargs, &heap).unwrap(),
             "2"
         );
         assert_eq!(
-            format_capture_for_test("!r", &mut args, &kwargs).unwrap(),
+            format_capture_for_test("!r", &mut args, &kwargs, &heap).unwrap(),
             "\"3\""
         );
         assert_eq!(
-            format_capture_for_test("a!r", &mut args, &kwargs).unwrap(),
+            format_capture_for_test("a!r", &mut args, &kwargs, &heap).unwrap(),
             "\"x\""
         );
         assert_eq!(
-            format_capture_for_test("a!s", &mut args, &kwargs).unwrap(),
+            format_capture_for_test("a!s", &mut args, &kwargs, &heap).unwrap(),
             "x"
         );
-        assert!(format_capture_for_test("1", &mut args, &kwargs).is_err());
+        assert!(format_capture_for_test("1", &mut args, &kwargs, &heap).is_err());
         let mut args = FormatArgs::new(original_args.iter().copied());
         assert_eq!(
-            format_capture_for_test("1", &mut args, &kwargs).unwrap(),
+            format_capture_for_test("1", &mut args, &kwargs, &heap).unwrap(),
             "2"
         );
-        assert!(format_capture_for_test("", &mut args, &kwargs).is_err());
+        assert!(format_capture_for_test("", &mut args, &kwargs, &heap).is_err());
     }
 
     #[test]
@@ -548,6 +645,27 @@ mod tests {
         assert::eq("'a{x}b{y}c{}'.format(1, x=2, y=3)", "'a2b3c1'")
     }
 
+    #[test]
+    fn test_format_field_access() {
+        assert::pass(
+            r#"
+s = struct(name = "n", inner = struct(xs = [1, 2]))
+assert_eq("{0.name}".format(s), "n")
+assert_eq("{.name}-{}".format(s, 3), "n-3")
+assert_eq("{x.inner.xs[1]}".format(x = s), "2")
+assert_eq("{0[key]!r}".format({"key": "v"}), '"v"')
+assert_eq("{0[1][0]}".format([0, "ab"]), "a")
+assert_eq("{d[0]}".format(d = {"0": "str", 0: "int"}), "int")
+"#,
+        );
+        assert::fail("'{0.missing}'.format(1)", "`0.missing`");
+        assert::fail("'{0[}'.format([1])", "Invalid field `0[`");
+        assert::fail("'{0.}'.format(1)", "Invalid field `0.`");
+        assert::fail("'{0[0]x}'.format([1])", "Invalid field `0[0]x`");
+        assert::fail("'{0[5]}'.format([1])", "out of bound");
+        assert::fail("'{0[k]}'.format({})", "not found");
+    }
+
     #[test]
     fn test_parse_format_one() {
         assert_eq!(
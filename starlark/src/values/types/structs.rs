@@ -60,6 +60,7 @@ use crate::values::layout::typed::string::StringValueLike;
 use crate::values::type_repr::StarlarkTypeRepr;
 use crate::values::AllocValue;
 use crate::values::Freeze;
+use crate::values::FrozenStringValue;
 use crate::values::FrozenValue;
 use crate::values::Heap;
 use crate::values::StarlarkValue;
@@ -82,6 +83,24 @@ impl<'v, V: ValueLike<'v>> StructGen<'v, V> {
         }
     }
 
+    /// Get a field whose name is the same string object as `name`.
+    ///
+    /// Field names passed as keyword arguments are interned in the module heap,
+    /// as are the field names in `x.field` expressions, so comparing pointers
+    /// avoids comparing strings. `None` means the caller should fall back
+    /// to a regular lookup, e.g. for a struct created from a `**kwargs` dict.
+    pub(crate) fn get_attr_interned(&self, name: FrozenStringValue) -> Option<V> {
+        // Linear scan is only cheaper than hash lookup for small structs.
+        const MAX_SCAN: usize = 16;
+        if self.fields.len() > MAX_SCAN {
+            return None;
+        }
+        self.fields
+            .iter()
+            .find(|(k, _)| k.to_string_value().to_value().ptr_eq(name.to_value()))
+            .map(|(_, v)| *v)
+    }
+
     /// Iterate over the elements in the struct.
     pub fn iter<'a>(&'a self) -> impl ExactSizeIterator<Item = (StringValue<'v>, V)> + 'a
     where
@@ -295,10 +314,14 @@ impl<'v, V: UnpackValue<'v>> StructOf<'v, V> {
 #[cfg(test)]
 mod tests {
     use crate::assert;
+    use crate::collections::SmallMap;
     use crate::values::docs;
     use crate::values::docs::DocItem;
     use crate::values::docs::DocString;
     use crate::values::docs::DocStringKind;
+    use crate::values::structs::FrozenStruct;
+    use crate::values::FrozenHeap;
+    use crate::values::FrozenValue;
 
     #[test]
     fn test_repr() {
@@ -306,6 +329,38 @@ mod tests {
         assert::eq("str(struct(a=1, b=[]))", "'struct(a=1, b=[])'");
     }
 
+    #[test]
+    fn test_get_attr_interned() {
+        let heap = FrozenHeap::new();
+        let a = heap.alloc_str_intern("field");
+        let mut fields = SmallMap::new();
+        fields.insert(a, FrozenValue::new_int(1));
+        let s = FrozenStruct::new(fields);
+        assert_eq!(
+            Some(1),
+            s.get_attr_interned(heap.alloc_str_intern("field"))
+                .and_then(|v| v.unpack_int())
+        );
+        // Equal but not the same string: the caller falls back to hash lookup.
+        assert!(s.get_attr_interned(heap.alloc_str("field")).is_none());
+    }
+
+    #[test]
+    fn test_field_access() {
+        assert::pass(
+            r#"
+s = struct(a = 1, b = 2)
+assert_eq(s.a + s.b, 3)
+t = struct(**{"a": 3})
+assert_eq(t.a, 3)
+def f(x):
+    return x.b
+assert_eq(f(s), 2)
+"#,
+        );
+        assert::fail("struct(a = 1).b", "has no attribute `b`");
+    }
+
     #[test]
     fn test_repr_cycle() {
         assert::eq(
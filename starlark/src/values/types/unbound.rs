@@ -35,12 +35,18 @@ pub(crate) enum MaybeUnboundValue {
 }
 
 impl MaybeUnboundValue {
-    /// Bind this object to given `this` value.
-    pub(crate) fn bind<'v>(self, this: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+    /// Bind this object, the member `name`, to given `this` value.
+    pub(crate) fn bind<'v>(
+        self,
+        this: Value<'v>,
+        name: &str,
+        heap: &'v Heap,
+    ) -> anyhow::Result<Value<'v>> {
         match self {
-            MaybeUnboundValue::Method(m) => {
-                Ok(heap.alloc_complex(BoundMethodGen::new(this.to_value(), m)))
-            }
+            MaybeUnboundValue::Method(m) => match this.get_ref().bind_method(this, name, heap) {
+                Some(v) => Ok(v),
+                None => Ok(heap.alloc_complex(BoundMethodGen::new(this.to_value(), m))),
+            },
             MaybeUnboundValue::Attr(a) => a.call(this, heap),
         }
     }
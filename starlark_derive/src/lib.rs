@@ -62,10 +62,17 @@ mod vtable;
 /// * A annotation `#[starlark(default = foo)] x : bool` means the argument defaults to `foo`
 ///   if not specified.
 ///
-/// During execution there are two local variables injected into scope:
+/// Two kinds of parameters are not Starlark parameters, but are supplied by the macro,
+/// whatever their name and position:
 ///
-/// * `eval` is the `Evaluator`.
-/// * `heap` is the `Heap`, obtained from `eval.heap()`.
+/// * A parameter of type `&mut Evaluator<'v, '_>` is the evaluator running the call,
+///   e.g. to call other functions or to reach host state stored in it.
+/// * A parameter of type `&'v Heap` is the heap of the evaluator, as returned by `eval.heap()`,
+///   for allocating values that live as long as the arguments.
+///
+/// A function can take both, and since the heap is obtained before the call,
+/// allocating on it doesn't conflict with borrowing the evaluator. Attributes
+/// can only take a `&'v Heap`.
 ///
 /// A function with the `#[starlark_module]` attribute can be added to a `GlobalsBuilder` value
 /// using the `with` function. Those `Globals` can be passed to `Evaluator` to provide global functions.
@@ -291,13 +291,6 @@ pub(crate) fn parse_fun(func: ItemFn, module_kind: ModuleKind) -> syn::Result<St
         }
     }
 
-    if eval.is_some() && heap.is_some() {
-        return Err(syn::Error::new(
-            sig_span,
-            "Can't have both `&mut Evaluator` and `&Heap` parameters",
-        ));
-    }
-
     if is_attribute {
         if eval.is_some() {
             return Err(syn::Error::new(
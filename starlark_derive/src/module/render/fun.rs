@@ -139,17 +139,21 @@ pub(crate) fn render_fun(x: StarFun) -> syn::Result<TokenStream> {
     } else {
         (None, None)
     };
-    let (heap_param, heap_arg) = if let Some(SpecialParam { ident, ty }) = heap {
+    let (heap_prepare, heap_param, heap_arg) = if let Some(SpecialParam { ident, ty }) = heap {
         (
+            // Obtain the heap before the call, so it can be passed along with `eval`.
+            Some(quote_spanned! {span=>
+                let __heap = eval.heap();
+            }),
             Some(quote_spanned! {span=>
                 #ident: #ty,
             }),
             Some(quote_spanned! {span=>
-                eval.heap(),
+                __heap,
             }),
         )
     } else {
-        (None, None)
+        (None, None, None)
     };
 
     let Bindings { prepare, bindings } = binding;
@@ -186,6 +190,7 @@ pub(crate) fn render_fun(x: StarFun) -> syn::Result<TokenStream> {
                 parameters: &starlark::eval::Arguments<'v, '_>,
             ) -> anyhow::Result<starlark::values::Value<'v>> {
                 #prepare
+                #heap_prepare
                 match Self::invoke_impl(#this_arg #( #binding_args, )* #eval_arg #heap_arg) {
                     Ok(v) => Ok(eval.heap().alloc(v)),
                     Err(e) => Err(e),